@@ -43,6 +43,8 @@ const int FIREWORK_GREEN = 5;
 const int FIREWORK_PURPLE = 6;
 const int FIREWORK_RED = 7;
 const int FIREWORK_YELLOW = 8;
+const int RAIN = 9;
+const int SNOW = 10;
 
 // meters per second squared (acceleration)
 const float earth_gravity = 9.807;
@@ -164,6 +166,24 @@ void main() {
 			3.0 + rand0,
 			vec3(0.6 + rand7 * 0.4)
 		);
+	} else if (inst_mode == RAIN) {
+		attr = Attr(
+			linear_motion(
+				vec3(0),
+				vec3(0.0, 0.0, -12.0 - rand0 * 2.0)
+			),
+			0.5,
+			vec3(0.5, 0.6, 0.9)
+		);
+	} else if (inst_mode == SNOW) {
+		attr = Attr(
+			linear_motion(
+				vec3(0),
+				vec3(0.0, 0.0, -2.0 - rand0)
+			) + vec3(sin(lifetime + rand1 * 6.28), cos(lifetime + rand2 * 6.28), 0.0),
+			0.8,
+			vec3(1.0)
+		);
 	} else {
 		attr = Attr(
 			linear_motion(
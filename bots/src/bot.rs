@@ -0,0 +1,324 @@
+//! A single bot: logging in and playing with scripted behaviors
+
+use crate::report::Event;
+use client::{Client, Error, Join, WorldExt};
+use common::{
+    clock::Clock,
+    comp::{self, humanoid, ControllerInputs},
+    msg::ClientState,
+    util::Dir,
+};
+use rand::prelude::*;
+use std::{
+    net::SocketAddr,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use vek::*;
+
+const TPS: u64 = 30;
+/// Time a bot may take to log in and enter the game before giving up
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(60);
+/// Time between a bot reporting its ping and asking for server stats
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+/// Time a bot keeps at one behavior before moving on to the next, in seconds
+const BEHAVIOR_TIME: f32 = 20.0;
+/// Distance within which fighting bots look for something to attack
+const FIGHT_RANGE: f32 = 40.0;
+const STARTER_TOOL: &str = "common.items.weapons.sword.starter_sword";
+
+const CHAT_LINES: &[&str] = &[
+    "Hello!",
+    "Anyone want to go to the dungeon?",
+    "Selling apples, cheap",
+    "Where's the nearest town?",
+    "Nice weather today",
+];
+
+/// What a bot spends its time doing
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Behavior {
+    /// Walk around in random directions, loading the terrain on the way
+    Walk,
+    /// Attack the nearest creature, or the air if there is none
+    Fight,
+    /// Craft whatever the bot has the ingredients for
+    Craft,
+    /// Talk in chat
+    Chat,
+    /// Stand still
+    Idle,
+}
+
+impl FromStr for Behavior {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "walk" => Ok(Behavior::Walk),
+            "fight" => Ok(Behavior::Fight),
+            "craft" => Ok(Behavior::Craft),
+            "chat" => Ok(Behavior::Chat),
+            "idle" => Ok(Behavior::Idle),
+            _ => Err(format!(
+                "Unknown behavior '{}', expected walk, fight, craft, chat or idle",
+                s
+            )),
+        }
+    }
+}
+
+/// How a bot connects to the server and what it does there
+#[derive(Clone)]
+pub struct BotConfig {
+    pub addr: SocketAddr,
+    pub username: String,
+    pub view_distance: u32,
+    pub behaviors: Vec<Behavior>,
+    /// Whether the bot asks the server for its stats, one bot doing so is
+    /// enough
+    pub report_stats: bool,
+}
+
+/// Connect a bot and play until `stop` is set or the connection is lost,
+/// sending what happens to `events`
+pub fn run(id: usize, config: BotConfig, stop: Arc<AtomicBool>, events: Sender<Event>) {
+    let started = Instant::now();
+    let mut client = match login(&config) {
+        Ok(client) => client,
+        Err(err) => {
+            let _ = events.send(Event::Failed {
+                id,
+                error: format!("{:?}", err),
+            });
+            return;
+        },
+    };
+    let _ = events.send(Event::Connected {
+        id,
+        login_time: started.elapsed(),
+    });
+
+    let mut bot = Bot::new(id, config.behaviors);
+    let mut clock = Clock::start();
+    let mut last_report = Instant::now();
+    while !stop.load(Ordering::Relaxed) {
+        let dt = clock.get_last_delta();
+        let inputs = bot.act(&mut client, dt.as_secs_f32());
+        let error = match client.tick(inputs, dt, |_| {}) {
+            Ok(frontend_events)
+                if frontend_events
+                    .iter()
+                    .any(|e| matches!(e, client::Event::Disconnect)) =>
+            {
+                Some(String::from("Disconnected by the server"))
+            },
+            Ok(_) => None,
+            Err(err) => Some(format!("{:?}", err)),
+        };
+        if let Some(error) = error {
+            let _ = events.send(Event::Disconnected { id, error });
+            return;
+        }
+        client.cleanup();
+
+        if last_report.elapsed() >= REPORT_INTERVAL {
+            last_report = Instant::now();
+            let _ = events.send(Event::Ping {
+                ping_ms: client.get_ping_ms(),
+            });
+            if config.report_stats {
+                if let Some(stats) = client.server_stats() {
+                    let _ = events.send(Event::ServerStats(stats.clone()));
+                }
+                if let Err(err) = client.request_server_stats_update() {
+                    let _ = events.send(Event::Disconnected {
+                        id,
+                        error: format!("{:?}", err),
+                    });
+                    return;
+                }
+            }
+        }
+
+        clock.tick(Duration::from_millis(1000 / TPS));
+    }
+
+    client.request_logout();
+}
+
+/// Register with the server and enter the game with the bot's character,
+/// creating it if it doesn't exist yet
+fn login(config: &BotConfig) -> Result<Client, Error> {
+    let mut client = Client::new(config.addr, Some(config.view_distance))?;
+    // Bots have no accounts to sign in with, so servers using an auth server
+    // can't be tested
+    client.register(config.username.clone(), String::new(), |_| false)?;
+
+    client.load_character_list();
+    wait_until(&mut client, |client| !client.character_list.loading)?;
+    if client.character_list.characters.is_empty() {
+        client.create_character(
+            config.username.clone(),
+            Some(STARTER_TOOL.to_string()),
+            comp::Body::Humanoid(humanoid::Body::random()),
+        );
+        wait_until(&mut client, |client| !client.character_list.loading)?;
+    }
+
+    let character_id = client
+        .character_list
+        .characters
+        .first()
+        .and_then(|item| item.character.id)
+        .ok_or_else(|| Error::Other(String::from("The bot has no character to play")))?;
+    client.request_character(character_id);
+    wait_until(&mut client, |client| {
+        client.get_client_state() == ClientState::Character
+    })?;
+
+    Ok(client)
+}
+
+/// Keep ticking the client until `done`, failing if the server reports an
+/// error with the character list or takes too long
+fn wait_until(client: &mut Client, done: impl Fn(&Client) -> bool) -> Result<(), Error> {
+    let started = Instant::now();
+    let mut clock = Clock::start();
+    while !done(client) {
+        if let Some(error) = client.character_list.error.take() {
+            return Err(Error::Other(error));
+        }
+        if started.elapsed() > LOGIN_TIMEOUT {
+            return Err(Error::Other(String::from("Timed out logging in")));
+        }
+        client.tick(ControllerInputs::default(), clock.get_last_delta(), |_| {})?;
+        client.cleanup();
+        clock.tick(Duration::from_millis(1000 / TPS));
+    }
+    Ok(())
+}
+
+/// The state of a bot's behaviors
+struct Bot {
+    behaviors: Vec<Behavior>,
+    current: usize,
+    behavior_time: f32,
+    /// Time until the bot next does something different within its behavior
+    action_time: f32,
+    move_dir: Vec2<f32>,
+    rng: ThreadRng,
+}
+
+impl Bot {
+    fn new(id: usize, behaviors: Vec<Behavior>) -> Self {
+        Self {
+            // Bots start at different points of the script so that they don't all do the
+            // same thing at once
+            current: id % behaviors.len().max(1),
+            behaviors,
+            behavior_time: BEHAVIOR_TIME,
+            action_time: 0.0,
+            move_dir: Vec2::zero(),
+            rng: thread_rng(),
+        }
+    }
+
+    fn behavior(&self) -> Behavior {
+        self.behaviors
+            .get(self.current)
+            .copied()
+            .unwrap_or(Behavior::Idle)
+    }
+
+    /// Decide what the bot does this tick, returning the inputs to control its
+    /// character with
+    fn act(&mut self, client: &mut Client, dt: f32) -> ControllerInputs {
+        self.behavior_time -= dt;
+        if self.behavior_time <= 0.0 {
+            self.behavior_time = BEHAVIOR_TIME;
+            self.current = (self.current + 1) % self.behaviors.len().max(1);
+        }
+        self.action_time -= dt;
+        let act_now = self.action_time <= 0.0;
+
+        let mut inputs = ControllerInputs::default();
+        match self.behavior() {
+            Behavior::Walk => {
+                if act_now {
+                    self.action_time = self.rng.gen_range(2.0, 6.0);
+                    let angle = self.rng.gen_range(0.0, std::f32::consts::PI * 2.0);
+                    self.move_dir = Vec2::new(angle.cos(), angle.sin());
+                }
+                inputs.move_dir = self.move_dir;
+                inputs.look_dir =
+                    Dir::from_unnormalized(Vec3::from(self.move_dir)).unwrap_or_default();
+                // Jump now and then to get over obstacles
+                inputs.jump.set_state(self.rng.gen_bool(0.01));
+            },
+            Behavior::Fight => {
+                if let Some(dir) = nearest_target(client) {
+                    if dir.magnitude_squared() > 9.0 {
+                        inputs.move_dir = Vec2::from(dir).try_normalized().unwrap_or_default();
+                    }
+                    inputs.look_dir = Dir::from_unnormalized(dir).unwrap_or_default();
+                }
+                // Press and release the attack so that each press is a new swing
+                if act_now {
+                    self.action_time = 0.5;
+                }
+                inputs.primary.set_state(self.action_time > 0.25);
+            },
+            Behavior::Craft => {
+                if act_now {
+                    self.action_time = self.rng.gen_range(5.0, 10.0);
+                    let craftable = client
+                        .available_recipes()
+                        .iter()
+                        .filter(|recipe| client.can_craft_recipe(recipe))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    if let Some(recipe) = craftable.choose(&mut self.rng) {
+                        client.craft_recipe(recipe);
+                    }
+                }
+            },
+            Behavior::Chat => {
+                if act_now {
+                    self.action_time = self.rng.gen_range(5.0, 15.0);
+                    if let Some(line) = CHAT_LINES.choose(&mut self.rng) {
+                        client.send_chat(line.to_string());
+                    }
+                }
+            },
+            Behavior::Idle => {},
+        }
+        inputs
+    }
+}
+
+/// The offset from the bot to the nearest living creature that isn't a player
+fn nearest_target(client: &Client) -> Option<Vec3<f32>> {
+    let ecs = client.state().ecs();
+    let positions = ecs.read_storage::<comp::Pos>();
+    let pos = positions.get(client.entity())?.0;
+    (
+        &positions,
+        &ecs.read_storage::<comp::Stats>(),
+        !&ecs.read_storage::<comp::Player>(),
+    )
+        .join()
+        .filter(|(_, stats, _)| !stats.is_dead)
+        .map(|(target, _, _)| target.0 - pos)
+        .filter(|dir| dir.magnitude_squared() < FIGHT_RANGE.powi(2))
+        .min_by(|a, b| {
+            a.magnitude_squared()
+                .partial_cmp(&b.magnitude_squared())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+}
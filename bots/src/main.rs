@@ -0,0 +1,143 @@
+//! Headless bots that connect to a server and play on it, to measure how many
+//! players a build can handle.
+//!
+//! Each bot runs its own client in its own thread. The server needs to run
+//! without an auth server, since the bots have no accounts to sign in with.
+
+#![deny(unsafe_code)]
+
+mod bot;
+mod report;
+
+use bot::{Behavior, BotConfig};
+use report::Report;
+use std::{
+    net::ToSocketAddrs,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use structopt::StructOpt;
+use tracing::{error, info};
+
+#[derive(StructOpt)]
+#[structopt(name = "veloren-bots", about = "Load test a server with headless bots")]
+struct Opts {
+    /// Address of the server
+    #[structopt(default_value = "127.0.0.1:14004")]
+    server: String,
+    /// Number of bots to connect
+    #[structopt(short, long, default_value = "10")]
+    bots: usize,
+    /// Seconds between bots connecting, so they don't all log in at once
+    #[structopt(long, default_value = "0.5")]
+    spawn_interval: f64,
+    /// Seconds to run for before disconnecting the bots, runs until killed if
+    /// not given
+    #[structopt(short, long)]
+    duration: Option<u64>,
+    /// Seconds between reports
+    #[structopt(long, default_value = "10")]
+    report_interval: u64,
+    /// Usernames of the bots, followed by their number
+    #[structopt(long, default_value = "bot")]
+    name_prefix: String,
+    /// View distance the bots ask for, in chunks
+    #[structopt(long, default_value = "5")]
+    view_distance: u32,
+    /// Behaviors the bots take turns at: walk, fight, craft, chat or idle
+    #[structopt(long, default_value = "walk,fight,craft,chat", use_delimiter = true)]
+    behaviors: Vec<Behavior>,
+}
+
+fn main() {
+    // Initialize logging.
+    tracing_subscriber::fmt::init();
+
+    let opts = Opts::from_args();
+    let addr = match opts.server.to_socket_addrs().map(|mut addrs| addrs.next()) {
+        Ok(Some(addr)) => addr,
+        _ => {
+            error!(?opts.server, "Invalid server address");
+            return;
+        },
+    };
+    if opts.behaviors.is_empty() {
+        error!("The bots need at least one behavior");
+        return;
+    }
+
+    info!(?addr, bots = opts.bots, "Starting bots");
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let (events_tx, events_rx) = mpsc::channel();
+
+    // Bots are started from a separate thread so that reports carry on while
+    // they're logging in
+    let spawner = {
+        let stop = Arc::clone(&stop);
+        let config = BotConfig {
+            addr,
+            username: String::new(),
+            view_distance: opts.view_distance,
+            behaviors: opts.behaviors.clone(),
+            report_stats: false,
+        };
+        let (bots, name_prefix) = (opts.bots, opts.name_prefix.clone());
+        let spawn_interval = Duration::from_secs_f64(opts.spawn_interval.max(0.0));
+        thread::spawn(move || {
+            let mut handles = Vec::new();
+            for id in 0..bots {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let config = BotConfig {
+                    username: format!("{}{}", name_prefix, id),
+                    report_stats: id == 0,
+                    ..config.clone()
+                };
+                let (stop, events_tx) = (Arc::clone(&stop), events_tx.clone());
+                handles.push(thread::spawn(move || bot::run(id, config, stop, events_tx)));
+                thread::sleep(spawn_interval);
+            }
+            for handle in handles {
+                let _ = handle.join();
+            }
+        })
+    };
+
+    let started = Instant::now();
+    let report_interval = Duration::from_secs(opts.report_interval);
+    let duration = opts.duration.map(Duration::from_secs);
+    let mut report = Report::new(opts.bots);
+    let mut last_report = Instant::now();
+    loop {
+        match events_rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(event) => report.record(event),
+            Err(RecvTimeoutError::Timeout) => {},
+            // Every bot has stopped
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if last_report.elapsed() >= report_interval {
+            info!("\n{}", report);
+            report.next_interval();
+            last_report = Instant::now();
+        }
+
+        if duration.map_or(false, |duration| started.elapsed() >= duration) {
+            break;
+        }
+    }
+
+    stop.store(true, Ordering::Relaxed);
+    let _ = spawner.join();
+    for event in events_rx.try_iter() {
+        report.record(event);
+    }
+    info!("Finished after {:?}\n{}", started.elapsed(), report);
+}
@@ -0,0 +1,184 @@
+//! Collecting what the bots measure into reports
+
+use common::msg::ServerStats;
+use std::{fmt, time::Duration};
+use tracing::{debug, warn};
+
+/// Something that happened to a bot, sent from its thread to be reported
+pub enum Event {
+    /// The bot logged in and entered the game with a character
+    Connected { id: usize, login_time: Duration },
+    /// The bot couldn't connect or log in
+    Failed { id: usize, error: String },
+    /// The bot's latency to the server
+    Ping { ping_ms: f64 },
+    /// Stats the server answered a bot with
+    ServerStats(ServerStats),
+    /// The bot lost its connection to the server
+    Disconnected { id: usize, error: String },
+}
+
+/// Measurements in milliseconds
+#[derive(Default)]
+struct Samples(Vec<f64>);
+
+impl Samples {
+    fn push(&mut self, sample: f64) { self.0.push(sample) }
+
+    fn clear(&mut self) { self.0.clear() }
+
+    /// The sample that `fraction` of the samples are at or below
+    fn percentile(&self, fraction: f64) -> Option<f64> {
+        let mut sorted = self.0.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let idx = ((sorted.len() as f64 * fraction).ceil() as usize).max(1) - 1;
+        sorted.get(idx).copied()
+    }
+
+    fn mean(&self) -> Option<f64> {
+        if self.0.is_empty() {
+            None
+        } else {
+            Some(self.0.iter().sum::<f64>() / self.0.len() as f64)
+        }
+    }
+}
+
+impl fmt::Display for Samples {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (
+            self.percentile(0.0),
+            self.mean(),
+            self.percentile(0.95),
+            self.percentile(1.0),
+        ) {
+            (Some(min), Some(mean), Some(p95), Some(max)) => write!(
+                f,
+                "min {:.1}ms, avg {:.1}ms, p95 {:.1}ms, max {:.1}ms",
+                min, mean, p95, max
+            ),
+            _ => write!(f, "no samples"),
+        }
+    }
+}
+
+/// Everything the bots have reported, with pings and server tick times kept
+/// for the current interval only so that each report shows how the server is
+/// doing at the time
+pub struct Report {
+    bots: usize,
+    online: usize,
+    failed: usize,
+    disconnected: usize,
+    login_times: Samples,
+    pings: Samples,
+    tick_times: Samples,
+    player_count: Option<u64>,
+}
+
+impl Report {
+    pub fn new(bots: usize) -> Self {
+        Self {
+            bots,
+            online: 0,
+            failed: 0,
+            disconnected: 0,
+            login_times: Samples::default(),
+            pings: Samples::default(),
+            tick_times: Samples::default(),
+            player_count: None,
+        }
+    }
+
+    pub fn record(&mut self, event: Event) {
+        match event {
+            Event::Connected { id, login_time } => {
+                debug!(?id, ?login_time, "Bot logged in");
+                self.online += 1;
+                self.login_times.push(login_time.as_secs_f64() * 1000.0);
+            },
+            Event::Failed { id, error } => {
+                warn!(?id, ?error, "Bot failed to log in");
+                self.failed += 1;
+            },
+            Event::Ping { ping_ms } => self.pings.push(ping_ms),
+            Event::ServerStats(stats) => {
+                self.tick_times.push(stats.tick_time.as_secs_f64() * 1000.0);
+                self.player_count = Some(stats.player_count);
+            },
+            Event::Disconnected { id, error } => {
+                warn!(?id, ?error, "Bot was disconnected");
+                self.online = self.online.saturating_sub(1);
+                self.disconnected += 1;
+            },
+        }
+    }
+
+    /// Forget the pings and tick times of the interval that was reported
+    pub fn next_interval(&mut self) {
+        self.pings.clear();
+        self.tick_times.clear();
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}/{} bots online, {} failed to log in, {} disconnected, {} players on the server",
+            self.online,
+            self.bots,
+            self.failed,
+            self.disconnected,
+            self.player_count
+                .map_or_else(|| String::from("unknown"), |count| count.to_string()),
+        )?;
+        writeln!(f, "  login time:  {}", self.login_times)?;
+        writeln!(f, "  ping:        {}", self.pings)?;
+        write!(f, "  server tick: {}", self.tick_times)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles() {
+        let mut samples = Samples::default();
+        assert_eq!(samples.percentile(0.5), None);
+        assert_eq!(samples.to_string(), "no samples");
+
+        for sample in (1..=100).rev() {
+            samples.push(sample as f64);
+        }
+        assert_eq!(samples.percentile(0.0), Some(1.0));
+        assert_eq!(samples.percentile(0.95), Some(95.0));
+        assert_eq!(samples.percentile(1.0), Some(100.0));
+        assert_eq!(samples.mean(), Some(50.5));
+    }
+
+    #[test]
+    fn test_report() {
+        let mut report = Report::new(3);
+        report.record(Event::Connected {
+            id: 0,
+            login_time: Duration::from_millis(200),
+        });
+        report.record(Event::Connected {
+            id: 1,
+            login_time: Duration::from_millis(400),
+        });
+        report.record(Event::Ping { ping_ms: 30.0 });
+        report.record(Event::Disconnected {
+            id: 1,
+            error: String::from("ServerTimeout"),
+        });
+        assert_eq!(report.online, 1);
+        assert_eq!(report.disconnected, 1);
+
+        report.next_interval();
+        assert_eq!(report.pings.mean(), None);
+        assert_eq!(report.login_times.mean(), Some(300.0));
+    }
+}
@@ -3,11 +3,66 @@
 
 use client::{Client, Event};
 use common::{clock::Clock, comp};
-use std::{io, net::ToSocketAddrs, sync::mpsc, thread, time::Duration};
+use serde::Serialize;
+use std::{
+    fs,
+    io::{self, BufRead},
+    net::ToSocketAddrs,
+    path::{Path, PathBuf},
+    process,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+use structopt::StructOpt;
 use tracing::{error, info};
 
 const TPS: u64 = 10; // Low value is okay, just reading messages.
 
+#[derive(StructOpt)]
+#[structopt(
+    name = "veloren-chat-cli",
+    about = "Chat on a Veloren server from the terminal"
+)]
+struct Opts {
+    /// Address of the server
+    server: String,
+    /// Username to log in with
+    #[structopt(short, long, env = "VELOREN_USERNAME")]
+    username: String,
+    /// Password to log in with, only needed on servers with an auth server
+    #[structopt(short, long, env = "VELOREN_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+    /// File to read the password from, to keep it off the command line
+    #[structopt(long, parse(from_os_str), conflicts_with = "password")]
+    password_file: Option<PathBuf>,
+    /// Auth server trusted with the password, can be given several times
+    #[structopt(long = "trust-auth", default_value = "https://auth.veloren.net")]
+    trusted_auth: Vec<String>,
+    /// Send the messages and commands in this file, or stdin if it is `-`, then
+    /// exit instead of chatting interactively
+    #[structopt(long, parse(from_os_str))]
+    send: Option<PathBuf>,
+    /// Seconds to keep printing incoming messages after the ones given with
+    /// `--send` have been sent
+    #[structopt(long, default_value = "2")]
+    linger: f64,
+    /// Print incoming messages as JSON, one per line
+    #[structopt(long)]
+    json: bool,
+}
+
+/// An incoming chat message as printed with `--json`
+#[derive(Serialize)]
+struct JsonChatMsg<'a> {
+    chat_type: &'a comp::ChatType<String>,
+    /// Alias of the player the message is from
+    sender: Option<&'a str>,
+    message: &'a str,
+    /// The message as it's printed without `--json`
+    formatted: String,
+}
+
 fn read_input() -> String {
     let mut buffer = String::new();
 
@@ -18,82 +73,131 @@ fn read_input() -> String {
     buffer.trim().to_string()
 }
 
-fn main() {
-    // Initialize logging.
-    tracing_subscriber::fmt::init();
+/// The non-empty lines of the file at `path`, or of stdin if it is `-`
+fn read_lines(path: &Path) -> io::Result<Vec<String>> {
+    let lines = if path.as_os_str() == "-" {
+        io::stdin().lock().lines().collect::<io::Result<Vec<_>>>()?
+    } else {
+        fs::read_to_string(path)?
+            .lines()
+            .map(String::from)
+            .collect()
+    };
+    Ok(lines
+        .into_iter()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
 
-    info!("Starting chat-cli...");
+fn print_message(client: &Client, msg: &comp::ChatMsg, json: bool) {
+    const SHOW_NAME: bool = false;
+    let formatted = client.format_message(msg, SHOW_NAME);
+    if json {
+        let msg = JsonChatMsg {
+            chat_type: &msg.chat_type,
+            sender: msg
+                .uid()
+                .and_then(|uid| client.player_list.get(&uid))
+                .map(|player| player.player_alias.as_str()),
+            message: &msg.message,
+            formatted,
+        };
+        match serde_json::to_string(&msg) {
+            Ok(line) => println!("{}", line),
+            Err(err) => error!(?err, "Failed to serialize chat message"),
+        }
+    } else {
+        println!("{}", formatted)
+    }
+}
+
+fn run(opts: Opts) -> Result<(), String> {
+    let password = match &opts.password_file {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read password file: {}", err))?
+            .trim()
+            .to_string(),
+        None => opts.password.clone().unwrap_or_default(),
+    };
+    // Read the messages to send before connecting, so a missing file doesn't cost
+    // a login
+    let batch = opts
+        .send
+        .as_deref()
+        .map(read_lines)
+        .transpose()
+        .map_err(|err| format!("Failed to read messages to send: {}", err))?;
 
     // Set up an fps clock.
     let mut clock = Clock::start();
 
-    println!("Enter your username");
-    let username = read_input();
-
-    println!("Enter the server address");
-    let server_addr = read_input();
-
-    println!("Enter your password");
-    let password = read_input();
+    let addr = opts
+        .server
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| format!("Invalid server address: {}", opts.server))?;
 
     // Create a client.
-    let mut client = Client::new(
-        server_addr
-            .to_socket_addrs()
-            .expect("Invalid server address")
-            .next()
-            .unwrap(),
-        None,
-    )
-    .expect("Failed to create client instance");
+    let mut client = Client::new(addr, None)
+        .map_err(|err| format!("Failed to create client instance: {:?}", err))?;
 
-    println!("Server info: {:?}", client.server_info);
+    info!("Server info: {:?}", client.server_info);
 
-    println!("Players online: {:?}", client.get_players());
+    info!("Players online: {:?}", client.get_players());
 
+    let trusted_auth = &opts.trusted_auth;
     client
-        .register(username, password, |provider| {
-            provider == "https://auth.veloren.net"
+        .register(opts.username.clone(), password, |provider| {
+            trusted_auth.iter().any(|trusted| trusted == provider)
         })
-        .unwrap();
+        .map_err(|err| format!("Failed to register: {:?}", err))?;
 
     let (tx, rx) = mpsc::channel();
-    thread::spawn(move || {
-        loop {
-            let msg = read_input();
-            tx.send(msg).unwrap();
-        }
-    });
+    // Time after which to exit, once the batch of messages has been sent
+    let exit_at = match batch {
+        Some(batch) => {
+            for msg in batch {
+                tx.send(msg).unwrap();
+            }
+            Some(Instant::now() + Duration::from_secs_f64(opts.linger.max(0.0)))
+        },
+        None => {
+            thread::spawn(move || {
+                loop {
+                    let msg = read_input();
+                    tx.send(msg).unwrap();
+                }
+            });
+            None
+        },
+    };
 
     loop {
         for msg in rx.try_iter() {
             client.send_chat(msg)
         }
 
-        let events = match client.tick(
-            comp::ControllerInputs::default(),
-            clock.get_last_delta(),
-            |_| {},
-        ) {
-            Ok(events) => events,
-            Err(err) => {
-                error!("Error: {:?}", err);
-                break;
-            },
-        };
+        let events = client
+            .tick(
+                comp::ControllerInputs::default(),
+                clock.get_last_delta(),
+                |_| {},
+            )
+            .map_err(|err| format!("Error: {:?}", err))?;
 
-        const SHOW_NAME: bool = false;
         for event in events {
             match event {
-                Event::Chat(m) => println!("{}", client.format_message(&m, SHOW_NAME)),
-                Event::Disconnect => {}, // TODO
+                Event::Chat(m) => print_message(&client, &m, opts.json),
+                Event::Disconnect => return Err(String::from("Disconnected by the server")),
                 Event::DisconnectionNotification(time) => {
                     let message = match time {
                         0 => String::from("Goodbye!"),
                         _ => format!("Connection lost. Kicking in {} seconds", time),
                     };
 
-                    println!("{}", message)
+                    info!("{}", message)
                 },
                 _ => {},
             }
@@ -102,7 +206,25 @@ fn main() {
         // Clean up the server after a tick.
         client.cleanup();
 
+        if exit_at.map_or(false, |exit_at| Instant::now() >= exit_at) {
+            client.request_logout();
+            return Ok(());
+        }
+
         // Wait for the next tick.
         clock.tick(Duration::from_millis(1000 / TPS));
     }
 }
+
+fn main() {
+    // Initialize logging. Logs go to stderr so that they don't get mixed up with
+    // the chat.
+    tracing_subscriber::fmt().with_writer(io::stderr).init();
+
+    info!("Starting chat-cli...");
+
+    if let Err(err) = run(Opts::from_args()) {
+        error!("{}", err);
+        process::exit(1);
+    }
+}
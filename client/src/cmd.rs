@@ -26,6 +26,7 @@ impl TabComplete for ArgumentSpec {
             ArgumentSpec::Any(_, _) => vec![],
             ArgumentSpec::Command(_) => complete_command(part),
             ArgumentSpec::Message(_) => complete_player(part, &client),
+            ArgumentSpec::Text(_, _) => vec![],
             ArgumentSpec::SubCommand => complete_command(part),
             ArgumentSpec::Enum(_, strings, _) => strings
                 .iter()
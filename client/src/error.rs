@@ -1,4 +1,5 @@
 use authc::AuthClientError;
+use common::msg::ProtocolVersions;
 pub use network::NetworkError;
 use network::{ParticipantError, StreamError};
 
@@ -16,6 +17,12 @@ pub enum Error {
     AuthErr(String),
     AuthClientError(AuthClientError),
     AuthServerNotTrusted,
+    /// The server doesn't accept the version of the game protocol the client
+    /// speaks
+    IncompatibleProtocol {
+        client: u32,
+        server: ProtocolVersions,
+    },
     /// Persisted character data is invalid or missing
     InvalidCharacter,
     //TODO: InvalidAlias,
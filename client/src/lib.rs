@@ -3,6 +3,8 @@
 
 pub mod cmd;
 pub mod error;
+pub mod replay;
+pub mod status;
 
 // Reexports
 pub use crate::error::Error;
@@ -18,19 +20,21 @@ use common::{
     character::CharacterItem,
     comp::{
         self, group, ControlAction, ControlEvent, Controller, ControllerInputs, GroupManip,
-        InventoryManip, InventoryUpdateEvent,
+        Inventory, InventoryManip, InventoryUpdateEvent, MAX_PICKUP_RANGE_SQR,
     },
     msg::{
-        validate_chat_msg, ChatMsgValidationError, ClientMsg, ClientState, InviteAnswer,
-        Notification, PlayerInfo, PlayerListUpdate, RegisterError, RequestStateError, ServerInfo,
-        ServerMsg, ServerStats, MAX_BYTES_CHAT_MSG,
+        delta::Baselines, validate_chat_msg, ChatMsgValidationError, ClientMsg, ClientState,
+        InviteAnswer, Notification, PlayerInfo, PlayerListUpdate, Poi, ProtocolVersions,
+        RegisterError, RequestStateError, ServerInfo, ServerMsg, ServerStats, MAX_BYTES_CHAT_MSG,
+        PROTOCOL_VERSION,
     },
     outcome::Outcome,
-    recipe::RecipeBook,
+    recipe::{CraftingStation, RecipeBook},
     state::State,
     sync::{Uid, UidAllocator, WorldSyncExt},
     terrain::{block::Block, neighbors, TerrainChunk, TerrainChunkSize},
     vol::RectVolSize,
+    weather::{Weather, WeatherGrid},
 };
 use futures_executor::block_on;
 use futures_timer::Delay;
@@ -98,11 +102,23 @@ pub struct Client {
     /// chunk (i.e. the sea level) in its x coordinate, and the maximum land
     /// height above this height (i.e. the max height) in its y coordinate.
     pub world_map: (Arc<DynamicImage>, Vec2<u16>, Vec2<f32>),
+    /// Towns, dungeons and other points of interest to show on the map
+    pub pois: Vec<Poi>,
     pub player_list: HashMap<Uid, PlayerInfo>,
     pub character_list: CharacterList,
     pub active_character_id: Option<i32>,
     recipe_book: RecipeBook,
     available_recipes: HashSet<String>,
+    // Crafting stations within reach, and the block position they were found from
+    nearby_stations: (Option<Vec3<i32>>, Vec<CraftingStation>),
+    // The container the player has open, and its contents
+    container: Option<(Vec3<i32>, Inventory)>,
+    // The position of the player's saved waypoint, if the server told us about one
+    waypoint: Option<Vec3<f32>>,
+    // The last stats the server answered with
+    server_stats: Option<ServerStats>,
+    // What the server encodes component deltas against
+    sync_baselines: Baselines,
 
     max_group_size: u32,
     // Client has received an invite (inviter uid, time out instant)
@@ -130,6 +146,12 @@ pub struct Client {
     // TODO: move into voxygen
     loaded_distance: f32,
 
+    // Whom the camera follows while spectating, the camera being the position
+    // of the client's entity
+    spectator_follow: Option<Uid>,
+    // Whether the spectator camera was moved since the server was last told
+    spectator_moved: bool,
+
     pending_chunks: HashMap<Vec2<i32>, Instant>,
 }
 
@@ -159,6 +181,17 @@ impl Client {
         let participant = block_on(network.connect(ProtocolAddr::Tcp(addr.into())))?;
         let mut stream = block_on(participant.open(10, PROMISES_ORDERED | PROMISES_CONSISTENCY))?;
 
+        // Tell the server which protocol version we speak before anything else, it
+        // answers with the versions it accepts
+        stream.send(PROTOCOL_VERSION)?;
+        let server_versions = block_on(stream.recv::<ProtocolVersions>())?;
+        if !server_versions.supports(PROTOCOL_VERSION) {
+            return Err(Error::IncompatibleProtocol {
+                client: PROTOCOL_VERSION,
+                server: server_versions,
+            });
+        }
+
         // Wait for initial sync
         let (
             state,
@@ -168,6 +201,7 @@ impl Client {
             lod_alt,
             lod_horizon,
             world_map,
+            pois,
             recipe_book,
             max_group_size,
         ) = block_on(async {
@@ -177,15 +211,16 @@ impl Client {
                         entity_package,
                         server_info,
                         time_of_day,
+                        weather,
                         max_group_size,
                         world_map,
                         recipe_book,
                     } => {
-                        // TODO: Display that versions don't match in Voxygen
+                        // The protocol versions have been checked already, a different build
+                        // of the server is no reason for concern
                         if server_info.git_hash != *common::util::GIT_HASH {
-                            warn!(
-                                "Server is running {}[{}], you are running {}[{}], versions might \
-                                 be incompatible!",
+                            debug!(
+                                "Server is running {}[{}], you are running {}[{}]",
                                 server_info.git_hash,
                                 server_info.git_date,
                                 common::util::GIT_HASH.to_string(),
@@ -204,6 +239,7 @@ impl Client {
 
                         let entity = state.ecs_mut().apply_entity_package(entity_package);
                         *state.ecs_mut().write_resource() = time_of_day;
+                        *state.ecs_mut().write_resource() = weather;
 
                         let map_size_lg = common::terrain::MapSizeLg::new(world_map.dimensions_lg)
                             .map_err(|_| {
@@ -217,6 +253,7 @@ impl Client {
                         let sea_level = world_map.sea_level;
                         let rgba = world_map.rgba;
                         let alt = world_map.alt;
+                        let pois = world_map.pois;
                         let expected_size =
                             (u32::from(map_size.x) * u32::from(map_size.y)) as usize;
                         if rgba.len() != expected_size {
@@ -353,11 +390,13 @@ impl Client {
                             lod_alt,
                             lod_horizon,
                             (world_map, map_size, map_bounds),
+                            pois,
                             recipe_book,
                             max_group_size,
                         ));
                     },
                     ServerMsg::TooManyPlayers => break Err(Error::TooManyPlayers),
+                    ServerMsg::Shutdown => break Err(Error::ServerShutdown),
                     err => {
                         warn!("whoops, server mad {:?}, ignoring", err);
                     },
@@ -378,6 +417,7 @@ impl Client {
             thread_pool,
             server_info,
             world_map,
+            pois,
             lod_base,
             lod_alt,
             lod_horizon,
@@ -386,6 +426,11 @@ impl Client {
             active_character_id: None,
             recipe_book,
             available_recipes: HashSet::default(),
+            nearby_stations: (None, Vec::new()),
+            container: None,
+            waypoint: None,
+            server_stats: None,
+            sync_baselines: Baselines::default(),
 
             max_group_size,
             group_invite: None,
@@ -408,6 +453,9 @@ impl Client {
             view_distance,
             loaded_distance: 0.0,
 
+            spectator_follow: None,
+            spectator_moved: false,
+
             pending_chunks: HashMap::new(),
         })
     }
@@ -430,6 +478,16 @@ impl Client {
         })
     }
 
+    /// Request `ServerStats` without waiting for them. They can be read with
+    /// `server_stats` once the server has answered.
+    pub fn request_server_stats_update(&mut self) -> Result<(), Error> {
+        self.singleton_stream.send(ClientMsg::ServerStats)?;
+        Ok(())
+    }
+
+    /// The last `ServerStats` received from the server
+    pub fn server_stats(&self) -> Option<&ServerStats> { self.server_stats.as_ref() }
+
     /// Request a state transition to `ClientState::Registered`.
     pub fn register(
         &mut self,
@@ -437,6 +495,13 @@ impl Client {
         password: String,
         mut auth_trusted: impl FnMut(&str) -> bool,
     ) -> Result<(), Error> {
+        // Only servers with local accounts take the password directly
+        let local_password = if self.server_info.local_accounts && !password.is_empty() {
+            Some(password.clone())
+        } else {
+            None
+        };
+
         // Authentication
         let token_or_username = self.server_info.auth_provider.as_ref().map(|addr|
                 // Query whether this is a trusted auth server
@@ -452,6 +517,7 @@ impl Client {
         self.singleton_stream.send(ClientMsg::Register {
             view_distance: self.view_distance,
             token_or_username,
+            password: local_password,
         })?;
         self.client_state = ClientState::Pending;
 
@@ -471,6 +537,8 @@ impl Client {
                         });
                     },
                     ServerMsg::StateAnswer(Ok(ClientState::Registered)) => break Ok(()),
+                    // The server refuses logins while shutting down
+                    ServerMsg::Shutdown => break Err(Error::ServerShutdown),
                     ignore => {
                         warn!(
                             "Ignoring what the server send till registered: {:? }",
@@ -535,6 +603,36 @@ impl Client {
         self.client_state = ClientState::Pending;
     }
 
+    /// Request a state transition to `ClientState::Spectator`, which only
+    /// admins and spectators named in the server settings may do.
+    pub fn request_spectate(&mut self) {
+        self.singleton_stream.send(ClientMsg::Spectate).unwrap();
+        self.client_state = ClientState::Pending;
+    }
+
+    /// Move the camera while spectating, which stops following anyone.
+    pub fn move_spectator_camera(&mut self, pos: Vec3<f32>) {
+        if let ClientState::Spectator = self.client_state {
+            self.state.write_component(self.entity, comp::Pos(pos));
+            self.spectator_follow = None;
+            self.spectator_moved = true;
+        }
+    }
+
+    /// Make the camera follow an entity while spectating, or stop following.
+    pub fn spectate_follow(&mut self, follow: Option<Uid>) {
+        if let ClientState::Spectator = self.client_state {
+            self.spectator_follow = follow;
+            self.spectator_moved = false;
+            self.singleton_stream
+                .send(ClientMsg::SpectatorFollow(follow))
+                .unwrap();
+        }
+    }
+
+    /// Whom the camera follows while spectating
+    pub fn spectator_follow(&self) -> Option<Uid> { self.spectator_follow }
+
     pub fn set_view_distance(&mut self, view_distance: u32) {
         self.view_distance = Some(view_distance.max(1).min(65));
         self.singleton_stream
@@ -567,6 +665,24 @@ impl Client {
             .unwrap();
     }
 
+    /// The position and contents of the container the player has open
+    pub fn container(&self) -> Option<(Vec3<i32>, &Inventory)> {
+        self.container.as_ref().map(|(pos, inv)| (*pos, inv))
+    }
+
+    pub fn close_container(&mut self) {
+        if self.container.take().is_some() {
+            self.singleton_stream
+                .send(ClientMsg::ControlEvent(ControlEvent::InventoryManip(
+                    InventoryManip::CloseContainer,
+                )))
+                .unwrap();
+        }
+    }
+
+    /// The position of the player's saved waypoint
+    pub fn waypoint(&self) -> Option<Vec3<f32>> { self.waypoint }
+
     pub fn pick_up(&mut self, entity: EcsEntity) {
         if let Some(uid) = self.state.read_component_cloned(entity) {
             self.singleton_stream
@@ -581,11 +697,14 @@ impl Client {
 
     pub fn available_recipes(&self) -> &HashSet<String> { &self.available_recipes }
 
+    /// Crafting stations near the player that can be used to craft recipes
+    pub fn nearby_stations(&self) -> &[CraftingStation] { &self.nearby_stations.1 }
+
     pub fn can_craft_recipe(&self, recipe: &str) -> bool {
         self.recipe_book
             .get(recipe)
             .zip(self.inventories().get(self.entity))
-            .map(|(recipe, inv)| inv.contains_ingredients(&*recipe).is_ok())
+            .map(|(recipe, inv)| recipe.is_available(inv, self.nearby_stations()))
             .unwrap_or(false)
     }
 
@@ -602,6 +721,28 @@ impl Client {
         }
     }
 
+    /// Look for crafting stations again if the player moved to another block
+    fn update_nearby_stations(&mut self) {
+        let pos = self
+            .state
+            .read_storage::<comp::Pos>()
+            .get(self.entity)
+            .map(|pos| pos.0);
+        let block_pos = pos.map(|pos| pos.map(|e| e.floor() as i32));
+
+        if block_pos != self.nearby_stations.0 {
+            let stations = pos
+                .map(|pos| CraftingStation::find_nearby(&*self.state.terrain(), pos))
+                .unwrap_or_default();
+            let changed = stations != self.nearby_stations.1;
+            self.nearby_stations = (block_pos, stations);
+
+            if changed {
+                self.update_available_recipes();
+            }
+        }
+    }
+
     fn update_available_recipes(&mut self) {
         self.available_recipes = self
             .recipe_book
@@ -836,6 +977,19 @@ impl Client {
         self.state.terrain().get_key_arc(chunk_pos).cloned()
     }
 
+    /// The current weather at the player's position
+    pub fn weather_at_player(&self) -> Weather {
+        self.state
+            .read_storage::<comp::Pos>()
+            .get(self.entity)
+            .map_or_else(Weather::default, |pos| {
+                self.state
+                    .ecs()
+                    .read_resource::<WeatherGrid>()
+                    .get_interpolated(pos.0.xy())
+            })
+    }
+
     pub fn inventories(&self) -> ReadStorage<comp::Inventory> { self.state.read_storage() }
 
     pub fn loadouts(&self) -> ReadStorage<comp::Loadout> { self.state.read_storage() }
@@ -975,6 +1129,36 @@ impl Client {
         // 4) Tick the client's LocalState
         self.state.tick(dt, add_foreign_systems, true);
 
+        self.update_nearby_stations();
+
+        // Close the open container if the player moved away from it
+        if let Some((container_pos, _)) = self.container {
+            let in_range = self
+                .state
+                .read_storage::<comp::Pos>()
+                .get(self.entity)
+                .map_or(false, |pos| {
+                    pos.0
+                        .distance_squared(container_pos.map(|e| e as f32 + 0.5))
+                        < MAX_PICKUP_RANGE_SQR
+                });
+            if !in_range {
+                self.close_container();
+            }
+        }
+
+        // Spectators see the world from whom they follow
+        if let (ClientState::Spectator, Some(follow)) = (self.client_state, self.spectator_follow) {
+            let followed_pos = self
+                .state
+                .ecs()
+                .entity_from_uid(follow.0)
+                .and_then(|entity| self.state.read_storage::<comp::Pos>().get(entity).copied());
+            if let Some(pos) = followed_pos {
+                self.state.write_component(self.entity, pos);
+            }
+        }
+
         // 5) Terrain
         let pos = self
             .state
@@ -1085,6 +1269,14 @@ impl Client {
                     .send(ClientMsg::PlayerPhysics { pos, vel, ori })?;
             }
         }
+        if let ClientState::Spectator = self.client_state {
+            if self.spectator_moved {
+                if let Some(pos) = self.state.read_storage::<comp::Pos>().get(self.entity) {
+                    self.singleton_stream.send(ClientMsg::SpectatorPos(pos.0))?;
+                }
+                self.spectator_moved = false;
+            }
+        }
 
         /*
         // Output debug metrics
@@ -1335,10 +1527,21 @@ impl Client {
                         return Err(Error::Other("Failed to find entity from uid.".to_owned()));
                     }
                 },
+                ServerMsg::SpectatorCamera { pos, follow } => {
+                    self.state.write_component(self.entity, comp::Pos(pos));
+                    self.spectator_follow = follow;
+                    self.spectator_moved = false;
+                },
                 ServerMsg::TimeOfDay(time_of_day) => {
                     *self.state.ecs_mut().write_resource() = time_of_day;
                 },
+                ServerMsg::WeatherUpdate(weather) => {
+                    *self.state.ecs_mut().write_resource() = weather;
+                },
                 ServerMsg::EntitySync(entity_sync_package) => {
+                    for uid in &entity_sync_package.deleted_entities {
+                        self.sync_baselines.forget(*uid);
+                    }
                     self.state
                         .ecs_mut()
                         .apply_entity_sync_package(entity_sync_package);
@@ -1348,10 +1551,14 @@ impl Client {
                         .ecs_mut()
                         .apply_comp_sync_package(comp_sync_package);
                 },
+                ServerMsg::DeltaSync(delta_sync_package) => {
+                    delta_sync_package.apply(&mut self.sync_baselines, self.state.ecs());
+                },
                 ServerMsg::CreateEntity(entity_package) => {
                     self.state.ecs_mut().apply_entity_package(entity_package);
                 },
                 ServerMsg::DeleteEntity(entity) => {
+                    self.sync_baselines.forget(entity.0);
                     if self.uid() != Some(entity) {
                         self.state
                             .ecs_mut()
@@ -1360,6 +1567,7 @@ impl Client {
                 },
                 // Cleanup for when the client goes back to the `Registered` state
                 ServerMsg::ExitIngameCleanup => {
+                    self.sync_baselines = Baselines::default();
                     self.clean_state();
                 },
                 ServerMsg::InventoryUpdate(inventory, event) => {
@@ -1375,6 +1583,12 @@ impl Client {
 
                     frontend_events.push(Event::InventoryUpdated(event));
                 },
+                ServerMsg::ContainerUpdate { pos, inventory } => {
+                    self.container = Some((pos, inventory));
+                },
+                ServerMsg::ContainerClosed => {
+                    self.container = None;
+                },
                 ServerMsg::TerrainChunkUpdate { key, chunk } => {
                     if let Ok(chunk) = chunk {
                         self.state.insert_chunk(key, *chunk);
@@ -1410,6 +1624,9 @@ impl Client {
                 ServerMsg::Notification(n) => {
                     frontend_events.push(Event::Notification(n));
                 },
+                ServerMsg::WaypointUpdate(pos) => {
+                    self.waypoint = Some(pos);
+                },
                 ServerMsg::CharacterDataLoadError(error) => {
                     self.clean_state();
                     self.character_list.error = Some(error);
@@ -1421,7 +1638,7 @@ impl Client {
                 ServerMsg::Outcomes(outcomes) => {
                     frontend_events.extend(outcomes.into_iter().map(Event::Outcome))
                 },
-                ServerMsg::ServerStats(_) => { /* How should this be handled */ },
+                ServerMsg::ServerStats(stats) => self.server_stats = Some(stats),
             }
         }
     }
@@ -1530,6 +1747,11 @@ impl Client {
 
     /// Clean client ECS state
     fn clean_state(&mut self) {
+        self.container = None;
+        self.waypoint = None;
+        self.spectator_follow = None;
+        self.spectator_moved = false;
+
         let client_uid = self
             .uid()
             .map(|u| u.into())
@@ -0,0 +1,159 @@
+//! Playing back replays recorded by a server
+//!
+//! A replay is played back by a small local server that speaks the protocol
+//! version it was recorded with and sends the recorded messages in the time
+//! they were sent, so that a regular `Client` connecting to it rebuilds the
+//! state the recorded player saw tick by tick. Note that the recorded player's
+//! own movement is only as accurate as the updates the server sent about it.
+
+use crate::Error;
+use common::{
+    msg::{ClientMsg, ProtocolVersions, ServerMsg},
+    replay::{ReplayError, ReplayReader},
+};
+use futures_executor::block_on;
+use futures_timer::Delay;
+use futures_util::{select, FutureExt};
+use network::{Network, Pid, ProtocolAddr, Stream};
+use std::{
+    io::Read,
+    net::{SocketAddr, TcpListener},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+
+/// How long the replay server waits for the client to connect
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct ReplayServer {
+    addr: SocketAddr,
+    finished: Arc<AtomicBool>,
+}
+
+impl ReplayServer {
+    /// Start serving `replay` on a local port, at `speed` times the speed it
+    /// was recorded at. The first client to connect is sent the replay.
+    pub fn start<R: Read + Send + 'static>(
+        replay: ReplayReader<R>,
+        speed: f64,
+    ) -> Result<Self, Error> {
+        //BUG: theoretically another process can grab the port between here and
+        // listening on it, however the timewindow is quite small
+        let addr = TcpListener::bind(("127.0.0.1", 0))
+            .and_then(|listener| listener.local_addr())
+            .map_err(|e| Error::Other(format!("Failed to find a port to replay on: {}", e)))?;
+        let finished = Arc::new(AtomicBool::new(false));
+
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let finished_ = Arc::clone(&finished);
+        thread::Builder::new()
+            .name("replay-server".into())
+            .spawn(move || {
+                let (network, scheduler) = Network::new(Pid::new());
+                thread::spawn(scheduler);
+                let listening = block_on(network.listen(ProtocolAddr::Tcp(addr)));
+                let listening_ok = listening.is_ok();
+                let _ = ready_tx.send(listening);
+                if listening_ok {
+                    match block_on(serve(&network, replay, speed, &finished_)) {
+                        // The client leaving is how a finished playback ends
+                        Err(e) if finished_.load(Ordering::Relaxed) => {
+                            debug!(?e, "Client left the replay")
+                        },
+                        Err(e) => warn!(?e, "Replay playback stopped"),
+                        Ok(()) => {},
+                    }
+                }
+                finished_.store(true, Ordering::Relaxed);
+            })
+            .map_err(|e| Error::Other(format!("Failed to start the replay server: {}", e)))?;
+
+        ready_rx
+            .recv()
+            .map_err(|_| Error::Other("The replay server stopped unexpectedly".into()))??;
+
+        Ok(Self { addr, finished })
+    }
+
+    /// Address to connect a `Client` to
+    pub fn addr(&self) -> SocketAddr { self.addr }
+
+    /// Whether every frame of the replay has been sent, or playback stopped
+    pub fn is_finished(&self) -> bool { self.finished.load(Ordering::Relaxed) }
+}
+
+async fn serve<R: Read>(
+    network: &Network,
+    mut replay: ReplayReader<R>,
+    speed: f64,
+    finished: &AtomicBool,
+) -> Result<(), Error> {
+    let participant = select!(
+        _ = Delay::new(CONNECT_TIMEOUT).fuse() => return Err(Error::ServerTimeout),
+        p = network.connected().fuse() => p?,
+    );
+    let mut stream = select!(
+        _ = Delay::new(CONNECT_TIMEOUT).fuse() => return Err(Error::ServerTimeout),
+        s = participant.opened().fuse() => s?,
+    );
+
+    // Only the protocol version of the recording is accepted, a client that
+    // speaks another one can't read the recorded messages
+    let client_version = stream.recv::<u32>().await?;
+    let versions = ProtocolVersions {
+        current: replay.protocol_version(),
+        oldest_supported: replay.protocol_version(),
+    };
+    stream.send(versions)?;
+    if !versions.supports(client_version) {
+        debug!(
+            ?client_version,
+            ?versions,
+            "Client can't play back the replay"
+        );
+        return Ok(());
+    }
+
+    let started = Instant::now();
+    while let Some(frame) = replay.next_frame().map_err(replay_err)? {
+        let due = Duration::from_secs_f64((frame.time / speed).max(0.0));
+        // Keep the client from timing out while waiting for the next frame
+        while started.elapsed() < due {
+            let wait = (due - started.elapsed()).min(Duration::from_millis(100));
+            answer_pings(&mut stream, wait).await?;
+        }
+        match frame.msg {
+            // Pings are answered by the replay server itself instead
+            ServerMsg::Ping | ServerMsg::Pong => {},
+            msg => stream.send(msg)?,
+        }
+    }
+    finished.store(true, Ordering::Relaxed);
+
+    // The network is kept until the client leaves, so that it gets the last
+    // frames
+    loop {
+        answer_pings(&mut stream, Duration::from_secs(1)).await?;
+    }
+}
+
+fn replay_err(err: ReplayError) -> Error { Error::Other(format!("Invalid replay: {}", err)) }
+
+/// Answer pings of the client, for up to `wait`
+async fn answer_pings(stream: &mut Stream, wait: Duration) -> Result<(), Error> {
+    let mut delay = Delay::new(wait).fuse();
+    loop {
+        let msg = select!(
+            _ = delay => return Ok(()),
+            msg = stream.recv::<ClientMsg>().fuse() => msg?,
+        );
+        if let ClientMsg::Ping = msg {
+            stream.send(ServerMsg::Pong)?;
+        }
+    }
+}
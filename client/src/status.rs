@@ -0,0 +1,48 @@
+//! Querying the status of a server without logging in
+
+use crate::Error;
+use common::msg::ServerStatus;
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+
+/// Port servers serve their status on by default
+pub const DEFAULT_STATUS_PORT: u16 = 14006;
+
+/// Ask the server serving its status at `addr` for it, giving up after
+/// `timeout`. Servers only answer each address about once a second.
+pub fn query_status(addr: SocketAddr, timeout: Duration) -> Result<ServerStatus, Error> {
+    let io_err = |e: io::Error| Error::Other(format!("Failed to query the server status: {}", e));
+
+    let mut stream = TcpStream::connect_timeout(&addr, timeout).map_err(io_err)?;
+    stream.set_read_timeout(Some(timeout)).map_err(io_err)?;
+    stream.set_write_timeout(Some(timeout)).map_err(io_err)?;
+    write!(
+        stream,
+        "GET / HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        addr
+    )
+    .map_err(io_err)?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).map_err(io_err)?;
+
+    let (head, body) = match response.find("\r\n\r\n") {
+        Some(idx) => (&response[..idx], &response[idx + 4..]),
+        None => return Err(Error::Other("Server sent an incomplete status".into())),
+    };
+    let status_line = head.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some("200") => serde_json::from_str(body)
+            .map_err(|e| Error::Other(format!("Server sent an invalid status: {}", e))),
+        Some("429") => Err(Error::Other(
+            "Queried the server status too often, try again later".into(),
+        )),
+        _ => Err(Error::Other(format!(
+            "Unexpected answer to the status query: {}",
+            status_line
+        ))),
+    }
+}
@@ -59,13 +59,17 @@ pub enum ChatCommand {
     MakeBlock,
     Motd,
     Object,
+    Password,
     Players,
     Region,
     RemoveLights,
     Say,
     SetLevel,
     SetMotd,
+    SetPassword,
+    Shutdown,
     Spawn,
+    Spectate,
     Sudo,
     Tell,
     Time,
@@ -102,13 +106,17 @@ pub static CHAT_COMMANDS: &[ChatCommand] = &[
     ChatCommand::MakeBlock,
     ChatCommand::Motd,
     ChatCommand::Object,
+    ChatCommand::Password,
     ChatCommand::Players,
     ChatCommand::Region,
     ChatCommand::RemoveLights,
     ChatCommand::Say,
     ChatCommand::SetLevel,
     ChatCommand::SetMotd,
+    ChatCommand::SetPassword,
+    ChatCommand::Shutdown,
     ChatCommand::Spawn,
+    ChatCommand::Spectate,
     ChatCommand::Sudo,
     ChatCommand::Tell,
     ChatCommand::Time,
@@ -144,6 +152,10 @@ lazy_static! {
         .iter()
         .map(|o| o.to_string().to_string())
         .collect();
+    static ref SHUTDOWN_ACTIONS: Vec<String> = vec!["shutdown", "restart", "abort"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
     static ref TIMES: Vec<String> = vec![
         "midnight", "night", "dawn", "morning", "day", "noon", "dusk"
     ]
@@ -303,6 +315,14 @@ impl ChatCommand {
                 "Spawn an object",
                 Admin,
             ),
+            ChatCommand::Password => cmd(
+                vec![
+                    Any("old_password", Required),
+                    Text("new_password", Required),
+                ],
+                "Change the password of your account, quoting the old one if it has spaces",
+                NoAdmin,
+            ),
             ChatCommand::Players => cmd(vec![], "Lists players currently online", NoAdmin),
             ChatCommand::RemoveLights => cmd(
                 vec![Float("radius", 20.0, Optional)],
@@ -327,6 +347,19 @@ impl ChatCommand {
             ChatCommand::SetMotd => {
                 cmd(vec![Message(Optional)], "Set the server description", Admin)
             },
+            ChatCommand::SetPassword => cmd(
+                vec![Any("username", Required), Text("password", Required)],
+                "Create an account or reset its password",
+                Admin,
+            ),
+            ChatCommand::Shutdown => cmd(
+                vec![
+                    Enum("action", SHUTDOWN_ACTIONS.clone(), Required),
+                    Integer("seconds", 60, Optional),
+                ],
+                "Shut down or restart the server after a countdown, or abort that",
+                Admin,
+            ),
             ChatCommand::Spawn => cmd(
                 vec![
                     Enum("alignment", ALIGNMENTS.clone(), Required),
@@ -337,6 +370,11 @@ impl ChatCommand {
                 "Spawn a test entity",
                 Admin,
             ),
+            ChatCommand::Spectate => cmd(
+                vec![PlayerName(Optional)],
+                "Spectate without being seen, following a player if given",
+                NoAdmin,
+            ),
             ChatCommand::Sudo => cmd(
                 vec![PlayerName(Required), SubCommand],
                 "Run command as if you were another player",
@@ -401,13 +439,17 @@ impl ChatCommand {
             ChatCommand::MakeBlock => "make_block",
             ChatCommand::Motd => "motd",
             ChatCommand::Object => "object",
+            ChatCommand::Password => "password",
             ChatCommand::Players => "players",
             ChatCommand::Region => "region",
             ChatCommand::RemoveLights => "remove_lights",
             ChatCommand::Say => "say",
             ChatCommand::SetLevel => "set_level",
             ChatCommand::SetMotd => "set_motd",
+            ChatCommand::SetPassword => "set_password",
+            ChatCommand::Shutdown => "shutdown",
             ChatCommand::Spawn => "spawn",
+            ChatCommand::Spectate => "spectate",
             ChatCommand::Sudo => "sudo",
             ChatCommand::Tell => "tell",
             ChatCommand::Time => "time",
@@ -445,6 +487,7 @@ impl ChatCommand {
                 ArgumentSpec::Any(_, _) => "{}",
                 ArgumentSpec::Command(_) => "{}",
                 ArgumentSpec::Message(_) => "{/.*/}",
+                ArgumentSpec::Text(_, _) => "{/.*/}",
                 ArgumentSpec::SubCommand => "{} {/.*/}",
                 ArgumentSpec::Enum(_, _, _) => "{}",
                 ArgumentSpec::Boolean(_, _, _) => "{}",
@@ -522,6 +565,11 @@ pub enum ArgumentSpec {
     /// This is the final argument, consuming all characters until the end of
     /// input.
     Message(Requirement),
+    /// Like `Message`, but with a label and without suggestions, e.g. for
+    /// passwords that contain spaces. The associated values are
+    /// * label
+    /// * whether it's optional
+    Text(&'static str, Requirement),
     /// This command is followed by another command (such as in /sudo)
     SubCommand,
     /// The argument is likely an enum. The associated values are
@@ -581,6 +629,13 @@ impl ArgumentSpec {
                     "[message]".to_string()
                 }
             },
+            ArgumentSpec::Text(label, req) => {
+                if &Requirement::Required == req {
+                    format!("<{}>", label)
+                } else {
+                    format!("[{}]", label)
+                }
+            },
             ArgumentSpec::SubCommand => "<[/]command> [args...]".to_string(),
             ArgumentSpec::Enum(label, _, req) => {
                 if &Requirement::Required == req {
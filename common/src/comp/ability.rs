@@ -1,17 +1,21 @@
 use crate::{
+    assets::{self, watch::ReloadIndicator, Asset},
     comp::{
         ability::Stage,
-        item::{armor::Protection, Item, ItemKind},
+        item::{armor::Protection, Item, ItemKind, ToolCategory, ToolKind},
         Body, CharacterState, EnergySource, Gravity, LightEmitter, Projectile, StateUpdate,
     },
     states::{triple_strike::*, *},
     sys::character_behavior::JoinData,
+    util::deserialize_duration,
 };
 use arraygen::Arraygen;
+use enum_iterator::IntoEnumIterator;
+use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
 use specs::{Component, FlaggedStorage};
 use specs_idvs::IdvStorage;
-use std::time::Duration;
+use std::{fs::File, io::BufReader, sync::Arc, time::Duration};
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub enum CharacterAbilityType {
@@ -47,7 +51,9 @@ impl From<&CharacterState> for CharacterAbilityType {
 pub enum CharacterAbility {
     BasicMelee {
         energy_cost: u32,
+        #[serde(deserialize_with = "deserialize_duration")]
         buildup_duration: Duration,
+        #[serde(deserialize_with = "deserialize_duration")]
         recover_duration: Duration,
         base_healthchange: i32,
         range: f32,
@@ -56,7 +62,9 @@ pub enum CharacterAbility {
     BasicRanged {
         energy_cost: u32,
         holdable: bool,
+        #[serde(deserialize_with = "deserialize_duration")]
         prepare_duration: Duration,
+        #[serde(deserialize_with = "deserialize_duration")]
         recover_duration: Duration,
         projectile: Projectile,
         projectile_body: Body,
@@ -64,12 +72,15 @@ pub enum CharacterAbility {
         projectile_gravity: Option<Gravity>,
     },
     Boost {
+        #[serde(deserialize_with = "deserialize_duration")]
         duration: Duration,
         only_up: bool,
     },
     DashMelee {
         energy_cost: u32,
+        #[serde(deserialize_with = "deserialize_duration")]
         buildup_duration: Duration,
+        #[serde(deserialize_with = "deserialize_duration")]
         recover_duration: Duration,
         base_damage: u32,
     },
@@ -78,17 +89,25 @@ pub enum CharacterAbility {
     TripleStrike {
         base_damage: u32,
         needs_timing: bool,
+        /// How many of the three strikes can be chained
+        #[serde(default = "default_triple_strike_stages")]
+        stages: u8,
     },
     LeapMelee {
         energy_cost: u32,
+        #[serde(deserialize_with = "deserialize_duration")]
         movement_duration: Duration,
+        #[serde(deserialize_with = "deserialize_duration")]
         buildup_duration: Duration,
+        #[serde(deserialize_with = "deserialize_duration")]
         recover_duration: Duration,
         base_damage: u32,
     },
     SpinMelee {
         energy_cost: u32,
+        #[serde(deserialize_with = "deserialize_duration")]
         buildup_duration: Duration,
+        #[serde(deserialize_with = "deserialize_duration")]
         recover_duration: Duration,
         base_damage: u32,
     },
@@ -99,14 +118,19 @@ pub enum CharacterAbility {
         max_damage: u32,
         initial_knockback: f32,
         max_knockback: f32,
+        #[serde(deserialize_with = "deserialize_duration")]
         prepare_duration: Duration,
+        #[serde(deserialize_with = "deserialize_duration")]
         charge_duration: Duration,
+        #[serde(deserialize_with = "deserialize_duration")]
         recover_duration: Duration,
         projectile_body: Body,
         projectile_light: Option<LightEmitter>,
     },
 }
 
+fn default_triple_strike_stages() -> u8 { 3 }
+
 impl CharacterAbility {
     /// Attempts to fulfill requirements, mutating `update` (taking energy) if
     /// applicable.
@@ -153,8 +177,201 @@ impl CharacterAbility {
             _ => true,
         }
     }
+
+    /// Scales the damage (or healing) dealt by the ability with the power of
+    /// the tool it is used with.
+    pub fn adjusted_by_power(mut self, power: f32) -> Self {
+        use CharacterAbility::*;
+        match self {
+            BasicMelee {
+                ref mut base_healthchange,
+                ..
+            } => *base_healthchange = (*base_healthchange as f32 * power) as i32,
+            BasicRanged {
+                ref mut projectile, ..
+            } => *projectile = projectile.clone().adjusted_by_power(power),
+            DashMelee {
+                ref mut base_damage,
+                ..
+            }
+            | TripleStrike {
+                ref mut base_damage,
+                ..
+            }
+            | LeapMelee {
+                ref mut base_damage,
+                ..
+            }
+            | SpinMelee {
+                ref mut base_damage,
+                ..
+            } => *base_damage = (*base_damage as f32 * power) as u32,
+            ChargedRanged {
+                ref mut initial_damage,
+                ref mut max_damage,
+                ..
+            } => {
+                *initial_damage = (*initial_damage as f32 * power) as u32;
+                *max_damage = (*max_damage as f32 * power) as u32;
+            },
+            Boost { .. } | BasicBlock | Roll => {},
+        }
+        self
+    }
+
+    /// Checks that the parameters of the ability make sense, returning a
+    /// description of the first problem found.
+    pub fn validate(&self) -> Result<(), String> {
+        use CharacterAbility::*;
+        match self {
+            BasicMelee {
+                range, max_angle, ..
+            } => {
+                if !range.is_finite() || *range <= 0.0 {
+                    return Err(format!("BasicMelee range must be positive, got {}", range));
+                }
+                if !(0.0..=180.0).contains(max_angle) {
+                    return Err(format!(
+                        "BasicMelee max_angle must be within 0..=180 degrees, got {}",
+                        max_angle
+                    ));
+                }
+            },
+            BasicRanged { projectile, .. } => {
+                if projectile.time_left == Duration::default() {
+                    return Err("BasicRanged projectile time_left must not be zero".to_string());
+                }
+            },
+            TripleStrike { stages, .. } => {
+                if !(1..=3).contains(stages) {
+                    return Err(format!(
+                        "TripleStrike stages must be within 1..=3, got {}",
+                        stages
+                    ));
+                }
+            },
+            Boost { duration, .. } => {
+                if *duration == Duration::default() {
+                    return Err("Boost duration must not be zero".to_string());
+                }
+            },
+            ChargedRanged {
+                initial_damage,
+                max_damage,
+                initial_knockback,
+                max_knockback,
+                charge_duration,
+                ..
+            } => {
+                if initial_damage > max_damage {
+                    return Err(format!(
+                        "ChargedRanged initial_damage ({}) exceeds max_damage ({})",
+                        initial_damage, max_damage
+                    ));
+                }
+                if initial_knockback > max_knockback {
+                    return Err(format!(
+                        "ChargedRanged initial_knockback ({}) exceeds max_knockback ({})",
+                        initial_knockback, max_knockback
+                    ));
+                }
+                if *charge_duration == Duration::default() {
+                    return Err("ChargedRanged charge_duration must not be zero".to_string());
+                }
+            },
+            DashMelee { .. } | BasicBlock | Roll | LeapMelee { .. } | SpinMelee { .. } => {},
+        }
+        Ok(())
+    }
+}
+
+/// The manifest listing the abilities granted by each kind of tool.
+pub const ABILITY_MANIFEST: &str = "common.abilities.weapon_ability_manifest";
+
+/// Abilities granted by wielding a tool, in the order they are assigned to
+/// `ability1`, `ability2` and `ability3` of an [`ItemConfig`]. Damage values
+/// are those of a tool with a power of 1.0.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AbilityMap {
+    /// Abilities of every tool within a category.
+    categories: HashMap<ToolCategory, Vec<CharacterAbility>>,
+    /// Overrides for specific tool kinds, e.g. `Staff("Sceptre")`.
+    #[serde(default)]
+    kinds: HashMap<ToolKind, Vec<CharacterAbility>>,
+}
+
+impl AbilityMap {
+    /// Get the abilities of a tool kind, falling back to those of its
+    /// category.
+    pub fn get(&self, kind: &ToolKind) -> &[CharacterAbility] {
+        self.kinds
+            .get(kind)
+            .or_else(|| self.categories.get(&ToolCategory::from(kind)))
+            .map_or(&[], |abilities| abilities.as_slice())
+    }
+
+    /// Iterate over the ability sets of every category, followed by those of
+    /// the kind specific overrides.
+    pub fn iter(&self) -> impl Iterator<Item = (String, &[CharacterAbility])> {
+        self.categories
+            .iter()
+            .map(|(category, abilities)| (format!("{:?}", category), abilities.as_slice()))
+            .chain(
+                self.kinds
+                    .iter()
+                    .map(|(kind, abilities)| (format!("{:?}", kind), abilities.as_slice())),
+            )
+    }
+
+    /// Load the ability manifest and register it to be reloaded when the file
+    /// changes. Abilities are read from the manifest when an `ItemConfig` is
+    /// built, so a reload applies the next time a tool is equipped.
+    pub fn load_watched(indicator: &mut ReloadIndicator) -> Arc<Self> {
+        assets::load_watched::<Self>(ABILITY_MANIFEST, indicator)
+            .expect("Failed to load the weapon ability manifest!")
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(category) =
+            ToolCategory::into_enum_iter().find(|c| !self.categories.contains_key(c))
+        {
+            return Err(format!(
+                "Missing abilities for tool category {:?}",
+                category
+            ));
+        }
+
+        for (name, abilities) in self.iter() {
+            if abilities.len() > 3 {
+                return Err(format!(
+                    "{} has {} abilities, but at most 3 can be used",
+                    name,
+                    abilities.len()
+                ));
+            }
+            for ability in abilities {
+                ability
+                    .validate()
+                    .map_err(|e| format!("Invalid ability for {}: {}", name, e))?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
+impl Asset for AbilityMap {
+    const ENDINGS: &'static [&'static str] = &["ron"];
+
+    fn parse(buf_reader: BufReader<File>) -> Result<Self, assets::Error> {
+        let map: Self = ron::de::from_reader(buf_reader).map_err(assets::Error::parse_error)?;
+        map.validate().map_err(assets::Error::parse_error)?;
+        Ok(map)
+    }
+}
+
+pub fn default_ability_map() -> Arc<AbilityMap> { assets::load_expect(ABILITY_MANIFEST) }
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct ItemConfig {
     pub item: Item,
@@ -165,6 +382,19 @@ pub struct ItemConfig {
     pub dodge_ability: Option<CharacterAbility>,
 }
 
+impl ItemConfig {
+    /// Replace the abilities with those currently listed in the ability
+    /// manifest for the configured tool.
+    pub fn refresh_abilities(&mut self) {
+        if let ItemKind::Tool(tool) = &self.item.kind {
+            let mut abilities = tool.get_abilities().into_iter();
+            self.ability1 = abilities.next();
+            self.ability2 = abilities.next();
+            self.ability3 = abilities.next();
+        }
+    }
+}
+
 #[derive(Arraygen, Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
 #[gen_array(pub fn get_armor: &Option<Item>)]
 pub struct Loadout {
@@ -205,13 +435,13 @@ impl Loadout {
             .flat_map(|armor| armor.as_ref())
             .filter_map(|item| {
                 if let ItemKind::Armor(armor) = &item.kind {
-                    Some(armor.get_protection())
+                    Some((armor.get_protection(), item.effectiveness()))
                 } else {
                     None
                 }
             })
-            .map(|protection| match protection {
-                Protection::Normal(protection) => Some(protection),
+            .map(|(protection, effectiveness)| match protection {
+                Protection::Normal(protection) => Some(protection * effectiveness),
                 Protection::Invincible => None,
             })
             .sum::<Option<f32>>();
@@ -220,6 +450,40 @@ impl Loadout {
             None => 1.0,
         }
     }
+
+    /// Fraction of its damage the active weapon currently deals
+    pub fn weapon_effectiveness(&self) -> f32 {
+        self.active_item
+            .as_ref()
+            .map_or(1.0, |item| item.item.effectiveness())
+    }
+
+    /// Wear down the active weapon, returning whether it changed
+    pub fn wear_weapon(&mut self, amount: u32) -> bool {
+        self.active_item
+            .as_mut()
+            .map_or(false, |item| item.item.wear(amount))
+    }
+
+    /// Wear down every piece of equipped armor, returning whether any changed
+    pub fn wear_armor(&mut self, amount: u32) -> bool {
+        [
+            &mut self.shoulder,
+            &mut self.chest,
+            &mut self.belt,
+            &mut self.hand,
+            &mut self.pants,
+            &mut self.foot,
+            &mut self.back,
+            &mut self.ring,
+            &mut self.neck,
+            &mut self.head,
+            &mut self.tabard,
+        ]
+        .iter_mut()
+        .filter_map(|armor| armor.as_mut())
+        .fold(false, |changed, armor| armor.wear(amount) || changed)
+    }
 }
 
 impl From<&CharacterAbility> for CharacterState {
@@ -284,9 +548,11 @@ impl From<&CharacterAbility> for CharacterState {
             CharacterAbility::TripleStrike {
                 base_damage,
                 needs_timing,
+                stages,
             } => CharacterState::TripleStrike(triple_strike::Data {
                 base_damage: *base_damage,
                 stage: triple_strike::Stage::First,
+                stages: *stages,
                 stage_exhausted: false,
                 stage_time_active: Duration::default(),
                 initialized: false,
@@ -363,3 +629,61 @@ impl From<&CharacterAbility> for CharacterState {
 impl Component for Loadout {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ability_manifest() {
+        let map = assets::load_expect::<AbilityMap>(ABILITY_MANIFEST);
+
+        assert!(
+            !map.get(&ToolKind::Sword("Starter".to_string())).is_empty(),
+            "Swords should grant abilities"
+        );
+        assert_ne!(
+            map.get(&ToolKind::Staff("Sceptre".to_string())),
+            map.get(&ToolKind::Staff("Starter".to_string())),
+            "Sceptre abilities should override the staff defaults"
+        );
+    }
+
+    #[test]
+    fn abilities_scale_with_power() {
+        let ability = CharacterAbility::DashMelee {
+            energy_cost: 700,
+            buildup_duration: Duration::from_millis(500),
+            recover_duration: Duration::from_millis(500),
+            base_damage: 100,
+        };
+
+        assert_eq!(
+            ability.adjusted_by_power(1.5),
+            CharacterAbility::DashMelee {
+                energy_cost: 700,
+                buildup_duration: Duration::from_millis(500),
+                recover_duration: Duration::from_millis(500),
+                base_damage: 150,
+            }
+        );
+    }
+
+    #[test]
+    fn persisted_durations_still_load() {
+        let ability = CharacterAbility::Boost {
+            duration: Duration::from_millis(50),
+            only_up: false,
+        };
+        let json = serde_json::to_string(&ability).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<CharacterAbility>(&json).unwrap(),
+            ability
+        );
+        assert_eq!(
+            ron::de::from_str::<CharacterAbility>("Boost(duration: 50, only_up: false)").unwrap(),
+            ability
+        );
+    }
+}
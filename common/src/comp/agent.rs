@@ -1,4 +1,4 @@
-use crate::{comp::Body, path::Chaser, sync::Uid};
+use crate::{comp::Body, hierarchical::ChunkPath, path::Chaser, sync::Uid};
 use specs::{Component, Entity as EcsEntity};
 use specs_idvs::IdvStorage;
 use vek::*;
@@ -81,6 +81,9 @@ impl<'a> From<&'a Body> for Psyche {
 #[derive(Clone, Debug, Default)]
 pub struct Agent {
     pub patrol_origin: Option<Vec3<f32>>,
+    /// A distant place the agent should travel to, becoming its new patrol
+    /// origin once reached
+    pub destination: Option<Vec3<f32>>,
     pub activity: Activity,
     /// Does the agent talk when e.g. hit by the player
     // TODO move speech patterns into a Behavior component
@@ -94,6 +97,22 @@ impl Agent {
         self
     }
 
+    pub fn with_destination(mut self, destination: Vec3<f32>) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    /// Send the agent along a route, its destination becoming its new patrol
+    /// origin once reached
+    pub fn with_route(mut self, path: ChunkPath) -> Self {
+        self.destination = Some(path.dest());
+        self.activity = Activity::Travel {
+            path,
+            chaser: Chaser::default(),
+        };
+        self
+    }
+
     pub fn new(origin: Vec3<f32>, can_speak: bool, body: &Body) -> Self {
         let patrol_origin = Some(origin);
         Agent {
@@ -123,12 +142,19 @@ pub enum Activity {
         been_close: bool,
         powerup: f32,
     },
+    /// Travel along a route across the world
+    Travel {
+        path: ChunkPath,
+        chaser: Chaser,
+    },
 }
 
 impl Activity {
     pub fn is_follow(&self) -> bool { matches!(self, Activity::Follow { .. }) }
 
     pub fn is_attack(&self) -> bool { matches!(self, Activity::Attack { .. }) }
+
+    pub fn is_travel(&self) -> bool { matches!(self, Activity::Travel { .. }) }
 }
 
 impl Default for Activity {
@@ -16,6 +16,7 @@ pub enum InventoryManip {
     Swap(Slot, Slot),
     Drop(Slot),
     CraftRecipe(String),
+    CloseContainer,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -7,13 +7,14 @@ pub use tool::{Hands, Tool, ToolCategory, ToolKind};
 use crate::{
     assets::{self, Asset},
     effect::Effect,
+    farming::Crop,
     lottery::Lottery,
     terrain::{Block, BlockKind},
 };
 use serde::{Deserialize, Serialize};
 use specs::{Component, FlaggedStorage};
 use specs_idvs::IdvStorage;
-use std::{fs::File, io::BufReader};
+use std::{fs::File, io::BufReader, sync::Arc};
 use vek::Rgb;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -35,6 +36,9 @@ pub enum Reagent {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Utility {
     Collar,
+    StorageCrate,
+    DungeonKey,
+    Seed(Crop),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -82,11 +86,64 @@ pub enum ItemKind {
     },
 }
 
+/// Fraction of their protection or damage that items keep once they're broken
+pub const BROKEN_ITEM_EFFECTIVENESS: f32 = 0.5;
+
+/// Wear state of an item that can break. Items are specified with only a
+/// maximum durability, and start out without any wear.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Durability {
+    max: u32,
+    #[serde(default)]
+    wear: u32,
+}
+
+impl Durability {
+    pub fn new(max: u32) -> Self { Self { max, wear: 0 } }
+
+    pub fn max(&self) -> u32 { self.max }
+
+    pub fn current(&self) -> u32 { self.max.saturating_sub(self.wear) }
+
+    pub fn is_broken(&self) -> bool { self.current() == 0 }
+
+    pub fn is_worn(&self) -> bool { self.wear > 0 }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Item {
     name: String,
     description: String,
     pub kind: ItemKind,
+    /// `None` for items that never wear out. Defaults to `None` so that items
+    /// saved before durability existed still load.
+    #[serde(default)]
+    durability: Option<Durability>,
+}
+
+/// The item asset that `item` was loaded from, found by its name and kind
+/// since items don't remember their asset
+fn asset_definition(item: &Item) -> Option<Arc<Item>> {
+    fn find_in(specifier: &str, item: &Item) -> Option<Arc<Item>> {
+        assets::read_dir(specifier)
+            .ok()?
+            .filter_map(Result::ok)
+            .find_map(|entry| {
+                let path = entry.path();
+                let name = path.file_stem()?.to_string_lossy();
+                let specifier = format!("{}.{}", specifier, name);
+                if path.is_dir() {
+                    find_in(&specifier, item)
+                } else {
+                    assets::load::<Item>(&specifier).ok().filter(|def| {
+                        def.name == item.name
+                            && std::mem::discriminant(&def.kind)
+                                == std::mem::discriminant(&item.kind)
+                    })
+                }
+            })
+    }
+    find_in("common.items", item)
 }
 
 impl Asset for Item {
@@ -105,6 +162,7 @@ impl Item {
             name: "Empty Item".to_owned(),
             description: "This item may grant abilities, but is invisible".to_owned(),
             kind: ItemKind::Tool(Tool::empty()),
+            durability: None,
         }
     }
 
@@ -143,6 +201,48 @@ impl Item {
         }
     }
 
+    pub fn durability(&self) -> Option<Durability> { self.durability }
+
+    pub fn is_broken(&self) -> bool { self.durability.map_or(false, |d| d.is_broken()) }
+
+    pub fn is_worn(&self) -> bool { self.durability.map_or(false, |d| d.is_worn()) }
+
+    /// Fraction of the item's protection or damage that is currently in effect
+    pub fn effectiveness(&self) -> f32 {
+        if self.is_broken() {
+            BROKEN_ITEM_EFFECTIVENESS
+        } else {
+            1.0
+        }
+    }
+
+    /// Wear the item down, returning whether it changed. Items without
+    /// durability, and items that are already broken, are unaffected.
+    pub fn wear(&mut self, amount: u32) -> bool {
+        match &mut self.durability {
+            Some(durability) if !durability.is_broken() && amount > 0 => {
+                durability.wear = (durability.wear + amount).min(durability.max);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Give an item saved before durability existed the durability set by its
+    /// item asset, if any
+    pub fn fill_default_durability(&mut self) {
+        if self.durability.is_none() {
+            self.durability = asset_definition(self).and_then(|def| def.durability);
+        }
+    }
+
+    /// Restore the item to full durability
+    pub fn repair(&mut self) {
+        if let Some(durability) = &mut self.durability {
+            durability.wear = 0;
+        }
+    }
+
     pub fn try_reclaim_from_block(block: Block) -> Option<Self> {
         match block.kind() {
             BlockKind::Apple => Some(assets::load_expect_cloned("common.items.food.apple")),
@@ -176,6 +276,12 @@ impl Item {
 
                 Some(assets::load_expect_cloned(chosen))
             },
+            BlockKind::BossChest => {
+                let chosen = assets::load_expect::<Lottery<String>>("common.loot_table_boss");
+                let chosen = chosen.choose();
+
+                Some(assets::load_expect_cloned(chosen))
+            },
             BlockKind::Stones => Some(assets::load_expect_cloned(
                 "common.items.crafting_ing.stones",
             )),
@@ -189,6 +295,19 @@ impl Item {
         }
     }
 
+    /// The item dropped by a block broken with a pickaxe
+    pub fn try_mine_from_block(block: Block) -> Option<Self> {
+        match block.kind() {
+            BlockKind::CopperOre => Some(assets::load_expect_cloned("common.items.ore.copper")),
+            BlockKind::IronOre => Some(assets::load_expect_cloned("common.items.ore.iron")),
+            BlockKind::Coal => Some(assets::load_expect_cloned("common.items.ore.coal")),
+            BlockKind::Rock | BlockKind::Normal => Some(assets::load_expect_cloned(
+                "common.items.crafting_ing.stones",
+            )),
+            _ => None,
+        }
+    }
+
     /// Determines whether two items are superficially equivalent to one another
     /// (i.e: one may be substituted for the other in crafting recipes or
     /// item possession checks).
@@ -486,7 +486,10 @@ impl Inventory {
         let mut slot_claims = vec![0; self.slots.len()];
         let mut missing = Vec::new();
 
-        for (input, mut needed) in recipe.inputs() {
+        // Tools aren't consumed, but at least one of each must be present
+        let tools = recipe.tools().map(|tool| (tool, 0));
+
+        for (input, mut needed) in recipe.inputs().chain(tools) {
             let mut contains_any = false;
 
             for (i, slot) in self.slots().iter().enumerate() {
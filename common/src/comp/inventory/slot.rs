@@ -10,6 +10,8 @@ use tracing::warn;
 pub enum Slot {
     Inventory(usize),
     Equip(EquipSlot),
+    /// A slot of the container (e.g. a chest) the entity currently has open
+    Container(usize),
 }
 
 #[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
@@ -42,7 +44,7 @@ pub enum ArmorSlot {
 impl Slot {
     pub fn can_hold(self, item_kind: &item::ItemKind) -> bool {
         match (self, item_kind) {
-            (Self::Inventory(_), _) => true,
+            (Self::Inventory(_), _) | (Self::Container(_), _) => true,
             (Self::Equip(slot), item_kind) => slot.can_hold(item_kind),
         }
     }
@@ -233,15 +235,64 @@ fn swap_loadout(slot_a: EquipSlot, slot_b: EquipSlot, loadout: &mut Loadout) {
     }
 }
 
+/// Swap the items in slots of two different inventories. Does nothing if
+/// either slot doesn't exist.
+///
+/// ```
+/// use veloren_common::{
+///     assets,
+///     comp::{slot::swap_inventories, Inventory, Item},
+/// };
+///
+/// let boots: Option<Item> = Some(assets::load_expect_cloned(
+///     "common.items.testing.test_boots",
+/// ));
+///
+/// let mut inv = Inventory {
+///     slots: vec![boots.clone()],
+///     amount: 1,
+/// };
+/// let mut container = Inventory {
+///     slots: vec![None, None],
+///     amount: 0,
+/// };
+///
+/// swap_inventories(0, 1, &mut inv, &mut container);
+/// assert_eq!(None, inv.slots[0]);
+/// assert_eq!(boots, container.slots[1]);
+/// ```
+pub fn swap_inventories(
+    slot_a: usize,
+    slot_b: usize,
+    inventory_a: &mut Inventory,
+    inventory_b: &mut Inventory,
+) {
+    if slot_a >= inventory_a.len() || slot_b >= inventory_b.len() {
+        return;
+    }
+
+    let item_a = inventory_a.remove(slot_a);
+    let item_b = inventory_b.remove(slot_b);
+    // Both slots are known to exist and are now empty, so neither insert can fail
+    if let Some(item) = item_a {
+        inventory_b.insert(slot_b, item).ok();
+    }
+    if let Some(item) = item_b {
+        inventory_a.insert(slot_a, item).ok();
+    }
+}
+
 // TODO: Should this report if a change actually occurred? (might be useful when
 // minimizing network use)
 
-/// Swap items from two slots, regardless of if either is inventory or loadout.
+/// Swap items from two slots, regardless of if either is inventory, loadout or
+/// container.
 pub fn swap(
     slot_a: Slot,
     slot_b: Slot,
     inventory: Option<&mut Inventory>,
     loadout: Option<&mut Loadout>,
+    container: Option<&mut Inventory>,
 ) {
     match (slot_a, slot_b) {
         (Slot::Inventory(slot_a), Slot::Inventory(slot_b)) => {
@@ -257,6 +308,26 @@ pub fn swap(
         (Slot::Equip(slot_a), Slot::Equip(slot_b)) => {
             loadout.map(|l| swap_loadout(slot_a, slot_b, l));
         },
+
+        (Slot::Container(slot_a), Slot::Container(slot_b)) => {
+            container.map(|c| c.swap_slots(slot_a, slot_b));
+        },
+        (Slot::Inventory(inv_slot), Slot::Container(container_slot)) => {
+            if let Some((inventory, container)) = inventory.zip(container) {
+                swap_inventories(inv_slot, container_slot, inventory, container);
+            }
+        },
+        (Slot::Container(container_slot), Slot::Inventory(inv_slot)) => {
+            if let Some((inventory, container)) = inventory.zip(container) {
+                swap_inventories(container_slot, inv_slot, container, inventory);
+            }
+        },
+        (Slot::Container(container_slot), Slot::Equip(equip_slot))
+        | (Slot::Equip(equip_slot), Slot::Container(container_slot)) => {
+            if let Some((container, loadout)) = container.zip(loadout) {
+                swap_inventory_loadout(container_slot, equip_slot, container, loadout);
+            }
+        },
     }
 }
 
@@ -18,3 +18,12 @@ pub enum Object {
 impl Component for Object {
     type Storage = IdvStorage<Self>;
 }
+
+/// How soaked an entity is from swimming or being out in the rain, between 0
+/// and 1. Wet entities regain energy more slowly.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Wetness(pub f32);
+
+impl Component for Wetness {
+    type Storage = IdvStorage<Self>;
+}
@@ -21,7 +21,9 @@ mod stats;
 pub mod visual;
 
 // Reexports
-pub use ability::{CharacterAbility, CharacterAbilityType, ItemConfig, Loadout};
+pub use ability::{
+    default_ability_map, AbilityMap, CharacterAbility, CharacterAbilityType, ItemConfig, Loadout,
+};
 pub use admin::{Admin, AdminList};
 pub use agent::{Agent, Alignment};
 pub use body::{
@@ -47,7 +49,7 @@ pub use inventory::{
 };
 pub use last::Last;
 pub use location::{Waypoint, WaypointArea};
-pub use misc::Object;
+pub use misc::{Object, Wetness};
 pub use phys::{Collider, ForceUpdate, Gravity, Mass, Ori, PhysicsState, Pos, Scale, Sticky, Vel};
 pub use player::{Player, MAX_MOUNT_RANGE_SQR};
 pub use projectile::Projectile;
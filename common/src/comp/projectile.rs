@@ -1,4 +1,4 @@
-use crate::sync::Uid;
+use crate::{sync::Uid, util::deserialize_duration};
 use serde::{Deserialize, Serialize};
 use specs::{Component, FlaggedStorage};
 use specs_idvs::IdvStorage;
@@ -21,10 +21,30 @@ pub struct Projectile {
     pub hit_solid: Vec<Effect>,
     pub hit_entity: Vec<Effect>,
     /// Time left until the projectile will despawn
+    #[serde(deserialize_with = "deserialize_duration")]
     pub time_left: Duration,
     pub owner: Option<Uid>,
 }
 
+impl Projectile {
+    /// Scales the damage and explosion power of the projectile's effects with
+    /// the power of the tool that fired it.
+    pub fn adjusted_by_power(mut self, power: f32) -> Self {
+        for effect in self.hit_solid.iter_mut().chain(self.hit_entity.iter_mut()) {
+            match effect {
+                Effect::Damage(damage) => *damage = (*damage as f32 * power) as i32,
+                Effect::Explode { power: explosion } => *explosion *= power,
+                Effect::Knockback(_)
+                | Effect::RewardEnergy(_)
+                | Effect::Vanish
+                | Effect::Stick
+                | Effect::Possess => {},
+            }
+        }
+        self
+    }
+}
+
 impl Component for Projectile {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
@@ -1,64 +1,257 @@
-use lazy_static::lazy_static;
+use crate::{
+    assets::{self, Asset},
+    comp::{
+        item::{Item, ItemKind, ToolCategory},
+        CharacterAbility,
+    },
+};
+use enum_iterator::IntoEnumIterator;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
+    fs::File,
     hash::Hash,
+    io::BufReader,
+    sync::Arc,
 };
 use tracing::warn;
 
-lazy_static! {
-    // Determines the skills that comprise each skill group - this data is used to determine
-    // which of a player's skill groups a particular skill should be added to when a skill unlock
-    // is requested. TODO: Externalise this data in a RON file for ease of modification
-    pub static ref SKILL_GROUP_DEFS: HashMap<SkillGroupType, HashSet<Skill>> = {
-        let mut defs = HashMap::new();
-        defs.insert(SkillGroupType::T1, [ Skill::TestT1Skill1,
-                                         Skill::TestT1Skill2,
-                                         Skill::TestT1Skill3,
-                                         Skill::TestT1Skill4,
-                                         Skill::TestT1Skill5]
-                                         .iter().cloned().collect::<HashSet<Skill>>());
-
-        defs.insert(SkillGroupType::Swords, [ Skill::TestSwordSkill1,
-                                         Skill::TestSwordSkill2,
-                                         Skill::TestSwordSkill3]
-                                         .iter().cloned().collect::<HashSet<Skill>>());
-
-        defs.insert(SkillGroupType::Axes, [ Skill::TestAxeSkill1,
-                                         Skill::TestAxeSkill2,
-                                         Skill::TestAxeSkill3]
-                                         .iter().cloned().collect::<HashSet<Skill>>());
-
-        defs
-    };
-}
+/// The asset defining the skills that comprise each skill group, their costs,
+/// prerequisites and effects.
+pub const SKILL_TREES_MANIFEST: &str = "common.skill_trees";
 
 /// Represents a skill that a player can unlock, that either grants them some
-/// kind of active ability, or a passive effect etc. Obviously because this is
-/// an enum it doesn't describe what the skill actually -does-, this will be
-/// handled by dedicated ECS systems.
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+/// kind of active ability, or a passive effect etc. What a skill actually
+/// does, costs and requires is described by its [`SkillDef`] in the skill tree
+/// asset.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize, IntoEnumIterator)]
 pub enum Skill {
-    TestT1Skill1,
-    TestT1Skill2,
-    TestT1Skill3,
-    TestT1Skill4,
-    TestT1Skill5,
-    TestSwordSkill1,
-    TestSwordSkill2,
-    TestSwordSkill3,
-    TestAxeSkill1,
-    TestAxeSkill2,
-    TestAxeSkill3,
+    HealthBoost1,
+    HealthBoost2,
+    SwordComboDamage,
+    SwordDashDamage,
+    SwordDashCost,
+    AxeComboDamage,
+    AxeSpinDamage,
+    AxeSpinCost,
+    HammerSmashDamage,
+    HammerLeapDamage,
+    HammerLeapCost,
+    BowArrowDamage,
+    BowChargeDamage,
+    StaffFireballDamage,
+    StaffExplosionDamage,
+    StaffExplosionCost,
+}
+
+/// An effect granted by an unlocked skill.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SkillEffect {
+    /// Scales the damage (or healing) of one of the abilities of the skill
+    /// group's weapon. `ability` is 1, 2 or 3, matching `ItemConfig`'s
+    /// `ability1`, `ability2` and `ability3`.
+    AbilityDamage { ability: u8, multiplier: f32 },
+    /// Scales the energy cost of one of the abilities of the skill group's
+    /// weapon.
+    AbilityEnergyCost { ability: u8, multiplier: f32 },
+    /// Increases maximum health.
+    MaxHealth(u32),
+}
+
+impl SkillEffect {
+    fn ability(&self) -> Option<u8> {
+        match self {
+            SkillEffect::AbilityDamage { ability, .. }
+            | SkillEffect::AbilityEnergyCost { ability, .. } => Some(*ability),
+            SkillEffect::MaxHealth(_) => None,
+        }
+    }
+
+    fn apply(&self, ability: &mut CharacterAbility) {
+        use CharacterAbility::*;
+        match self {
+            SkillEffect::AbilityDamage { multiplier, .. } => {
+                *ability = ability.clone().adjusted_by_power(*multiplier);
+            },
+            SkillEffect::AbilityEnergyCost { multiplier, .. } => match ability {
+                BasicMelee { energy_cost, .. }
+                | BasicRanged { energy_cost, .. }
+                | DashMelee { energy_cost, .. }
+                | LeapMelee { energy_cost, .. }
+                | SpinMelee { energy_cost, .. }
+                | ChargedRanged { energy_cost, .. } => {
+                    *energy_cost = (*energy_cost as f32 * multiplier) as u32;
+                },
+                Boost { .. } | BasicBlock | Roll | TripleStrike { .. } => {},
+            },
+            SkillEffect::MaxHealth(_) => {},
+        }
+    }
+}
+
+fn default_skill_cost() -> u8 { 1 }
+
+/// Describes how a skill is unlocked and what it does.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SkillDef {
+    /// Skill points needed to unlock the skill
+    #[serde(default = "default_skill_cost")]
+    pub cost: u8,
+    /// Skills that have to be unlocked before this one
+    #[serde(default)]
+    pub prerequisites: Vec<Skill>,
+    pub effects: Vec<SkillEffect>,
+}
+
+/// The skills that comprise each skill group. This data is used to determine
+/// which of a player's skill groups a particular skill should be added to when
+/// a skill unlock is requested, and what unlocking it requires.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SkillTrees(HashMap<SkillGroupType, HashMap<Skill, SkillDef>>);
+
+impl SkillTrees {
+    pub fn get(&self, skill: &Skill) -> Option<(SkillGroupType, &SkillDef)> {
+        self.0.iter().find_map(|(skill_group_type, skills)| {
+            skills.get(skill).map(|def| (*skill_group_type, def))
+        })
+    }
+
+    /// Iterate over the skills of a skill group.
+    pub fn skills(
+        &self,
+        skill_group_type: SkillGroupType,
+    ) -> impl Iterator<Item = (&Skill, &SkillDef)> {
+        self.0
+            .get(&skill_group_type)
+            .into_iter()
+            .flat_map(|skills| skills.iter())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for skill in Skill::into_enum_iter() {
+            let groups = self
+                .0
+                .values()
+                .filter(|skills| skills.contains_key(&skill))
+                .count();
+            if groups != 1 {
+                return Err(format!(
+                    "{:?} must be in exactly one skill group, found in {}",
+                    skill, groups
+                ));
+            }
+        }
+
+        for (skill_group_type, skills) in &self.0 {
+            for (skill, def) in skills {
+                if let Some(prerequisite) = def
+                    .prerequisites
+                    .iter()
+                    .find(|prerequisite| !skills.contains_key(prerequisite))
+                {
+                    return Err(format!(
+                        "{:?} requires {:?}, which is not in skill group {:?}",
+                        skill, prerequisite, skill_group_type
+                    ));
+                }
+                if self.requires(skill, skill) {
+                    return Err(format!("{:?} (indirectly) requires itself", skill));
+                }
+                for effect in &def.effects {
+                    if effect.ability().map_or(false, |a| !(1..=3).contains(&a)) {
+                        return Err(format!(
+                            "{:?} refers to ability {:?}, but only 1, 2 and 3 exist",
+                            skill,
+                            effect.ability()
+                        ));
+                    }
+                    if effect.ability().is_some() && skill_group_type.tool_category().is_none() {
+                        return Err(format!(
+                            "{:?} modifies an ability, but skill group {:?} has no weapon",
+                            skill, skill_group_type
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether `skill` requires `other`, directly or through its
+    /// prerequisites.
+    fn requires(&self, skill: &Skill, other: &Skill) -> bool {
+        let mut visited = HashSet::new();
+        let mut open = vec![*skill];
+        while let Some(skill) = open.pop() {
+            if let Some((_, def)) = self.get(&skill) {
+                for prerequisite in &def.prerequisites {
+                    if prerequisite == other {
+                        return true;
+                    }
+                    if visited.insert(*prerequisite) {
+                        open.push(*prerequisite);
+                    }
+                }
+            }
+        }
+        false
+    }
 }
 
+impl Asset for SkillTrees {
+    const ENDINGS: &'static [&'static str] = &["ron"];
+
+    fn parse(buf_reader: BufReader<File>) -> Result<Self, assets::Error> {
+        let trees: Self = ron::de::from_reader(buf_reader).map_err(assets::Error::parse_error)?;
+        trees.validate().map_err(assets::Error::parse_error)?;
+        Ok(trees)
+    }
+}
+
+pub fn default_skill_trees() -> Arc<SkillTrees> { assets::load_expect(SKILL_TREES_MANIFEST) }
+
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SkillGroupType {
-    T1,
+    /// Called `T1` before the skill trees were defined in assets
+    #[serde(alias = "T1")]
+    General,
     Swords,
     Axes,
+    Hammers,
+    Bows,
+    Staffs,
+}
+
+impl SkillGroupType {
+    /// The weapons whose abilities are modified by the skills of this group
+    pub fn tool_category(&self) -> Option<ToolCategory> {
+        match self {
+            SkillGroupType::General => None,
+            SkillGroupType::Swords => Some(ToolCategory::Sword),
+            SkillGroupType::Axes => Some(ToolCategory::Axe),
+            SkillGroupType::Hammers => Some(ToolCategory::Hammer),
+            SkillGroupType::Bows => Some(ToolCategory::Bow),
+            SkillGroupType::Staffs => Some(ToolCategory::Staff),
+        }
+    }
 }
 
+/// The reasons a skill can't be unlocked or refunded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkillError {
+    /// The skill is not part of any skill group
+    UnknownSkill,
+    /// The player hasn't unlocked the skill group of the skill
+    MissingSkillGroup,
+    AlreadyUnlocked,
+    NotUnlocked,
+    InsufficientSkillPoints,
+    /// A prerequisite of the skill hasn't been unlocked
+    MissingPrerequisite(Skill),
+    /// The skill can't be refunded while this unlocked skill requires it
+    RequiredBy(Skill),
+}
 /// A group of skills that have been unlocked by a player. Each skill group has
 /// independent exp and skill points which are used to unlock skills in that
 /// skill group.
@@ -134,7 +327,8 @@ impl SkillSet {
     }
 
     /// Unlocks a skill for a player, assuming they have the relevant skill
-    /// group unlocked and available SP in that skill group.
+    /// group unlocked, all prerequisites of the skill unlocked and enough
+    /// available SP in that skill group.
     ///
     /// ```
     /// use veloren_common::comp::skills::{Skill, SkillGroupType, SkillSet};
@@ -143,40 +337,40 @@ impl SkillSet {
     /// skillset.unlock_skill_group(SkillGroupType::Axes);
     /// skillset.add_skill_points(SkillGroupType::Axes, 1);
     ///
-    /// skillset.unlock_skill(Skill::TestAxeSkill2);
+    /// assert!(skillset.unlock_skill(Skill::AxeSpinDamage).is_ok());
     ///
     /// assert_eq!(skillset.skills.len(), 1);
     /// ```
-    pub fn unlock_skill(&mut self, skill: Skill) {
-        if !self.skills.contains(&skill) {
-            if let Some(skill_group_type) = SkillSet::get_skill_group_type_for_skill(&skill) {
-                if let Some(mut skill_group) = self
-                    .skill_groups
-                    .iter_mut()
-                    .find(|x| x.skill_group_type == skill_group_type)
-                {
-                    if skill_group.available_sp > 0 {
-                        skill_group.available_sp -= 1;
-                        self.skills.insert(skill);
-                    } else {
-                        warn!("Tried to unlock skill for skill group with no available SP");
-                    }
-                } else {
-                    warn!("Tried to unlock skill for a skill group that player does not have");
-                }
-            } else {
-                warn!(
-                    ?skill,
-                    "Tried to unlock skill that does not exist in any skill group!"
-                );
-            }
-        } else {
-            warn!("Tried to unlock already unlocked skill");
+    pub fn unlock_skill(&mut self, skill: Skill) -> Result<(), SkillError> {
+        if self.skills.contains(&skill) {
+            return Err(SkillError::AlreadyUnlocked);
+        }
+        let trees = default_skill_trees();
+        let (skill_group_type, def) = trees.get(&skill).ok_or(SkillError::UnknownSkill)?;
+        if let Some(prerequisite) = def
+            .prerequisites
+            .iter()
+            .find(|prerequisite| !self.skills.contains(prerequisite))
+        {
+            return Err(SkillError::MissingPrerequisite(*prerequisite));
+        }
+        let skill_group = self
+            .skill_groups
+            .iter_mut()
+            .find(|x| x.skill_group_type == skill_group_type)
+            .ok_or(SkillError::MissingSkillGroup)?;
+        if skill_group.available_sp < def.cost {
+            return Err(SkillError::InsufficientSkillPoints);
         }
+
+        skill_group.available_sp -= def.cost;
+        self.skills.insert(skill);
+        Ok(())
     }
 
-    /// Removes a skill from a player and refunds 1 skill point in the relevant
-    /// skill group.
+    /// Removes a skill from a player and refunds its skill points in the
+    /// relevant skill group. Skills can't be refunded while another unlocked
+    /// skill requires them.
     ///
     /// ```
     /// use veloren_common::comp::skills::{Skill, SkillGroupType, SkillSet};
@@ -184,46 +378,80 @@ impl SkillSet {
     /// let mut skillset = SkillSet::new();
     /// skillset.unlock_skill_group(SkillGroupType::Axes);
     /// skillset.add_skill_points(SkillGroupType::Axes, 1);
-    /// skillset.unlock_skill(Skill::TestAxeSkill2);
+    /// skillset.unlock_skill(Skill::AxeSpinDamage).unwrap();
     ///
-    /// skillset.refund_skill(Skill::TestAxeSkill2);
+    /// assert!(skillset.refund_skill(Skill::AxeSpinDamage).is_ok());
     ///
     /// assert_eq!(skillset.skills.len(), 0);
     /// ```
-    pub fn refund_skill(&mut self, skill: Skill) {
-        if self.skills.contains(&skill) {
-            if let Some(skill_group_type) = SkillSet::get_skill_group_type_for_skill(&skill) {
-                if let Some(mut skill_group) = self
-                    .skill_groups
-                    .iter_mut()
-                    .find(|x| x.skill_group_type == skill_group_type)
-                {
-                    skill_group.available_sp += 1;
-                    self.skills.remove(&skill);
-                } else {
-                    warn!("Tried to refund skill for a skill group that player does not have");
-                }
-            } else {
-                warn!(
-                    ?skill,
-                    "Tried to refund skill that does not exist in any skill group"
-                )
-            }
-        } else {
-            warn!("Tried to refund skill that has not been unlocked");
+    pub fn refund_skill(&mut self, skill: Skill) -> Result<(), SkillError> {
+        if !self.skills.contains(&skill) {
+            return Err(SkillError::NotUnlocked);
+        }
+        let trees = default_skill_trees();
+        let (skill_group_type, def) = trees.get(&skill).ok_or(SkillError::UnknownSkill)?;
+        if let Some(dependent) = self.skills.iter().find(|unlocked| {
+            trees
+                .get(unlocked)
+                .map_or(false, |(_, def)| def.prerequisites.contains(&skill))
+        }) {
+            return Err(SkillError::RequiredBy(*dependent));
+        }
+        let skill_group = self
+            .skill_groups
+            .iter_mut()
+            .find(|x| x.skill_group_type == skill_group_type)
+            .ok_or(SkillError::MissingSkillGroup)?;
+
+        skill_group.available_sp = skill_group.available_sp.saturating_add(def.cost);
+        self.skills.remove(&skill);
+        Ok(())
+    }
+
+    /// Applies the effects of the unlocked skills to one of the abilities of
+    /// an equipped item. `slot` is 1, 2 or 3 for `ability1`, `ability2` and
+    /// `ability3` respectively.
+    pub fn modify_ability(
+        &self,
+        item: &Item,
+        slot: u8,
+        ability: &CharacterAbility,
+    ) -> CharacterAbility {
+        let mut ability = ability.clone();
+        if self.skills.is_empty() {
+            return ability;
         }
+        let category = match &item.kind {
+            ItemKind::Tool(tool) => ToolCategory::from(&tool.kind),
+            _ => return ability,
+        };
+
+        let trees = default_skill_trees();
+        self.skills
+            .iter()
+            .filter_map(|skill| trees.get(skill))
+            .filter(|(skill_group_type, _)| skill_group_type.tool_category() == Some(category))
+            .flat_map(|(_, def)| def.effects.iter())
+            .filter(|effect| effect.ability() == Some(slot))
+            .for_each(|effect| effect.apply(&mut ability));
+        ability
     }
 
-    /// Returns the skill group type for a skill from the static skill group
-    /// definitions.
-    fn get_skill_group_type_for_skill(skill: &Skill) -> Option<SkillGroupType> {
-        SKILL_GROUP_DEFS.iter().find_map(|(key, val)| {
-            if val.contains(&skill) {
-                Some(*key)
-            } else {
-                None
-            }
-        })
+    /// The maximum health granted by unlocked skills.
+    pub fn max_health_bonus(&self) -> u32 {
+        if self.skills.is_empty() {
+            return 0;
+        }
+        let trees = default_skill_trees();
+        self.skills
+            .iter()
+            .filter_map(|skill| trees.get(skill))
+            .flat_map(|(_, def)| def.effects.iter())
+            .map(|effect| match effect {
+                SkillEffect::MaxHealth(amount) => *amount,
+                _ => 0,
+            })
+            .sum()
     }
 
     /// Adds skill points to a skill group as long as the player has that skill
@@ -264,19 +492,19 @@ mod tests {
         let mut skillset = SkillSet::new();
         skillset.unlock_skill_group(SkillGroupType::Axes);
         skillset.add_skill_points(SkillGroupType::Axes, 1);
-        skillset.unlock_skill(Skill::TestAxeSkill2);
+        skillset.unlock_skill(Skill::AxeSpinDamage).unwrap();
 
         assert_eq!(skillset.skill_groups[0].available_sp, 0);
         assert_eq!(skillset.skills.len(), 1);
         assert_eq!(
-            skillset.skills.get(&Skill::TestAxeSkill2),
-            Some(&Skill::TestAxeSkill2)
+            skillset.skills.get(&Skill::AxeSpinDamage),
+            Some(&Skill::AxeSpinDamage)
         );
 
-        skillset.refund_skill(Skill::TestAxeSkill2);
+        skillset.refund_skill(Skill::AxeSpinDamage).unwrap();
 
         assert_eq!(skillset.skill_groups[0].available_sp, 1);
-        assert_eq!(skillset.skills.get(&Skill::TestAxeSkill2), None);
+        assert_eq!(skillset.skills.get(&Skill::AxeSpinDamage), None);
     }
 
     #[test]
@@ -302,20 +530,50 @@ mod tests {
         assert_eq!(skillset.skills.len(), 0);
 
         // Try unlocking a skill with enough skill points
-        skillset.unlock_skill(Skill::TestAxeSkill2);
+        assert_eq!(skillset.unlock_skill(Skill::AxeSpinDamage), Ok(()));
 
         assert_eq!(skillset.skill_groups[0].available_sp, 0);
         assert_eq!(skillset.skills.len(), 1);
         assert_eq!(
-            skillset.skills.get(&Skill::TestAxeSkill2),
-            Some(&Skill::TestAxeSkill2)
+            skillset.skills.get(&Skill::AxeSpinDamage),
+            Some(&Skill::AxeSpinDamage)
         );
 
         // Try unlocking a skill without enough skill points
-        skillset.unlock_skill(Skill::TestAxeSkill1);
+        assert_eq!(
+            skillset.unlock_skill(Skill::AxeComboDamage),
+            Err(SkillError::InsufficientSkillPoints)
+        );
 
         assert_eq!(skillset.skills.len(), 1);
-        assert_eq!(skillset.skills.get(&Skill::TestAxeSkill1), None);
+        assert_eq!(skillset.skills.get(&Skill::AxeComboDamage), None);
+    }
+
+    #[test]
+    fn test_unlock_skill_prerequisites() {
+        let mut skillset = SkillSet::new();
+        skillset.unlock_skill_group(SkillGroupType::Axes);
+        skillset.add_skill_points(SkillGroupType::Axes, 2);
+
+        // Spin cost reduction requires the spin damage skill
+        assert_eq!(
+            skillset.unlock_skill(Skill::AxeSpinCost),
+            Err(SkillError::MissingPrerequisite(Skill::AxeSpinDamage))
+        );
+        assert_eq!(skillset.unlock_skill(Skill::AxeSpinDamage), Ok(()));
+        assert_eq!(skillset.unlock_skill(Skill::AxeSpinCost), Ok(()));
+
+        // Which in turn can't be refunded while the cost reduction is unlocked
+        assert_eq!(
+            skillset.refund_skill(Skill::AxeSpinDamage),
+            Err(SkillError::RequiredBy(Skill::AxeSpinCost))
+        );
+    }
+
+    #[test]
+    fn test_skill_trees() {
+        // Validation happens when parsing
+        assets::load_expect::<SkillTrees>(SKILL_TREES_MANIFEST);
     }
 
     #[test]
@@ -145,8 +145,11 @@ impl Stats {
 
     // TODO: Delete this once stat points will be a thing
     pub fn update_max_hp(&mut self, body: Body) {
-        self.health
-            .set_maximum(body.base_health() + body.base_health_increase() * self.level.amount);
+        self.health.set_maximum(
+            body.base_health()
+                + body.base_health_increase() * self.level.amount
+                + self.skill_set.max_health_bonus(),
+        );
     }
 }
 
@@ -47,6 +47,18 @@ pub enum ServerEvent {
         projectile: comp::Projectile,
         gravity: Option<comp::Gravity>,
     },
+    /// Strike the block `entity` is looking at with its pickaxe
+    MineBlock {
+        entity: EcsEntity,
+        dir: Dir,
+        range: f32,
+    },
+    /// Till the block `entity` is looking at with its farming tool
+    TillSoil {
+        entity: EcsEntity,
+        dir: Dir,
+        range: f32,
+    },
     LandOnGround {
         entity: EcsEntity,
         vel: Vec3<f32>,
@@ -68,6 +80,10 @@ pub enum ServerEvent {
     ExitIngame {
         entity: EcsEntity,
     },
+    Spectate {
+        entity: EcsEntity,
+        follow: Option<Uid>,
+    },
     CreateNpc {
         pos: comp::Pos,
         stats: comp::Stats,
@@ -0,0 +1,153 @@
+//! Crops that can be planted on tilled soil and grown for their produce
+
+use crate::{
+    assets,
+    comp::Item,
+    terrain::{Block, BlockKind},
+};
+use serde::{Deserialize, Serialize};
+use vek::*;
+
+/// Time a crop takes to grow from one stage to the next, in seconds of server
+/// uptime. That is about four hours of in-game time.
+pub const STAGE_TIME: f64 = 5.0 * 60.0;
+
+/// Color of tilled soil
+pub const FARMLAND_COLOR: Rgb<u8> = Rgb::new(92, 62, 38);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Crop {
+    Wheat,
+    Flax,
+    Corn,
+    Cabbage,
+    Carrot,
+    Radish,
+    Tomato,
+    Turnip,
+}
+
+impl Crop {
+    pub const ALL: [Crop; 8] = [
+        Crop::Wheat,
+        Crop::Flax,
+        Crop::Corn,
+        Crop::Cabbage,
+        Crop::Carrot,
+        Crop::Radish,
+        Crop::Tomato,
+        Crop::Turnip,
+    ];
+
+    /// The blocks the crop grows through, from a freshly planted seed to a
+    /// crop that is ready to harvest
+    pub fn stages(self) -> &'static [BlockKind] {
+        match self {
+            Crop::Wheat => &[
+                BlockKind::Sprout,
+                BlockKind::WheatGreen,
+                BlockKind::WheatYellow,
+            ],
+            Crop::Flax => &[BlockKind::Sprout, BlockKind::Flax],
+            Crop::Corn => &[BlockKind::Sprout, BlockKind::Corn],
+            Crop::Cabbage => &[BlockKind::Sprout, BlockKind::Cabbage],
+            Crop::Carrot => &[BlockKind::Sprout, BlockKind::Carrot],
+            Crop::Radish => &[BlockKind::Sprout, BlockKind::Radish],
+            Crop::Tomato => &[BlockKind::Sprout, BlockKind::Tomato],
+            Crop::Turnip => &[BlockKind::Sprout, BlockKind::Turnip],
+        }
+    }
+
+    /// The block of the crop at `stage`, past the last stage it stays ripe
+    pub fn block(self, stage: usize) -> Block {
+        let stages = self.stages();
+        Block::new(stages[stage.min(stages.len() - 1)], Rgb::zero())
+    }
+
+    pub fn is_ripe(self, stage: usize) -> bool { stage + 1 >= self.stages().len() }
+
+    /// The crop a block grew from and the stage it is at. Sprouts look the
+    /// same for every crop, so they can't be told apart.
+    pub fn from_block(kind: BlockKind) -> Option<(Self, usize)> {
+        Self::ALL.iter().find_map(|crop| {
+            crop.stages()
+                .iter()
+                .position(|stage| *stage == kind && *stage != BlockKind::Sprout)
+                .map(|stage| (*crop, stage))
+        })
+    }
+
+    fn produce(self) -> &'static str {
+        match self {
+            Crop::Wheat => "common.items.crafting_ing.wheat",
+            Crop::Flax => "common.items.crafting_ing.flax",
+            Crop::Corn => "common.items.food.corn",
+            Crop::Cabbage => "common.items.food.cabbage",
+            Crop::Carrot => "common.items.food.carrot",
+            Crop::Radish => "common.items.food.radish",
+            Crop::Tomato => "common.items.food.tomato",
+            Crop::Turnip => "common.items.food.turnip",
+        }
+    }
+
+    fn seeds(self) -> &'static str {
+        match self {
+            Crop::Wheat => "common.items.seeds.wheat",
+            Crop::Flax => "common.items.seeds.flax",
+            Crop::Corn => "common.items.seeds.corn",
+            Crop::Cabbage => "common.items.seeds.cabbage",
+            Crop::Carrot => "common.items.seeds.carrot",
+            Crop::Radish => "common.items.seeds.radish",
+            Crop::Tomato => "common.items.seeds.tomato",
+            Crop::Turnip => "common.items.seeds.turnip",
+        }
+    }
+
+    /// The items gathered when harvesting the crop. Ripe crops give their
+    /// produce and seeds to plant again, unripe crops only give back a seed.
+    pub fn harvest(self, ripe: bool) -> Vec<Item> {
+        let mut seeds = assets::load_expect_cloned::<Item>(self.seeds());
+        if ripe {
+            let _ = seeds.set_amount(2);
+            vec![assets::load_expect_cloned(self.produce()), seeds]
+        } else {
+            vec![seeds]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stages() {
+        for crop in Crop::ALL.iter() {
+            let stages = crop.stages();
+            assert_eq!(stages[0], BlockKind::Sprout);
+            assert!(!crop.is_ripe(0));
+            assert!(crop.is_ripe(stages.len() - 1));
+            assert_eq!(crop.block(stages.len() + 5).kind(), *stages.last().unwrap());
+
+            // Every grown stage can be recognised again once it's in the world
+            for (i, stage) in stages.iter().enumerate().skip(1) {
+                assert_eq!(Crop::from_block(*stage), Some((*crop, i)));
+            }
+        }
+        assert_eq!(Crop::from_block(BlockKind::Sprout), None);
+        assert_eq!(Crop::from_block(BlockKind::Pumpkin), None);
+    }
+
+    #[test]
+    fn test_harvest() {
+        for crop in Crop::ALL.iter() {
+            let ripe = crop.harvest(true);
+            assert_eq!(ripe.len(), 2);
+            assert_eq!(ripe[1].amount(), 2);
+
+            let unripe = crop.harvest(false);
+            assert_eq!(unripe.len(), 1);
+            assert!(unripe[0].superficially_eq(&ripe[1]));
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     comp::{self, humanoid, Alignment, Body, Item},
+    hierarchical::ChunkPath,
     npc::{self, NPC_NAMES},
 };
 use vek::*;
@@ -21,6 +22,8 @@ pub struct EntityInfo {
     pub scale: f32,
     pub level: Option<u32>,
     pub loot_drop: Option<Item>,
+    /// A route the entity sets off along as soon as it is spawned
+    pub route: Option<ChunkPath>,
 }
 
 impl EntityInfo {
@@ -38,6 +41,7 @@ impl EntityInfo {
             scale: 1.0,
             level: None,
             loot_drop: None,
+            route: None,
         }
     }
 
@@ -93,6 +97,11 @@ impl EntityInfo {
         self
     }
 
+    pub fn with_route(mut self, route: ChunkPath) -> Self {
+        self.route = Some(route);
+        self
+    }
+
     pub fn with_scale(mut self, scale: f32) -> Self {
         self.scale = scale;
         self
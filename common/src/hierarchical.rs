@@ -1,139 +1,363 @@
+//! Pathfinding over long distances
+//!
+//! [`Chaser`](crate::path::Chaser) searches for paths block by block, which
+//! gets far too expensive beyond a few chunks. Long journeys are therefore
+//! planned in two tiers: a [`ChunkPath`] is first searched from chunk to chunk,
+//! preferring paths and roads, and the agent then chases waypoints a couple of
+//! chunks ahead along that route, refining it block by block as it goes.
+
 use crate::{
-    astar::astar,
-    pathfinding::WorldPath,
-    vol::{ReadVol, RectRasterableVol},
-    volumes::vol_grid_2d::VolGrid2d,
+    astar::{Astar, PathResult},
+    path::Path,
+    terrain::{TerrainChunkSize, TerrainGrid},
+    vol::{ReadVol, RectVolSize},
 };
-
-use std::fmt::Debug;
+use hashbrown::hash_map::DefaultHashBuilder;
 use vek::*;
 
+/// Maximum number of chunks visited when searching for a route
+const MAX_ROUTE_ITERS: usize = 20_000;
+/// Cost of crossing a chunk along a path or road, relative to open terrain
+const WAY_COST: f32 = 0.5;
+/// Extra cost of climbing or descending a full chunk width
+const SLOPE_COST: f32 = 4.0;
+/// Maximum difference in altitude between neighbouring chunks, in blocks
+const MAX_CLIMB: f32 = 32.0;
+/// How many chunks ahead of the agent the next waypoint lies
+const WAYPOINT_LOOKAHEAD: usize = 2;
+
+const NEIGHBORS: [Vec2<i32>; 8] = [
+    Vec2::new(1, 0),
+    Vec2::new(1, 1),
+    Vec2::new(0, 1),
+    Vec2::new(-1, 1),
+    Vec2::new(-1, 0),
+    Vec2::new(-1, -1),
+    Vec2::new(0, -1),
+    Vec2::new(1, -1),
+];
+
+/// What an agent needs to know about a chunk to plan a route through it
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct NavChunk {
+    /// Altitude of the ground, in blocks
+    pub alt: f32,
+    /// Whether the chunk is covered by deep water
+    pub water: bool,
+    /// Whether a path or road crosses the chunk
+    pub way: bool,
+}
+
+/// Source of the chunk data used to plan routes
+pub trait ChunkNav {
+    /// The navigation data of a chunk, or `None` if nothing is known about it
+    fn nav_chunk(&self, key: Vec2<i32>) -> Option<NavChunk>;
+}
+
+/// Navigation data for every chunk of the world map, built by the server from
+/// the world simulation. Empty when no world map is available, in which case
+/// routes are planned through the loaded terrain instead.
 #[derive(Clone, Debug, Default)]
+pub struct NavGrid {
+    size: Vec2<u32>,
+    chunks: Vec<NavChunk>,
+}
+
+impl NavGrid {
+    /// Create a grid of `size` chunks from their data, in row-major order
+    pub fn new(size: Vec2<u32>, chunks: Vec<NavChunk>) -> Self {
+        assert_eq!(chunks.len(), size.product() as usize);
+        Self { size, chunks }
+    }
+
+    pub fn size(&self) -> Vec2<u32> { self.size }
+
+    pub fn is_empty(&self) -> bool { self.chunks.is_empty() }
+}
+
+impl ChunkNav for NavGrid {
+    fn nav_chunk(&self, key: Vec2<i32>) -> Option<NavChunk> {
+        if key.x >= 0 && key.y >= 0 && key.x < self.size.x as i32 && key.y < self.size.y as i32 {
+            Some(self.chunks[(key.y * self.size.x as i32 + key.x) as usize])
+        } else {
+            None
+        }
+    }
+}
+
+impl ChunkNav for TerrainGrid {
+    /// Samples the surface at the center of a loaded chunk
+    fn nav_chunk(&self, key: Vec2<i32>) -> Option<NavChunk> {
+        let chunk = self.get_key(key)?;
+        let offs = TerrainChunkSize::RECT_SIZE.map(|e| e as i32 / 2);
+        (chunk.get_min_z() - 1..chunk.get_max_z())
+            .rev()
+            .find_map(|z| match chunk.get(Vec3::new(offs.x, offs.y, z)) {
+                Ok(block) if !block.is_air() => Some(NavChunk {
+                    alt: (z + 1) as f32,
+                    water: block.is_fluid(),
+                    way: false,
+                }),
+                _ => None,
+            })
+    }
+}
+
+fn chunk_center(key: Vec2<i32>) -> Vec2<f32> {
+    let size = TerrainChunkSize::RECT_SIZE.map(|e| e as f32);
+    key.map(|e| e as f32) * size + size / 2.0
+}
+
+/// Cost of walking between two neighbouring chunks, or `None` if the slope is
+/// too steep to be climbed
+fn transition_cost(from: &NavChunk, to: &NavChunk, dist: f32) -> Option<f32> {
+    let climb = (to.alt - from.alt).abs();
+    if climb > MAX_CLIMB {
+        return None;
+    }
+    let terrain = if from.way && to.way { WAY_COST } else { 1.0 };
+    let slope = climb / TerrainChunkSize::RECT_SIZE.x as f32;
+    Some(dist * (terrain + slope * SLOPE_COST))
+}
+
+/// A route from chunk to chunk towards a destination that can be progressed
+/// along
+#[derive(Clone, Debug)]
 pub struct ChunkPath {
-    pub from: Vec3<f32>,
-    pub dest: Vec3<f32>,
-    pub chunk_path: Option<Vec<Vec2<i32>>>,
+    route: Path<Vec2<i32>>,
+    next_idx: usize,
+    dest: Vec3<f32>,
 }
 
 impl ChunkPath {
-    pub fn new<V: RectRasterableVol + ReadVol + Debug>(
-        vol: &VolGrid2d<V>,
-        from: Vec3<f32>,
-        dest: Vec3<f32>,
-    ) -> Self {
-        let ifrom: Vec3<i32> = Vec3::from(from.map(|e| e.floor() as i32));
-        let idest: Vec3<i32> = Vec3::from(dest.map(|e| e.floor() as i32));
-
-        let start_chunk = vol.pos_key(ifrom);
-        let end_chunk = vol.pos_key(idest);
-
-        let chunk_path = astar(
-            start_chunk,
-            end_chunk,
-            chunk_euclidean_distance,
-            |pos| ChunkPath::chunk_get_neighbors(vol, pos),
-            chunk_transition_cost,
+    /// Search for a route from `from` to `dest`, avoiding water and steep
+    /// slopes. Returns `None` if there is no such route or if the destination
+    /// is too far away to be found.
+    pub fn find<N: ChunkNav>(nav: &N, from: Vec3<f32>, dest: Vec3<f32>) -> Option<Self> {
+        let start = TerrainGrid::chunk_key(from.map(|e| e.floor() as i32));
+        let end = TerrainGrid::chunk_key(dest.map(|e| e.floor() as i32));
+        // The start and the destination are always allowed, even in water
+        let passable = |key: Vec2<i32>| {
+            nav.nav_chunk(key)
+                .filter(|chunk| !chunk.water || key == start || key == end)
+        };
+
+        let heuristic =
+            |key: &Vec2<i32>| key.map(|e| e as f32).distance(end.map(|e| e as f32)) * WAY_COST;
+        let neighbors = |key: &Vec2<i32>| {
+            let key = *key;
+            let here = passable(key);
+            NEIGHBORS
+                .iter()
+                .map(move |dir| key + *dir)
+                .filter(move |next| {
+                    here.zip(passable(*next))
+                        .and_then(|(here, next)| transition_cost(&here, &next, 1.0))
+                        .is_some()
+                })
+        };
+        let transition = |a: &Vec2<i32>, b: &Vec2<i32>| {
+            let dist = a.map(|e| e as f32).distance(b.map(|e| e as f32));
+            passable(*a)
+                .zip(passable(*b))
+                .and_then(|(a, b)| transition_cost(&a, &b, dist))
+                .unwrap_or(f32::MAX)
+        };
+
+        let mut astar = Astar::new(
+            MAX_ROUTE_ITERS,
+            start,
+            heuristic,
+            DefaultHashBuilder::default(),
         );
+        match astar.poll(MAX_ROUTE_ITERS, heuristic, neighbors, transition, |key| {
+            *key == end
+        }) {
+            PathResult::Path(route) => Some(Self {
+                route,
+                next_idx: 0,
+                dest,
+            }),
+            _ => None,
+        }
+    }
 
+    /// Follow a route that is already known, such as a road between sites
+    pub fn along(chunks: impl IntoIterator<Item = Vec2<i32>>, dest: Vec3<f32>) -> Self {
         Self {
-            from,
+            route: chunks.into_iter().collect(),
+            next_idx: 0,
             dest,
-            chunk_path,
         }
     }
 
-    pub fn chunk_get_neighbors<V: RectRasterableVol + ReadVol + Debug>(
-        _vol: &VolGrid2d<V>,
-        pos: &Vec2<i32>,
-    ) -> impl Iterator<Item = Vec2<i32>> {
-        let directions = vec![
-            Vec2::new(1, 0),  // Right chunk
-            Vec2::new(-1, 0), // Left chunk
-            Vec2::new(0, 1),  // Top chunk
-            Vec2::new(0, -1), // Bottom chunk
-        ];
-
-        let mut neighbors = Vec::new();
-        for x in -2..3 {
-            for y in -2..3 {
-                neighbors.push(pos + Vec2::new(x, y));
+    /// The chunks along the route, from start to destination
+    pub fn chunks(&self) -> &[Vec2<i32>] { self.route.nodes() }
+
+    pub fn dest(&self) -> Vec3<f32> { self.dest }
+
+    /// The position to chase next when standing at `pos`. This is a point a
+    /// couple of chunks ahead along the route, or the destination itself once
+    /// it is close.
+    pub fn next_waypoint<N: ChunkNav>(&mut self, nav: &N, pos: Vec3<f32>) -> Vec3<f32> {
+        let key = TerrainGrid::chunk_key(pos.map(|e| e.floor() as i32));
+        let chunks = self.route.nodes();
+        if let Some(skipped) = chunks[self.next_idx..].iter().position(|c| *c == key) {
+            self.next_idx += skipped;
+        }
+
+        let waypoint_idx = self.next_idx + WAYPOINT_LOOKAHEAD;
+        if waypoint_idx + 1 >= chunks.len() {
+            self.dest
+        } else {
+            let key = chunks[waypoint_idx];
+            let alt = nav.nav_chunk(key).map_or(pos.z, |chunk| chunk.alt);
+            Vec3::from(chunk_center(key)) + Vec3::unit_z() * alt
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        path::{Chaser, TraversalConfig},
+        terrain::{Block, BlockKind, TerrainChunk, TerrainChunkMeta},
+    };
+    use std::sync::Arc;
+
+    const GROUND: i32 = 64;
+
+    /// A flat world of `size` chunks, with lakes wherever `water` says so
+    fn terrain(size: Vec2<i32>, water: impl Fn(Vec2<i32>) -> bool) -> TerrainGrid {
+        let mut terrain = TerrainGrid::new().unwrap();
+        for x in 0..size.x {
+            for y in 0..size.y {
+                let key = Vec2::new(x, y);
+                let ground = if water(key) {
+                    Block::new(BlockKind::Water, Rgb::zero())
+                } else {
+                    Block::new(BlockKind::Normal, Rgb::broadcast(128))
+                };
+                terrain.insert(
+                    key,
+                    Arc::new(TerrainChunk::new(
+                        GROUND,
+                        ground,
+                        Block::empty(),
+                        TerrainChunkMeta::void(),
+                    )),
+                );
             }
         }
+        terrain
+    }
 
-        //let neighbors: Vec<Vec2<i32>> = directions.into_iter().map(|dir| dir +
-        // pos).collect();
+    fn center(key: Vec2<i32>) -> Vec3<f32> {
+        Vec3::from(chunk_center(key)) + Vec3::unit_z() * GROUND as f32
+    }
+
+    #[test]
+    fn test_route_across_chunks() {
+        let terrain = terrain(Vec2::new(24, 4), |_| false);
+        let path =
+            ChunkPath::find(&terrain, center(Vec2::new(0, 1)), center(Vec2::new(23, 2))).unwrap();
+        assert_eq!(path.chunks().first(), Some(&Vec2::new(0, 1)));
+        assert_eq!(path.chunks().last(), Some(&Vec2::new(23, 2)));
+        assert_eq!(path.chunks().len(), 24);
 
-        neighbors.into_iter()
+        // Nothing is known beyond the loaded terrain
+        assert!(
+            ChunkPath::find(&terrain, center(Vec2::new(0, 1)), center(Vec2::new(40, 1))).is_none()
+        );
     }
 
-    pub fn worldpath_get_neighbors<V: RectRasterableVol + ReadVol + Debug>(
-        &mut self,
-        vol: &VolGrid2d<V>,
-        pos: Vec3<i32>,
-    ) -> impl Iterator<Item = Vec3<i32>> {
-        let directions = vec![
-            Vec3::new(0, 1, 0),   // Forward
-            Vec3::new(0, 1, 1),   // Forward upward
-            Vec3::new(0, 1, 2),   // Forward Upwardx2
-            Vec3::new(0, 1, -1),  // Forward downward
-            Vec3::new(1, 0, 0),   // Right
-            Vec3::new(1, 0, 1),   // Right upward
-            Vec3::new(1, 0, 2),   // Right Upwardx2
-            Vec3::new(1, 0, -1),  // Right downward
-            Vec3::new(0, -1, 0),  // Backwards
-            Vec3::new(0, -1, 1),  // Backward Upward
-            Vec3::new(0, -1, 2),  // Backward Upwardx2
-            Vec3::new(0, -1, -1), // Backward downward
-            Vec3::new(-1, 0, 0),  // Left
-            Vec3::new(-1, 0, 1),  // Left upward
-            Vec3::new(-1, 0, 2),  // Left Upwardx2
-            Vec3::new(-1, 0, -1), // Left downward
-        ];
-
-        let neighbors: Vec<Vec3<i32>> = directions
-            .into_iter()
-            .map(|dir| dir + pos)
-            .filter(|new_pos| self.is_valid_space(vol, *new_pos))
-            .collect();
-        neighbors.into_iter()
+    #[test]
+    fn test_avoid_water() {
+        // A lake across the middle of the map with a single crossing
+        let terrain = terrain(Vec2::new(16, 16), |key| key.x == 8 && key.y != 12);
+        let path =
+            ChunkPath::find(&terrain, center(Vec2::new(2, 2)), center(Vec2::new(14, 2))).unwrap();
+        assert!(path.chunks().contains(&Vec2::new(8, 12)));
+        assert!(
+            path.chunks()
+                .iter()
+                .all(|key| !terrain.nav_chunk(*key).unwrap().water)
+        );
+
+        // No way across at all
+        let terrain = self::terrain(Vec2::new(16, 16), |key| key.x == 8);
+        assert!(
+            ChunkPath::find(&terrain, center(Vec2::new(2, 2)), center(Vec2::new(14, 2))).is_none()
+        );
     }
 
-    pub fn is_valid_space<V: RectRasterableVol + ReadVol + Debug>(
-        &mut self,
-        vol: &VolGrid2d<V>,
-        pos: Vec3<i32>,
-    ) -> bool {
-        let is_walkable_position = WorldPath::is_walkable_space(vol, pos);
-        let mut is_within_chunk = false;
-        match self.chunk_path.clone() {
-            Some(chunk_path) => {
-                is_within_chunk = chunk_path
-                    .iter()
-                    .any(|new_pos| new_pos.cmpeq(&vol.pos_key(pos)).iter().all(|e| *e));
-            },
-            _ => {
-                //println!("No chunk path");
-            },
-        }
-        return is_walkable_position && is_within_chunk;
+    #[test]
+    fn test_prefer_ways() {
+        // A road that makes a detour is still quicker than the open land
+        let size = Vec2::new(16, 8);
+        let chunks = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| Vec2::new(x, y)))
+            .map(|key| NavChunk {
+                alt: 0.0,
+                water: false,
+                way: key.y == 5 || (key.y >= 1 && (key.x == 1 || key.x == 14)),
+            })
+            .collect();
+        let nav = NavGrid::new(size.map(|e| e as u32), chunks);
+        let path =
+            ChunkPath::find(&nav, center(Vec2::new(1, 1)), center(Vec2::new(14, 1))).unwrap();
+        assert!(path.chunks().iter().any(|key| key.y == 5));
+        assert!(
+            path.chunks()
+                .iter()
+                .all(|key| nav.nav_chunk(*key).unwrap().way)
+        );
     }
 
-    pub fn get_worldpath<V: RectRasterableVol + ReadVol + Debug>(
-        &mut self,
-        vol: &VolGrid2d<V>,
-    ) -> Result<WorldPath, ()> {
-        let wp = WorldPath::new(vol, self.from, self.dest, |vol, pos| {
-            self.worldpath_get_neighbors(vol, pos)
-        });
-        //println!("Fetching world path from hierarchical path: {:?}", wp);
-        wp
+    #[test]
+    fn test_avoid_cliffs() {
+        let size = Vec2::new(12, 6);
+        let chunks = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| Vec2::new(x, y)))
+            .map(|key| NavChunk {
+                alt: if key.x == 6 && key.y < 5 { 200.0 } else { 0.0 },
+                water: false,
+                way: false,
+            })
+            .collect();
+        let nav = NavGrid::new(size.map(|e| e as u32), chunks);
+        let path =
+            ChunkPath::find(&nav, center(Vec2::new(1, 0)), center(Vec2::new(10, 0))).unwrap();
+        assert!(path.chunks().contains(&Vec2::new(6, 5)));
     }
-}
 
-pub fn chunk_euclidean_distance(start: &Vec2<i32>, end: &Vec2<i32>) -> f32 {
-    let istart = start.map(|e| e as f32);
-    let iend = end.map(|e| e as f32);
-    istart.distance(iend)
-}
+    #[test]
+    fn test_traverse() {
+        let terrain = terrain(Vec2::new(20, 3), |_| false);
+        let dest = center(Vec2::new(19, 1));
+        let mut path = ChunkPath::find(&terrain, center(Vec2::new(0, 1)), dest).unwrap();
+        let mut chaser = Chaser::default();
+        let mut pos = center(Vec2::new(0, 1));
 
-pub fn chunk_transition_cost(_start: &Vec2<i32>, _end: &Vec2<i32>) -> f32 { 1.0f32 }
+        // Walk along the bearings given by the chaser
+        for _ in 0..1000 {
+            if pos.xy().distance(dest.xy()) < 2.0 {
+                return;
+            }
+            let waypoint = path.next_waypoint(&terrain, pos);
+            assert!(waypoint.xy().distance(pos.xy()) < 100.0);
+            let (bearing, speed) = chaser
+                .chase(&terrain, pos, Vec3::zero(), waypoint, TraversalConfig {
+                    node_tolerance: 1.5,
+                    slow_factor: 0.0,
+                    on_ground: true,
+                    min_tgt_dist: 1.0,
+                })
+                .unwrap();
+            let dir = bearing.xy().try_normalized().unwrap_or_default();
+            pos += Vec3::from(dir) * speed.max(0.5) * 2.0;
+        }
+        panic!("Never reached the destination");
+    }
+}
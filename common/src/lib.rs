@@ -21,8 +21,10 @@ pub mod cmd;
 pub mod comp;
 pub mod effect;
 pub mod event;
+pub mod farming;
 pub mod figure;
 pub mod generation;
+pub mod hierarchical;
 pub mod loadout_builder;
 pub mod lottery;
 pub mod msg;
@@ -32,6 +34,7 @@ pub mod path;
 pub mod ray;
 pub mod recipe;
 pub mod region;
+pub mod replay;
 pub mod spiral;
 pub mod state;
 pub mod states;
@@ -43,5 +46,6 @@ pub mod typed;
 pub mod util;
 pub mod vol;
 pub mod volumes;
+pub mod weather;
 
 pub use loadout_builder::LoadoutBuilder;
@@ -51,14 +51,16 @@ mod tests {
     use crate::{assets, comp::Item};
     #[test]
     fn test_loot_table() {
-        let test = assets::load_expect::<Lottery<String>>("common.loot_table");
+        for table in &["common.loot_table", "common.loot_table_boss"] {
+            let test = assets::load_expect::<Lottery<String>>(table);
 
-        for (_, item) in test.iter() {
-            assert!(
-                assets::load::<Item>(item).is_ok(),
-                "Invalid loot table item '{}'",
-                item
-            );
+            for (_, item) in test.iter() {
+                assert!(
+                    assets::load::<Item>(item).is_ok(),
+                    "Invalid loot table item '{}'",
+                    item
+                );
+            }
         }
     }
 }
@@ -1,6 +1,7 @@
 use crate::{
     comp,
     comp::{Skill, SkillGroupType},
+    sync::Uid,
     terrain::block::Block,
 };
 use serde::{Deserialize, Serialize};
@@ -12,6 +13,8 @@ pub enum ClientMsg {
     Register {
         view_distance: Option<u32>,
         token_or_username: String,
+        /// Password of a local account, for servers without an auth server
+        password: Option<String>,
     },
     RequestCharacterList,
     CreateCharacter {
@@ -25,6 +28,10 @@ pub enum ClientMsg {
     ExitIngame,
     /// Request `ClientState::Spectator` from a registered or ingame state
     Spectate,
+    /// Move the camera of a spectator, which stops it from following anyone
+    SpectatorPos(Vec3<f32>),
+    /// Make the camera of a spectator follow an entity, or stop following
+    SpectatorFollow(Option<Uid>),
     ControllerInputs(comp::ControllerInputs),
     ControlEvent(comp::ControlEvent),
     ControlAction(comp::ControlAction),
@@ -50,3 +57,37 @@ pub enum ClientMsg {
     UnlockSkillGroup(SkillGroupType),
     ServerStats,
 }
+
+impl ClientMsg {
+    /// Name of the message variant, for metrics
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            ClientMsg::Register { .. } => "Register",
+            ClientMsg::RequestCharacterList => "RequestCharacterList",
+            ClientMsg::CreateCharacter { .. } => "CreateCharacter",
+            ClientMsg::DeleteCharacter(_) => "DeleteCharacter",
+            ClientMsg::Character(_) => "Character",
+            ClientMsg::ExitIngame => "ExitIngame",
+            ClientMsg::Spectate => "Spectate",
+            ClientMsg::SpectatorPos(_) => "SpectatorPos",
+            ClientMsg::SpectatorFollow(_) => "SpectatorFollow",
+            ClientMsg::ControllerInputs(_) => "ControllerInputs",
+            ClientMsg::ControlEvent(_) => "ControlEvent",
+            ClientMsg::ControlAction(_) => "ControlAction",
+            ClientMsg::SetViewDistance(_) => "SetViewDistance",
+            ClientMsg::BreakBlock(_) => "BreakBlock",
+            ClientMsg::PlaceBlock(_, _) => "PlaceBlock",
+            ClientMsg::Ping => "Ping",
+            ClientMsg::Pong => "Pong",
+            ClientMsg::ChatMsg(_) => "ChatMsg",
+            ClientMsg::PlayerPhysics { .. } => "PlayerPhysics",
+            ClientMsg::TerrainChunkRequest { .. } => "TerrainChunkRequest",
+            ClientMsg::Disconnect => "Disconnect",
+            ClientMsg::Terminate => "Terminate",
+            ClientMsg::UnlockSkill(_) => "UnlockSkill",
+            ClientMsg::RefundSkill(_) => "RefundSkill",
+            ClientMsg::UnlockSkillGroup(_) => "UnlockSkillGroup",
+            ClientMsg::ServerStats => "ServerStats",
+        }
+    }
+}
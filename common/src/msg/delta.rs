@@ -0,0 +1,440 @@
+//! Compact updates of synced components, encoded against what each client last
+//! received
+//!
+//! The server keeps a [`Baselines`] per client with the last values it sent
+//! through a [`DeltaSyncPackage`], and the client keeps one with the last
+//! values it received. Both only change through the packages themselves (and
+//! entity deletion), so they stay in lockstep over the reliable stream:
+//! positions are sent as small offsets from the baseline and the big
+//! components as patches of their serialized bytes.
+
+use crate::{
+    comp::{self, Ori, Pos, Vel},
+    sync,
+    util::Dir,
+};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use specs::{Entity, World};
+use tracing::warn;
+use vek::*;
+
+/// Steps per block positions are quantized to
+const POS_SCALE: f32 = 64.0;
+/// Steps per block per second velocities are quantized to
+const VEL_SCALE: f32 = 64.0;
+/// Steps per unit orientation components are quantized to
+const ORI_SCALE: f32 = i16::MAX as f32;
+/// Bytes that may be unchanged between two changed ones before a patch starts
+/// a new run for them, as every run costs its offset and length
+const MAX_RUN_GAP: usize = 12;
+
+pub fn quantize_pos(pos: Pos) -> Vec3<i32> { pos.0.map(|e| (e * POS_SCALE).round() as i32) }
+
+pub fn quantize_vel(vel: Vel) -> Vec3<i16> {
+    let max = i16::MAX as f32;
+    vel.0
+        .map(|e| (e * VEL_SCALE).round().max(-max).min(max) as i16)
+}
+
+pub fn quantize_ori(ori: Ori) -> Vec3<i16> { ori.0.map(|e| (e * ORI_SCALE).round() as i16) }
+
+fn dequantize_pos(pos: Vec3<i32>) -> Pos { Pos(pos.map(|e| e as f32 / POS_SCALE)) }
+
+fn dequantize_vel(vel: Vec3<i16>) -> Vel { Vel(vel.map(|e| e as f32 / VEL_SCALE)) }
+
+fn dequantize_ori(ori: Vec3<i16>) -> Ori {
+    Ori(Dir::from_unnormalized(ori.map(|e| e as f32 / ORI_SCALE)).unwrap_or_default())
+}
+
+/// Changes to the physics of an entity, only carrying what changed
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhysicsUpdate {
+    /// Position relative to the baseline, if it's close enough
+    pub pos_offset: Option<Vec3<i16>>,
+    pub pos: Option<Vec3<i32>>,
+    pub vel: Option<Vec3<i16>>,
+    pub ori: Option<Vec3<i16>>,
+}
+
+impl PhysicsUpdate {
+    pub fn is_empty(&self) -> bool { *self == Self::default() }
+}
+
+/// Components large enough to be sent as a patch of their last value
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize_repr, Deserialize_repr)]
+#[repr(u8)]
+pub enum DeltaComp {
+    Stats,
+    Loadout,
+    CharacterState,
+}
+
+impl DeltaComp {
+    /// Name of the component, for metrics
+    pub fn name(self) -> &'static str {
+        match self {
+            DeltaComp::Stats => "Stats",
+            DeltaComp::Loadout => "Loadout",
+            DeltaComp::CharacterState => "CharacterState",
+        }
+    }
+
+    /// Deserialize the component from `bytes` and set it on `entity`
+    fn apply(self, bytes: &[u8], entity: Entity, world: &World) -> Result<(), bincode::Error> {
+        match self {
+            DeltaComp::Stats => {
+                sync::handle_modify(bincode::deserialize::<comp::Stats>(bytes)?, entity, world)
+            },
+            DeltaComp::Loadout => {
+                sync::handle_modify(bincode::deserialize::<comp::Loadout>(bytes)?, entity, world)
+            },
+            DeltaComp::CharacterState => sync::handle_modify(
+                bincode::deserialize::<comp::CharacterState>(bytes)?,
+                entity,
+                world,
+            ),
+        }
+        Ok(())
+    }
+}
+
+/// The changed byte runs between two serialized values
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BytePatch {
+    len: u32,
+    runs: Vec<(u32, Vec<u8>)>,
+}
+
+impl BytePatch {
+    /// The patch turning `old` into `new`
+    pub fn between(old: &[u8], new: &[u8]) -> Self {
+        let mut runs: Vec<(u32, Vec<u8>)> = Vec::new();
+        let mut last_changed = None;
+        for (i, byte) in new.iter().enumerate() {
+            if old.get(i) == Some(byte) {
+                continue;
+            }
+            match (last_changed, runs.last_mut()) {
+                (Some(last), Some((start, run))) if i - last <= MAX_RUN_GAP => {
+                    run.extend_from_slice(&new[*start as usize + run.len()..=i]);
+                },
+                _ => runs.push((i as u32, vec![*byte])),
+            }
+            last_changed = Some(i);
+        }
+        Self {
+            len: new.len() as u32,
+            runs,
+        }
+    }
+
+    /// Apply the patch to `base`, failing if it doesn't fit
+    pub fn apply(&self, base: &[u8]) -> Option<Vec<u8>> {
+        let mut bytes = base.to_vec();
+        bytes.resize(self.len as usize, 0);
+        for (start, run) in &self.runs {
+            bytes
+                .get_mut(*start as usize..*start as usize + run.len())?
+                .copy_from_slice(run);
+        }
+        Some(bytes)
+    }
+
+    /// Bytes the patch takes up serialized
+    fn encoded_len(&self) -> usize {
+        4 + 8
+            + self
+                .runs
+                .iter()
+                .map(|(_, run)| 4 + 8 + run.len())
+                .sum::<usize>()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CompDelta {
+    /// The whole serialized component, replacing the baseline
+    Full(Vec<u8>),
+    Patch(BytePatch),
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DeltaSyncPackage {
+    pub physics: Vec<(u64, PhysicsUpdate)>,
+    pub comps: Vec<(u64, DeltaComp, CompDelta)>,
+}
+
+impl DeltaSyncPackage {
+    pub fn is_empty(&self) -> bool { self.physics.is_empty() && self.comps.is_empty() }
+
+    /// Apply the updates to the entities in `world`, keeping `baselines` up to
+    /// date even for entities that don't exist (anymore)
+    pub fn apply(self, baselines: &mut Baselines, world: &World) {
+        let entity_from_uid = |uid| {
+            world
+                .read_resource::<sync::UidAllocator>()
+                .retrieve_entity_internal(uid)
+        };
+
+        for (uid, update) in self.physics {
+            let (pos, vel, ori) = baselines.decode_physics(uid, &update);
+            if let Some(entity) = entity_from_uid(uid) {
+                if let Some(pos) = pos {
+                    sync::handle_insert(pos, entity, world);
+                }
+                if let Some(vel) = vel {
+                    sync::handle_insert(vel, entity, world);
+                }
+                if let Some(ori) = ori {
+                    sync::handle_insert(ori, entity, world);
+                }
+            }
+        }
+
+        for (uid, kind, delta) in self.comps {
+            match baselines.decode_comp(uid, kind, delta) {
+                Some(bytes) => {
+                    if let Some(entity) = entity_from_uid(uid) {
+                        if let Err(e) = kind.apply(bytes, entity, world) {
+                            warn!(?e, ?kind, "Failed to deserialize a component delta");
+                        }
+                    }
+                },
+                None => warn!(?kind, "Component delta doesn't fit its baseline"),
+            }
+        }
+    }
+}
+
+/// Last physics values sent to (or received by) a client for an entity
+#[derive(Default)]
+struct PhysicsBaseline {
+    pos: Option<Vec3<i32>>,
+    vel: Option<Vec3<i16>>,
+    ori: Option<Vec3<i16>>,
+}
+
+/// The values updates are encoded against, see the module documentation
+#[derive(Default)]
+pub struct Baselines {
+    physics: HashMap<u64, PhysicsBaseline>,
+    comps: HashMap<(u64, DeltaComp), Vec<u8>>,
+}
+
+impl Baselines {
+    /// Encode the quantized physics of an entity, only including what changed
+    /// unless `force` is set
+    pub fn encode_physics(
+        &mut self,
+        uid: u64,
+        pos: Vec3<i32>,
+        vel: Option<Vec3<i16>>,
+        ori: Option<Vec3<i16>>,
+        force: bool,
+    ) -> PhysicsUpdate {
+        let baseline = self.physics.entry(uid).or_default();
+        let mut update = PhysicsUpdate::default();
+
+        if force || baseline.pos != Some(pos) {
+            let offset = baseline.pos.and_then(|base| {
+                let offset = pos - base;
+                let fits = offset.map(|e| e.abs() <= i16::MAX as i32);
+                if fits.reduce_and() {
+                    Some(offset.map(|e| e as i16))
+                } else {
+                    None
+                }
+            });
+            match offset {
+                Some(offset) => update.pos_offset = Some(offset),
+                None => update.pos = Some(pos),
+            }
+            baseline.pos = Some(pos);
+        }
+        // A removed velocity or orientation is forgotten so that it is sent again once
+        // it's back
+        if force || baseline.vel != vel {
+            update.vel = vel;
+            baseline.vel = vel;
+        }
+        if force || baseline.ori != ori {
+            update.ori = ori;
+            baseline.ori = ori;
+        }
+
+        update
+    }
+
+    pub fn decode_physics(
+        &mut self,
+        uid: u64,
+        update: &PhysicsUpdate,
+    ) -> (Option<Pos>, Option<Vel>, Option<Ori>) {
+        let baseline = self.physics.entry(uid).or_default();
+
+        let pos = match (update.pos, update.pos_offset, baseline.pos) {
+            (Some(pos), _, _) => Some(pos),
+            (None, Some(offset), Some(base)) => Some(base + offset.map(i32::from)),
+            (None, Some(_), None) => {
+                warn!("Position offset for an entity without a baseline");
+                None
+            },
+            (None, None, _) => None,
+        };
+        if pos.is_some() {
+            baseline.pos = pos;
+        }
+        if update.vel.is_some() {
+            baseline.vel = update.vel;
+        }
+        if update.ori.is_some() {
+            baseline.ori = update.ori;
+        }
+
+        (
+            pos.map(dequantize_pos),
+            update.vel.map(dequantize_vel),
+            update.ori.map(dequantize_ori),
+        )
+    }
+
+    /// Encode a serialized component against the last one sent, `None` if it
+    /// didn't change
+    pub fn encode_comp(&mut self, uid: u64, kind: DeltaComp, bytes: &[u8]) -> Option<CompDelta> {
+        let delta = match self.comps.get(&(uid, kind)) {
+            Some(base) if base.as_slice() == bytes => return None,
+            Some(base) => {
+                let patch = BytePatch::between(base, bytes);
+                if patch.encoded_len() < 8 + bytes.len() {
+                    CompDelta::Patch(patch)
+                } else {
+                    CompDelta::Full(bytes.to_vec())
+                }
+            },
+            None => CompDelta::Full(bytes.to_vec()),
+        };
+        self.comps.insert((uid, kind), bytes.to_vec());
+        Some(delta)
+    }
+
+    /// The serialized component a delta results in, `None` if it doesn't fit
+    /// the baseline
+    pub fn decode_comp(&mut self, uid: u64, kind: DeltaComp, delta: CompDelta) -> Option<&[u8]> {
+        let bytes = match delta {
+            CompDelta::Full(bytes) => bytes,
+            CompDelta::Patch(patch) => patch.apply(self.comps.get(&(uid, kind))?)?,
+        };
+        let baseline = self.comps.entry((uid, kind)).or_default();
+        *baseline = bytes;
+        Some(baseline)
+    }
+
+    /// Drop the baselines of a deleted entity
+    pub fn forget(&mut self, uid: u64) {
+        self.physics.remove(&uid);
+        self.comps.retain(|(comp_uid, _), _| *comp_uid != uid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_patch() {
+        let old = (0..100).collect::<Vec<u8>>();
+        let mut new = old.clone();
+        new[3] = 200;
+        new[10] = 201;
+        new[60] = 202;
+        new.truncate(80);
+
+        let patch = BytePatch::between(&old, &new);
+        // Nearby changes share a run
+        assert_eq!(patch.runs.len(), 2);
+        assert_eq!(patch.apply(&old).as_ref(), Some(&new));
+
+        // Growing
+        let patch = BytePatch::between(&new, &old);
+        assert_eq!(patch.apply(&new).as_ref(), Some(&old));
+        assert_eq!(patch.apply(&[]).map(|b| b.len()), Some(old.len()));
+    }
+
+    #[test]
+    fn test_comp_delta() {
+        let (mut server, mut client) = (Baselines::default(), Baselines::default());
+        let old = vec![1; 64];
+        let mut new = old.clone();
+        new[5] = 2;
+
+        let full = server.encode_comp(1, DeltaComp::Stats, &old).unwrap();
+        assert!(matches!(full, CompDelta::Full(_)));
+        assert_eq!(
+            client.decode_comp(1, DeltaComp::Stats, full),
+            Some(&old[..])
+        );
+
+        assert_eq!(server.encode_comp(1, DeltaComp::Stats, &old), None);
+
+        let patch = server.encode_comp(1, DeltaComp::Stats, &new).unwrap();
+        assert!(matches!(patch, CompDelta::Patch(_)));
+        assert_eq!(
+            client.decode_comp(1, DeltaComp::Stats, patch),
+            Some(&new[..])
+        );
+
+        // After forgetting an entity it's sent whole again
+        server.forget(1);
+        client.forget(1);
+        let full = server.encode_comp(1, DeltaComp::Stats, &new).unwrap();
+        assert!(matches!(full, CompDelta::Full(_)));
+        assert_eq!(
+            client.decode_comp(1, DeltaComp::Stats, full),
+            Some(&new[..])
+        );
+    }
+
+    #[test]
+    fn test_physics_delta() {
+        let (mut server, mut client) = (Baselines::default(), Baselines::default());
+        let pos = Pos(Vec3::new(1000.3, 2000.7, 100.0));
+        let vel = Vel(Vec3::new(1.0, -2.5, 0.0));
+
+        let update =
+            server.encode_physics(1, quantize_pos(pos), Some(quantize_vel(vel)), None, false);
+        assert!(update.pos.is_some() && update.pos_offset.is_none());
+        let (decoded_pos, decoded_vel, _) = client.decode_physics(1, &update);
+        assert!(decoded_pos.unwrap().0.distance(pos.0) < 1.0 / POS_SCALE);
+        assert_eq!(decoded_vel, Some(vel));
+
+        // Small moves are sent as offsets, and nothing is sent for what didn't change
+        let moved = Pos(pos.0 + Vec3::new(0.5, 0.0, -0.25));
+        let update =
+            server.encode_physics(1, quantize_pos(moved), Some(quantize_vel(vel)), None, false);
+        assert!(update.pos.is_none() && update.pos_offset.is_some());
+        assert!(update.vel.is_none());
+        let (decoded_pos, decoded_vel, _) = client.decode_physics(1, &update);
+        assert!(decoded_pos.unwrap().0.distance(moved.0) < 1.0 / POS_SCALE);
+        assert_eq!(decoded_vel, None);
+
+        assert!(
+            server
+                .encode_physics(1, quantize_pos(moved), Some(quantize_vel(vel)), None, false)
+                .is_empty()
+        );
+        assert!(
+            !server
+                .encode_physics(1, quantize_pos(moved), Some(quantize_vel(vel)), None, true)
+                .is_empty()
+        );
+
+        // Teleports don't fit an offset
+        let far = Pos(moved.0 + Vec3::new(5000.0, 0.0, 0.0));
+        let update = server.encode_physics(1, quantize_pos(far), None, None, false);
+        assert!(update.pos.is_some());
+        let (decoded_pos, _, _) = client.decode_physics(1, &update);
+        assert!(decoded_pos.unwrap().0.distance(far.0) < 1.0 / POS_SCALE);
+    }
+}
@@ -58,6 +58,62 @@ sum_type! {
         Ori(PhantomData<comp::Ori>),
     }
 }
+impl EcsCompPacket {
+    /// Name of the component, for metrics
+    pub fn comp_name(&self) -> &'static str {
+        match self {
+            EcsCompPacket::Body(_) => "Body",
+            EcsCompPacket::Player(_) => "Player",
+            EcsCompPacket::CanBuild(_) => "CanBuild",
+            EcsCompPacket::Stats(_) => "Stats",
+            EcsCompPacket::Energy(_) => "Energy",
+            EcsCompPacket::LightEmitter(_) => "LightEmitter",
+            EcsCompPacket::Item(_) => "Item",
+            EcsCompPacket::Scale(_) => "Scale",
+            EcsCompPacket::Group(_) => "Group",
+            EcsCompPacket::MountState(_) => "MountState",
+            EcsCompPacket::Mounting(_) => "Mounting",
+            EcsCompPacket::Mass(_) => "Mass",
+            EcsCompPacket::Collider(_) => "Collider",
+            EcsCompPacket::Gravity(_) => "Gravity",
+            EcsCompPacket::Sticky(_) => "Sticky",
+            EcsCompPacket::Loadout(_) => "Loadout",
+            EcsCompPacket::CharacterState(_) => "CharacterState",
+            EcsCompPacket::Pos(_) => "Pos",
+            EcsCompPacket::Vel(_) => "Vel",
+            EcsCompPacket::Ori(_) => "Ori",
+        }
+    }
+}
+
+impl EcsCompPhantom {
+    /// Name of the component, for metrics
+    pub fn comp_name(&self) -> &'static str {
+        match self {
+            EcsCompPhantom::Body(_) => "Body",
+            EcsCompPhantom::Player(_) => "Player",
+            EcsCompPhantom::CanBuild(_) => "CanBuild",
+            EcsCompPhantom::Stats(_) => "Stats",
+            EcsCompPhantom::Energy(_) => "Energy",
+            EcsCompPhantom::LightEmitter(_) => "LightEmitter",
+            EcsCompPhantom::Item(_) => "Item",
+            EcsCompPhantom::Scale(_) => "Scale",
+            EcsCompPhantom::Group(_) => "Group",
+            EcsCompPhantom::MountState(_) => "MountState",
+            EcsCompPhantom::Mounting(_) => "Mounting",
+            EcsCompPhantom::Mass(_) => "Mass",
+            EcsCompPhantom::Collider(_) => "Collider",
+            EcsCompPhantom::Gravity(_) => "Gravity",
+            EcsCompPhantom::Sticky(_) => "Sticky",
+            EcsCompPhantom::Loadout(_) => "Loadout",
+            EcsCompPhantom::CharacterState(_) => "CharacterState",
+            EcsCompPhantom::Pos(_) => "Pos",
+            EcsCompPhantom::Vel(_) => "Vel",
+            EcsCompPhantom::Ori(_) => "Ori",
+        }
+    }
+}
+
 impl sync::CompPacket for EcsCompPacket {
     type Phantom = EcsCompPhantom;
 
@@ -1,14 +1,16 @@
 pub mod client;
+pub mod delta;
 pub mod ecs_packet;
 pub mod server;
 
 // Reexports
 pub use self::{
     client::ClientMsg,
+    delta::DeltaSyncPackage,
     ecs_packet::EcsCompPacket,
     server::{
-        CharacterInfo, InviteAnswer, Notification, PlayerInfo, PlayerListUpdate, RegisterError,
-        RequestStateError, ServerInfo, ServerMsg, ServerStats,
+        CharacterInfo, InviteAnswer, Notification, PlayerInfo, PlayerListUpdate, Poi, PoiKind,
+        RegisterError, RequestStateError, ServerInfo, ServerMsg, ServerStats, ServerStatus,
     },
 };
 use serde::{Deserialize, Serialize};
@@ -22,6 +24,35 @@ pub enum ClientState {
     Character,
 }
 
+/// Version of the game protocol, to be increased whenever messages change in a
+/// way that the previous version can't understand
+pub const PROTOCOL_VERSION: u32 = 4;
+/// Oldest version of the game protocol servers still accept clients with
+pub const OLDEST_SUPPORTED_PROTOCOL_VERSION: u32 = 4;
+
+/// The versions of the game protocol a server accepts, sent in answer to the
+/// client's version before anything else. Its layout must never change so that
+/// every client can read it, however old.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolVersions {
+    pub current: u32,
+    pub oldest_supported: u32,
+}
+
+impl ProtocolVersions {
+    /// The versions this build accepts
+    pub fn supported() -> Self {
+        Self {
+            current: PROTOCOL_VERSION,
+            oldest_supported: OLDEST_SUPPORTED_PROTOCOL_VERSION,
+        }
+    }
+
+    pub fn supports(&self, version: u32) -> bool {
+        (self.oldest_supported..=self.current).contains(&version)
+    }
+}
+
 pub const MAX_BYTES_CHAT_MSG: usize = 256;
 
 pub enum ChatMsgValidationError {
@@ -36,3 +67,21 @@ pub fn validate_chat_msg(msg: &str) -> Result<(), ChatMsgValidationError> {
         Err(ChatMsgValidationError::TooLong)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_versions() {
+        let versions = ProtocolVersions {
+            current: 5,
+            oldest_supported: 3,
+        };
+        assert!(!versions.supports(2));
+        assert!(versions.supports(3));
+        assert!(versions.supports(5));
+        assert!(!versions.supports(6));
+        assert!(ProtocolVersions::supported().supports(PROTOCOL_VERSION));
+    }
+}
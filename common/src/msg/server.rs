@@ -1,4 +1,4 @@
-use super::{ClientState, EcsCompPacket};
+use super::{ClientState, DeltaSyncPackage, EcsCompPacket};
 use crate::{
     character::CharacterItem,
     comp,
@@ -7,10 +7,12 @@ use crate::{
     state, sync,
     sync::Uid,
     terrain::{Block, TerrainChunk},
+    weather::WeatherGrid,
 };
 use authc::AuthClientError;
 use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use vek::*;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +22,9 @@ pub struct ServerInfo {
     pub git_hash: String,
     pub git_date: String,
     pub auth_provider: Option<String>,
+    /// Whether players log in with the password of an account kept by the
+    /// server itself
+    pub local_accounts: bool,
 }
 
 /// Inform the client of updates to the player list.
@@ -167,6 +172,26 @@ pub struct WorldMapMsg {
     /// angles, or that we don't need as much precision as we currently have
     /// (256 possible angles).
     pub horizons: [(Vec<u8>, Vec<u8>); 2],
+    /// Points of interest (towns, dungeons, ...) to show on the map
+    pub pois: Vec<Poi>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PoiKind {
+    Town,
+    Dungeon,
+    Castle,
+}
+
+/// A point of interest shown on the world map
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Poi {
+    pub kind: PoiKind,
+    pub name: String,
+    /// Position of the center of the site, in world coordinates
+    pub wpos: Vec2<i32>,
+    /// How dangerous the site is, only known for dungeons
+    pub difficulty: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -179,6 +204,23 @@ pub enum InviteAnswer {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerStats {
     pub player_count: u64,
+    /// How long the server took to run its last tick
+    pub tick_time: Duration,
+}
+
+/// What a server tells anyone who asks for its status, without them having to
+/// connect as a client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerStatus {
+    /// Name, description (the MOTD), version and auth server of the server
+    pub info: ServerInfo,
+    pub player_count: u32,
+    pub max_players: u32,
+    pub world_seed: u32,
+    /// Size of the world, in chunks
+    pub world_size: Vec2<u32>,
+    /// Seconds since the server was started
+    pub uptime: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -193,6 +235,7 @@ pub enum ServerMsg {
         entity_package: sync::EntityPackage<EcsCompPacket>,
         server_info: ServerInfo,
         time_of_day: state::TimeOfDay,
+        weather: WeatherGrid,
         max_group_size: u32,
         world_map: WorldMapMsg,
         recipe_book: RecipeBook,
@@ -230,12 +273,31 @@ pub enum ServerMsg {
     /// formatting the message and turning it into a speech bubble.
     ChatMsg(comp::ChatMsg),
     SetPlayerEntity(Uid),
+    /// Where the camera of a spectating client is and whom it follows, sent
+    /// when spectating starts and whenever the server moves the camera
+    SpectatorCamera {
+        pos: Vec3<f32>,
+        follow: Option<Uid>,
+    },
     TimeOfDay(state::TimeOfDay),
+    /// The weather over the whole map, sent at a low frequency
+    WeatherUpdate(WeatherGrid),
     EntitySync(sync::EntitySyncPackage),
     CompSync(sync::CompSyncPackage<EcsCompPacket>),
+    /// Physics and large component updates encoded against what the client
+    /// last received
+    DeltaSync(DeltaSyncPackage),
     CreateEntity(sync::EntityPackage<EcsCompPacket>),
     DeleteEntity(Uid),
     InventoryUpdate(comp::Inventory, comp::InventoryUpdateEvent),
+    /// The contents of the container the client has open, sent when it is
+    /// opened and whenever it changes
+    ContainerUpdate {
+        pos: Vec3<i32>,
+        inventory: comp::Inventory,
+    },
+    /// The container the client had open can no longer be accessed
+    ContainerClosed,
     TerrainChunkUpdate {
         key: Vec2<i32>,
         chunk: Result<Box<TerrainChunk>, ()>,
@@ -246,11 +308,57 @@ pub enum ServerMsg {
     TooManyPlayers,
     /// Send a popup notification such as "Waypoint Saved"
     Notification(Notification),
+    /// The position of the player's saved waypoint
+    WaypointUpdate(Vec3<f32>),
     SetViewDistance(u32),
     Outcomes(Vec<Outcome>),
     ServerStats(ServerStats),
 }
 
+impl ServerMsg {
+    /// Name of the message variant, for metrics
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            ServerMsg::InitialSync { .. } => "InitialSync",
+            ServerMsg::CharacterDataLoadError(_) => "CharacterDataLoadError",
+            ServerMsg::CharacterListUpdate(_) => "CharacterListUpdate",
+            ServerMsg::CharacterActionError(_) => "CharacterActionError",
+            ServerMsg::PlayerListUpdate(_) => "PlayerListUpdate",
+            ServerMsg::GroupUpdate(_) => "GroupUpdate",
+            ServerMsg::GroupInvite { .. } => "GroupInvite",
+            ServerMsg::InvitePending(_) => "InvitePending",
+            ServerMsg::InviteComplete { .. } => "InviteComplete",
+            ServerMsg::StateAnswer(_) => "StateAnswer",
+            ServerMsg::ExitIngameCleanup => "ExitIngameCleanup",
+            ServerMsg::Ping => "Ping",
+            ServerMsg::Pong => "Pong",
+            ServerMsg::ChatMsg(_) => "ChatMsg",
+            ServerMsg::SetPlayerEntity(_) => "SetPlayerEntity",
+            ServerMsg::SpectatorCamera { .. } => "SpectatorCamera",
+            ServerMsg::TimeOfDay(_) => "TimeOfDay",
+            ServerMsg::WeatherUpdate(_) => "WeatherUpdate",
+            ServerMsg::EntitySync(_) => "EntitySync",
+            ServerMsg::CompSync(_) => "CompSync",
+            ServerMsg::DeltaSync(_) => "DeltaSync",
+            ServerMsg::CreateEntity(_) => "CreateEntity",
+            ServerMsg::DeleteEntity(_) => "DeleteEntity",
+            ServerMsg::InventoryUpdate(_, _) => "InventoryUpdate",
+            ServerMsg::ContainerUpdate { .. } => "ContainerUpdate",
+            ServerMsg::ContainerClosed => "ContainerClosed",
+            ServerMsg::TerrainChunkUpdate { .. } => "TerrainChunkUpdate",
+            ServerMsg::TerrainBlockUpdates(_) => "TerrainBlockUpdates",
+            ServerMsg::Disconnect => "Disconnect",
+            ServerMsg::Shutdown => "Shutdown",
+            ServerMsg::TooManyPlayers => "TooManyPlayers",
+            ServerMsg::Notification(_) => "Notification",
+            ServerMsg::WaypointUpdate(_) => "WaypointUpdate",
+            ServerMsg::SetViewDistance(_) => "SetViewDistance",
+            ServerMsg::Outcomes(_) => "Outcomes",
+            ServerMsg::ServerStats(_) => "ServerStats",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum RequestStateError {
     RegisterDenied(RegisterError),
@@ -1,15 +1,83 @@
 use crate::{
     assets::{self, Asset},
     comp::{Inventory, Item},
+    terrain::{Block, BlockKind},
+    util::deserialize_duration,
+    vol::ReadVol,
 };
 use hashbrown::HashMap;
-use serde::{Deserialize, Serialize};
-use std::{fs::File, io::BufReader, sync::Arc};
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use std::{fmt, fs::File, io::BufReader, sync::Arc, time::Duration};
+use vek::*;
+
+/// The maximum distance (in blocks, along each axis) from which a crafting
+/// station can be used.
+pub const MAX_CRAFTING_STATION_RANGE: i32 = 4;
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CraftingStation {
+    Anvil,
+    Cauldron,
+    Workbench,
+}
+
+impl CraftingStation {
+    pub fn from_block_kind(kind: BlockKind) -> Option<Self> {
+        match kind {
+            BlockKind::Anvil => Some(CraftingStation::Anvil),
+            BlockKind::Cauldron => Some(CraftingStation::Cauldron),
+            BlockKind::CraftingBench => Some(CraftingStation::Workbench),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CraftingStation::Anvil => "Anvil",
+            CraftingStation::Cauldron => "Cauldron",
+            CraftingStation::Workbench => "Workbench",
+        }
+    }
+
+    /// Find every kind of crafting station within reach of `pos`
+    pub fn find_nearby<V: ReadVol<Vox = Block>>(terrain: &V, pos: Vec3<f32>) -> Vec<Self> {
+        let center = pos.map(|e| e.floor() as i32);
+        let range = -MAX_CRAFTING_STATION_RANGE..=MAX_CRAFTING_STATION_RANGE;
+
+        let mut stations = Vec::new();
+        for x in range.clone() {
+            for y in range.clone() {
+                for z in range.clone() {
+                    if let Some(station) = terrain
+                        .get(center + Vec3::new(x, y, z))
+                        .ok()
+                        .and_then(|block| Self::from_block_kind(block.kind()))
+                    {
+                        if !stations.contains(&station) {
+                            stations.push(station);
+                        }
+                    }
+                }
+            }
+        }
+        stations
+    }
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Recipe {
     pub output: (Item, usize),
     pub inputs: Vec<(Item, usize)>,
+    /// Items that must be in the inventory, but aren't consumed by the recipe
+    pub tools: Vec<Item>,
+    /// Crafting station that must be near the player
+    pub station: Option<CraftingStation>,
+    pub craft_time: Duration,
+    /// Repair a worn item matching the output instead of crafting a new one
+    pub repair: bool,
 }
 
 #[allow(clippy::type_complexity)]
@@ -19,6 +87,15 @@ impl Recipe {
         &self,
         inv: &mut Inventory,
     ) -> Result<Option<(Item, usize)>, Vec<(&Item, usize)>> {
+        let repair_target = if self.repair {
+            Some(
+                self.repair_target(inv)
+                    .ok_or_else(|| vec![(&self.output.0, 1)])?,
+            )
+        } else {
+            None
+        };
+
         // Get ingredient cells from inventory,
         inv.contains_ingredients(self)?
             .into_iter()
@@ -29,6 +106,13 @@ impl Recipe {
                 })
             });
 
+        if let Some(slot) = repair_target {
+            if let Some(item) = inv.slots[slot].as_mut() {
+                item.repair();
+            }
+            return Ok(None);
+        }
+
         for i in 0..self.output.1 {
             if let Some(item) = inv.push(self.output.0.clone()) {
                 return Ok(Some((item, self.output.1 - i)));
@@ -41,6 +125,32 @@ impl Recipe {
     pub fn inputs(&self) -> impl ExactSizeIterator<Item = (&Item, usize)> {
         self.inputs.iter().map(|(item, amount)| (item, *amount))
     }
+
+    pub fn tools(&self) -> impl ExactSizeIterator<Item = &Item> { self.tools.iter() }
+
+    /// For repair recipes, the first inventory slot holding a worn item that
+    /// the recipe can repair
+    pub fn repair_target(&self, inv: &Inventory) -> Option<usize> {
+        inv.slots().iter().position(|slot| {
+            slot.as_ref().map_or(false, |item| {
+                item.is_worn() && item.superficially_eq(&self.output.0)
+            })
+        })
+    }
+
+    /// Whether the required crafting station (if any) is among `stations`
+    pub fn has_station(&self, stations: &[CraftingStation]) -> bool {
+        self.station
+            .map_or(true, |station| stations.contains(&station))
+    }
+
+    /// Whether the recipe can be performed with the given inventory and
+    /// nearby crafting stations
+    pub fn is_available(&self, inv: &Inventory, stations: &[CraftingStation]) -> bool {
+        self.has_station(stations)
+            && inv.contains_ingredients(self).is_ok()
+            && (!self.repair || self.repair_target(inv).is_some())
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -53,47 +163,200 @@ impl RecipeBook {
 
     pub fn iter(&self) -> impl ExactSizeIterator<Item = (&String, &Recipe)> { self.recipes.iter() }
 
-    pub fn get_available(&self, inv: &Inventory) -> Vec<(String, Recipe)> {
+    /// Get the recipes that can be performed with the given inventory and
+    /// nearby crafting stations (see [`CraftingStation::find_nearby`])
+    pub fn get_available(
+        &self,
+        inv: &Inventory,
+        stations: &[CraftingStation],
+    ) -> Vec<(String, Recipe)> {
         self.recipes
             .iter()
-            .filter(|(_, recipe)| inv.contains_ingredients(recipe).is_ok())
+            .filter(|(_, recipe)| recipe.is_available(inv, stations))
             .map(|(name, recipe)| (name.clone(), recipe.clone()))
             .collect()
     }
 }
 
+/// A recipe as written in the recipe book, either as the legacy
+/// `((output, amount), [(input, amount)])` tuple or as a struct that may also
+/// specify tools, a crafting station, a craft time and whether it repairs the
+/// output.
+#[derive(Deserialize)]
+struct RawRecipe {
+    output: (String, usize),
+    inputs: Vec<(String, usize)>,
+    #[serde(default)]
+    tools: Vec<String>,
+    #[serde(default)]
+    station: Option<CraftingStation>,
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    craft_time: Duration,
+    #[serde(default)]
+    repair: bool,
+}
+
+struct RawRecipeDef(RawRecipe);
+
+impl<'de> Deserialize<'de> for RawRecipeDef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RawRecipeVisitor;
+
+        impl<'de> Visitor<'de> for RawRecipeVisitor {
+            type Value = RawRecipeDef;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a recipe tuple or struct")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let output = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let inputs = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                Ok(RawRecipeDef(RawRecipe {
+                    output,
+                    inputs,
+                    tools: Vec::new(),
+                    station: None,
+                    craft_time: Duration::default(),
+                    repair: false,
+                }))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                RawRecipe::deserialize(de::value::MapAccessDeserializer::new(map)).map(RawRecipeDef)
+            }
+        }
+
+        deserializer.deserialize_any(RawRecipeVisitor)
+    }
+}
+
+fn load_item(specifier: &str) -> Result<Item, assets::Error> {
+    Ok((&*assets::load::<Item>(specifier)?).clone())
+}
+
 impl Asset for RecipeBook {
     const ENDINGS: &'static [&'static str] = &["ron"];
 
     fn parse(buf_reader: BufReader<File>) -> Result<Self, assets::Error> {
-        ron::de::from_reader::<
-            BufReader<File>,
-            HashMap<String, ((String, usize), Vec<(String, usize)>)>,
-        >(buf_reader)
-        .map_err(assets::Error::parse_error)
-        .and_then(|recipes| {
-            Ok(RecipeBook {
-                recipes: recipes
-                    .into_iter()
-                    .map::<Result<(String, Recipe), assets::Error>, _>(
-                        |(name, ((output, amount), inputs))| {
-                            Ok((name, Recipe {
-                                output: ((&*assets::load::<Item>(&output)?).clone(), amount),
-                                inputs: inputs
-                                    .into_iter()
-                                    .map::<Result<(Item, usize), assets::Error>, _>(
-                                        |(name, amount)| {
-                                            Ok(((&*assets::load::<Item>(&name)?).clone(), amount))
-                                        },
-                                    )
-                                    .collect::<Result<_, _>>()?,
-                            }))
-                        },
-                    )
-                    .collect::<Result<_, _>>()?,
+        ron::de::from_reader::<BufReader<File>, HashMap<String, RawRecipeDef>>(buf_reader)
+            .map_err(assets::Error::parse_error)
+            .and_then(|recipes| {
+                Ok(RecipeBook {
+                    recipes: recipes
+                        .into_iter()
+                        .map::<Result<(String, Recipe), assets::Error>, _>(
+                            |(name, RawRecipeDef(raw))| {
+                                Ok((name, Recipe {
+                                    output: (load_item(&raw.output.0)?, raw.output.1),
+                                    inputs: raw
+                                        .inputs
+                                        .iter()
+                                        .map::<Result<(Item, usize), assets::Error>, _>(
+                                            |(name, amount)| Ok((load_item(name)?, *amount)),
+                                        )
+                                        .collect::<Result<_, _>>()?,
+                                    tools: raw
+                                        .tools
+                                        .iter()
+                                        .map(|name| load_item(name))
+                                        .collect::<Result<_, _>>()?,
+                                    station: raw.station,
+                                    craft_time: raw.craft_time,
+                                    repair: raw.repair,
+                                }))
+                            },
+                        )
+                        .collect::<Result<_, _>>()?,
+                })
             })
-        })
     }
 }
 
 pub fn default_recipe_book() -> Arc<RecipeBook> { assets::load_expect("common.recipe_book") }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recipe_formats() {
+        let recipes: HashMap<String, RawRecipeDef> = ron::de::from_str(
+            r#"{
+                "legacy": (("a", 1), [("b", 2), ("c", 0)]),
+                "full": (
+                    output: ("a", 2),
+                    inputs: [("b", 1)],
+                    tools: ["c"],
+                    station: Some(Anvil),
+                    craft_time: 1500,
+                ),
+            }"#,
+        )
+        .expect("Failed to parse recipes");
+
+        let legacy = &recipes["legacy"].0;
+        assert_eq!(legacy.output, ("a".to_string(), 1));
+        assert_eq!(legacy.inputs.len(), 2);
+        assert!(legacy.tools.is_empty());
+        assert_eq!(legacy.station, None);
+        assert_eq!(legacy.craft_time, Duration::default());
+
+        let full = &recipes["full"].0;
+        assert_eq!(full.output, ("a".to_string(), 2));
+        assert_eq!(full.tools, vec!["c".to_string()]);
+        assert_eq!(full.station, Some(CraftingStation::Anvil));
+        assert_eq!(full.craft_time, Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_repair_recipe() {
+        let book = default_recipe_book();
+        let recipe = book
+            .get("repair_sword")
+            .expect("Missing sword repair recipe");
+        assert!(recipe.repair);
+
+        let mut sword = load_item("common.items.weapons.sword.starter_sword").unwrap();
+        let mut stones = load_item("common.items.crafting_ing.stones").unwrap();
+        stones.set_amount(5).unwrap();
+        let mut inv = Inventory {
+            slots: vec![None; 8],
+            amount: 0,
+        };
+        inv.push(stones);
+        inv.push(load_item("common.items.ore.veloritefrag").unwrap());
+        inv.push(load_item("common.items.crafting_tools.craftsman_hammer").unwrap());
+        inv.push(sword.clone());
+
+        // Nothing to repair yet
+        assert!(!recipe.is_available(&inv, &[CraftingStation::Anvil]));
+
+        assert!(sword.wear(sword.durability().unwrap().max()));
+        assert!(sword.is_broken());
+        inv.slots[3] = Some(sword);
+        assert!(!recipe.is_available(&inv, &[]));
+        assert!(recipe.is_available(&inv, &[CraftingStation::Anvil]));
+
+        assert_eq!(recipe.perform(&mut inv), Ok(None));
+        let sword = inv.get(3).expect("The repaired item should stay in place");
+        assert!(!sword.is_worn());
+        // The materials were consumed, the tool wasn't
+        assert!(inv.get(0).is_none());
+        assert!(inv.get(2).is_some());
+    }
+
+    #[test]
+    fn test_recipe_book() {
+        let book = default_recipe_book();
+        assert!(book.get("crafting_hammer").is_some());
+        assert!(
+            book.iter()
+                .any(|(_, recipe)| recipe.station.is_some() || !recipe.tools.is_empty())
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! The file format of replays: recordings of the messages a server sent to a
+//! client
+//!
+//! A replay starts with a header naming the format and protocol version it was
+//! recorded with, followed by frames of a timestamp and a `ServerMsg`, each
+//! prefixed with its length so that a recording cut short by a crash can still
+//! be read up to its last complete frame.
+
+use crate::msg::{ServerMsg, PROTOCOL_VERSION};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
+
+const MAGIC: &[u8; 4] = b"VRPL";
+/// Version of the replay format, to be increased whenever the header or the
+/// framing changes
+pub const REPLAY_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Bincode(bincode::Error),
+    /// The file doesn't start like a replay
+    NotAReplay,
+    UnsupportedFormat(u32),
+    /// The recording reached the size it was limited to
+    TooLarge,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "{}", e),
+            ReplayError::Bincode(e) => write!(f, "Invalid frame: {}", e),
+            ReplayError::NotAReplay => write!(f, "Not a replay"),
+            ReplayError::UnsupportedFormat(version) => write!(
+                f,
+                "Replay format version {} isn't supported, expected {}",
+                version, REPLAY_FORMAT_VERSION
+            ),
+            ReplayError::TooLarge => write!(f, "The replay reached its maximum size"),
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self { ReplayError::Io(err) }
+}
+
+impl From<bincode::Error> for ReplayError {
+    fn from(err: bincode::Error) -> Self { ReplayError::Bincode(err) }
+}
+
+/// Which messages are recorded
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplayFilter {
+    /// Everything sent to the client
+    All,
+    /// Only what it takes to rebuild the world the client saw: entities, their
+    /// components, outcomes and terrain
+    Sync,
+}
+
+impl Default for ReplayFilter {
+    fn default() -> Self { ReplayFilter::All }
+}
+
+impl ReplayFilter {
+    pub fn records(self, msg: &ServerMsg) -> bool {
+        match self {
+            ReplayFilter::All => true,
+            ReplayFilter::Sync => matches!(
+                msg,
+                ServerMsg::InitialSync { .. }
+                    | ServerMsg::StateAnswer(_)
+                    | ServerMsg::ExitIngameCleanup
+                    | ServerMsg::SetPlayerEntity(_)
+                    | ServerMsg::SpectatorCamera { .. }
+                    | ServerMsg::TimeOfDay(_)
+                    | ServerMsg::WeatherUpdate(_)
+                    | ServerMsg::EntitySync(_)
+                    | ServerMsg::CompSync(_)
+                    | ServerMsg::DeltaSync(_)
+                    | ServerMsg::CreateEntity(_)
+                    | ServerMsg::DeleteEntity(_)
+                    | ServerMsg::InventoryUpdate(..)
+                    | ServerMsg::TerrainChunkUpdate { .. }
+                    | ServerMsg::TerrainBlockUpdates(_)
+                    | ServerMsg::Outcomes(_)
+            ),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct FrameRef<'a> {
+    time: f64,
+    msg: &'a ServerMsg,
+}
+
+/// A message and when it was sent, in seconds since the recording started
+#[derive(Clone, Debug, Deserialize)]
+pub struct ReplayFrame {
+    pub time: f64,
+    pub msg: ServerMsg,
+}
+
+pub struct ReplayWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> ReplayWriter<W> {
+    /// Start a replay, writing its header
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&REPLAY_FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&PROTOCOL_VERSION.to_le_bytes())?;
+        Ok(Self { writer })
+    }
+
+    /// Carry on with a replay whose header has already been written, e.g. by a
+    /// writer that has been taken apart with `into_inner`
+    pub fn resume(writer: W) -> Self { Self { writer } }
+
+    /// Write a frame, returning its size in bytes
+    pub fn record(&mut self, time: f64, msg: &ServerMsg) -> Result<usize, ReplayError> {
+        let frame = bincode::serialize(&FrameRef { time, msg })?;
+        self.writer.write_all(&(frame.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&frame)?;
+        Ok(4 + frame.len())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> { self.writer.flush() }
+
+    pub fn into_inner(self) -> W { self.writer }
+}
+
+pub struct ReplayReader<R: Read> {
+    reader: R,
+    protocol_version: u32,
+}
+
+impl<R: Read> ReplayReader<R> {
+    /// Open a replay, checking its header
+    pub fn new(mut reader: R) -> Result<Self, ReplayError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+        let format_version = read_u32(&mut reader)?;
+        if format_version != REPLAY_FORMAT_VERSION {
+            return Err(ReplayError::UnsupportedFormat(format_version));
+        }
+        let protocol_version = read_u32(&mut reader)?;
+        Ok(Self {
+            reader,
+            protocol_version,
+        })
+    }
+
+    /// Version of the game protocol the messages were sent with, they can only
+    /// be read by a build speaking it
+    pub fn protocol_version(&self) -> u32 { self.protocol_version }
+
+    /// The next frame, `None` at the end of the replay or after its last
+    /// complete frame
+    pub fn next_frame(&mut self) -> Result<Option<ReplayFrame>, ReplayError> {
+        let len = match read_u32(&mut self.reader) {
+            Ok(len) => len,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut frame = vec![0; len as usize];
+        match self.reader.read_exact(&mut frame) {
+            Ok(()) => Ok(Some(bincode::deserialize(&frame)?)),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::Uid;
+
+    #[test]
+    fn test_replay_roundtrip() {
+        let mut writer = ReplayWriter::new(Vec::new()).unwrap();
+        writer.record(0.0, &ServerMsg::Ping).unwrap();
+        writer
+            .record(1.5, &ServerMsg::DeleteEntity(Uid(7)))
+            .unwrap();
+        let mut bytes = writer.into_inner();
+
+        // A frame cut short is left out
+        let complete = bytes.len();
+        let mut cut = ReplayWriter::resume(Vec::new());
+        cut.record(2.0, &ServerMsg::Pong).unwrap();
+        bytes.extend_from_slice(&cut.into_inner()[..5]);
+
+        let mut reader = ReplayReader::new(&bytes[..]).unwrap();
+        assert_eq!(reader.protocol_version(), PROTOCOL_VERSION);
+        let frame = reader.next_frame().unwrap().unwrap();
+        assert_eq!(frame.time, 0.0);
+        assert!(matches!(frame.msg, ServerMsg::Ping));
+        let frame = reader.next_frame().unwrap().unwrap();
+        assert_eq!(frame.time, 1.5);
+        assert!(matches!(frame.msg, ServerMsg::DeleteEntity(Uid(7))));
+        assert!(reader.next_frame().unwrap().is_none());
+
+        assert!(matches!(
+            ReplayReader::new(&bytes[4..complete]),
+            Err(ReplayError::NotAReplay)
+        ));
+    }
+
+    #[test]
+    fn test_replay_filter() {
+        assert!(ReplayFilter::All.records(&ServerMsg::Ping));
+        assert!(!ReplayFilter::Sync.records(&ServerMsg::Ping));
+        assert!(ReplayFilter::Sync.records(&ServerMsg::DeleteEntity(Uid(1))));
+    }
+}
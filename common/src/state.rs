@@ -1,11 +1,13 @@
 use crate::{
     comp,
     event::{EventBus, LocalEvent, ServerEvent},
+    hierarchical::NavGrid,
     region::RegionMap,
     sync::WorldSyncExt,
     sys,
     terrain::{Block, TerrainChunk, TerrainGrid},
     vol::WriteVol,
+    weather::WeatherGrid,
 };
 use hashbrown::{HashMap, HashSet};
 use rayon::{ThreadPool, ThreadPoolBuilder};
@@ -141,6 +143,9 @@ impl State {
         // TODO: only register on the client
         ecs.register::<comp::LightAnimation>();
 
+        // Register components computed locally by both the server and clients
+        ecs.register::<comp::Wetness>();
+
         // Register server-local components
         // TODO: only register on the server
         ecs.register::<comp::Last<comp::Pos>>();
@@ -163,6 +168,7 @@ impl State {
 
         // Register synced resources used by the ECS.
         ecs.insert(TimeOfDay(0.0));
+        ecs.insert(WeatherGrid::default());
 
         // Register unsynced resources used by the ECS.
         ecs.insert(Time(0.0));
@@ -170,11 +176,21 @@ impl State {
         ecs.insert(TerrainGrid::new().unwrap());
         ecs.insert(BlockChange::default());
         ecs.insert(TerrainChanges::default());
+        ecs.insert(NavGrid::default());
         ecs.insert(EventBus::<LocalEvent>::default());
         // TODO: only register on the server
         ecs.insert(EventBus::<ServerEvent>::default());
         ecs.insert(comp::group::GroupManager::default());
         ecs.insert(RegionMap::new());
+        ecs.insert(sys::AgentTimer::default());
+        ecs.insert(sys::MountTimer::default());
+        ecs.insert(sys::ControllerTimer::default());
+        ecs.insert(sys::CharacterBehaviorTimer::default());
+        ecs.insert(sys::StatsTimer::default());
+        ecs.insert(sys::PhysicsTimer::default());
+        ecs.insert(sys::ProjectileTimer::default());
+        ecs.insert(sys::CombatTimer::default());
+        ecs.insert(sys::WetnessTimer::default());
 
         ecs
     }
@@ -1,5 +1,9 @@
 use crate::{
-    comp::{Attacking, CharacterState, EnergySource, StateUpdate},
+    comp::{
+        item::{ItemKind, ToolKind},
+        Attacking, CharacterState, EnergySource, StateUpdate,
+    },
+    event::ServerEvent,
     states::utils::*,
     sys::character_behavior::*,
 };
@@ -53,6 +57,22 @@ impl CharacterBehavior for Data {
                 knockback: 0.0,
             });
 
+            // Pickaxes and farming tools also work the terrain being looked at
+            if let Some(ItemKind::Tool(tool)) =
+                data.loadout.active_item.as_ref().map(|i| &i.item.kind)
+            {
+                let (entity, dir, range) = (data.entity, data.inputs.look_dir, self.range);
+                match tool.kind {
+                    ToolKind::Pickaxe(_) => update
+                        .server_events
+                        .push_front(ServerEvent::MineBlock { entity, dir, range }),
+                    ToolKind::Farming(_) => update
+                        .server_events
+                        .push_front(ServerEvent::TillSoil { entity, dir, range }),
+                    _ => {},
+                }
+            }
+
             update.character = CharacterState::BasicMelee(Data {
                 buildup_duration: self.buildup_duration,
                 recover_duration: self.recover_duration,
@@ -5,12 +5,14 @@ use crate::{
     util::Dir,
 };
 use serde::{Deserialize, Serialize};
-use vek::Vec2;
+use vek::{Vec2, Vec3};
 
 // Gravity is 9.81 * 4, so this makes gravity equal to .15
 const GLIDE_ANTIGRAV: f32 = crate::sys::phys::GRAVITY * 0.90;
 const GLIDE_ACCEL: f32 = 12.0;
 const GLIDE_SPEED: f32 = 45.0;
+/// How quickly gliders pick up the speed of the wind
+const GLIDE_WIND_ACCEL: f32 = 0.3;
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, Eq, Hash)]
 pub struct Data;
@@ -45,6 +47,13 @@ impl CharacterBehavior for Data {
                 0.0
             };
 
+        // Drift with the wind, until moving along with it
+        let wind = data.weather.get_interpolated(data.pos.0.xy()).wind;
+        let wind_speed = wind.magnitude();
+        if wind_speed > 0.0 && Vec2::<f32>::from(update.vel.0).dot(wind / wind_speed) < wind_speed {
+            update.vel.0 += Vec3::from(wind * GLIDE_WIND_ACCEL * data.dt.0);
+        }
+
         // Determine orientation vector from movement direction vector
         let ori_dir = Vec2::from(update.vel.0);
         update.ori.0 = Dir::slerp_to_vec3(update.ori.0, ori_dir.into(), 2.0 * data.dt.0);
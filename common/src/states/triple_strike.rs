@@ -23,6 +23,17 @@ pub enum Stage {
     Third,
 }
 
+impl Stage {
+    /// The stage following this one, if the attack has that many `stages`
+    pub fn next(self, stages: u8) -> Option<Self> {
+        match self {
+            Stage::First if stages >= 2 => Some(Stage::Second),
+            Stage::Second if stages >= 3 => Some(Stage::Third),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, Eq, Hash)]
 pub enum TimingState {
     NotPressed,
@@ -56,6 +67,8 @@ pub struct Data {
     pub base_damage: u32,
     /// What stage (of 3) the attack is in
     pub stage: Stage,
+    /// How many stages can be chained before the attack ends
+    pub stages: u8,
     /// How long current stage has been active
     pub stage_time_active: Duration,
     /// Whether current stage has exhausted its attack
@@ -157,6 +170,7 @@ impl CharacterBehavior for Data {
             CharacterState::TripleStrike(Data {
                 base_damage: self.base_damage,
                 stage: self.stage,
+                stages: self.stages,
                 stage_time_active,
                 stage_exhausted: true,
                 initialized,
@@ -167,11 +181,7 @@ impl CharacterBehavior for Data {
             // Determine whether stage can transition based on TransitionStyle
             if let Hold(Holding) | Timed(Success) = transition_style {
                 // Determine what stage to transition to
-                match self.stage {
-                    Stage::First => Some(Stage::Second),
-                    Stage::Second => Some(Stage::Third),
-                    Stage::Third => None,
-                }
+                self.stage.next(self.stages)
             }
             // Player messed up inputs, don't transition
             else { None };
@@ -182,6 +192,7 @@ impl CharacterBehavior for Data {
                 CharacterState::TripleStrike(Data {
                     base_damage: self.base_damage,
                     stage,
+                    stages: self.stages,
                     stage_time_active: Duration::default(),
                     stage_exhausted: false,
                     initialized,
@@ -200,6 +211,7 @@ impl CharacterBehavior for Data {
             CharacterState::TripleStrike(Data {
                 base_damage: self.base_damage,
                 stage: self.stage,
+                stages: self.stages,
                 stage_time_active,
                 stage_exhausted: self.stage_exhausted,
                 initialized,
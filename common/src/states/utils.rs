@@ -1,7 +1,7 @@
 use crate::{
     comp::{
         item::{Hands, ItemKind, Tool},
-        Body, CharacterState, StateUpdate,
+        Body, CharacterAbility, CharacterState, ItemConfig, StateUpdate,
     },
     event::LocalEvent,
     states::*,
@@ -221,6 +221,19 @@ pub fn handle_jump(data: &JoinData, update: &mut StateUpdate) {
     }
 }
 
+/// Applies the effects of the character's skills to one of the abilities of
+/// an equipped item
+fn modified_ability(
+    data: &JoinData,
+    item_config: &ItemConfig,
+    slot: u8,
+    ability: &CharacterAbility,
+) -> CharacterAbility {
+    data.stats
+        .skill_set
+        .modify_ability(&item_config.item, slot, ability)
+}
+
 /// Will attempt to go into `loadout.active_item.ability1`
 pub fn handle_ability1_input(data: &JoinData, update: &mut StateUpdate) {
     if data.inputs.primary.is_pressed() {
@@ -228,10 +241,10 @@ pub fn handle_ability1_input(data: &JoinData, update: &mut StateUpdate) {
             .loadout
             .active_item
             .as_ref()
-            .and_then(|i| i.ability1.as_ref())
+            .and_then(|i| i.ability1.as_ref().map(|a| modified_ability(data, i, 1, a)))
             .filter(|ability| ability.requirements_paid(data, update))
         {
-            update.character = ability.into();
+            update.character = (&ability).into();
         }
     }
 }
@@ -258,10 +271,10 @@ pub fn handle_ability2_input(data: &JoinData, update: &mut StateUpdate) {
                     .loadout
                     .active_item
                     .as_ref()
-                    .and_then(|i| i.ability2.as_ref())
+                    .and_then(|i| i.ability2.as_ref().map(|a| modified_ability(data, i, 2, a)))
                     .filter(|ability| ability.requirements_paid(data, update))
                 {
-                    update.character = ability.into();
+                    update.character = (&ability).into();
                 }
             },
             (_, Some(Hands::OneHand)) => {
@@ -269,10 +282,10 @@ pub fn handle_ability2_input(data: &JoinData, update: &mut StateUpdate) {
                     .loadout
                     .second_item
                     .as_ref()
-                    .and_then(|i| i.ability2.as_ref())
+                    .and_then(|i| i.ability2.as_ref().map(|a| modified_ability(data, i, 2, a)))
                     .filter(|ability| ability.requirements_paid(data, update))
                 {
-                    update.character = ability.into();
+                    update.character = (&ability).into();
                 }
             },
             (_, _) => {},
@@ -287,10 +300,10 @@ pub fn handle_ability3_input(data: &JoinData, update: &mut StateUpdate) {
             .loadout
             .active_item
             .as_ref()
-            .and_then(|i| i.ability3.as_ref())
+            .and_then(|i| i.ability3.as_ref().map(|a| modified_ability(data, i, 3, a)))
             .filter(|ability| ability.requirements_paid(data, update))
         {
-            update.character = ability.into();
+            update.character = (&ability).into();
         }
     }
 }
@@ -164,4 +164,26 @@ impl<P: CompPacket> CompSyncPackage<P> {
         tracker.get_updates_for(uids, storage, filter, &mut self.comp_updates);
         self
     }
+
+    /// Like `with_component`, for components whose modifications are synced
+    /// another way
+    pub fn with_component_insertions_and_removals<'a, C: Component + Clone + Send + Sync>(
+        mut self,
+        uids: &ReadStorage<'a, Uid>,
+        tracker: &UpdateTracker<C>,
+        storage: &ReadStorage<'a, C>,
+        filter: impl Join + Copy,
+    ) -> Self
+    where
+        P: From<C>,
+        C: TryFrom<P>,
+        P::Phantom: From<PhantomData<C>>,
+        P::Phantom: TryInto<PhantomData<C>>,
+        C::Storage: specs::storage::Tracked,
+    {
+        tracker.get_insertions_and_removals_for(uids, storage, filter, &mut self.comp_updates);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool { self.comp_updates.is_empty() }
 }
@@ -100,19 +100,38 @@ impl<C: Component + Clone + Send + Sync> UpdateTracker<C> {
         P::Phantom: TryInto<PhantomData<C>>,
         C::Storage: specs::storage::Tracked,
     {
-        // Generate inserted updates
-        for (uid, comp, _, _) in (uids, storage, &self.inserted, entity_filter).join() {
+        self.get_insertions_and_removals_for(uids, storage, entity_filter, buf);
+
+        // Generate modified updates
+        for (uid, comp, _, _) in (uids, storage, &self.modified, entity_filter).join() {
             buf.push((
                 (*uid).into(),
-                CompUpdateKind::Inserted(P::from(comp.clone())),
+                CompUpdateKind::Modified(P::from(comp.clone())),
             ));
         }
+    }
 
-        // Generate modified updates
-        for (uid, comp, _, _) in (uids, storage, &self.modified, entity_filter).join() {
+    /// Like `get_updates_for`, but leaving out modifications for components
+    /// that are synced another way
+    pub fn get_insertions_and_removals_for<'a, P>(
+        &self,
+        uids: &specs::ReadStorage<'a, Uid>,
+        storage: &specs::ReadStorage<'a, C>,
+        entity_filter: impl Join + Copy,
+        buf: &mut Vec<(u64, CompUpdateKind<P>)>,
+    ) where
+        P: CompPacket,
+        P: From<C>,
+        C: TryFrom<P>,
+        P::Phantom: From<PhantomData<C>>,
+        P::Phantom: TryInto<PhantomData<C>>,
+        C::Storage: specs::storage::Tracked,
+    {
+        // Generate inserted updates
+        for (uid, comp, _, _) in (uids, storage, &self.inserted, entity_filter).join() {
             buf.push((
                 (*uid).into(),
-                CompUpdateKind::Modified(P::from(comp.clone())),
+                CompUpdateKind::Inserted(P::from(comp.clone())),
             ));
         }
 
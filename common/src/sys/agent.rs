@@ -10,12 +10,15 @@ use crate::{
         Vel,
     },
     event::{EventBus, ServerEvent},
+    hierarchical::{ChunkPath, NavGrid},
     path::{Chaser, TraversalConfig},
     state::{DeltaTime, Time},
     sync::{Uid, UidAllocator},
+    sys::SysTimer,
     terrain::TerrainGrid,
     util::Dir,
     vol::ReadVol,
+    weather::WeatherGrid,
 };
 use rand::{thread_rng, Rng};
 use specs::{
@@ -52,6 +55,9 @@ impl<'a> System<'a> for Sys {
         WriteStorage<'a, Controller>,
         ReadStorage<'a, MountState>,
         ReadStorage<'a, Invite>,
+        Read<'a, WeatherGrid>,
+        Read<'a, NavGrid>,
+        Write<'a, SysTimer<Self>>,
     );
 
     #[allow(clippy::or_fun_call)] // TODO: Pending review in #587
@@ -81,8 +87,23 @@ impl<'a> System<'a> for Sys {
             mut controllers,
             mount_states,
             invites,
+            weather,
+            nav_grid,
+            mut timer,
         ): Self::SystemData,
     ) {
+        timer.start();
+
+        // Plan routes on the world map when we have one, otherwise through the
+        // loaded terrain
+        let find_route = |from, dest| {
+            if nav_grid.is_empty() {
+                ChunkPath::find(&*terrain, from, dest)
+            } else {
+                ChunkPath::find(&*nav_grid, from, dest)
+            }
+        };
+
         for (
             entity,
             pos,
@@ -152,9 +173,16 @@ impl<'a> System<'a> for Sys {
             const SIGHT_DIST: f32 = 128.0;
             const MIN_ATTACK_DIST: f32 = 3.5;
             const MAX_FLEE_DIST: f32 = 32.0;
+            const TRAVEL_DIST: f32 = 64.0;
+            const ARRIVAL_DIST: f32 = 8.0;
 
             let scale = scales.get(entity).map(|s| s.0).unwrap_or(1.0);
 
+            // Rain, snow and clouds make it harder to spot targets
+            let visibility = weather.get_interpolated(pos.0.xy()).visibility();
+            let search_dist = SEARCH_DIST * visibility;
+            let sight_dist = SIGHT_DIST * visibility;
+
             // This controls how picky NPCs are about their pathfinding. Giants are larger
             // and so can afford to be less precise when trying to move around
             // the world (especially since they would otherwise get stuck on
@@ -164,6 +192,7 @@ impl<'a> System<'a> for Sys {
 
             let mut do_idle = false;
             let mut choose_target = false;
+            let mut travel_to = None;
 
             'activity: {
                 match &mut agent.activity {
@@ -217,6 +246,45 @@ impl<'a> System<'a> for Sys {
                         if thread_rng().gen::<f32>() < 0.1 {
                             choose_target = true;
                         }
+
+                        // Set off towards our destination after a while
+                        if agent.destination.is_some() && thread_rng().gen::<f32>() < 0.01 {
+                            travel_to = agent.destination;
+                        }
+                    },
+                    Activity::Travel { path, chaser } => {
+                        if pos.0.xy().distance_squared(path.dest().xy()) < ARRIVAL_DIST.powf(2.0) {
+                            // We've arrived, so this is where we stay
+                            if let Some(destination) = agent.destination.take() {
+                                agent.patrol_origin = Some(destination);
+                            }
+                            do_idle = true;
+                        } else {
+                            let waypoint = if nav_grid.is_empty() {
+                                path.next_waypoint(&*terrain, pos.0)
+                            } else {
+                                path.next_waypoint(&*nav_grid, pos.0)
+                            };
+                            if let Some((bearing, speed)) =
+                                chaser.chase(&*terrain, pos.0, vel.0, waypoint, TraversalConfig {
+                                    node_tolerance,
+                                    slow_factor,
+                                    on_ground: physics_state.on_ground,
+                                    min_tgt_dist: 1.25,
+                                })
+                            {
+                                inputs.move_dir =
+                                    bearing.xy().try_normalized().unwrap_or(Vec2::zero()) * speed;
+                                inputs.jump.set_state(bearing.z > 1.5);
+                                inputs.swimup.set_state(bearing.z > 0.5);
+                                inputs.swimdown.set_state(bearing.z < 0.5);
+                            }
+
+                            // Keep an eye out for threats along the way
+                            if thread_rng().gen::<f32>() < 0.1 {
+                                choose_target = true;
+                            }
+                        }
                     },
                     Activity::Follow { target, chaser } => {
                         if let (Some(tgt_pos), _tgt_stats) =
@@ -352,7 +420,7 @@ impl<'a> System<'a> for Sys {
                                     Tactic::RangedPowerup => inputs.roll.set_state(true),
                                 }
                             } else if dist_sqrd < MAX_CHASE_DIST.powf(2.0)
-                                || (dist_sqrd < SIGHT_DIST.powf(2.0)
+                                || (dist_sqrd < sight_dist.powf(2.0)
                                     && (!*been_close || !matches!(tactic, Tactic::Melee)))
                             {
                                 let can_see_tgt = terrain
@@ -427,6 +495,19 @@ impl<'a> System<'a> for Sys {
                 agent.activity = Activity::Idle(Vec2::zero());
             }
 
+            if let Some(dest) = travel_to {
+                match find_route(pos.0, dest) {
+                    Some(path) => {
+                        agent.activity = Activity::Travel {
+                            path,
+                            chaser: Chaser::default(),
+                        };
+                    },
+                    // Give up on destinations that can't be reached
+                    None => agent.destination = None,
+                }
+            }
+
             // Choose a new target to attack: only go out of our way to attack targets we
             // are hostile toward!
             if choose_target {
@@ -435,7 +516,7 @@ impl<'a> System<'a> for Sys {
                 let closest_entity = (&entities, &positions, &stats, alignments.maybe())
                     .join()
                     .filter(|(e, e_pos, e_stats, e_alignment)| {
-                        ((e_pos.0.distance_squared(pos.0) < SEARCH_DIST.powf(2.0) &&
+                        ((e_pos.0.distance_squared(pos.0) < search_dist.powf(2.0) &&
                             // Within our view
                             (e_pos.0 - pos.0).try_normalized().map(|v| v.dot(*inputs.look_dir) > 0.15).unwrap_or(true))
                                 // Within listen distance
@@ -515,7 +596,17 @@ impl<'a> System<'a> for Sys {
 
                     let owner_pos = positions.get(owner)?;
                     let dist_sqrd = pos.0.distance_squared(owner_pos.0);
-                    if dist_sqrd > MAX_FOLLOW_DIST.powf(2.0) && !agent.activity.is_follow() {
+                    if dist_sqrd > TRAVEL_DIST.powf(2.0) {
+                        // Too far away to chase after, find a route to them instead
+                        if !agent.activity.is_travel() && thread_rng().gen::<f32>() < 0.05 {
+                            if let Some(path) = find_route(pos.0, owner_pos.0) {
+                                agent.activity = Activity::Travel {
+                                    path,
+                                    chaser: Chaser::default(),
+                                };
+                            }
+                        }
+                    } else if dist_sqrd > MAX_FOLLOW_DIST.powf(2.0) && !agent.activity.is_follow() {
                         agent.activity = Activity::Follow {
                             target: owner,
                             chaser: Chaser::default(),
@@ -567,5 +658,7 @@ impl<'a> System<'a> for Sys {
                     .push(ControlEvent::GroupManip(GroupManip::Decline));
             }
         }
+
+        timer.end();
     }
 }
@@ -7,12 +7,15 @@ use crate::{
     state::DeltaTime,
     states,
     sync::{Uid, UidAllocator},
+    sys::SysTimer,
+    weather::WeatherGrid,
 };
 
 use specs::{
     hibitset,
     storage::{PairedStorage, SequentialRestriction},
-    Entities, Entity, FlaggedStorage, Join, LazyUpdate, Read, ReadStorage, System, WriteStorage,
+    Entities, Entity, FlaggedStorage, Join, LazyUpdate, Read, ReadStorage, System, Write,
+    WriteStorage,
 };
 use specs_idvs::IdvStorage;
 
@@ -62,6 +65,7 @@ pub struct JoinData<'a> {
     pub physics: &'a PhysicsState,
     pub attacking: Option<&'a Attacking>,
     pub updater: &'a LazyUpdate,
+    pub weather: &'a WeatherGrid,
 }
 
 type RestrictedMut<'a, C> = PairedStorage<
@@ -107,7 +111,12 @@ fn incorporate_update(tuple: &mut JoinTuple, state_update: StateUpdate) {
 }
 
 impl<'a> JoinData<'a> {
-    fn new(j: &'a JoinTuple<'a>, updater: &'a LazyUpdate, dt: &'a DeltaTime) -> Self {
+    fn new(
+        j: &'a JoinTuple<'a>,
+        updater: &'a LazyUpdate,
+        dt: &'a DeltaTime,
+        weather: &'a WeatherGrid,
+    ) -> Self {
         Self {
             entity: j.0,
             uid: j.1,
@@ -125,6 +134,7 @@ impl<'a> JoinData<'a> {
             attacking: j.12,
             updater,
             dt,
+            weather,
         }
     }
 }
@@ -143,6 +153,7 @@ impl<'a> System<'a> for Sys {
         Read<'a, EventBus<LocalEvent>>,
         Read<'a, DeltaTime>,
         Read<'a, LazyUpdate>,
+        Read<'a, WeatherGrid>,
         WriteStorage<'a, CharacterState>,
         WriteStorage<'a, Pos>,
         WriteStorage<'a, Vel>,
@@ -156,6 +167,7 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Attacking>,
         ReadStorage<'a, Uid>,
         ReadStorage<'a, Mounting>,
+        Write<'a, SysTimer<Self>>,
     );
 
     #[allow(clippy::while_let_on_iterator)] // TODO: Pending review in #587
@@ -168,6 +180,7 @@ impl<'a> System<'a> for Sys {
             local_bus,
             dt,
             updater,
+            weather,
             mut character_states,
             mut positions,
             mut velocities,
@@ -181,8 +194,11 @@ impl<'a> System<'a> for Sys {
             attacking_storage,
             uids,
             mountings,
+            mut timer,
         ): Self::SystemData,
     ) {
+        timer.start();
+
         let mut server_emitter = server_bus.emitter();
         let mut local_emitter = local_bus.emitter();
 
@@ -220,7 +236,7 @@ impl<'a> System<'a> for Sys {
 
             let actions = std::mem::replace(&mut tuple.8.actions, Vec::new());
             for action in actions {
-                let j = JoinData::new(&tuple, &updater, &dt);
+                let j = JoinData::new(&tuple, &updater, &dt, &weather);
                 let mut state_update = match j.character {
                     CharacterState::Idle => states::idle::Data.handle_event(&j, action),
                     CharacterState::Climb => states::climb::Data.handle_event(&j, action),
@@ -257,7 +273,7 @@ impl<'a> System<'a> for Sys {
                 incorporate_update(&mut tuple, state_update);
             }
 
-            let j = JoinData::new(&tuple, &updater, &dt);
+            let j = JoinData::new(&tuple, &updater, &dt, &weather);
 
             let mut state_update = match j.character {
                 CharacterState::Idle => states::idle::Data.behavior(&j),
@@ -285,5 +301,7 @@ impl<'a> System<'a> for Sys {
             server_emitter.append(&mut state_update.server_events);
             incorporate_update(&mut tuple, state_update);
         }
+
+        timer.end();
     }
 }
@@ -5,9 +5,10 @@ use crate::{
     },
     event::{EventBus, LocalEvent, ServerEvent},
     sync::Uid,
+    sys::SysTimer,
     util::Dir,
 };
-use specs::{Entities, Join, Read, ReadStorage, System, WriteStorage};
+use specs::{Entities, Join, Read, ReadStorage, System, Write, WriteStorage};
 use vek::*;
 
 pub const BLOCK_EFFICIENCY: f32 = 0.9;
@@ -32,6 +33,7 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, group::Group>,
         WriteStorage<'a, Attacking>,
         WriteStorage<'a, CharacterState>,
+        Write<'a, SysTimer<Self>>,
     );
 
     fn run(
@@ -50,8 +52,11 @@ impl<'a> System<'a> for Sys {
             groups,
             mut attacking_storage,
             character_states,
+            mut timer,
         ): Self::SystemData,
     ) {
+        timer.start();
+
         let mut server_emitter = server_bus.emitter();
         let mut local_emitter = local_bus.emitter();
         // Attacks
@@ -117,8 +122,12 @@ impl<'a> System<'a> for Sys {
                     } else {
                         DamageSource::Melee
                     };
+                    // Broken weapons deal less damage
+                    let effectiveness = loadouts
+                        .get(entity)
+                        .map_or(1.0, |loadout| loadout.weapon_effectiveness());
                     let mut damage = Damage {
-                        healthchange: attack.base_healthchange as f32,
+                        healthchange: attack.base_healthchange as f32 * effectiveness,
                         source,
                     };
 
@@ -149,5 +158,7 @@ impl<'a> System<'a> for Sys {
                 }
             }
         }
+
+        timer.end();
     }
 }
@@ -6,10 +6,11 @@ use crate::{
     event::{EventBus, LocalEvent, ServerEvent},
     state::DeltaTime,
     sync::{Uid, UidAllocator},
+    sys::SysTimer,
 };
 use specs::{
     saveload::{Marker, MarkerAllocator},
-    Entities, Join, Read, ReadStorage, System, WriteStorage,
+    Entities, Join, Read, ReadStorage, System, Write, WriteStorage,
 };
 
 // const CHARGE_COST: i32 = 200;
@@ -28,6 +29,7 @@ impl<'a> System<'a> for Sys {
         WriteStorage<'a, Controller>,
         WriteStorage<'a, CharacterState>,
         ReadStorage<'a, Uid>,
+        Write<'a, SysTimer<Self>>,
     );
 
     fn run(
@@ -41,8 +43,11 @@ impl<'a> System<'a> for Sys {
             mut controllers,
             mut character_states,
             uids,
+            mut timer,
         ): Self::SystemData,
     ) {
+        timer.start();
+
         let mut server_emitter = server_bus.emitter();
 
         for (entity, _uid, controller, character_state) in
@@ -103,5 +108,7 @@ impl<'a> System<'a> for Sys {
                 }
             }
         }
+
+        timer.end();
     }
 }
@@ -6,9 +6,21 @@ mod mount;
 pub mod phys;
 mod projectile;
 mod stats;
+mod wetness;
 
 // External
 use specs::DispatcherBuilder;
+use std::{marker::PhantomData, time::Instant};
+
+pub type AgentTimer = SysTimer<agent::Sys>;
+pub type MountTimer = SysTimer<mount::Sys>;
+pub type ControllerTimer = SysTimer<controller::Sys>;
+pub type CharacterBehaviorTimer = SysTimer<character_behavior::Sys>;
+pub type StatsTimer = SysTimer<stats::Sys>;
+pub type PhysicsTimer = SysTimer<phys::Sys>;
+pub type ProjectileTimer = SysTimer<projectile::Sys>;
+pub type CombatTimer = SysTimer<combat::Sys>;
+pub type WetnessTimer = SysTimer<wetness::Sys>;
 
 // System names
 pub const CHARACTER_BEHAVIOR_SYS: &str = "character_behavior_sys";
@@ -19,6 +31,7 @@ pub const MOUNT_SYS: &str = "mount_sys";
 pub const PHYS_SYS: &str = "phys_sys";
 pub const PROJECTILE_SYS: &str = "projectile_sys";
 pub const STATS_SYS: &str = "stats_sys";
+pub const WETNESS_SYS: &str = "wetness_sys";
 
 pub fn add_local_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch_builder.add(agent::Sys, AGENT_SYS, &[]);
@@ -31,4 +44,51 @@ pub fn add_local_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch_builder.add(phys::Sys, PHYS_SYS, &[CONTROLLER_SYS, MOUNT_SYS, STATS_SYS]);
     dispatch_builder.add(projectile::Sys, PROJECTILE_SYS, &[PHYS_SYS]);
     dispatch_builder.add(combat::Sys, COMBAT_SYS, &[PROJECTILE_SYS]);
+    dispatch_builder.add(wetness::Sys, WETNESS_SYS, &[PHYS_SYS]);
+}
+
+/// Used to keep track of how much time each system takes
+pub struct SysTimer<S> {
+    pub nanos: u64,
+    start: Option<Instant>,
+    /// Whether the system ran since the last sample was taken
+    unsampled: bool,
+    _phantom: PhantomData<S>,
+}
+
+impl<S> SysTimer<S> {
+    pub fn start(&mut self) {
+        if self.start.is_some() {
+            panic!("Timer already started");
+        }
+        self.start = Some(Instant::now());
+    }
+
+    pub fn end(&mut self) {
+        self.nanos = self
+            .start
+            .take()
+            .expect("Timer ended without starting it")
+            .elapsed()
+            .as_nanos() as u64;
+        self.unsampled = true;
+    }
+
+    /// The time the system took, if it ran since this was last called.
+    /// Systems that run at an interval keep their last time in `nanos`, so
+    /// this is what to record each run of them.
+    pub fn take_sample(&mut self) -> Option<u64> {
+        std::mem::take(&mut self.unsampled).then_some(self.nanos)
+    }
+}
+
+impl<S> Default for SysTimer<S> {
+    fn default() -> Self {
+        Self {
+            nanos: 0,
+            start: None,
+            unsampled: false,
+            _phantom: PhantomData,
+        }
+    }
 }
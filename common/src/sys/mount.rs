@@ -1,10 +1,11 @@
 use crate::{
     comp::{Controller, MountState, Mounting, Ori, Pos, Vel},
     sync::UidAllocator,
+    sys::SysTimer,
 };
 use specs::{
     saveload::{Marker, MarkerAllocator},
-    Entities, Join, Read, System, WriteStorage,
+    Entities, Join, Read, System, Write, WriteStorage,
 };
 use vek::*;
 
@@ -21,6 +22,7 @@ impl<'a> System<'a> for Sys {
         WriteStorage<'a, Pos>,
         WriteStorage<'a, Vel>,
         WriteStorage<'a, Ori>,
+        Write<'a, SysTimer<Self>>,
     );
 
     fn run(
@@ -34,8 +36,11 @@ impl<'a> System<'a> for Sys {
             mut positions,
             mut velocities,
             mut orientations,
+            mut timer,
         ): Self::SystemData,
     ) {
+        timer.start();
+
         // Mounted entities.
         for (entity, mut mount_states) in (&entities, &mut mount_state.restrict_mut()).join() {
             match mount_states.get_unchecked() {
@@ -86,5 +91,7 @@ impl<'a> System<'a> for Sys {
         for entity in to_unmount {
             mountings.remove(entity);
         }
+
+        timer.end();
     }
 }
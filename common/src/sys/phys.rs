@@ -6,13 +6,14 @@ use crate::{
     event::{EventBus, ServerEvent},
     state::DeltaTime,
     sync::{Uid, UidAllocator},
+    sys::SysTimer,
     terrain::{Block, BlockKind, TerrainGrid},
     vol::ReadVol,
 };
 use rayon::iter::ParallelIterator;
 use specs::{
     saveload::MarkerAllocator, Entities, Join, ParJoin, Read, ReadExpect, ReadStorage, System,
-    WriteStorage,
+    Write, WriteStorage,
 };
 use std::ops::Range;
 use vek::*;
@@ -66,6 +67,7 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Mounting>,
         ReadStorage<'a, Group>,
         ReadStorage<'a, Projectile>,
+        Write<'a, SysTimer<Self>>,
     );
 
     #[allow(clippy::or_fun_call)] // TODO: Pending review in #587
@@ -91,8 +93,11 @@ impl<'a> System<'a> for Sys {
             mountings,
             groups,
             projectiles,
+            mut timer,
         ): Self::SystemData,
     ) {
+        timer.start();
+
         let mut event_emitter = event_bus.emitter();
 
         // Add physics state components
@@ -583,5 +588,7 @@ impl<'a> System<'a> for Sys {
                 }
             }
         }
+
+        timer.end();
     }
 }
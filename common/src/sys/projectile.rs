@@ -6,9 +6,12 @@ use crate::{
     event::{EventBus, LocalEvent, ServerEvent},
     state::DeltaTime,
     sync::UidAllocator,
+    sys::SysTimer,
     util::Dir,
 };
-use specs::{saveload::MarkerAllocator, Entities, Join, Read, ReadStorage, System, WriteStorage};
+use specs::{
+    saveload::MarkerAllocator, Entities, Join, Read, ReadStorage, System, Write, WriteStorage,
+};
 use std::time::Duration;
 use vek::*;
 
@@ -29,6 +32,7 @@ impl<'a> System<'a> for Sys {
         WriteStorage<'a, Projectile>,
         WriteStorage<'a, Energy>,
         ReadStorage<'a, Loadout>,
+        Write<'a, SysTimer<Self>>,
     );
 
     fn run(
@@ -46,8 +50,11 @@ impl<'a> System<'a> for Sys {
             mut projectiles,
             mut energies,
             loadouts,
+            mut timer,
         ): Self::SystemData,
     ) {
+        timer.start();
+
         let mut local_emitter = local_bus.emitter();
         let mut server_emitter = server_bus.emitter();
 
@@ -88,8 +95,13 @@ impl<'a> System<'a> for Sys {
                     match effect {
                         projectile::Effect::Damage(healthchange) => {
                             let owner_uid = projectile.owner.unwrap();
+                            // Broken weapons deal less damage
+                            let effectiveness = uid_allocator
+                                .retrieve_entity_internal(owner_uid.into())
+                                .and_then(|owner| loadouts.get(owner))
+                                .map_or(1.0, |loadout| loadout.weapon_effectiveness());
                             let mut damage = Damage {
-                                healthchange: healthchange as f32,
+                                healthchange: healthchange as f32 * effectiveness,
                                 source: DamageSource::Projectile,
                             };
 
@@ -169,5 +181,7 @@ impl<'a> System<'a> for Sys {
                 .checked_sub(Duration::from_secs_f32(dt.0))
                 .unwrap_or_default();
         }
+
+        timer.end();
     }
 }
@@ -1,9 +1,10 @@
 use crate::{
-    comp::{CharacterState, Energy, EnergySource, HealthSource, Stats},
+    comp::{CharacterState, Energy, EnergySource, HealthSource, Stats, Wetness},
     event::{EventBus, ServerEvent},
     state::DeltaTime,
+    sys::SysTimer,
 };
-use specs::{Entities, Join, Read, ReadStorage, System, WriteStorage};
+use specs::{Entities, Join, Read, ReadStorage, System, Write, WriteStorage};
 
 const ENERGY_REGEN_ACCEL: f32 = 10.0;
 
@@ -18,12 +19,25 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, CharacterState>,
         WriteStorage<'a, Stats>,
         WriteStorage<'a, Energy>,
+        ReadStorage<'a, Wetness>,
+        Write<'a, SysTimer<Self>>,
     );
 
     fn run(
         &mut self,
-        (entities, dt, server_event_bus, character_states, mut stats, mut energies): Self::SystemData,
+        (
+            entities,
+            dt,
+            server_event_bus,
+            character_states,
+            mut stats,
+            mut energies,
+            wetnesses,
+            mut timer,
+        ): Self::SystemData,
     ) {
+        timer.start();
+
         let mut server_event_emitter = server_event_bus.emitter();
 
         // Increment last change timer
@@ -69,9 +83,15 @@ impl<'a> System<'a> for Sys {
         }
 
         // Update energies
-        for (character_state, mut energy) in
-            (&character_states, &mut energies.restrict_mut()).join()
+        for (character_state, mut energy, wetness) in (
+            &character_states,
+            &mut energies.restrict_mut(),
+            wetnesses.maybe(),
+        )
+            .join()
         {
+            // Being soaked slows down recovery
+            let regen_factor = 1.0 - wetness.map_or(0.0, |w| w.0) * 0.5;
             match character_state {
                 // Accelerate recharging energy.
                 CharacterState::Idle { .. }
@@ -92,8 +112,8 @@ impl<'a> System<'a> for Sys {
                         let mut energy = energy.get_mut_unchecked();
                         // Have to account for Calc I differential equations due to acceleration
                         energy.change_by(
-                            (energy.regen_rate * dt.0 + ENERGY_REGEN_ACCEL * dt.0.powf(2.0) / 2.0)
-                                as i32,
+                            ((energy.regen_rate * dt.0 + ENERGY_REGEN_ACCEL * dt.0.powf(2.0) / 2.0)
+                                * regen_factor) as i32,
                             EnergySource::Regen,
                         );
                         energy.regen_rate =
@@ -131,5 +151,7 @@ impl<'a> System<'a> for Sys {
                 CharacterState::Roll { .. } | CharacterState::Climb { .. } => {},
             }
         }
+
+        timer.end();
     }
 }
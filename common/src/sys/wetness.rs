@@ -0,0 +1,97 @@
+use crate::{
+    comp::{PhysicsState, Pos, Stats, Wetness},
+    state::DeltaTime,
+    sys::SysTimer,
+    terrain::TerrainGrid,
+    vol::ReadVol,
+    weather::WeatherGrid,
+};
+use specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage};
+use vek::*;
+
+/// Wetness gained per second while swimming
+const SWIM_WETTING: f32 = 0.5;
+/// Wetness gained per second when out in the heaviest rain
+const RAIN_WETTING: f32 = 0.05;
+/// Wetness lost per second when out of the rain
+const DRYING: f32 = 0.02;
+/// How far above an entity a roof still gives shelter, in blocks
+const SHELTER_HEIGHT: f32 = 32.0;
+
+/// This system soaks entities that swim or stand in the rain and dries them
+/// off again otherwise
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, DeltaTime>,
+        Read<'a, WeatherGrid>,
+        ReadExpect<'a, TerrainGrid>,
+        ReadStorage<'a, Pos>,
+        ReadStorage<'a, PhysicsState>,
+        ReadStorage<'a, Stats>,
+        WriteStorage<'a, Wetness>,
+        Write<'a, SysTimer<Self>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            dt,
+            weather,
+            terrain,
+            positions,
+            physics_states,
+            stats,
+            mut wetnesses,
+            mut timer,
+        ): Self::SystemData,
+    ) {
+        timer.start();
+
+        for (entity, pos, physics, _) in (&entities, &positions, &physics_states, &stats).join() {
+            let wetting = if physics.in_fluid.is_some() {
+                SWIM_WETTING
+            } else {
+                let weather = weather.get_interpolated(pos.0.xy());
+                // Snow doesn't soak as much as rain does
+                let precipitation = weather.rain + weather.snow * 0.5;
+                let sheltered = || {
+                    let from = pos.0 + Vec3::unit_z() * 2.0;
+                    terrain
+                        .ray(from, from + Vec3::unit_z() * SHELTER_HEIGHT)
+                        .until(|block| block.is_solid())
+                        .cast()
+                        .0
+                        < SHELTER_HEIGHT
+                };
+                if precipitation > 0.0 && !sheltered() {
+                    precipitation * RAIN_WETTING
+                } else {
+                    0.0
+                }
+            };
+
+            match wetnesses.get_mut(entity) {
+                Some(wetness) => {
+                    wetness.0 = if wetting > 0.0 {
+                        (wetness.0 + wetting * dt.0).min(1.0)
+                    } else {
+                        wetness.0 - DRYING * dt.0
+                    };
+                    if wetness.0 <= 0.0 {
+                        wetnesses.remove(entity);
+                    }
+                },
+                None if wetting > 0.0 => {
+                    let _ = wetnesses.insert(entity, Wetness((wetting * dt.0).min(1.0)));
+                },
+                None => {},
+            }
+        }
+
+        timer.end();
+    }
+}
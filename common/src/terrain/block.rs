@@ -91,6 +91,16 @@ pub enum BlockKind {
     DropGate,
     DropGateBottom,
     GrassSnow,
+    Anvil,
+    Cauldron,
+    CraftingBench,
+    SealedDoor,
+    BossChest,
+    CopperOre,
+    IronOre,
+    Coal,
+    Farmland,
+    Sprout,
 }
 
 impl fmt::Display for BlockKind {
@@ -197,6 +207,11 @@ impl BlockKind {
             BlockKind::DropGate => false,
             BlockKind::DropGateBottom => false,
             BlockKind::GrassSnow => true,
+            BlockKind::Anvil => false,
+            BlockKind::Cauldron => false,
+            BlockKind::CraftingBench => false,
+            BlockKind::BossChest => true,
+            BlockKind::Sprout => true,
             _ => false,
         }
     }
@@ -293,6 +308,11 @@ impl BlockKind {
             BlockKind::DropGate => false,
             BlockKind::DropGateBottom => false,
             BlockKind::GrassSnow => false,
+            BlockKind::Anvil => false,
+            BlockKind::Cauldron => false,
+            BlockKind::CraftingBench => false,
+            BlockKind::BossChest => false,
+            BlockKind::Sprout => false,
             _ => true,
         }
     }
@@ -370,6 +390,11 @@ impl BlockKind {
             BlockKind::DropGate => true,
             BlockKind::DropGateBottom => false,
             BlockKind::GrassSnow => false,
+            BlockKind::Anvil => true,
+            BlockKind::Cauldron => true,
+            BlockKind::CraftingBench => true,
+            BlockKind::BossChest => true,
+            BlockKind::Sprout => false,
             _ => true,
         }
     }
@@ -378,6 +403,8 @@ impl BlockKind {
         match self {
             BlockKind::Leaves | BlockKind::Grass | BlockKind::Rock | BlockKind::GrassSnow => true,
             BlockKind::Air => false,
+            // Containers would take their contents with them
+            bk if bk.is_container() => false,
             bk => bk.is_air(), // Temporary catch for terrain sprites
         }
     }
@@ -415,6 +442,10 @@ impl BlockKind {
             BlockKind::WardrobeSingle => 3.0,
             BlockKind::WardrobeDouble => 3.0,
             BlockKind::Pot => 0.90,
+            BlockKind::Anvil => 0.81,
+            BlockKind::Cauldron => 1.09,
+            BlockKind::CraftingBench => 1.27,
+            BlockKind::BossChest => 1.09,
             _ => 1.0,
         }
     }
@@ -435,14 +466,58 @@ impl BlockKind {
             BlockKind::Mushroom => true,
             BlockKind::Velorite => true,
             BlockKind::VeloriteFrag => true,
-            BlockKind::Chest => true,
             BlockKind::Coconut => true,
             BlockKind::Stones => true,
             BlockKind::Twigs => true,
             BlockKind::ShinyGem => true,
+            BlockKind::Sprout => true,
+            BlockKind::WheatGreen => true,
+            BlockKind::WheatYellow => true,
+            BlockKind::Flax => true,
+            BlockKind::Corn => true,
+            BlockKind::Cabbage => true,
+            BlockKind::Carrot => true,
+            BlockKind::Radish => true,
+            BlockKind::Tomato => true,
+            BlockKind::Turnip => true,
             _ => false,
         }
     }
+
+    /// Whether the block opens a container inventory when interacted with
+    pub fn is_container(&self) -> bool {
+        matches!(
+            self,
+            BlockKind::Chest | BlockKind::Crate | BlockKind::BossChest
+        )
+    }
+
+    /// Whether the block is part of a door that can only be opened with a key
+    pub fn is_sealed(&self) -> bool { matches!(self, BlockKind::SealedDoor) }
+
+    /// Whether crops can be planted on top of the block
+    pub fn is_farmland(&self) -> bool { matches!(self, BlockKind::Farmland) }
+
+    /// Whether the block is part of an ore vein
+    pub fn is_ore(&self) -> bool {
+        matches!(
+            self,
+            BlockKind::CopperOre | BlockKind::IronOre | BlockKind::Coal
+        )
+    }
+
+    /// How much mining it takes to break the block with a pickaxe, or `None`
+    /// if the block can't be mined
+    pub fn mining_hardness(&self) -> Option<f32> {
+        match self {
+            BlockKind::Normal => Some(2.0),
+            BlockKind::Rock => Some(4.0),
+            BlockKind::Coal => Some(5.0),
+            BlockKind::CopperOre => Some(6.0),
+            BlockKind::IronOre => Some(8.0),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -497,6 +572,9 @@ impl Block {
             | BlockKind::Pot
             | BlockKind::DropGate
             | BlockKind::DropGateBottom
+            | BlockKind::Anvil
+            | BlockKind::Cauldron
+            | BlockKind::CraftingBench
             | BlockKind::Door => Some(self.color[0] & 0b111),
             _ => None,
         }
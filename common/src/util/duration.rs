@@ -0,0 +1,30 @@
+use serde::{Deserialize, Deserializer};
+use std::time::Duration;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SerdeDuration {
+    Millis(u64),
+    Full { secs: u64, nanos: u32 },
+}
+
+/// Deserializes a `Duration` from either a plain number of milliseconds (as
+/// written in asset files) or serde's default `(secs, nanos)` representation
+/// (as found in already persisted loadouts).
+///
+/// Non self-describing formats (such as the bincode used by the network
+/// protocol) don't support untagged enums, so they always use the default
+/// representation.
+pub fn deserialize_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return Duration::deserialize(deserializer);
+    }
+
+    Ok(match SerdeDuration::deserialize(deserializer)? {
+        SerdeDuration::Millis(millis) => Duration::from_millis(millis),
+        SerdeDuration::Full { secs, nanos } => Duration::new(secs, nanos),
+    })
+}
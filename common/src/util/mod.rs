@@ -1,5 +1,6 @@
 mod color;
 mod dir;
+mod duration;
 
 pub const GIT_VERSION: &str = include_str!(concat!(env!("OUT_DIR"), "/githash"));
 
@@ -10,3 +11,4 @@ lazy_static::lazy_static! {
 
 pub use color::*;
 pub use dir::*;
+pub use duration::*;
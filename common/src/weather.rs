@@ -0,0 +1,129 @@
+//! Weather conditions over the world map, simulated by the server on a coarse
+//! grid and synced to clients.
+
+use crate::{terrain::TerrainChunkSize, vol::RectVolSize};
+use serde::{Deserialize, Serialize};
+use vek::*;
+
+/// Width of a weather cell, in chunks
+pub const CELL_SIZE: u32 = 32;
+
+/// Width of a weather cell, in blocks
+pub fn cell_size_blocks() -> Vec2<f32> {
+    TerrainChunkSize::RECT_SIZE.map(|e| (e * CELL_SIZE) as f32)
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Weather {
+    /// Cloud cover, between 0 and 1
+    pub cloud: f32,
+    /// Rain intensity, between 0 and 1
+    pub rain: f32,
+    /// Snowfall intensity, between 0 and 1
+    pub snow: f32,
+    /// Wind velocity, in blocks per second
+    pub wind: Vec2<f32>,
+}
+
+impl Weather {
+    pub fn lerp(a: &Self, b: &Self, t: f32) -> Self {
+        Self {
+            cloud: Lerp::lerp(a.cloud, b.cloud, t),
+            rain: Lerp::lerp(a.rain, b.rain, t),
+            snow: Lerp::lerp(a.snow, b.snow, t),
+            wind: Vec2::lerp(a.wind, b.wind, t),
+        }
+    }
+
+    /// Combined rain and snowfall intensity, between 0 and 1
+    pub fn precipitation(&self) -> f32 { (self.rain + self.snow).min(1.0) }
+
+    pub fn is_storm(&self) -> bool { self.precipitation() > 0.6 && self.wind.magnitude() > 15.0 }
+
+    /// How far can be seen compared to a clear day, between 0.3 and 1
+    pub fn visibility(&self) -> f32 {
+        (1.0 - self.precipitation() * 0.6 - self.cloud * 0.1).max(0.3)
+    }
+}
+
+/// The weather of every cell of the world map. Cells are [`CELL_SIZE`]
+/// chunks wide, and weather is interpolated between their centers.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeatherGrid {
+    size: Vec2<u32>,
+    cells: Vec<Weather>,
+}
+
+impl WeatherGrid {
+    /// Create a grid of clear weather, `size` being the number of cells along
+    /// each axis
+    pub fn new(size: Vec2<u32>) -> Self {
+        Self {
+            size,
+            cells: vec![Weather::default(); size.product() as usize],
+        }
+    }
+
+    /// The grid size needed to cover a map of `map_size` chunks
+    pub fn size_for_map(map_size: Vec2<u32>) -> Vec2<u32> {
+        map_size.map(|e| ((e + CELL_SIZE - 1) / CELL_SIZE).max(1))
+    }
+
+    pub fn size(&self) -> Vec2<u32> { self.size }
+
+    fn idx(&self, cell: Vec2<i32>) -> Option<usize> {
+        if cell.x >= 0 && cell.y >= 0 && cell.x < self.size.x as i32 && cell.y < self.size.y as i32
+        {
+            Some((cell.y * self.size.x as i32 + cell.x) as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, cell: Vec2<i32>) -> Option<&Weather> {
+        self.idx(cell).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, cell: Vec2<i32>) -> Option<&mut Weather> {
+        self.idx(cell).map(move |idx| &mut self.cells[idx])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2<i32>, &Weather)> {
+        let width = self.size.x as i32;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, w)| (Vec2::new(i as i32 % width, i as i32 / width), w))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Vec2<i32>, &mut Weather)> {
+        let width = self.size.x as i32;
+        self.cells
+            .iter_mut()
+            .enumerate()
+            .map(move |(i, w)| (Vec2::new(i as i32 % width, i as i32 / width), w))
+    }
+
+    /// The weather at a world position, interpolated between the nearest
+    /// cells. Positions outside of the map get the weather of the closest
+    /// cell.
+    pub fn get_interpolated(&self, wpos: Vec2<f32>) -> Weather {
+        if self.cells.is_empty() {
+            return Weather::default();
+        }
+
+        let pos = wpos / cell_size_blocks() - 0.5;
+        let max = self.size.map(|e| e as i32 - 1);
+        let cell = |offs: Vec2<i32>| {
+            let cell = (pos.map(|e| e.floor() as i32) + offs).map2(max, |e, max| e.max(0).min(max));
+            self.cells[(cell.y * self.size.x as i32 + cell.x) as usize]
+        };
+        let frac = pos.map(|e| e - e.floor());
+
+        Weather::lerp(
+            &Weather::lerp(&cell(Vec2::new(0, 0)), &cell(Vec2::new(1, 0)), frac.x),
+            &Weather::lerp(&cell(Vec2::new(0, 1)), &cell(Vec2::new(1, 1)), frac.x),
+            frac.y,
+        )
+    }
+}
@@ -17,6 +17,22 @@ pub struct MessageBuffer {
     pub data: Vec<u8>,
 }
 
+impl MessageBuffer {
+    /// Serialize a Message the same way [`send`] does, e.g. to know how many
+    /// bytes it takes before passing it to [`send_raw`]
+    ///
+    /// [`send`]: crate::api::Stream::send
+    /// [`send_raw`]: crate::api::Stream::send_raw
+    pub fn serialize<M: Serialize>(message: &M) -> Self { serialize(message) }
+
+    /// Deserialize a Message received with [`recv_raw`] the same way [`recv`]
+    /// does
+    ///
+    /// [`recv`]: crate::api::Stream::recv
+    /// [`recv_raw`]: crate::api::Stream::recv_raw
+    pub fn deserialize<M: DeserializeOwned>(self) -> bincode::Result<M> { deserialize(self) }
+}
+
 #[derive(Debug)]
 pub(crate) struct OutgoingMessage {
     pub buffer: Arc<MessageBuffer>,
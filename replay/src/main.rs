@@ -0,0 +1,148 @@
+//! Headless playback of replays recorded by a server, to reproduce what a
+//! player was sent without having to run a game client.
+//!
+//! The replay is fed through the regular client, which rebuilds the state the
+//! recorded player saw tick by tick. Servers record replays when `replay_dir`
+//! is set in their settings.
+
+#![deny(unsafe_code)]
+
+use client::{replay::ReplayServer, Client, Event, Join, WorldExt};
+use common::{
+    clock::Clock,
+    comp,
+    replay::{ReplayError, ReplayReader},
+};
+use std::{
+    fs::File,
+    io::BufReader,
+    path::PathBuf,
+    process,
+    time::{Duration, Instant},
+};
+use structopt::StructOpt;
+use tracing::{error, info};
+
+const TPS: u64 = 30;
+/// How long to keep playing after the last frame was sent, for the client to
+/// handle it
+const DRAIN_TIME: Duration = Duration::from_secs(2);
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "veloren-replay",
+    about = "Play back a replay recorded by a server"
+)]
+struct Opts {
+    /// The replay to play back
+    #[structopt(parse(from_os_str))]
+    replay: PathBuf,
+    /// Playback speed, relative to the speed it was recorded at
+    #[structopt(short, long, default_value = "1.0")]
+    speed: f64,
+    /// Seconds between summaries of the state
+    #[structopt(long, default_value = "10")]
+    report_interval: u64,
+}
+
+fn main() {
+    // Initialize logging.
+    tracing_subscriber::fmt::init();
+
+    let opts = Opts::from_args();
+    if opts.speed <= 0.0 {
+        error!(?opts.speed, "The playback speed has to be positive");
+        process::exit(1);
+    }
+    let replay = match File::open(&opts.replay)
+        .map_err(ReplayError::from)
+        .and_then(|file| ReplayReader::new(BufReader::new(file)))
+    {
+        Ok(replay) => replay,
+        Err(e) => {
+            error!(%e, ?opts.replay, "Failed to open the replay");
+            process::exit(1);
+        },
+    };
+    info!(
+        protocol_version = replay.protocol_version(),
+        "Playing back {:?}", opts.replay
+    );
+
+    let server = match ReplayServer::start(replay, opts.speed) {
+        Ok(server) => server,
+        Err(e) => {
+            error!(?e, "Failed to start playback");
+            process::exit(1);
+        },
+    };
+    let mut client = match Client::new(server.addr(), None) {
+        Ok(client) => client,
+        Err(e) => {
+            error!(?e, "Failed to play back the replay");
+            process::exit(1);
+        },
+    };
+
+    let started = Instant::now();
+    let report_interval = Duration::from_secs(opts.report_interval);
+    let mut last_report = Instant::now();
+    let mut outcomes = 0;
+    let mut clock = Clock::start();
+    let mut finished_at = None;
+    let mut failed = false;
+    while finished_at.map_or(true, |t: Instant| t.elapsed() < DRAIN_TIME) {
+        if finished_at.is_none() && server.is_finished() {
+            finished_at = Some(Instant::now());
+        }
+
+        match client.tick(
+            comp::ControllerInputs::default(),
+            clock.get_last_delta(),
+            |_| {},
+        ) {
+            Ok(events) => {
+                outcomes += events
+                    .iter()
+                    .filter(|e| matches!(e, Event::Outcome(_)))
+                    .count();
+            },
+            Err(e) => {
+                error!(?e, "Playback stopped");
+                failed = true;
+                break;
+            },
+        }
+        client.cleanup();
+
+        if last_report.elapsed() >= report_interval {
+            last_report = Instant::now();
+            report(&client, outcomes);
+        }
+
+        clock.tick(Duration::from_millis(1000 / TPS));
+    }
+
+    info!("Finished after {:?}", started.elapsed());
+    report(&client, outcomes);
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn report(client: &Client, outcomes: usize) {
+    let ecs = client.state().ecs();
+    let pos = ecs
+        .read_storage::<comp::Pos>()
+        .get(client.entity())
+        .map(|pos| pos.0);
+    info!(
+        state = ?client.get_client_state(),
+        ?pos,
+        entities = ecs.entities().join().count(),
+        chunks = client.state().terrain().iter().count(),
+        outcomes,
+        time_of_day = client.state().get_time_of_day(),
+        "Replayed state"
+    );
+}
@@ -0,0 +1,88 @@
+//! Commands typed into the console the server runs in
+
+use server::shutdown::ShutdownKind;
+use std::{
+    io::{self, BufRead},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+use tracing::{info, warn};
+
+const USAGE: &str =
+    "Commands: shutdown [seconds], restart [seconds], abort, password <username> <password>";
+
+/// Seconds until a shutdown when none are given
+const DEFAULT_DELAY: u64 = 60;
+
+pub enum Command {
+    Shutdown {
+        kind: ShutdownKind,
+        delay: Duration,
+    },
+    Abort,
+    /// Create a local account or reset its password
+    Password {
+        username: String,
+        password: String,
+    },
+}
+
+impl Command {
+    fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        let kind = match words.next()? {
+            "shutdown" => ShutdownKind::Shutdown,
+            "restart" => ShutdownKind::Restart,
+            "abort" => return Some(Command::Abort),
+            "password" => {
+                // The password is the rest of the line, so it may contain spaces
+                let args = line.trim_start()["password".len()..].trim_start();
+                let (username, password) = args.split_at(args.find(' ')?);
+                let password = &password[1..];
+                if password.is_empty() {
+                    return None;
+                }
+                return Some(Command::Password {
+                    username: username.to_owned(),
+                    password: password.to_owned(),
+                });
+            },
+            _ => return None,
+        };
+        let delay = match words.next() {
+            Some(secs) => Duration::from_secs(secs.parse().ok()?),
+            None => Duration::from_secs(DEFAULT_DELAY),
+        };
+        Some(Command::Shutdown { kind, delay })
+    }
+}
+
+/// Read commands from the console in the background, until it is closed
+pub fn spawn() -> mpsc::Receiver<Command> {
+    let (command_tx, command_rx) = mpsc::channel();
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    warn!(?e, "Failed to read from the console");
+                    break;
+                },
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match Command::parse(&line) {
+                Some(command) => {
+                    if command_tx.send(command).is_err() {
+                        break;
+                    }
+                },
+                None => info!("{}", USAGE),
+            }
+        }
+    });
+    command_rx
+}
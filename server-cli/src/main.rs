@@ -1,9 +1,18 @@
 #![deny(unsafe_code)]
 
+mod console;
+
 use common::clock::Clock;
-use server::{Event, Input, Server, ServerSettings};
-use std::time::Duration;
-use tracing::{info, Level};
+use console::Command;
+use server::{shutdown::ShutdownKind, Event, Input, Server, ServerSettings};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tracing::{info, warn, Level};
 use tracing_subscriber::{filter::LevelFilter, EnvFilter, FmtSubscriber};
 
 const TPS: u64 = 30;
@@ -48,11 +57,40 @@ fn main() {
     // Create server
     let mut server = Server::new(settings).expect("Failed to create server instance!");
 
+    // Being terminated or interrupted shuts the server down right away, but
+    // still in an orderly way
+    let terminate = Arc::new(AtomicBool::new(false));
+    for signal in &[signal_hook::SIGTERM, signal_hook::SIGINT] {
+        if let Err(e) = signal_hook::flag::register(*signal, Arc::clone(&terminate)) {
+            warn!(?e, ?signal, "Failed to handle signal");
+        }
+    }
+    let console = console::spawn();
+
     info!("Server is ready to accept connections.");
     info!(?metrics_port, "starting metrics at port");
     info!(?server_port, "starting server at port");
 
-    loop {
+    let kind = 'running: loop {
+        if terminate.swap(false, Ordering::Relaxed) {
+            server.shutdown_in(ShutdownKind::Shutdown, Duration::from_secs(0));
+        }
+        for command in console.try_iter() {
+            match command {
+                Command::Shutdown { kind, delay } => server.shutdown_in(kind, delay),
+                Command::Abort => {
+                    if !server.abort_shutdown() {
+                        info!("No shutdown is pending");
+                    }
+                },
+                Command::Password { username, password } => {
+                    if let Err(e) = server.set_account_password(&username, &password) {
+                        warn!(?e, ?username, "Failed to set the account password");
+                    }
+                },
+            }
+        }
+
         let events = server
             .tick(Input::default(), clock.get_last_delta())
             .expect("Failed to tick server");
@@ -62,6 +100,7 @@ fn main() {
                 Event::ClientConnected { entity: _ } => info!("Client connected!"),
                 Event::ClientDisconnected { entity: _ } => info!("Client disconnected!"),
                 Event::Chat { entity: _, msg } => info!("[Client] {}", msg),
+                Event::Shutdown { kind } => break 'running kind,
             }
         }
 
@@ -70,5 +109,11 @@ fn main() {
 
         // Wait for the next tick.
         clock.tick(Duration::from_millis(1000 / TPS));
-    }
+    };
+
+    info!(?kind, "Shutting down...");
+    // Dropping the server disconnects everyone and saves what is persisted
+    drop(server);
+    info!("Server stopped");
+    std::process::exit(kind.exit_code());
 }
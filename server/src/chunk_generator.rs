@@ -1,12 +1,16 @@
+use crate::metrics::ChunkGenMetrics;
 #[cfg(not(feature = "worldgen"))]
 use crate::test_world::{IndexOwned, World};
 use common::{generation::ChunkSupplement, terrain::TerrainChunk};
 use crossbeam::channel;
 use hashbrown::{hash_map::Entry, HashMap};
 use specs::Entity as EcsEntity;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
 };
 use vek::*;
 #[cfg(feature = "worldgen")]
@@ -21,15 +25,16 @@ pub struct ChunkGenerator {
     chunk_tx: channel::Sender<ChunkGenResult>,
     chunk_rx: channel::Receiver<ChunkGenResult>,
     pending_chunks: HashMap<Vec2<i32>, Arc<AtomicBool>>,
+    metrics: ChunkGenMetrics,
 }
 impl ChunkGenerator {
-    #[allow(clippy::new_without_default)] // TODO: Pending review in #587
-    pub fn new() -> Self {
+    pub fn new(metrics: ChunkGenMetrics) -> Self {
         let (chunk_tx, chunk_rx) = channel::unbounded();
         Self {
             chunk_tx,
             chunk_rx,
             pending_chunks: HashMap::new(),
+            metrics,
         }
     }
 
@@ -49,11 +54,17 @@ impl ChunkGenerator {
         let cancel = Arc::new(AtomicBool::new(false));
         v.insert(Arc::clone(&cancel));
         let chunk_tx = self.chunk_tx.clone();
+        let chunk_gen_time = self.metrics.chunk_gen_time.clone();
+        let requested = Instant::now();
         thread_pool.execute(move || {
             let index = index.as_index_ref();
             let payload = world
                 .generate_chunk(index, key, || cancel.load(Ordering::Relaxed))
                 .map_err(|_| entity);
+            // Cancelled chunks would skew the latency
+            if payload.is_ok() {
+                chunk_gen_time.observe(requested.elapsed().as_secs_f64());
+            }
             let _ = chunk_tx.send((key, payload));
         });
     }
@@ -1,23 +1,35 @@
-use crate::error::Error;
-use common::msg::{ClientMsg, ClientState, RequestStateError, ServerMsg};
+use crate::{error::Error, metrics::MessageMetrics, replay::Recorder};
+use common::{
+    comp::Pos,
+    msg::{delta::Baselines, ClientMsg, ClientState, RequestStateError, ServerMsg},
+    sync::Uid,
+};
 use hashbrown::HashSet;
-use network::{Participant, Stream};
+use network::{MessageBuffer, Participant, Pid, Stream, StreamError};
 use specs::{Component, FlaggedStorage};
 use specs_idvs::IdvStorage;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
-    Mutex,
+    Arc, Mutex,
 };
-use tracing::debug;
+use tracing::{debug, warn};
 use vek::*;
 
 pub struct Client {
     pub client_state: ClientState,
+    /// Identifies the connection, even once the participant was taken on
+    /// disconnect
+    pub pid: Pid,
     pub participant: Mutex<Option<Participant>>,
     pub singleton_stream: Stream,
     pub network_error: AtomicBool,
     pub last_ping: f64,
     pub login_msg_sent: bool,
+    /// What component deltas sent to the client are encoded against
+    pub sync_baselines: Baselines,
+    /// Records what is sent to the client, if its session is recorded
+    pub recorder: Option<Recorder>,
+    pub message_metrics: MessageMetrics,
 }
 
 impl Component for Client {
@@ -26,18 +38,46 @@ impl Component for Client {
 
 impl Client {
     pub fn notify(&mut self, msg: ServerMsg) {
+        // The client forgets its baselines along with the entities
+        match &msg {
+            ServerMsg::EntitySync(package) => package
+                .deleted_entities
+                .iter()
+                .for_each(|uid| self.sync_baselines.forget(*uid)),
+            ServerMsg::DeleteEntity(uid) => self.sync_baselines.forget(uid.0),
+            ServerMsg::ExitIngameCleanup => self.sync_baselines = Baselines::default(),
+            _ => {},
+        }
+        self.record(&msg);
         if !self.network_error.load(Ordering::Relaxed) {
-            if let Err(e) = self.singleton_stream.send(msg) {
+            if let Err(e) = self.send(&msg) {
                 debug!(?e, "got a network error with client");
                 self.network_error.store(true, Ordering::Relaxed);
             }
         }
     }
 
+    fn send(&mut self, msg: &ServerMsg) -> Result<(), StreamError> {
+        let buffer = MessageBuffer::serialize(msg);
+        self.message_metrics
+            .sent(msg.variant_name(), buffer.data.len());
+        self.singleton_stream.send_raw(Arc::new(buffer))
+    }
+
     pub async fn recv(&mut self) -> Result<ClientMsg, Error> {
         if !self.network_error.load(Ordering::Relaxed) {
-            match self.singleton_stream.recv().await {
-                Ok(r) => Ok(r),
+            let msg = self.singleton_stream.recv_raw().await.and_then(|buffer| {
+                let bytes = buffer.data.len();
+                buffer
+                    .deserialize::<ClientMsg>()
+                    .map(|msg| (msg, bytes))
+                    .map_err(StreamError::from)
+            });
+            match msg {
+                Ok((msg, bytes)) => {
+                    self.message_metrics.received(msg.variant_name(), bytes);
+                    Ok(msg)
+                },
                 Err(e) => {
                     debug!(?e, "got a network error with client while recv");
                     self.network_error.store(true, Ordering::Relaxed);
@@ -45,7 +85,7 @@ impl Client {
                 },
             }
         } else {
-            Err(Error::StreamErr(network::StreamError::StreamClosed))
+            Err(Error::StreamErr(StreamError::StreamClosed))
         }
     }
 
@@ -65,9 +105,18 @@ impl Client {
 
     pub fn allow_state(&mut self, new_state: ClientState) {
         self.client_state = new_state;
-        let _ = self
-            .singleton_stream
-            .send(ServerMsg::StateAnswer(Ok(new_state)));
+        let msg = ServerMsg::StateAnswer(Ok(new_state));
+        self.record(&msg);
+        let _ = self.send(&msg);
+    }
+
+    fn record(&mut self, msg: &ServerMsg) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.record(msg) {
+                warn!(?e, "Failed to record a message, stopping the recording");
+                self.recorder = None;
+            }
+        }
     }
 
     pub fn error_state(&mut self, error: RequestStateError) {
@@ -89,3 +138,23 @@ pub struct RegionSubscription {
 impl Component for RegionSubscription {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
+
+/// Where a spectating client sees the world from, since it has no body that
+/// others could see
+#[derive(Clone, Debug)]
+pub struct SpectatorCamera {
+    pub pos: Vec3<f32>,
+    /// Entity the camera moves along with
+    pub follow: Option<Uid>,
+}
+
+impl Component for SpectatorCamera {
+    type Storage = IdvStorage<Self>;
+}
+
+/// Where a client sees the world from: its spectator camera, or its character
+pub fn viewpoint(pos: Option<&Pos>, camera: Option<&SpectatorCamera>) -> Option<Vec3<f32>> {
+    camera
+        .map(|camera| camera.pos)
+        .or_else(|| pos.map(|pos| pos.0))
+}
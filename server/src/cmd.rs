@@ -2,14 +2,19 @@
 //! To implement a new command, add an instance of `ChatCommand` to
 //! `CHAT_COMMANDS` and provide a handler function.
 
-use crate::{client::Client, Server, StateExt};
+use crate::{
+    client::{Client, SpectatorCamera},
+    login_provider::LoginProvider,
+    shutdown::ShutdownKind,
+    Server, StateExt,
+};
 use chrono::{NaiveTime, Timelike};
 use common::{
     assets,
     cmd::{ChatCommand, CHAT_COMMANDS, CHAT_SHORTCUTS},
     comp::{self, ChatType, Item, LightEmitter, WaypointArea},
     event::{EventBus, ServerEvent},
-    msg::{Notification, PlayerListUpdate, ServerMsg},
+    msg::{ClientState, Notification, PlayerListUpdate, ServerMsg},
     npc::{self, get_npc_name},
     state::TimeOfDay,
     sync::{Uid, WorldSyncExt},
@@ -20,7 +25,7 @@ use common::{
 };
 use rand::Rng;
 use specs::{Builder, Entity as EcsEntity, Join, WorldExt};
-use std::convert::TryFrom;
+use std::{convert::TryFrom, time::Duration};
 use vek::*;
 use world::util::Sampler;
 
@@ -87,13 +92,17 @@ fn get_handler(cmd: &ChatCommand) -> CommandHandler {
         ChatCommand::MakeBlock => handle_make_block,
         ChatCommand::Motd => handle_motd,
         ChatCommand::Object => handle_object,
+        ChatCommand::Password => handle_password,
         ChatCommand::Players => handle_players,
         ChatCommand::Region => handle_region,
         ChatCommand::RemoveLights => handle_remove_lights,
         ChatCommand::Say => handle_say,
         ChatCommand::SetLevel => handle_set_level,
         ChatCommand::SetMotd => handle_set_motd,
+        ChatCommand::SetPassword => handle_set_password,
+        ChatCommand::Shutdown => handle_shutdown,
         ChatCommand::Spawn => handle_spawn,
+        ChatCommand::Spectate => handle_spectate,
         ChatCommand::Sudo => handle_sudo,
         ChatCommand::Tell => handle_tell,
         ChatCommand::Time => handle_time,
@@ -254,6 +263,63 @@ fn handle_set_motd(
     }
 }
 
+fn handle_set_password(
+    server: &mut Server,
+    client: EcsEntity,
+    _target: EcsEntity,
+    args: String,
+    action: &ChatCommand,
+) {
+    if let Ok((username, password)) = scan_fmt!(&args, &action.arg_fmt(), String, String) {
+        let msg = match server.set_account_password(&username, &password) {
+            Ok(true) => {
+                ChatType::CommandInfo.server_msg(format!("Created account \"{}\"", username))
+            },
+            Ok(false) => {
+                ChatType::CommandInfo.server_msg(format!("Reset the password of \"{}\"", username))
+            },
+            Err(e) => ChatType::CommandError.server_msg(e),
+        };
+        server.notify_client(client, msg);
+    } else {
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg(action.help_string()),
+        );
+    }
+}
+
+fn handle_shutdown(
+    server: &mut Server,
+    client: EcsEntity,
+    _target: EcsEntity,
+    args: String,
+    action: &ChatCommand,
+) {
+    let (shutdown_action, seconds) = scan_fmt_some!(&args, &action.arg_fmt(), String, u64);
+    let kind = match shutdown_action.as_deref() {
+        Some("shutdown") => ShutdownKind::Shutdown,
+        Some("restart") => ShutdownKind::Restart,
+        Some("abort") => {
+            if !server.abort_shutdown() {
+                server.notify_client(
+                    client,
+                    ChatType::CommandError.server_msg("No shutdown is pending."),
+                );
+            }
+            return;
+        },
+        _ => {
+            server.notify_client(
+                client,
+                ChatType::CommandError.server_msg(action.help_string()),
+            );
+            return;
+        },
+    };
+    server.shutdown_in(kind, Duration::from_secs(seconds.unwrap_or(60)));
+}
+
 fn handle_jump(
     server: &mut Server,
     client: EcsEntity,
@@ -527,6 +593,89 @@ fn handle_tp(
     }
 }
 
+fn handle_spectate(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: String,
+    action: &ChatCommand,
+) {
+    let may_spectate = server.settings().may_spectate(
+        server
+            .state
+            .ecs()
+            .read_storage::<comp::Player>()
+            .get(client),
+        server.entity_is_admin(client),
+    );
+    if !may_spectate {
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg(format!(
+                "You don't have permission to use '/{}'.",
+                action.keyword()
+            )),
+        );
+        return;
+    }
+
+    let follow = if let Some(alias) = scan_fmt_some!(&args, &action.arg_fmt(), String) {
+        let ecs = server.state.ecs();
+        let uid = (
+            &ecs.read_storage::<Uid>(),
+            &ecs.read_storage::<comp::Player>(),
+        )
+            .join()
+            .find(|(_, player)| player.alias == alias)
+            .map(|(uid, _)| *uid);
+        if uid.is_none() {
+            server.notify_client(
+                client,
+                ChatType::CommandError.server_msg("Player not found!"),
+            );
+            server.notify_client(
+                client,
+                ChatType::CommandError.server_msg(action.help_string()),
+            );
+            return;
+        }
+        uid
+    } else {
+        None
+    };
+
+    let ecs = server.state.ecs();
+    let client_state = ecs
+        .read_storage::<Client>()
+        .get(target)
+        .map(|c| c.client_state);
+    match client_state {
+        Some(ClientState::Spectator) => {
+            if let (Some(camera), Some(target_client)) = (
+                ecs.write_storage::<SpectatorCamera>().get_mut(target),
+                ecs.write_storage::<Client>().get_mut(target),
+            ) {
+                camera.follow = follow;
+                target_client.notify(ServerMsg::SpectatorCamera {
+                    pos: camera.pos,
+                    follow,
+                });
+            }
+        },
+        Some(ClientState::Registered) | Some(ClientState::Character) => {
+            ecs.read_resource::<EventBus<ServerEvent>>()
+                .emit_now(ServerEvent::Spectate {
+                    entity: target,
+                    follow,
+                });
+        },
+        _ => server.notify_client(
+            client,
+            ChatType::CommandError.server_msg("Only logged in players can spectate"),
+        ),
+    }
+}
+
 fn handle_spawn(
     server: &mut Server,
     client: EcsEntity,
@@ -858,6 +1007,70 @@ fn handle_kill_npcs(
     server.notify_client(client, ChatType::CommandInfo.server_msg(text));
 }
 
+fn handle_password(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: String,
+    action: &ChatCommand,
+) {
+    if let Some((old_password, new_password)) = parse_password_args(&args) {
+        let pid = server
+            .state
+            .ecs()
+            .read_storage::<Client>()
+            .get(client)
+            .map(|client| client.pid);
+        let (username, pid) = match (
+            server.state.read_component_cloned::<comp::Player>(target),
+            pid,
+        ) {
+            (Some(player), Some(pid)) => (player.alias, pid),
+            _ => {
+                server.notify_client(
+                    client,
+                    ChatType::CommandError.server_msg("You have no account."),
+                );
+                return;
+            },
+        };
+        let changed = server
+            .state
+            .ecs()
+            .write_resource::<LoginProvider>()
+            .change_password(pid, &username, old_password, new_password);
+        let msg = match changed {
+            Ok(()) => ChatType::CommandInfo.server_msg("Your password was changed."),
+            Err(e) => ChatType::CommandError.server_msg(e),
+        };
+        server.notify_client(client, msg);
+    } else {
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg(action.help_string()),
+        );
+    }
+}
+
+/// Split the arguments of `/password` into the old and the new password. The
+/// new password is the rest of the line, so an old password with spaces has to
+/// be put in quotes.
+fn parse_password_args(args: &str) -> Option<(&str, &str)> {
+    let args = args.trim_start();
+    let (old_password, rest) = if let Some(quoted) = args.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        (&quoted[..end], &quoted[end + 1..])
+    } else {
+        args.split_at(args.find(' ')?)
+    };
+    let new_password = rest.strip_prefix(' ')?;
+    if old_password.is_empty() || new_password.is_empty() {
+        None
+    } else {
+        Some((old_password, new_password))
+    }
+}
+
 #[allow(clippy::float_cmp)] // TODO: Pending review in #587
 #[allow(clippy::needless_return)] // TODO: Pending review in #587
 #[allow(clippy::useless_format)] // TODO: Pending review in #587
@@ -1101,6 +1314,7 @@ fn handle_waypoint(
                 .insert(target, comp::Waypoint::new(pos.0, *time));
             server.notify_client(client, ChatType::CommandInfo.server_msg("Waypoint saved!"));
             server.notify_client(client, ServerMsg::Notification(Notification::WaypointSaved));
+            server.notify_client(target, ServerMsg::WaypointUpdate(pos.0));
         },
         None => server.notify_client(
             client,
@@ -1809,3 +2023,24 @@ fn handle_whitelist(
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_passwords_with_spaces() {
+        assert_eq!(parse_password_args("old new"), Some(("old", "new")));
+        assert_eq!(
+            parse_password_args("old correct horse battery"),
+            Some(("old", "correct horse battery"))
+        );
+        assert_eq!(
+            parse_password_args("\"old pass\" new pass"),
+            Some(("old pass", "new pass"))
+        );
+        assert_eq!(parse_password_args("old"), None);
+        assert_eq!(parse_password_args("old "), None);
+        assert_eq!(parse_password_args("\"old pass new"), None);
+    }
+}
@@ -0,0 +1,241 @@
+//! Storage for the inventories of container blocks (chests, crates, ...)
+
+use crate::{client::Client, StateExt};
+use common::{
+    comp::{self, Inventory, Item},
+    msg::ServerMsg,
+    state::{State, TerrainChanges},
+    terrain::{Block, BlockKind, TerrainGrid},
+};
+use hashbrown::{HashMap, HashSet};
+use rand::Rng;
+use specs::{Builder, Component, HashMapStorage, Join, WorldExt};
+use vek::*;
+
+/// Number of slots in a container inventory
+pub const CONTAINER_SIZE: usize = 18;
+
+/// The container an entity currently has open
+pub struct OpenedContainer(pub Vec3<i32>);
+
+impl Component for OpenedContainer {
+    type Storage = HashMapStorage<Self>;
+}
+
+/// A container as it is persisted
+#[derive(Clone, Debug)]
+pub struct ContainerData {
+    pub inventory: Inventory,
+    /// Whether the block was placed by a player rather than generated with
+    /// the world, in which case it is put back when its chunk is generated
+    /// again
+    pub placed: bool,
+}
+
+/// The inventories of all containers in the world that have been opened at
+/// least once, keyed by the position of their block.
+///
+/// All access happens on the main thread during event handling, so players
+/// using the same container at the same time always see a consistent state.
+pub struct Containers {
+    containers: HashMap<Vec3<i32>, ContainerData>,
+    /// The containers placed by players, by the key of their chunk
+    placed: HashMap<Vec2<i32>, HashSet<Vec3<i32>>>,
+    /// Containers that changed or were removed since they were last persisted
+    dirty: HashSet<Vec3<i32>>,
+}
+
+impl Containers {
+    pub fn new(containers: impl IntoIterator<Item = (Vec3<i32>, ContainerData)>) -> Self {
+        let containers = containers.into_iter().collect::<HashMap<_, _>>();
+        let mut placed = HashMap::<_, HashSet<_>>::new();
+        for (pos, _) in containers.iter().filter(|(_, container)| container.placed) {
+            placed
+                .entry(TerrainGrid::chunk_key(*pos))
+                .or_default()
+                .insert(*pos);
+        }
+        Self {
+            containers,
+            placed,
+            dirty: HashSet::new(),
+        }
+    }
+
+    pub fn get(&self, pos: Vec3<i32>) -> Option<&Inventory> {
+        self.containers
+            .get(&pos)
+            .map(|container| &container.inventory)
+    }
+
+    /// Get the inventory of the container at `pos`, creating it if this is the
+    /// first time it is accessed. Chests are filled with loot when created,
+    /// boss chests with more and better loot.
+    pub fn get_or_create(&mut self, pos: Vec3<i32>, block: Block) -> &mut Inventory {
+        let dirty = &mut self.dirty;
+        &mut self
+            .containers
+            .entry(pos)
+            .or_insert_with(|| {
+                dirty.insert(pos);
+                let mut inventory = empty_inventory();
+                let loot_count = match block.kind() {
+                    BlockKind::Chest => rand::thread_rng().gen_range(1, 4),
+                    BlockKind::BossChest => rand::thread_rng().gen_range(3, 6),
+                    _ => 0,
+                };
+                for _ in 0..loot_count {
+                    if let Some(item) = Item::try_reclaim_from_block(block) {
+                        inventory.push(item);
+                    }
+                }
+                ContainerData {
+                    inventory,
+                    placed: false,
+                }
+            })
+            .inventory
+    }
+
+    /// Add the empty container of a block a player placed at `pos`
+    pub fn place(&mut self, pos: Vec3<i32>) {
+        self.containers.insert(pos, ContainerData {
+            inventory: empty_inventory(),
+            placed: true,
+        });
+        self.placed
+            .entry(TerrainGrid::chunk_key(pos))
+            .or_default()
+            .insert(pos);
+        self.dirty.insert(pos);
+    }
+
+    /// Get the inventory of a container for modification, marking it as
+    /// needing to be persisted
+    pub fn get_mut(&mut self, pos: Vec3<i32>) -> Option<&mut Inventory> {
+        let container = self.containers.get_mut(&pos)?;
+        self.dirty.insert(pos);
+        Some(&mut container.inventory)
+    }
+
+    /// Remove the container at `pos`, because its block was replaced,
+    /// returning its contents
+    pub fn remove(&mut self, pos: Vec3<i32>) -> Option<Inventory> {
+        let container = self.containers.remove(&pos)?;
+        if container.placed {
+            let key = TerrainGrid::chunk_key(pos);
+            if let Some(placed) = self.placed.get_mut(&key) {
+                placed.remove(&pos);
+                if placed.is_empty() {
+                    self.placed.remove(&key);
+                }
+            }
+        }
+        self.dirty.insert(pos);
+        Some(container.inventory)
+    }
+
+    /// The positions of the containers placed by players in the chunk at `key`
+    pub fn placed_in(&self, key: Vec2<i32>) -> impl Iterator<Item = Vec3<i32>> + '_ {
+        self.placed.get(&key).into_iter().flatten().copied()
+    }
+
+    /// Take the containers that changed since the last call, `None` for those
+    /// that were removed
+    pub fn take_dirty(&mut self) -> Vec<(Vec3<i32>, Option<ContainerData>)> {
+        let containers = &self.containers;
+        self.dirty
+            .drain()
+            .map(|pos| (pos, containers.get(&pos).cloned()))
+            .collect()
+    }
+}
+
+fn empty_inventory() -> Inventory {
+    Inventory {
+        slots: vec![None; CONTAINER_SIZE],
+        amount: 0,
+    }
+}
+
+/// Drop the contents of containers whose block was replaced (by mining,
+/// building, explosions, ...) and put the blocks of containers placed by
+/// players back into chunks that were generated again
+pub fn handle_terrain_changes(state: &mut State) {
+    let (removed, placed) = {
+        let terrain_changes = state.ecs().read_resource::<TerrainChanges>();
+        let mut containers = state.ecs().write_resource::<Containers>();
+        let removed = terrain_changes
+            .modified_blocks
+            .iter()
+            .filter(|(_, block)| !block.is_container())
+            .filter_map(|(pos, _)| Some((*pos, containers.remove(*pos)?)))
+            .collect::<Vec<_>>();
+        let placed = terrain_changes
+            .new_chunks
+            .iter()
+            .flat_map(|key| containers.placed_in(*key))
+            .collect::<Vec<_>>();
+        (removed, placed)
+    };
+
+    for pos in placed {
+        state.set_block(pos, Block::new(BlockKind::Crate, Rgb::zero()));
+    }
+
+    for (pos, inventory) in removed {
+        let openers = (
+            &state.ecs().entities(),
+            &state.ecs().read_storage::<OpenedContainer>(),
+        )
+            .join()
+            .filter(|(_, opened)| opened.0 == pos)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in openers {
+            state
+                .ecs()
+                .write_storage::<OpenedContainer>()
+                .remove(entity);
+            if let Some(client) = state.ecs().write_storage::<Client>().get_mut(entity) {
+                client.notify(ServerMsg::ContainerClosed);
+            }
+        }
+
+        for item in inventory.slots.into_iter().flatten() {
+            state
+                .create_object(
+                    comp::Pos(pos.map(|e| e as f32 + 0.5)),
+                    comp::object::Body::Pouch,
+                )
+                .with(item)
+                .build();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_placed_containers() {
+        let mut containers = Containers::new(Vec::new());
+        let pos = Vec3::new(40, -3, 12);
+        let key = TerrainGrid::chunk_key(pos);
+
+        containers.place(pos);
+        assert_eq!(containers.placed_in(key).collect::<Vec<_>>(), vec![pos]);
+        let dirty = containers.take_dirty();
+        assert_eq!(dirty.len(), 1);
+        assert!(dirty[0].1.as_ref().map_or(false, |data| data.placed));
+
+        assert!(containers.remove(pos).is_some());
+        assert!(containers.get(pos).is_none());
+        assert_eq!(containers.placed_in(key).count(), 0);
+        let dirty = containers.take_dirty();
+        assert_eq!(dirty.len(), 1);
+        assert!(dirty[0].1.is_none());
+        assert!(containers.remove(pos).is_none());
+    }
+}
@@ -19,6 +19,10 @@ use specs::{join::Join, saveload::MarkerAllocator, Entity as EcsEntity, WorldExt
 use tracing::error;
 use vek::Vec3;
 
+/// Durability lost by the weapon of the attacker and by each piece of armor
+/// of the target when a hit deals damage
+const WEAR_PER_HIT: u32 = 1;
+
 pub fn handle_damage(server: &Server, uid: Uid, change: HealthChange) {
     let state = &server.state;
     let ecs = state.ecs();
@@ -26,6 +30,24 @@ pub fn handle_damage(server: &Server, uid: Uid, change: HealthChange) {
         if let Some(stats) = ecs.write_storage::<Stats>().get_mut(entity) {
             stats.health.change_by(change);
         }
+
+        // Wear down the equipment involved in the hit
+        if change.amount < 0 {
+            let mut loadouts = ecs.write_storage::<comp::Loadout>();
+            if let Some(loadout) = loadouts.get_mut(entity) {
+                loadout.wear_armor(WEAR_PER_HIT);
+            }
+            if let Some(attacker) = match change.cause {
+                HealthSource::Attack { by } | HealthSource::Projectile { owner: Some(by) } => {
+                    ecs.entity_from_uid(by.into())
+                },
+                _ => None,
+            } {
+                if let Some(loadout) = loadouts.get_mut(attacker) {
+                    loadout.wear_weapon(WEAR_PER_HIT);
+                }
+            }
+        }
     }
 }
 
@@ -1,15 +1,26 @@
 use crate::{
     client::{Client, RegionSubscription},
-    Server,
+    farming::Farms,
+    mining::MinedBlocks,
+    Server, StateExt,
 };
 use common::{
     assets,
     comp::{self, item},
+    farming::FARMLAND_COLOR,
     msg::ServerMsg,
+    state::Time,
     sync::{Uid, WorldSyncExt},
+    terrain::{Block, BlockKind},
+    util::Dir,
+    vol::ReadVol,
 };
-use specs::{world::WorldExt, Entity as EcsEntity};
+use specs::{world::WorldExt, Builder, Entity as EcsEntity};
 use tracing::error;
+use vek::*;
+
+/// Durability lost by a pickaxe each time it breaks a block
+const WEAR_PER_BLOCK: u32 = 1;
 
 pub fn handle_lantern(server: &mut Server, entity: EcsEntity) {
     let ecs = server.state_mut().ecs();
@@ -190,3 +201,121 @@ pub fn handle_possess(server: &Server, possessor_uid: Uid, possesse_uid: Uid) {
         }
     }
 }
+
+/// The block `entity` is looking at within `range`, and its position
+fn targeted_block(
+    server: &Server,
+    entity: EcsEntity,
+    dir: Dir,
+    range: f32,
+) -> Option<(Vec3<i32>, Block)> {
+    let ecs = server.state.ecs();
+    let eye = match (
+        ecs.read_storage::<comp::Pos>().get(entity),
+        ecs.read_storage::<comp::Body>().get(entity),
+    ) {
+        (Some(pos), Some(body)) => pos.0 + Vec3::unit_z() * body.height() * 0.9,
+        _ => return None,
+    };
+
+    let terrain = server.state.terrain();
+    let (dist, hit) = terrain
+        .ray(eye, eye + *dir * range)
+        .until(|block| block.is_tangible())
+        .cast();
+    match hit {
+        Ok(Some(block)) => Some((
+            (eye + *dir * (dist + 0.01)).map(|e| e.floor() as i32),
+            *block,
+        )),
+        _ => None,
+    }
+}
+
+/// Strike the block `entity` is looking at with its pickaxe. Once the block
+/// has taken enough hits it breaks and the miner gets what it drops.
+pub fn handle_mine_block(server: &mut Server, entity: EcsEntity, dir: Dir, range: f32) {
+    let power = server
+        .state
+        .ecs()
+        .read_storage::<comp::Loadout>()
+        .get(entity)
+        .and_then(|loadout| loadout.active_item.as_ref())
+        .and_then(|active| match &active.item.kind {
+            item::ItemKind::Tool(tool) => Some(tool.base_power() * active.item.effectiveness()),
+            _ => None,
+        })
+        .unwrap_or(0.0);
+    let (block_pos, block) = match targeted_block(server, entity, dir, range) {
+        Some(target) => target,
+        None => return,
+    };
+
+    // Sites are protected so that towns and dungeons can't be dug through
+    #[cfg(feature = "worldgen")]
+    {
+        let chunk_key = server.state.terrain().pos_key(block_pos);
+        if server
+            .world
+            .sim()
+            .get(chunk_key)
+            .map_or(false, |chunk| !chunk.sites.is_empty())
+        {
+            return;
+        }
+    }
+
+    let state = server.state_mut();
+    let time = state.ecs().read_resource::<Time>().0;
+    let broken =
+        state
+            .ecs()
+            .write_resource::<MinedBlocks>()
+            .hit(block_pos, block.kind(), power, time);
+    if !broken || state.try_set_block(block_pos, Block::empty()).is_none() {
+        return;
+    }
+
+    if let Some(loadout) = state.ecs().write_storage::<comp::Loadout>().get_mut(entity) {
+        loadout.wear_weapon(WEAR_PER_BLOCK);
+    }
+
+    if let Some(item) = comp::Item::try_mine_from_block(block) {
+        // Drop the item where the block was if the miner has no room for it
+        if !state.give_item(entity, item.clone()) {
+            state
+                .create_object(Default::default(), comp::object::Body::Pouch)
+                .with(comp::Pos(block_pos.map(|e| e as f32 + 0.5)))
+                .with(item)
+                .build();
+        }
+    }
+}
+
+/// Till the grass `entity` is looking at with its farming tool, so that crops
+/// can be planted in it
+pub fn handle_till_soil(server: &mut Server, entity: EcsEntity, dir: Dir, range: f32) {
+    let (block_pos, block) = match targeted_block(server, entity, dir, range) {
+        Some(target) => target,
+        None => return,
+    };
+    let above = block_pos + Vec3::unit_z();
+    let clear_above = server
+        .state
+        .terrain()
+        .get(above)
+        .map_or(false, |block| !block.is_solid() && !block.is_fluid());
+    if block.kind() != BlockKind::Grass || !clear_above {
+        return;
+    }
+
+    let state = server.state_mut();
+    if state
+        .try_set_block(block_pos, Block::new(BlockKind::Farmland, FARMLAND_COLOR))
+        .is_some()
+    {
+        // Clear away the plants growing on top
+        state.set_block(above, Block::empty());
+        state.ecs().write_resource::<Farms>().till(block_pos);
+    }
+}
@@ -1,19 +1,29 @@
-use crate::{client::Client, Server, StateExt};
+use crate::{
+    client::Client,
+    container::{Containers, OpenedContainer},
+    farming::Farms,
+    sys::crafting::PendingCraft,
+    Server, StateExt,
+};
 use common::{
     comp::{
         self, item,
         slot::{self, Slot},
         Pos, MAX_PICKUP_RANGE_SQR,
     },
+    farming::Crop,
     msg::ServerMsg,
-    recipe::default_recipe_book,
+    recipe::{default_recipe_book, CraftingStation},
+    state::{State, Time},
     sync::{Uid, WorldSyncExt},
-    terrain::block::Block,
+    terrain::{block::Block, BlockKind},
     vol::{ReadVol, Vox},
 };
 use comp::LightEmitter;
+use hashbrown::HashSet;
 use rand::Rng;
 use specs::{join::Join, world::WorldExt, Builder, Entity as EcsEntity, WriteStorage};
+use std::time::Duration;
 use tracing::{debug, error};
 use vek::{Rgb, Vec3};
 
@@ -32,12 +42,99 @@ pub fn snuff_lantern(storage: &mut WriteStorage<comp::LightEmitter>, entity: Ecs
     storage.remove(entity);
 }
 
+fn within_reach(entity_pos: Vec3<f32>, block_pos: Vec3<i32>) -> bool {
+    entity_pos.distance_squared(block_pos.map(|e| e as f32 + 0.5)) < MAX_PICKUP_RANGE_SQR
+}
+
+/// Get the position of the container the entity has open. The container is
+/// closed if it can't be accessed anymore, because the entity moved out of
+/// range or the block isn't a container anymore.
+fn opened_container(state: &State, entity: EcsEntity) -> Option<Vec3<i32>> {
+    let pos = state.ecs().read_storage::<OpenedContainer>().get(entity)?.0;
+
+    let in_range = state
+        .ecs()
+        .read_storage::<comp::Pos>()
+        .get(entity)
+        .map_or(false, |entity_pos| within_reach(entity_pos.0, pos));
+    let is_container = state
+        .terrain()
+        .get(pos)
+        .map_or(false, |block| block.is_container());
+
+    if in_range && is_container {
+        Some(pos)
+    } else {
+        state
+            .ecs()
+            .write_storage::<OpenedContainer>()
+            .remove(entity);
+        if let Some(client) = state.ecs().write_storage::<Client>().get_mut(entity) {
+            client.notify(ServerMsg::ContainerClosed);
+        }
+        None
+    }
+}
+
+/// Remove the sealed door block at `pos`, along with all sealed door blocks
+/// connected to it
+fn unseal(state: &mut State, pos: Vec3<i32>) {
+    const MAX_DOOR_BLOCKS: usize = 512;
+
+    let mut visited = HashSet::new();
+    let mut open = vec![pos];
+    while let Some(pos) = open.pop() {
+        if visited.len() >= MAX_DOOR_BLOCKS || !visited.insert(pos) {
+            continue;
+        }
+        if state
+            .terrain()
+            .get(pos)
+            .map_or(false, |block| block.is_sealed())
+        {
+            state.set_block(pos, Block::empty());
+            open.extend(
+                [
+                    Vec3::unit_x(),
+                    -Vec3::unit_x(),
+                    Vec3::unit_y(),
+                    -Vec3::unit_y(),
+                    Vec3::unit_z(),
+                    -Vec3::unit_z(),
+                ]
+                .iter()
+                .map(|dir| pos + *dir),
+            );
+        }
+    }
+}
+
+/// Send the contents of the container at `pos` to everyone that has it open
+fn sync_container(state: &State, pos: Vec3<i32>) {
+    let containers = state.ecs().read_resource::<Containers>();
+    if let Some(inventory) = containers.get(pos) {
+        for (client, opened) in (
+            &mut state.ecs().write_storage::<Client>(),
+            &state.ecs().read_storage::<OpenedContainer>(),
+        )
+            .join()
+            .filter(|(_, opened)| opened.0 == pos)
+        {
+            client.notify(ServerMsg::ContainerUpdate {
+                pos: opened.0,
+                inventory: inventory.clone(),
+            });
+        }
+    }
+}
+
 #[allow(clippy::blocks_in_if_conditions)]
 #[allow(clippy::same_item_push)] // TODO: Pending review in #587
 pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::InventoryManip) {
     let state = server.state_mut();
     let mut dropped_items = Vec::new();
     let mut thrown_items = Vec::new();
+    let mut placed_blocks = Vec::new();
 
     match manip {
         comp::InventoryManip::Pickup(uid) => {
@@ -99,7 +196,59 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
         comp::InventoryManip::Collect(pos) => {
             let block = state.terrain().get(pos).ok().copied();
 
-            if let Some(block) = block {
+            if let Some(block) = block.filter(|block| block.is_container()) {
+                // Open the container
+                let in_range = state
+                    .ecs()
+                    .read_storage::<comp::Pos>()
+                    .get(entity)
+                    .map_or(false, |entity_pos| within_reach(entity_pos.0, pos));
+
+                if in_range {
+                    state
+                        .ecs()
+                        .write_resource::<Containers>()
+                        .get_or_create(pos, block);
+                    let _ = state
+                        .ecs()
+                        .write_storage()
+                        .insert(entity, OpenedContainer(pos));
+                    sync_container(state, pos);
+                }
+            } else if block.map_or(false, |block| block.is_sealed()) {
+                // Open the door with a key, if the entity carries one
+                let in_range = state
+                    .ecs()
+                    .read_storage::<comp::Pos>()
+                    .get(entity)
+                    .map_or(false, |entity_pos| within_reach(entity_pos.0, pos));
+                let used_key = in_range
+                    && state
+                        .ecs()
+                        .write_storage::<comp::Inventory>()
+                        .get_mut(entity)
+                        .and_then(|inv| {
+                            let slot = inv.slots().iter().position(|slot| {
+                                matches!(
+                                    slot.as_ref().map(|item| &item.kind),
+                                    Some(item::ItemKind::Utility {
+                                        kind: item::Utility::DungeonKey,
+                                        ..
+                                    })
+                                )
+                            })?;
+                            inv.take(slot)
+                        })
+                        .is_some();
+
+                let event = if used_key {
+                    unseal(state, pos);
+                    comp::InventoryUpdateEvent::Used
+                } else {
+                    comp::InventoryUpdateEvent::CollectFailed
+                };
+                state.write_component(entity, comp::InventoryUpdate::new(event));
+            } else if let Some(block) = block {
                 let has_inv_space = state
                     .ecs()
                     .read_storage::<comp::Inventory>()
@@ -115,8 +264,25 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
                 } else if block.is_collectible()
                     && state.try_set_block(pos, Block::empty()).is_some()
                 {
-                    comp::Item::try_reclaim_from_block(block)
-                        .map(|item| state.give_item(entity, item));
+                    // Crops give produce and seeds, the ones planted on farms only once
+                    // they're ripe
+                    let time = state.ecs().read_resource::<Time>().0;
+                    let crop = state
+                        .ecs()
+                        .write_resource::<Farms>()
+                        .harvest(pos - Vec3::unit_z(), time)
+                        .or_else(|| {
+                            Crop::from_block(block.kind())
+                                .map(|(crop, stage)| (crop, crop.is_ripe(stage)))
+                        });
+                    if let Some((crop, ripe)) = crop {
+                        for item in crop.harvest(ripe) {
+                            state.give_item(entity, item);
+                        }
+                    } else {
+                        comp::Item::try_reclaim_from_block(block)
+                            .map(|item| state.give_item(entity, item));
+                    }
                 }
             }
         },
@@ -134,6 +300,7 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
             };
 
             let mut maybe_effect = None;
+            let mut changed_container = None;
 
             let event = match slot {
                 Slot::Inventory(slot) => {
@@ -179,6 +346,71 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
                                 }
                                 Some(comp::InventoryUpdateEvent::Used)
                             },
+                            ItemKind::Utility {
+                                kind: comp::item::Utility::StorageCrate,
+                                ..
+                            } => {
+                                // Place the crate on the ground in front of the entity
+                                let target = state
+                                    .read_storage::<comp::Pos>()
+                                    .get(entity)
+                                    .zip(state.read_storage::<comp::Ori>().get(entity))
+                                    .map(|(pos, ori)| {
+                                        (pos.0 + *ori.0 * 1.5).map(|e| e.floor() as i32)
+                                    })
+                                    .filter(|target| {
+                                        let terrain = state.terrain();
+                                        terrain
+                                            .get(*target)
+                                            .map_or(false, |b| b.kind() == BlockKind::Air)
+                                            && terrain
+                                                .get(*target - Vec3::unit_z())
+                                                .map_or(false, |b| b.is_solid())
+                                    });
+
+                                if let Some(target) = target {
+                                    state.ecs().write_resource::<Containers>().place(target);
+                                    placed_blocks
+                                        .push((target, Block::new(BlockKind::Crate, Rgb::zero())));
+                                    Some(comp::InventoryUpdateEvent::Used)
+                                } else {
+                                    let _ = inventory.insert_or_stack(slot, item);
+                                    None
+                                }
+                            },
+                            ItemKind::Utility {
+                                kind: comp::item::Utility::Seed(crop),
+                                ..
+                            } => {
+                                // Plant the seed in the tilled soil in front of the entity
+                                let time = state.ecs().read_resource::<Time>().0;
+                                let target = state
+                                    .read_storage::<comp::Pos>()
+                                    .get(entity)
+                                    .zip(state.read_storage::<comp::Ori>().get(entity))
+                                    .map(|(pos, ori)| {
+                                        (pos.0 + *ori.0 * 1.5).map(|e| e.floor() as i32)
+                                    })
+                                    .filter(|target| {
+                                        state
+                                            .terrain()
+                                            .get(*target)
+                                            .map_or(false, |b| b.kind() == BlockKind::Air)
+                                            && state.ecs().write_resource::<Farms>().plant(
+                                                *target - Vec3::unit_z(),
+                                                *crop,
+                                                time,
+                                            )
+                                    });
+
+                                if let Some(target) = target {
+                                    placed_blocks.push((target, crop.block(0)));
+                                    Some(comp::InventoryUpdateEvent::Used)
+                                } else {
+                                    let _ = inventory.insert_or_stack(slot, item);
+                                    None
+                                }
+                            },
                             ItemKind::Utility {
                                 kind: comp::item::Utility::Collar,
                                 ..
@@ -293,9 +525,28 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
                         None
                     }
                 },
+                Slot::Container(slot) => {
+                    // Move the item into the inventory
+                    changed_container = opened_container(state, entity);
+                    changed_container.and_then(|pos| {
+                        let mut containers = state.ecs().write_resource::<Containers>();
+                        let container = containers.get_mut(pos)?;
+                        let item = container.remove(slot)?;
+                        if let Some(item) = inventory.push(item) {
+                            // The inventory is full, put the item back
+                            let _ = container.insert(slot, item);
+                            None
+                        } else {
+                            Some(comp::InventoryUpdateEvent::Swapped)
+                        }
+                    })
+                },
             };
 
             drop(inventories);
+            if let Some(pos) = changed_container {
+                sync_container(state, pos);
+            }
             if let Some(effect) = maybe_effect {
                 state.apply_effect(entity, effect);
             }
@@ -305,18 +556,32 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
         },
 
         comp::InventoryManip::Swap(a, b) => {
+            let container_pos =
+                if matches!(a, Slot::Container(_)) || matches!(b, Slot::Container(_)) {
+                    opened_container(state, entity)
+                } else {
+                    None
+                };
+
             let ecs = state.ecs();
             let mut inventories = ecs.write_storage();
             let mut loadouts = ecs.write_storage();
+            let mut containers = ecs.write_resource::<Containers>();
             let inventory = inventories.get_mut(entity);
             let loadout = loadouts.get_mut(entity);
+            let container = container_pos.and_then(|pos| containers.get_mut(pos));
 
-            slot::swap(a, b, inventory, loadout);
+            slot::swap(a, b, inventory, loadout, container);
 
             // :/
+            drop(containers);
             drop(loadouts);
             drop(inventories);
 
+            if let Some(pos) = container_pos {
+                sync_container(state, pos);
+            }
+
             state.write_component(
                 entity,
                 comp::InventoryUpdate::new(comp::InventoryUpdateEvent::Swapped),
@@ -335,6 +600,15 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
                     .write_storage()
                     .get_mut(entity)
                     .and_then(|ldt| slot::loadout_remove(slot, ldt)),
+                Slot::Container(slot) => opened_container(state, entity).and_then(|pos| {
+                    let item = state
+                        .ecs()
+                        .write_resource::<Containers>()
+                        .get_mut(pos)?
+                        .remove(slot);
+                    sync_container(state, pos);
+                    item
+                }),
             };
 
             // FIXME: We should really require the drop and write to be atomic!
@@ -355,14 +629,48 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
             );
         },
 
+        comp::InventoryManip::CloseContainer => {
+            state
+                .ecs()
+                .write_storage::<OpenedContainer>()
+                .remove(entity);
+        },
+
         comp::InventoryManip::CraftRecipe(recipe) => {
+            let stations = state
+                .ecs()
+                .read_storage::<comp::Pos>()
+                .get(entity)
+                .map(|pos| CraftingStation::find_nearby(&*state.terrain(), pos.0))
+                .unwrap_or_default();
+            let mut pending_crafts = state.ecs().write_storage::<PendingCraft>();
+
             if let Some(inv) = state
                 .ecs()
                 .write_storage::<comp::Inventory>()
                 .get_mut(entity)
             {
                 let recipe_book = default_recipe_book();
-                let craft_result = recipe_book.get(&recipe).and_then(|r| r.perform(inv).ok());
+                let craft_result = recipe_book
+                    .get(&recipe)
+                    .filter(|r| r.has_station(&stations))
+                    .filter(|r| match pending_crafts.get(entity) {
+                        // Finish the pending craft
+                        Some(pending) => pending.recipe == recipe && pending.is_finished(),
+                        None if r.craft_time == Duration::default() => true,
+                        // Start crafting, the craft is performed once the craft time has
+                        // elapsed
+                        None => {
+                            if r.is_available(inv, &stations) {
+                                let _ = pending_crafts.insert(
+                                    entity,
+                                    PendingCraft::new(recipe.clone(), r.craft_time),
+                                );
+                            }
+                            false
+                        },
+                    })
+                    .and_then(|r| r.perform(inv).ok());
 
                 // FIXME: We should really require the drop and write to be atomic!
                 if craft_result.is_some() {
@@ -387,9 +695,21 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
                     }
                 }
             }
+
+            // Remove the finished craft, even if its requirements are no longer met
+            if pending_crafts.get(entity).map_or(false, |pending| {
+                pending.recipe == recipe && pending.is_finished()
+            }) {
+                pending_crafts.remove(entity);
+            }
         },
     }
 
+    // Place blocks
+    for (pos, block) in placed_blocks {
+        state.set_block(pos, block);
+    }
+
     // Drop items
     for (pos, ori, item) in dropped_items {
         let vel = *ori.0 * 5.0
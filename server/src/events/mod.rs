@@ -1,4 +1,4 @@
-use crate::{state_ext::StateExt, Server};
+use crate::{shutdown::ShutdownKind, state_ext::StateExt, Server};
 use common::event::{EventBus, ServerEvent};
 use entity_creation::{
     handle_create_npc, handle_create_waypoint, handle_initialize_character,
@@ -9,9 +9,12 @@ use entity_manipulation::{
     handle_respawn,
 };
 use group_manip::handle_group;
-use interaction::{handle_lantern, handle_mount, handle_possess, handle_unmount};
+use interaction::{
+    handle_lantern, handle_mine_block, handle_mount, handle_possess, handle_till_soil,
+    handle_unmount,
+};
 use inventory_manip::handle_inventory;
-use player::{handle_client_disconnect, handle_exit_ingame};
+use player::{handle_client_disconnect, handle_exit_ingame, handle_spectate};
 use specs::{Entity as EcsEntity, WorldExt};
 
 mod entity_creation;
@@ -32,6 +35,11 @@ pub enum Event {
         entity: Option<EcsEntity>,
         msg: String,
     },
+    /// The countdown to a shutdown ran out, the frontend should drop the
+    /// server and exit
+    Shutdown {
+        kind: ShutdownKind,
+    },
 }
 
 impl Server {
@@ -70,6 +78,12 @@ impl Server {
                 ServerEvent::InventoryManip(entity, manip) => handle_inventory(self, entity, manip),
                 ServerEvent::GroupManip(entity, manip) => handle_group(self, entity, manip),
                 ServerEvent::Respawn(entity) => handle_respawn(&self, entity),
+                ServerEvent::MineBlock { entity, dir, range } => {
+                    handle_mine_block(self, entity, dir, range)
+                },
+                ServerEvent::TillSoil { entity, dir, range } => {
+                    handle_till_soil(self, entity, dir, range)
+                },
                 ServerEvent::LandOnGround { entity, vel } => {
                     handle_land_on_ground(&self, entity, vel)
                 },
@@ -88,6 +102,7 @@ impl Server {
                 },
                 ServerEvent::LevelUp(entity, new_level) => handle_level_up(self, entity, new_level),
                 ServerEvent::ExitIngame { entity } => handle_exit_ingame(self, entity),
+                ServerEvent::Spectate { entity, follow } => handle_spectate(self, entity, follow),
                 ServerEvent::CreateNpc {
                     pos,
                     stats,
@@ -1,6 +1,10 @@
 use super::Event;
 use crate::{
-    client::Client, login_provider::LoginProvider, persistence, state_ext::StateExt, Server,
+    client::{Client, SpectatorCamera},
+    login_provider::LoginProvider,
+    persistence,
+    state_ext::StateExt,
+    sys, Server, SpawnPoint,
 };
 use common::{
     comp,
@@ -13,6 +17,52 @@ use specs::{saveload::MarkerAllocator, Builder, Entity as EcsEntity, WorldExt};
 use tracing::{debug, error, trace, warn};
 
 pub fn handle_exit_ingame(server: &mut Server, entity: EcsEntity) {
+    leave_character(server, entity, ClientState::Registered);
+}
+
+pub fn handle_spectate(server: &mut Server, entity: EcsEntity, follow: Option<Uid>) {
+    let state = server.state_mut();
+
+    // Spectating starts from where the character was, or the spawn point
+    let character_pos = state.read_component_cloned::<comp::Pos>(entity);
+    let pos =
+        character_pos.map_or_else(|| state.ecs().read_resource::<SpawnPoint>().0, |pos| pos.0);
+
+    // Spectators have no body, which keeps them out of the region map and so
+    // invisible to everyone else
+    let entity = if character_pos.is_some() {
+        match leave_character(server, entity, ClientState::Spectator) {
+            Some(entity) => entity,
+            None => return,
+        }
+    } else {
+        let mut clients = server.state().ecs().write_storage::<Client>();
+        if let Some(client) = clients.get_mut(entity) {
+            client.allow_state(ClientState::Spectator);
+        }
+        entity
+    };
+
+    let ecs = server.state().ecs();
+    if let Some(client) = ecs.write_storage::<Client>().get_mut(entity) {
+        client.notify(ServerMsg::SpectatorCamera { pos, follow });
+    }
+    if let Err(e) = ecs
+        .write_storage()
+        .insert(entity, SpectatorCamera { pos, follow })
+    {
+        error!(?e, "Failed to insert spectator camera component");
+    }
+    sys::subscription::initialize_region_subscription(ecs, entity);
+}
+
+/// Replace the entity of a client's character with one that just has its
+/// `Client`, `Uid`, and `Player` components, returning it
+fn leave_character(
+    server: &mut Server,
+    entity: EcsEntity,
+    new_state: ClientState,
+) -> Option<EcsEntity> {
     let state = server.state_mut();
 
     // Create new entity with just `Client`, `Uid`, and `Player` components
@@ -27,9 +77,10 @@ pub fn handle_exit_ingame(server: &mut Server, entity: EcsEntity) {
         .write_storage::<group::Group>()
         .get(entity)
         .cloned();
+    let mut new_entity = None;
     if let (Some(mut client), Some(uid), Some(player)) = (maybe_client, maybe_uid, maybe_player) {
         // Tell client its request was successful
-        client.allow_state(ClientState::Registered);
+        client.allow_state(new_state);
         // Tell client to clear out other entities and its own components
         client.notify(ServerMsg::ExitIngameCleanup);
 
@@ -45,7 +96,8 @@ pub fn handle_exit_ingame(server: &mut Server, entity: EcsEntity) {
             .world
             .write_resource::<UidAllocator>()
             .allocate(entity_builder.entity, Some(uid.into()));
-        let new_entity = entity_builder.with(uid).build();
+        let rebuilt = entity_builder.with(uid).build();
+        new_entity = Some(rebuilt);
         if let Some(group) = maybe_group {
             let mut group_manager = state.ecs().write_resource::<group::GroupManager>();
             if group_manager
@@ -54,7 +106,7 @@ pub fn handle_exit_ingame(server: &mut Server, entity: EcsEntity) {
                 .unwrap_or(false)
             {
                 group_manager.assign_leader(
-                    new_entity,
+                    rebuilt,
                     &state.ecs().read_storage(),
                     &state.ecs().entities(),
                     &state.ecs().read_storage(),
@@ -75,6 +127,7 @@ pub fn handle_exit_ingame(server: &mut Server, entity: EcsEntity) {
             "Failed to delete entity when removing character"
         );
     }
+    new_entity
 }
 
 pub fn handle_client_disconnect(server: &mut Server, entity: EcsEntity) -> Event {
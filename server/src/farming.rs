@@ -0,0 +1,267 @@
+//! Farmland tilled by players and the crops growing on it
+//!
+//! Crops grow with server uptime whether or not anyone is around to see it.
+//! Chunks are generated afresh when they are loaded again, so the farms in
+//! them are put back as soon as they arrive. Farms only live in memory and are
+//! lost when the server restarts.
+
+use common::{
+    farming::{Crop, FARMLAND_COLOR, STAGE_TIME},
+    state::{State, TerrainChanges},
+    terrain::{Block, BlockKind, TerrainGrid},
+};
+use hashbrown::{HashMap, HashSet};
+use specs::WorldExt;
+use vek::*;
+
+struct Planted {
+    crop: Crop,
+    planted_at: f64,
+    /// The stage of the crop block last placed in the world
+    stage: usize,
+}
+
+impl Planted {
+    fn stage_at(&self, time: f64) -> usize {
+        ((time - self.planted_at) / STAGE_TIME).max(0.0) as usize
+    }
+}
+
+/// Every block of farmland and the crop planted on it, if any, keyed by the
+/// position of the farmland
+#[derive(Default)]
+pub struct Farms {
+    plots: HashMap<Vec3<i32>, Option<Planted>>,
+    /// The positions of the plots, by the key of their chunk
+    chunks: HashMap<Vec2<i32>, HashSet<Vec3<i32>>>,
+}
+
+impl Farms {
+    pub fn till(&mut self, pos: Vec3<i32>) {
+        self.plots.entry(pos).or_insert(None);
+        self.chunks
+            .entry(TerrainGrid::chunk_key(pos))
+            .or_default()
+            .insert(pos);
+    }
+
+    pub fn is_tilled(&self, pos: Vec3<i32>) -> bool { self.plots.contains_key(&pos) }
+
+    /// Forget the farmland at `pos` and its crop, because the block was
+    /// replaced
+    pub fn remove(&mut self, pos: Vec3<i32>) {
+        if self.plots.remove(&pos).is_some() {
+            let key = TerrainGrid::chunk_key(pos);
+            if let Some(chunk) = self.chunks.get_mut(&key) {
+                chunk.remove(&pos);
+                if chunk.is_empty() {
+                    self.chunks.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// Plant `crop` in the farmland at `pos`, returning whether there was
+    /// room for it
+    pub fn plant(&mut self, pos: Vec3<i32>, crop: Crop, time: f64) -> bool {
+        match self.plots.get_mut(&pos) {
+            Some(plot) if plot.is_none() => {
+                *plot = Some(Planted {
+                    crop,
+                    planted_at: time,
+                    stage: 0,
+                });
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Take the crop off the farmland at `pos`, returning the crop and
+    /// whether it was ripe
+    pub fn harvest(&mut self, pos: Vec3<i32>, time: f64) -> Option<(Crop, bool)> {
+        self.plots
+            .get_mut(&pos)?
+            .take()
+            .map(|planted| (planted.crop, planted.crop.is_ripe(planted.stage_at(time))))
+    }
+
+    /// Advance crops to the stage they have reached by `time`, returning the
+    /// blocks of the crops that grew
+    pub fn grow(&mut self, time: f64) -> Vec<(Vec3<i32>, Block)> {
+        self.plots
+            .iter_mut()
+            .filter_map(|(pos, plot)| {
+                let planted = plot.as_mut()?;
+                let stage = planted.stage_at(time).min(planted.crop.stages().len() - 1);
+                if stage == planted.stage {
+                    return None;
+                }
+                planted.stage = stage;
+                Some((*pos + Vec3::unit_z(), planted.crop.block(stage)))
+            })
+            .collect()
+    }
+
+    /// The farmland and crop blocks within the chunk at `key`
+    pub fn blocks_in(&self, key: Vec2<i32>) -> impl Iterator<Item = (Vec3<i32>, Block)> + '_ {
+        self.chunks
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .flat_map(move |pos| {
+                let farmland = (*pos, Block::new(BlockKind::Farmland, FARMLAND_COLOR));
+                let crop = self.plots[pos]
+                    .as_ref()
+                    .map(|planted| (*pos + Vec3::unit_z(), planted.crop.block(planted.stage)));
+                std::iter::once(farmland).chain(crop)
+            })
+    }
+}
+
+/// Forget the farmland that was dug up or built over and put the farms back
+/// into chunks that were generated again. Needs to run after the terrain
+/// changes were applied.
+pub fn handle_terrain_changes(state: &mut State) {
+    let restored = {
+        let terrain_changes = state.ecs().read_resource::<TerrainChanges>();
+        let mut farms = state.ecs().write_resource::<Farms>();
+        for (pos, block) in terrain_changes.modified_blocks.iter() {
+            if block.kind() != BlockKind::Farmland {
+                farms.remove(*pos);
+            }
+        }
+        terrain_changes
+            .new_chunks
+            .iter()
+            .flat_map(|key| farms.blocks_in(*key))
+            .collect::<Vec<_>>()
+    };
+
+    for (pos, block) in restored {
+        state.set_block(pos, block);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{
+        terrain::{TerrainChunk, TerrainChunkMeta},
+        vol::ReadVol,
+    };
+
+    #[test]
+    fn test_plant() {
+        let mut farms = Farms::default();
+        let pos = Vec3::new(3, 4, 5);
+
+        assert!(!farms.plant(pos, Crop::Carrot, 0.0));
+        farms.till(pos);
+        assert!(farms.is_tilled(pos));
+        assert!(farms.plant(pos, Crop::Carrot, 0.0));
+        assert!(!farms.plant(pos, Crop::Wheat, 0.0));
+
+        assert_eq!(farms.harvest(pos, 0.0), Some((Crop::Carrot, false)));
+        assert_eq!(farms.harvest(pos, 0.0), None);
+        assert!(farms.is_tilled(pos));
+        assert!(farms.plant(pos, Crop::Wheat, 0.0));
+    }
+
+    #[test]
+    fn test_grow() {
+        let mut farms = Farms::default();
+        let pos = Vec3::new(-10, 20, 64);
+        farms.till(pos);
+        farms.plant(pos, Crop::Wheat, 1000.0);
+
+        assert!(farms.grow(1000.0 + STAGE_TIME * 0.5).is_empty());
+
+        let grown = farms.grow(1000.0 + STAGE_TIME * 1.5);
+        assert_eq!(grown.len(), 1);
+        assert_eq!(grown[0].0, pos + Vec3::unit_z());
+        assert_eq!(grown[0].1.kind(), BlockKind::WheatGreen);
+        assert!(farms.grow(1000.0 + STAGE_TIME * 1.6).is_empty());
+
+        // Crops stay ripe however long they're left
+        let grown = farms.grow(1000.0 + STAGE_TIME * 10.0);
+        assert_eq!(grown[0].1.kind(), BlockKind::WheatYellow);
+        assert!(farms.grow(1000.0 + STAGE_TIME * 20.0).is_empty());
+        assert_eq!(
+            farms.harvest(pos, 1000.0 + STAGE_TIME * 20.0),
+            Some((Crop::Wheat, true))
+        );
+    }
+
+    #[test]
+    fn test_blocks_in() {
+        let mut farms = Farms::default();
+        let planted = Vec3::new(1, 1, 10);
+        let bare = Vec3::new(2, 1, 10);
+        let far = Vec3::new(1000, 1000, 10);
+        for pos in &[planted, bare, far] {
+            farms.till(*pos);
+        }
+        farms.plant(planted, Crop::Tomato, 0.0);
+
+        let mut blocks = farms
+            .blocks_in(TerrainGrid::chunk_key(planted))
+            .map(|(pos, block)| (pos, block.kind()))
+            .collect::<Vec<_>>();
+        blocks.sort_by_key(|(pos, _)| (pos.x, pos.z));
+        assert_eq!(blocks, vec![
+            (planted, BlockKind::Farmland),
+            (planted + Vec3::unit_z(), BlockKind::Sprout),
+            (bare, BlockKind::Farmland),
+        ]);
+
+        farms.remove(bare);
+        farms.remove(planted);
+        assert!(!farms.is_tilled(planted));
+        assert_eq!(farms.blocks_in(TerrainGrid::chunk_key(planted)).count(), 0);
+        assert_eq!(farms.blocks_in(TerrainGrid::chunk_key(far)).count(), 1);
+    }
+
+    #[test]
+    fn test_replaced_farmland_stays_gone() {
+        let mut state = State::default();
+        state.ecs_mut().insert(Farms::default());
+        let key = Vec2::new(0, 0);
+        let chunk = || {
+            TerrainChunk::new(
+                0,
+                Block::new(BlockKind::Normal, Rgb::broadcast(128)),
+                Block::empty(),
+                TerrainChunkMeta::void(),
+            )
+        };
+        let tick = |state: &mut State| {
+            state.apply_terrain_changes();
+            handle_terrain_changes(state);
+            state.cleanup();
+        };
+        let kind_at = |state: &State, pos| state.terrain().get(pos).unwrap().kind();
+        state.insert_chunk(key, chunk());
+        tick(&mut state);
+
+        let tilled = Vec3::new(4, 4, -1);
+        let replaced = Vec3::new(5, 4, -1);
+        for pos in &[tilled, replaced] {
+            state.set_block(*pos, Block::new(BlockKind::Farmland, FARMLAND_COLOR));
+            state.ecs().write_resource::<Farms>().till(*pos);
+        }
+        tick(&mut state);
+        state.set_block(replaced, Block::empty());
+        tick(&mut state);
+        assert!(state.ecs().read_resource::<Farms>().is_tilled(tilled));
+        assert!(!state.ecs().read_resource::<Farms>().is_tilled(replaced));
+
+        // Only the remaining farmland is put back into the regenerated chunk
+        state.remove_chunk(key);
+        state.insert_chunk(key, chunk());
+        tick(&mut state);
+        tick(&mut state);
+        assert_eq!(kind_at(&state, tilled), BlockKind::Farmland);
+        assert_eq!(kind_at(&state, replaced), BlockKind::Normal);
+    }
+}
@@ -0,0 +1,367 @@
+//! Flow of water blocks
+//!
+//! Water falls into air below it and otherwise spreads sideways into
+//! neighbouring air. Water placed by worldgen or by players is a source that
+//! never runs dry. Water created by the flow has a level that drops by one with
+//! every block it spreads sideways, so pools stay bounded, and it drains away
+//! again once nothing feeds it.
+//!
+//! Only blocks next to a terrain change are simulated, so lakes that nobody
+//! touches stay as worldgen left them.
+//!
+//! Flowed water is not persisted. Chunks are generated again when they are
+//! loaded, so the levels of the water in a chunk are forgotten once the chunk
+//! is unloaded instead of turning the water into sources later on.
+
+use common::{
+    state::{State, TerrainChanges},
+    terrain::{Block, BlockKind, TerrainGrid},
+    vol::{ReadVol, Vox},
+};
+use hashbrown::{HashMap, HashSet};
+use specs::WorldExt;
+use std::collections::VecDeque;
+use vek::*;
+
+/// Level of the water that flows out of a source
+pub const MAX_LEVEL: u8 = 7;
+/// Sources behave like water one level above the highest flowing water
+const SOURCE_LEVEL: u8 = MAX_LEVEL + 1;
+/// Maximum number of blocks waiting to be simulated
+const MAX_ACTIVE: usize = 4096;
+/// Maximum number of blocks simulated per step
+const MAX_UPDATES: usize = 512;
+/// Maximum number of water blocks created by the flow
+const MAX_FLOWING: usize = 16384;
+
+const HORIZONTAL: [Vec3<i32>; 4] = [
+    Vec3 { x: 1, y: 0, z: 0 },
+    Vec3 { x: -1, y: 0, z: 0 },
+    Vec3 { x: 0, y: 1, z: 0 },
+    Vec3 { x: 0, y: -1, z: 0 },
+];
+
+fn water() -> Block { Block::new(BlockKind::Water, Rgb::zero()) }
+
+fn is_air<V: ReadVol<Vox = Block>>(terrain: &V, pos: Vec3<i32>) -> bool {
+    terrain
+        .get(pos)
+        .map_or(false, |block| block.kind() == BlockKind::Air)
+}
+
+#[derive(Default)]
+pub struct Fluids {
+    /// Levels of the water blocks created by the flow
+    levels: HashMap<Vec3<i32>, u8>,
+    active: VecDeque<Vec3<i32>>,
+    queued: HashSet<Vec3<i32>>,
+}
+
+impl Fluids {
+    /// Wake up a changed block and its neighbours. Activations beyond the
+    /// limit are dropped, leaving the water where it is.
+    pub fn activate_around(&mut self, pos: Vec3<i32>) {
+        self.activate(pos);
+        self.activate(pos + Vec3::unit_z());
+        self.activate(pos - Vec3::unit_z());
+        for dir in HORIZONTAL.iter() {
+            self.activate(pos + *dir);
+        }
+    }
+
+    fn activate(&mut self, pos: Vec3<i32>) {
+        if self.queued.len() < MAX_ACTIVE && self.queued.insert(pos) {
+            self.active.push_back(pos);
+        }
+    }
+
+    /// Forget the water that flowed in a chunk that was unloaded or generated
+    /// again
+    pub fn forget_chunk(&mut self, key: Vec2<i32>) {
+        self.levels
+            .retain(|pos, _| TerrainGrid::chunk_key(*pos) != key);
+        let queued = &mut self.queued;
+        self.active.retain(|pos| {
+            let keep = TerrainGrid::chunk_key(*pos) != key;
+            if !keep {
+                queued.remove(pos);
+            }
+            keep
+        });
+    }
+
+    /// Number of blocks waiting to be simulated
+    pub fn active_count(&self) -> usize { self.active.len() }
+
+    /// Number of water blocks created by the flow
+    pub fn flowing_count(&self) -> usize { self.levels.len() }
+
+    fn level<V: ReadVol<Vox = Block>>(&self, terrain: &V, pos: Vec3<i32>) -> Option<u8> {
+        match terrain.get(pos) {
+            Ok(block) if block.is_fluid() => {
+                Some(self.levels.get(&pos).copied().unwrap_or(SOURCE_LEVEL))
+            },
+            _ => None,
+        }
+    }
+
+    /// Simulate a batch of active blocks and return the blocks that should
+    /// change. The changed blocks need to be passed to
+    /// [`Fluids::activate_around`] once they have been applied.
+    pub fn step<V: ReadVol<Vox = Block>>(&mut self, terrain: &V) -> HashMap<Vec3<i32>, Block> {
+        if self.levels.len() >= MAX_FLOWING {
+            // Forget about water in chunks that have been unloaded since
+            let levels = &mut self.levels;
+            levels.retain(|pos, _| terrain.get(*pos).map_or(false, |block| block.is_fluid()));
+        }
+
+        let mut changes = HashMap::new();
+        for _ in 0..MAX_UPDATES {
+            let pos = match self.active.pop_front() {
+                Some(pos) => pos,
+                None => break,
+            };
+            self.queued.remove(&pos);
+
+            let level = match self.level(terrain, pos) {
+                Some(level) => level,
+                None => {
+                    self.levels.remove(&pos);
+                    continue;
+                },
+            };
+
+            // Flowing water drains away once nothing feeds it anymore
+            if level < SOURCE_LEVEL {
+                let fed = self.level(terrain, pos + Vec3::unit_z()).is_some()
+                    || HORIZONTAL.iter().any(|dir| {
+                        self.level(terrain, pos + *dir)
+                            .map_or(false, |other| other > level)
+                    });
+                if !fed {
+                    self.levels.remove(&pos);
+                    changes.entry(pos).or_insert_with(Block::empty);
+                    continue;
+                }
+            }
+
+            let below = pos - Vec3::unit_z();
+            let targets = if is_air(terrain, below) {
+                vec![(below, MAX_LEVEL)]
+            } else if level > 1 && self.level(terrain, below).is_none() {
+                // Only spread out over solid ground, not on top of other water
+                HORIZONTAL
+                    .iter()
+                    .map(|dir| (pos + *dir, level - 1))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            for (target, target_level) in targets {
+                if is_air(terrain, target) {
+                    if self.levels.len() < MAX_FLOWING && !changes.contains_key(&target) {
+                        self.levels.insert(target, target_level);
+                        changes.insert(target, water());
+                    }
+                } else if self
+                    .level(terrain, target)
+                    .map_or(false, |other| other < target_level)
+                {
+                    // Raise flowing water that is now fed by a higher level
+                    self.levels.insert(target, target_level);
+                    self.activate(target);
+                }
+            }
+        }
+
+        changes
+    }
+}
+
+/// Let water flow around the blocks changed during this tick and forget the
+/// water in chunks that were unloaded or generated again. Needs to run after
+/// the terrain changes were applied.
+pub fn handle_terrain_changes(state: &State) {
+    let terrain_changes = state.ecs().read_resource::<TerrainChanges>();
+    let mut fluids = state.ecs().write_resource::<Fluids>();
+    for key in terrain_changes
+        .removed_chunks
+        .iter()
+        .chain(terrain_changes.new_chunks.iter())
+    {
+        fluids.forget_chunk(*key);
+    }
+    for pos in terrain_changes.modified_blocks.keys() {
+        fluids.activate_around(*pos);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{
+        terrain::{TerrainChunk, TerrainChunkMeta},
+        vol::WriteVol,
+        volumes::dyna::Dyna,
+    };
+
+    type Vol = Dyna<Block, ()>;
+
+    fn stone() -> Block { Block::new(BlockKind::Normal, Rgb::broadcast(128)) }
+
+    /// A basin of air with a stone floor at z = 0
+    fn basin() -> Vol {
+        let mut vol = Vol::filled(Vec3::new(24, 24, 12), Block::empty(), ());
+        for x in 0..24 {
+            for y in 0..24 {
+                vol.set(Vec3::new(x, y, 0), stone()).unwrap();
+            }
+        }
+        vol
+    }
+
+    fn set(vol: &mut Vol, fluids: &mut Fluids, pos: Vec3<i32>, block: Block) {
+        vol.set(pos, block).unwrap();
+        fluids.activate_around(pos);
+    }
+
+    /// Run the simulation until it settles, returning the number of steps
+    fn settle(vol: &mut Vol, fluids: &mut Fluids) -> usize {
+        for i in 0..1000 {
+            let changes = fluids.step(&*vol);
+            if changes.is_empty() && fluids.active_count() == 0 {
+                return i;
+            }
+            for (pos, block) in changes {
+                set(vol, fluids, pos, block);
+            }
+        }
+        panic!("Water did not settle");
+    }
+
+    fn water_count(vol: &Vol) -> usize {
+        (0..24)
+            .flat_map(|x| (0..24).flat_map(move |y| (0..12).map(move |z| Vec3::new(x, y, z))))
+            .filter(|pos| vol.get(*pos).unwrap().is_fluid())
+            .count()
+    }
+
+    #[test]
+    fn test_fall_and_spread() {
+        let mut vol = basin();
+        let mut fluids = Fluids::default();
+        let source = Vec3::new(12, 12, 8);
+        set(&mut vol, &mut fluids, source, water());
+        settle(&mut vol, &mut fluids);
+
+        // The water fell down to the floor...
+        for z in 1..8 {
+            assert!(vol.get(Vec3::new(12, 12, z)).unwrap().is_fluid());
+        }
+        // ...and spread a bounded distance along it
+        assert!(vol.get(Vec3::new(12 + 6, 12, 1)).unwrap().is_fluid());
+        assert!(vol.get(Vec3::new(12 + 7, 12, 1)).unwrap().is_air());
+        assert!(vol.get(Vec3::new(12 + 1, 12, 2)).unwrap().is_air());
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut vol = basin();
+        let mut fluids = Fluids::default();
+        let source = Vec3::new(12, 12, 4);
+        set(&mut vol, &mut fluids, source, water());
+        settle(&mut vol, &mut fluids);
+        assert!(water_count(&vol) > 1);
+
+        set(&mut vol, &mut fluids, source, Block::empty());
+        settle(&mut vol, &mut fluids);
+        assert_eq!(water_count(&vol), 0);
+        assert_eq!(fluids.flowing_count(), 0);
+    }
+
+    #[test]
+    fn test_untouched_lake() {
+        let mut vol = basin();
+        let mut fluids = Fluids::default();
+        // A lake whose surface touches air on every side, as long as nothing
+        // around it changes it is left alone
+        for x in 4..20 {
+            for y in 4..20 {
+                vol.set(Vec3::new(x, y, 1), water()).unwrap();
+            }
+        }
+        assert_eq!(settle(&mut vol, &mut fluids), 0);
+        assert_eq!(water_count(&vol), 16 * 16);
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut vol = basin();
+        let mut fluids = Fluids::default();
+        for x in 0..24 {
+            for y in 0..24 {
+                for z in 1..12 {
+                    fluids.activate_around(Vec3::new(x, y, z));
+                }
+            }
+        }
+        assert!(fluids.active_count() <= MAX_ACTIVE);
+        assert!(fluids.step(&vol).is_empty());
+        assert!(fluids.active_count() <= MAX_ACTIVE - MAX_UPDATES);
+        settle(&mut vol, &mut fluids);
+    }
+
+    /// Run server ticks that only apply block changes and simulate the water
+    fn tick(state: &mut State, ticks: usize) {
+        for _ in 0..ticks {
+            state.apply_terrain_changes();
+            handle_terrain_changes(state);
+            let changes = {
+                let terrain = state.terrain();
+                state.ecs().write_resource::<Fluids>().step(&*terrain)
+            };
+            state.cleanup();
+            for (pos, block) in changes {
+                state.set_block(pos, block);
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_change_through_tick() {
+        let mut state = State::default();
+        state.ecs_mut().insert(Fluids::default());
+        let key = Vec2::new(0, 0);
+        let chunk = || TerrainChunk::new(0, stone(), Block::empty(), TerrainChunkMeta::void());
+        state.insert_chunk(key, chunk());
+        tick(&mut state, 1);
+
+        let source = Vec3::new(16, 16, 4);
+        state.set_block(source, water());
+        tick(&mut state, 50);
+        assert!(
+            state
+                .terrain()
+                .get(Vec3::new(16, 16, 0))
+                .unwrap()
+                .is_fluid()
+        );
+        assert!(
+            state
+                .terrain()
+                .get(Vec3::new(17, 16, 0))
+                .unwrap()
+                .is_fluid()
+        );
+        assert!(state.ecs().read_resource::<Fluids>().flowing_count() > 0);
+
+        // The flowed water is gone after the chunk was loaded again
+        state.remove_chunk(key);
+        state.insert_chunk(key, chunk());
+        tick(&mut state, 1);
+        let fluids = state.ecs().read_resource::<Fluids>();
+        assert_eq!(fluids.flowing_count(), 0);
+        assert_eq!(fluids.active_count(), 0);
+    }
+}
@@ -6,16 +6,24 @@ pub mod alias_validator;
 pub mod chunk_generator;
 pub mod client;
 pub mod cmd;
+pub mod container;
 pub mod error;
 pub mod events;
+pub mod farming;
+pub mod fluid;
 pub mod input;
 pub mod login_provider;
 pub mod metrics;
+pub mod mining;
 pub mod persistence;
+pub mod replay;
 pub mod settings;
+pub mod shutdown;
 pub mod state_ext;
+pub mod status;
 pub mod sys;
 #[cfg(not(feature = "worldgen"))] mod test_world;
+pub mod weather;
 
 // Reexports
 pub use crate::{error::Error, events::Event, input::Input, settings::ServerSettings};
@@ -23,28 +31,39 @@ pub use crate::{error::Error, events::Event, input::Input, settings::ServerSetti
 use crate::{
     alias_validator::AliasValidator,
     chunk_generator::ChunkGenerator,
-    client::{Client, RegionSubscription},
+    client::{Client, RegionSubscription, SpectatorCamera},
     cmd::ChatCommandExt,
-    login_provider::LoginProvider,
+    container::{Containers, OpenedContainer},
+    fluid::Fluids,
+    login_provider::{LocalAccounts, LoginProvider},
+    replay::Recorder,
+    shutdown::{Shutdown, ShutdownKind},
     state_ext::StateExt,
+    status::StatusServer,
     sys::sentinel::{DeletedEntities, TrackedComps},
+    weather::WeatherSim,
 };
 use common::{
+    assets::watch::ReloadIndicator,
     cmd::ChatCommand,
     comp::{self, ChatType},
     event::{EventBus, ServerEvent},
-    msg::{server::WorldMapMsg, ClientState, ServerInfo, ServerMsg},
+    msg::{
+        delta::Baselines, server::WorldMapMsg, ClientState, ProtocolVersions, ServerInfo,
+        ServerMsg, ServerStatus,
+    },
     outcome::Outcome,
     recipe::default_recipe_book,
     state::{State, TimeOfDay},
     sync::WorldSyncExt,
     terrain::TerrainChunkSize,
     vol::{ReadVol, RectVolSize},
+    weather::WeatherGrid,
 };
 use futures_executor::block_on;
 use futures_timer::Delay;
 use futures_util::{select, FutureExt};
-use metrics::{ServerMetrics, TickMetrics};
+use metrics::{ChunkGenMetrics, MessageMetrics, PersistenceMetrics, ServerMetrics, TickMetrics};
 use network::{Network, Pid, ProtocolAddr};
 use persistence::character::{CharacterLoader, CharacterLoaderResponseType, CharacterUpdater};
 use specs::{join::Join, Builder, Entity as EcsEntity, RunNow, SystemData, WorldExt};
@@ -62,7 +81,7 @@ use vek::*;
 #[cfg(feature = "worldgen")]
 use world::{
     civ::SiteKind,
-    sim::{FileOpts, WorldOpts, DEFAULT_WORLD_MAP},
+    sim::{FileOpts, GenOpts, WorldOpts, DEFAULT_WORLD_MAP},
     IndexOwned, World,
 };
 
@@ -79,11 +98,16 @@ struct SpawnPoint(Vec3<f32>);
 #[derive(Copy, Clone, Default)]
 pub struct Tick(u64);
 
+// Time the last tick took to run, reported to clients that ask for server stats
+#[derive(Copy, Clone, Default)]
+pub struct TickTime(Duration);
+
 pub struct Server {
     state: State,
     world: Arc<World>,
     index: IndexOwned,
     map: WorldMapMsg,
+    ability_map_indicator: ReloadIndicator,
 
     network: Network,
 
@@ -91,6 +115,8 @@ pub struct Server {
 
     metrics: ServerMetrics,
     tick_metrics: TickMetrics,
+    message_metrics: MessageMetrics,
+    status: Option<StatusServer>,
 }
 
 impl Server {
@@ -98,17 +124,41 @@ impl Server {
     #[allow(clippy::expect_fun_call)] // TODO: Pending review in #587
     #[allow(clippy::needless_update)] // TODO: Pending review in #587
     pub fn new(settings: ServerSettings) -> Result<Self, Error> {
+        let mut metrics = ServerMetrics::new();
+        // register all metrics submodules here
+        let tick_metrics = TickMetrics::new(metrics.tick_clone())
+            .expect("Failed to initialize server tick metrics submodule.");
+        tick_metrics
+            .register(&metrics.registry())
+            .expect("failed to register tick metrics");
+        let message_metrics =
+            MessageMetrics::new().expect("Failed to initialize server message metrics submodule.");
+        message_metrics
+            .register(&metrics.registry())
+            .expect("failed to register message metrics");
+        let chunk_gen_metrics = ChunkGenMetrics::new()
+            .expect("Failed to initialize server chunk generation metrics submodule.");
+        chunk_gen_metrics
+            .register(&metrics.registry())
+            .expect("failed to register chunk generation metrics");
+        let persistence_metrics = PersistenceMetrics::new()
+            .expect("Failed to initialize server persistence metrics submodule.");
+        persistence_metrics
+            .register(&metrics.registry())
+            .expect("failed to register persistence metrics");
+
         let mut state = State::default();
         state.ecs_mut().insert(settings.clone());
         state.ecs_mut().insert(EventBus::<ServerEvent>::default());
-        state
-            .ecs_mut()
-            .insert(LoginProvider::new(settings.auth_server_address.clone()));
         state.ecs_mut().insert(Tick(0));
-        state.ecs_mut().insert(ChunkGenerator::new());
+        state.ecs_mut().insert(TickTime::default());
         state
             .ecs_mut()
-            .insert(CharacterUpdater::new(settings.persistence_db_dir.clone()));
+            .insert(ChunkGenerator::new(chunk_gen_metrics));
+        state.ecs_mut().insert(CharacterUpdater::new(
+            settings.persistence_db_dir.clone(),
+            persistence_metrics.clone(),
+        ));
         state
             .ecs_mut()
             .insert(CharacterLoader::new(settings.persistence_db_dir.clone()));
@@ -116,11 +166,27 @@ impl Server {
             .ecs_mut()
             .insert(persistence::character::CharacterUpdater::new(
                 settings.persistence_db_dir.clone(),
+                persistence_metrics.clone(),
+            ));
+        state
+            .ecs_mut()
+            .insert(persistence::container::ContainerUpdater::new(
+                settings.persistence_db_dir.clone(),
+                persistence_metrics.clone(),
             ));
         state
             .ecs_mut()
             .insert(comp::AdminList(settings.admins.clone()));
         state.ecs_mut().insert(Vec::<Outcome>::new());
+        state.ecs_mut().insert(Fluids::default());
+        state.ecs_mut().insert(mining::MinedBlocks::default());
+        state.ecs_mut().insert(farming::Farms::default());
+        state.ecs_mut().insert(Shutdown::default());
+
+        // Load the weapon abilities up front so that a broken manifest is reported at
+        // startup, and watch it for changes
+        let mut ability_map_indicator = ReloadIndicator::new();
+        comp::AbilityMap::load_watched(&mut ability_map_indicator);
 
         // System timers for performance monitoring
         state.ecs_mut().insert(sys::EntitySyncTimer::default());
@@ -132,15 +198,30 @@ impl Server {
         state.ecs_mut().insert(sys::WaypointTimer::default());
         state.ecs_mut().insert(sys::InviteTimeoutTimer::default());
         state.ecs_mut().insert(sys::PersistenceTimer::default());
+        state.ecs_mut().insert(sys::WeatherTimer::default());
+        state.ecs_mut().insert(sys::FluidTimer::default());
+        state.ecs_mut().insert(sys::FarmingTimer::default());
 
         // System schedulers to control execution of systems
         state
             .ecs_mut()
             .insert(sys::PersistenceScheduler::every(Duration::from_secs(10)));
+        state
+            .ecs_mut()
+            .insert(sys::WeatherScheduler::every(Duration::from_secs(10)));
+        state
+            .ecs_mut()
+            .insert(sys::FluidScheduler::every(Duration::from_millis(250)));
+        state
+            .ecs_mut()
+            .insert(sys::FarmingScheduler::every(Duration::from_secs(10)));
 
         // Server-only components
         state.ecs_mut().register::<RegionSubscription>();
+        state.ecs_mut().register::<SpectatorCamera>();
         state.ecs_mut().register::<Client>();
+        state.ecs_mut().register::<sys::crafting::PendingCraft>();
+        state.ecs_mut().register::<OpenedContainer>();
 
         //Alias validator
         let banned_words_paths = &settings.banned_words_files;
@@ -178,11 +259,14 @@ impl Server {
             seed_elements: true,
             world_file: if let Some(ref opts) = settings.map_file {
                 opts.clone()
+            } else if settings.world_gen != GenOpts::default() {
+                // The default map was not generated with these parameters.
+                FileOpts::Generate
             } else {
                 // Load default map from assets.
                 FileOpts::LoadAsset(DEFAULT_WORLD_MAP.into())
             },
-            ..WorldOpts::default()
+            gen_opts: settings.world_gen.clone(),
         });
         #[cfg(feature = "worldgen")]
         let map = world.get_map_data(index.as_index_ref());
@@ -195,6 +279,7 @@ impl Server {
             max_height: 1.0,
             rgba: vec![0],
             horizons: [(vec![0], vec![0]), (vec![0], vec![0])],
+            pois: Vec::new(),
         };
 
         #[cfg(feature = "worldgen")]
@@ -261,6 +346,22 @@ impl Server {
         #[cfg(not(feature = "worldgen"))]
         let spawn_point = Vec3::new(0.0, 0.0, 256.0);
 
+        // Simulate the weather from the climate of the world
+        #[cfg(feature = "worldgen")]
+        let mut weather_sim = WeatherSim::from_world(world.sim(), u64::from(settings.world_seed));
+        #[cfg(not(feature = "worldgen"))]
+        let mut weather_sim = WeatherSim::new(
+            Vec2::one(),
+            vec![weather::Climate::default()],
+            u64::from(settings.world_seed),
+        );
+        weather_sim.tick(0.0, &mut state.ecs_mut().write_resource::<WeatherGrid>());
+        state.ecs_mut().insert(weather_sim);
+
+        // Give NPCs a map to plan long journeys with
+        #[cfg(feature = "worldgen")]
+        state.ecs_mut().insert(world.sim().nav_grid());
+
         // set the spawn point we calculated above
         state.ecs_mut().insert(SpawnPoint(spawn_point));
 
@@ -271,14 +372,9 @@ impl Server {
         sys::sentinel::register_trackers(&mut state.ecs_mut());
 
         state.ecs_mut().insert(DeletedEntities::default());
-
-        let mut metrics = ServerMetrics::new();
-        // register all metrics submodules here
-        let tick_metrics = TickMetrics::new(metrics.tick_clone())
-            .expect("Failed to initialize server tick metrics submodule.");
-        tick_metrics
-            .register(&metrics.registry())
-            .expect("failed to register tick metrics");
+        state
+            .ecs_mut()
+            .insert(sys::entity_sync::SyncBandwidth::default());
 
         let thread_pool = ThreadPoolBuilder::new()
             .name("veloren-worker".to_string())
@@ -290,11 +386,12 @@ impl Server {
         thread_pool.execute(f);
         block_on(network.listen(ProtocolAddr::Tcp(settings.gameserver_address)))?;
 
-        let this = Self {
+        let mut this = Self {
             state,
             world: Arc::new(world),
             index,
             map,
+            ability_map_indicator,
 
             network,
 
@@ -302,8 +399,17 @@ impl Server {
 
             metrics,
             tick_metrics,
+            message_metrics,
+            status: None,
         };
 
+        if let Some(status_address) = settings.status_address {
+            match StatusServer::run(status_address, &this.get_server_status()) {
+                Ok(status) => this.status = Some(status),
+                Err(e) => error!(?e, "Failed to start serving the server status"),
+            }
+        }
+
         // Run pending DB migrations (if any)
         debug!("Running DB migrations...");
 
@@ -311,6 +417,37 @@ impl Server {
             info!(?e, "Migration error");
         }
 
+        // Container contents can only be loaded once the migrations have run
+        let containers = persistence::container::load_containers(&settings.persistence_db_dir)
+            .unwrap_or_else(|e| {
+                error!(?e, "Failed to load container contents");
+                Vec::new()
+            });
+        this.state.ecs_mut().insert(Containers::new(containers));
+
+        // As can local accounts
+        let local_accounts = (settings.local_accounts && settings.auth_server_address.is_none())
+            .then(|| {
+                let password_hashes =
+                    persistence::account::load_accounts(&settings.persistence_db_dir)
+                        .unwrap_or_else(|e| {
+                            error!(?e, "Failed to load accounts");
+                            Vec::new()
+                        });
+                LocalAccounts::new(
+                    password_hashes,
+                    settings.account_registration,
+                    persistence::account::AccountUpdater::new(
+                        settings.persistence_db_dir.clone(),
+                        persistence_metrics,
+                    ),
+                )
+            });
+        this.state.ecs_mut().insert(LoginProvider::new(
+            settings.auth_server_address.clone(),
+            local_accounts,
+        ));
+
         debug!(?settings, "created veloren server with");
 
         let git_hash = *common::util::GIT_HASH;
@@ -328,6 +465,30 @@ impl Server {
             git_hash: common::util::GIT_HASH.to_string(),
             git_date: common::util::GIT_DATE.to_string(),
             auth_provider: settings.auth_server_address.clone(),
+            local_accounts: self
+                .state
+                .ecs()
+                .read_resource::<LoginProvider>()
+                .uses_local_accounts(),
+        }
+    }
+
+    /// The status shown to server browsers and monitoring
+    pub fn get_server_status(&self) -> ServerStatus {
+        let settings = self.settings();
+        ServerStatus {
+            info: self.get_server_info(),
+            player_count: self
+                .state
+                .ecs()
+                .read_storage::<Client>()
+                .join()
+                .filter(|client| client.is_registered())
+                .count() as u32,
+            max_players: settings.max_players as u32,
+            world_seed: settings.world_seed,
+            world_size: self.map.dimensions_lg.map(|e| 1 << e),
+            uptime: self.state.get_time() as u64,
         }
     }
 
@@ -385,7 +546,19 @@ impl Server {
         // 1) Build up a list of events for this frame, to be passed to the frontend.
         let mut frontend_events = Vec::new();
 
-        // 2)
+        // 2) Announce a pending shutdown, and tell the frontend once it is due
+        let announcement = self
+            .state
+            .ecs()
+            .write_resource::<Shutdown>()
+            .next_announcement();
+        if let Some(announcement) = announcement {
+            self.state
+                .notify_registered_clients(ChatType::Meta.server_msg(announcement));
+        }
+        if let Some(kind) = self.state.ecs().read_resource::<Shutdown>().due() {
+            frontend_events.push(Event::Shutdown { kind });
+        }
 
         let before_new_connections = Instant::now();
 
@@ -418,6 +591,9 @@ impl Server {
         // `ServerEvent` mediated effects
         self.state.update_region_map();
         self.state.apply_terrain_changes();
+        container::handle_terrain_changes(&mut self.state);
+        fluid::handle_terrain_changes(&self.state);
+        farming::handle_terrain_changes(&mut self.state);
 
         let before_sync = Instant::now();
 
@@ -536,7 +712,29 @@ impl Server {
             });
         }
 
+        if self.ability_map_indicator.reloaded() {
+            // Reapply the abilities of equipped tools so that players don't need to
+            // re-equip them to see the changes. NPCs keep the abilities they were
+            // spawned with, since some of them are customised.
+            info!("Weapon ability manifest changed, updating player loadouts");
+            let ecs = self.state.ecs();
+            for (loadout, _) in (
+                &mut ecs.write_storage::<comp::Loadout>(),
+                &ecs.read_storage::<comp::Player>(),
+            )
+                .join()
+            {
+                loadout
+                    .active_item
+                    .iter_mut()
+                    .chain(loadout.second_item.iter_mut())
+                    .for_each(comp::ItemConfig::refresh_abilities);
+            }
+        }
+
         let end_of_server_tick = Instant::now();
+        self.state.ecs().write_resource::<TickTime>().0 =
+            end_of_server_tick - before_new_connections;
 
         // 8) Update Metrics
         // Get system timing info
@@ -569,8 +767,10 @@ impl Server {
             .ecs()
             .read_resource::<sys::PersistenceTimer>()
             .nanos as i64;
+        let fluid_nanos = self.state.ecs().read_resource::<sys::FluidTimer>().nanos as i64;
+        let farming_nanos = self.state.ecs().read_resource::<sys::FarmingTimer>().nanos as i64;
         let total_sys_ran_in_dispatcher_nanos =
-            terrain_nanos + waypoint_nanos + invite_timeout_nanos;
+            terrain_nanos + waypoint_nanos + invite_timeout_nanos + fluid_nanos + farming_nanos;
 
         // Report timing info
         self.tick_metrics
@@ -640,8 +840,137 @@ impl Server {
             .tick_time
             .with_label_values(&["persistence:stats"])
             .set(stats_persistence_nanos);
+        self.tick_metrics
+            .tick_time
+            .with_label_values(&["fluid"])
+            .set(fluid_nanos);
+        self.tick_metrics
+            .tick_time
+            .with_label_values(&["farming"])
+            .set(farming_nanos);
+
+        // Only systems that ran this tick are recorded, scheduled systems run
+        // every few ticks
+        let system_times = {
+            let ecs = self.state.ecs();
+            [
+                (
+                    "entity sync",
+                    ecs.write_resource::<sys::EntitySyncTimer>().take_sample(),
+                ),
+                (
+                    "message",
+                    ecs.write_resource::<sys::MessageTimer>().take_sample(),
+                ),
+                (
+                    "sentinel",
+                    ecs.write_resource::<sys::SentinelTimer>().take_sample(),
+                ),
+                (
+                    "subscription",
+                    ecs.write_resource::<sys::SubscriptionTimer>().take_sample(),
+                ),
+                (
+                    "terrain sync",
+                    ecs.write_resource::<sys::TerrainSyncTimer>().take_sample(),
+                ),
+                (
+                    "terrain",
+                    ecs.write_resource::<sys::TerrainTimer>().take_sample(),
+                ),
+                (
+                    "waypoint",
+                    ecs.write_resource::<sys::WaypointTimer>().take_sample(),
+                ),
+                (
+                    "invite timeout",
+                    ecs.write_resource::<sys::InviteTimeoutTimer>()
+                        .take_sample(),
+                ),
+                (
+                    "persistence:stats",
+                    ecs.write_resource::<sys::PersistenceTimer>().take_sample(),
+                ),
+                (
+                    "weather",
+                    ecs.write_resource::<sys::WeatherTimer>().take_sample(),
+                ),
+                (
+                    "fluid",
+                    ecs.write_resource::<sys::FluidTimer>().take_sample(),
+                ),
+                (
+                    "farming",
+                    ecs.write_resource::<sys::FarmingTimer>().take_sample(),
+                ),
+                (
+                    "agent",
+                    ecs.write_resource::<common::sys::AgentTimer>()
+                        .take_sample(),
+                ),
+                (
+                    "mount",
+                    ecs.write_resource::<common::sys::MountTimer>()
+                        .take_sample(),
+                ),
+                (
+                    "controller",
+                    ecs.write_resource::<common::sys::ControllerTimer>()
+                        .take_sample(),
+                ),
+                (
+                    "character behavior",
+                    ecs.write_resource::<common::sys::CharacterBehaviorTimer>()
+                        .take_sample(),
+                ),
+                (
+                    "stats",
+                    ecs.write_resource::<common::sys::StatsTimer>()
+                        .take_sample(),
+                ),
+                (
+                    "physics",
+                    ecs.write_resource::<common::sys::PhysicsTimer>()
+                        .take_sample(),
+                ),
+                (
+                    "projectile",
+                    ecs.write_resource::<common::sys::ProjectileTimer>()
+                        .take_sample(),
+                ),
+                (
+                    "combat",
+                    ecs.write_resource::<common::sys::CombatTimer>()
+                        .take_sample(),
+                ),
+                (
+                    "wetness",
+                    ecs.write_resource::<common::sys::WetnessTimer>()
+                        .take_sample(),
+                ),
+            ]
+        };
+        for &(system, nanos) in system_times.iter() {
+            if let Some(nanos) = nanos {
+                self.tick_metrics
+                    .system_time
+                    .with_label_values(&[system])
+                    .observe(Duration::from_nanos(nanos).as_secs_f64());
+            }
+        }
 
         // Report other info
+        for (comp, bytes) in self
+            .state
+            .ecs()
+            .write_resource::<sys::entity_sync::SyncBandwidth>()
+            .take()
+        {
+            self.tick_metrics
+                .sync_bytes
+                .with_label_values(&[comp])
+                .inc_by(bytes as i64);
+        }
         self.tick_metrics
             .player_online
             .set(self.state.ecs().read_storage::<Client>().join().count() as u64);
@@ -659,6 +988,10 @@ impl Server {
 
             let entity_count = self.state.ecs().entities().join().count();
             self.tick_metrics.entity_count.set(entity_count as i64);
+
+            if let Some(status) = &self.status {
+                status.update(&self.get_server_status());
+            }
         }
         //self.metrics.entity_count.set(self.state.);
         self.tick_metrics
@@ -696,7 +1029,7 @@ impl Server {
             };
             debug!("New Participant connected to the server");
 
-            let singleton_stream = match select!(
+            let mut singleton_stream = match select!(
                 _ = Delay::new(SLOWLORIS_TIMEOUT).fuse() => None,
                 sr = participant.opened().fuse() => Some(sr),
             ) {
@@ -711,16 +1044,70 @@ impl Server {
                 },
             };
 
+            // The client tells us its protocol version before anything else is sent, and
+            // learns which versions we accept in return
+            let client_version = match select!(
+                _ = Delay::new(SLOWLORIS_TIMEOUT).fuse() => None,
+                v = singleton_stream.recv::<u32>().fuse() => Some(v),
+            ) {
+                None => {
+                    warn!("Client didn't send its protocol version in time, dropping");
+                    return Ok(());
+                },
+                Some(Ok(v)) => v,
+                Some(Err(e)) => {
+                    warn!(
+                        ?e,
+                        "Failed to receive the protocol version of a client. dropping"
+                    );
+                    continue;
+                },
+            };
+            let versions = ProtocolVersions::supported();
+            if let Err(e) = singleton_stream.send(versions) {
+                warn!(?e, "Failed to send protocol versions to client. dropping");
+                continue;
+            }
+            if !versions.supports(client_version) {
+                // Note: the client is dropped, it tells the player about the mismatch itself
+                info!(
+                    ?client_version,
+                    ?versions,
+                    "Client with incompatible protocol version connected, dropping"
+                );
+                continue;
+            }
+
             let mut client = Client {
                 client_state: ClientState::Connected,
+                pid: participant.remote_pid(),
                 participant: std::sync::Mutex::new(Some(participant)),
                 singleton_stream,
                 network_error: std::sync::atomic::AtomicBool::new(false),
                 last_ping: self.state.get_time(),
                 login_msg_sent: false,
+                sync_baselines: Baselines::default(),
+                recorder: self.settings().replay_dir.as_ref().map(|_| {
+                    Recorder::new(
+                        self.settings().replay_filter,
+                        self.settings()
+                            .replay_max_size_mb
+                            .saturating_mul(1024 * 1024),
+                    )
+                }),
+                message_metrics: self.message_metrics.clone(),
             };
 
-            if self.settings().max_players
+            if self
+                .state
+                .ecs()
+                .read_resource::<Shutdown>()
+                .pending()
+                .is_some()
+            {
+                // Note: in this case the client is dropped
+                client.notify(ServerMsg::Shutdown);
+            } else if self.settings().max_players
                 <= self.state.ecs().read_storage::<Client>().join().count()
             {
                 // Note: in this case the client is dropped
@@ -733,7 +1120,7 @@ impl Server {
                     .with(client)
                     .build();
                 // Send client all the tracked components currently attached to its entity as
-                // well as synced resources (currently only `TimeOfDay`)
+                // well as synced resources (`TimeOfDay` and the weather)
                 debug!("Starting initial sync with client.");
                 self.state
                     .ecs()
@@ -746,6 +1133,7 @@ impl Server {
                             .create_entity_package(entity, None, None, None),
                         server_info: self.get_server_info(),
                         time_of_day: *self.state.ecs().read_resource(),
+                        weather: self.state.ecs().read_resource::<WeatherGrid>().clone(),
                         max_group_size: self.settings().max_player_group_size,
                         world_map: self.map.clone(),
                         recipe_book: (&*default_recipe_book()).clone(),
@@ -815,8 +1203,74 @@ impl Server {
             .join()
             .count() as u64
     }
+
+    /// Announce that the server shuts down or restarts once `delay` has
+    /// passed, replacing the shutdown that is pending. Nobody can log in
+    /// anymore until then.
+    pub fn shutdown_in(&mut self, kind: ShutdownKind, delay: Duration) {
+        info!(?kind, ?delay, "Shutdown requested");
+        let announcement = self
+            .state
+            .ecs()
+            .write_resource::<Shutdown>()
+            .start(kind, delay);
+        self.state
+            .notify_registered_clients(ChatType::Meta.server_msg(announcement));
+    }
+
+    /// Cancel the pending shutdown, returning whether there was one
+    pub fn abort_shutdown(&mut self) -> bool {
+        let aborted = self.state.ecs().write_resource::<Shutdown>().abort();
+        if aborted {
+            info!("Shutdown aborted");
+            self.state.notify_registered_clients(
+                ChatType::Meta.server_msg("The server will no longer shut down."),
+            );
+        }
+        aborted
+    }
+
+    /// Create a local account or reset its password, returning whether the
+    /// account was created
+    pub fn set_account_password(&mut self, username: &str, password: &str) -> Result<bool, String> {
+        let created = self
+            .state
+            .ecs()
+            .write_resource::<LoginProvider>()
+            .set_password(username, password)?;
+        info!(?username, ?created, "Account password set");
+        Ok(created)
+    }
 }
 
 impl Drop for Server {
-    fn drop(&mut self) { self.state.notify_registered_clients(ServerMsg::Shutdown); }
+    fn drop(&mut self) {
+        self.state.notify_registered_clients(ServerMsg::Shutdown);
+
+        // Save everyone who is still playing, the persistence system only does so
+        // every few seconds. The updates are written before the updater is dropped
+        // along with the rest of the state.
+        let ecs = self.state.ecs();
+        ecs.read_resource::<CharacterUpdater>().batch_update(
+            (
+                &ecs.read_storage::<comp::Player>(),
+                &ecs.read_storage::<comp::Stats>(),
+                &ecs.read_storage::<comp::Inventory>(),
+                &ecs.read_storage::<comp::Loadout>(),
+            )
+                .join()
+                .filter_map(|(player, stats, inventory, loadout)| {
+                    player
+                        .character_id
+                        .map(|id| (id, stats, inventory, loadout))
+                }),
+        );
+
+        // Save the containers that changed since the last persistence run
+        let containers = self.state.ecs().write_resource::<Containers>().take_dirty();
+        self.state
+            .ecs()
+            .read_resource::<persistence::container::ContainerUpdater>()
+            .batch_update(containers);
+    }
 }
@@ -1,9 +1,26 @@
+use crate::persistence::account::AccountUpdater;
 use authc::{AuthClient, AuthToken, Uuid};
 use common::msg::RegisterError;
 use hashbrown::HashMap;
-use std::str::FromStr;
+use network::Pid;
+use rand::Rng;
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
 use tracing::{error, info};
 
+/// Most passwords hashed or verified per second. Each one takes milliseconds
+/// of the tick, so this bounds how much clients can stall the server by
+/// logging in over and over.
+const MAX_HASHES_PER_SECOND: u32 = 5;
+/// Time a connection has to wait between logins and password changes, so
+/// that a single client can't use up all hashes of a second on its own
+const CONNECTION_INTERVAL: Duration = Duration::from_secs(1);
+/// Number of connections remembered at most. Those that may log in again are
+/// forgotten first, then those that tried the longest time ago.
+const MAX_TRACKED_CONNECTIONS: usize = 1024;
+
 fn derive_uuid(username: &str) -> Uuid {
     let mut state = 144066263297769815596495629667062367629;
 
@@ -15,24 +32,209 @@ fn derive_uuid(username: &str) -> Uuid {
     Uuid::from_slice(&state.to_be_bytes()).unwrap()
 }
 
+/// Salt and hash a password, into a PHC string that holds both
+fn hash_password(password: &str) -> Result<String, String> {
+    let salt = rand::thread_rng().gen::<[u8; 16]>();
+    argon2::hash_encoded(password.as_bytes(), &salt, &argon2::Config::default())
+        .map_err(|e| e.to_string())
+}
+
+fn verify_password(password_hash: &str, password: &str) -> bool {
+    argon2::verify_encoded(password_hash, password.as_bytes()).unwrap_or_else(|e| {
+        error!(?e, "Failed to verify password hash");
+        false
+    })
+}
+
+/// Counts the passwords hashed or verified within the current second
+struct HashLimit {
+    since: Instant,
+    count: u32,
+}
+
+impl HashLimit {
+    fn new(now: Instant) -> Self {
+        Self {
+            since: now,
+            count: 0,
+        }
+    }
+
+    /// Whether another password may be hashed at `now`
+    fn take(&mut self, now: Instant) -> bool {
+        if now.duration_since(self.since) >= Duration::from_secs(1) {
+            *self = Self::new(now);
+        }
+        if self.count >= MAX_HASHES_PER_SECOND {
+            return false;
+        }
+        self.count += 1;
+        true
+    }
+}
+
+/// Turns away connections that try to log in or change a password too often
+#[derive(Default)]
+struct ConnectionLimit {
+    last_attempt: HashMap<Pid, Instant>,
+}
+
+impl ConnectionLimit {
+    /// Whether an attempt of `pid` at `now` is allowed
+    fn allow(&mut self, pid: Pid, now: Instant) -> bool {
+        match self.last_attempt.get(&pid) {
+            Some(last) if now.duration_since(*last) < CONNECTION_INTERVAL => false,
+            Some(_) => {
+                self.last_attempt.insert(pid, now);
+                true
+            },
+            None => {
+                if self.last_attempt.len() >= MAX_TRACKED_CONNECTIONS {
+                    self.last_attempt
+                        .retain(|_, last| now.duration_since(*last) < CONNECTION_INTERVAL);
+                }
+                if self.last_attempt.len() >= MAX_TRACKED_CONNECTIONS {
+                    if let Some(oldest) = self
+                        .last_attempt
+                        .iter()
+                        .min_by_key(|(_, last)| **last)
+                        .map(|(pid, _)| *pid)
+                    {
+                        self.last_attempt.remove(&oldest);
+                    }
+                }
+                self.last_attempt.insert(pid, now);
+                true
+            },
+        }
+    }
+}
+
+/// The built-in account store, for servers that don't use an auth server.
+/// Players log in with their username and a password, which is stored salted
+/// and hashed in the server database.
+pub struct LocalAccounts {
+    password_hashes: HashMap<String, String>,
+    /// Whether logging in with an unused username registers it
+    registration: bool,
+    updater: AccountUpdater,
+    hash_limit: HashLimit,
+    connection_limit: ConnectionLimit,
+}
+
+impl LocalAccounts {
+    pub fn new(
+        password_hashes: Vec<(String, String)>,
+        registration: bool,
+        updater: AccountUpdater,
+    ) -> Self {
+        Self {
+            password_hashes: password_hashes.into_iter().collect(),
+            registration,
+            updater,
+            hash_limit: HashLimit::new(Instant::now()),
+            connection_limit: ConnectionLimit::default(),
+        }
+    }
+
+    /// Count a login or password change of the connection `pid`, failing if
+    /// it tried too recently
+    fn limit_connection(&mut self, pid: Pid) -> Result<(), String> {
+        if self.connection_limit.allow(pid, Instant::now()) {
+            Ok(())
+        } else {
+            Err("Too many attempts, try again in a moment".to_owned())
+        }
+    }
+
+    /// Count a password about to be hashed or verified, failing if too many
+    /// were already this second
+    fn limit_hashes(&mut self) -> Result<(), String> {
+        if self.hash_limit.take(Instant::now()) {
+            Ok(())
+        } else {
+            Err("The server is busy, try again in a moment".to_owned())
+        }
+    }
+
+    fn authenticate(
+        &mut self,
+        pid: Pid,
+        username: &str,
+        password: &str,
+        admins: &[String],
+    ) -> Result<(), RegisterError> {
+        let err = |msg: &str| Err(RegisterError::AuthError(msg.to_owned()));
+        self.limit_connection(pid)
+            .map_err(RegisterError::AuthError)?;
+        if self.password_hashes.contains_key(username) {
+            self.limit_hashes().map_err(RegisterError::AuthError)?;
+        }
+        match self.password_hashes.get(username) {
+            Some(password_hash) => {
+                if verify_password(password_hash, password) {
+                    Ok(())
+                } else {
+                    err("Wrong password")
+                }
+            },
+            None if !self.registration => {
+                err("There is no account with this username, ask an admin to create one")
+            },
+            // Otherwise anyone could take an admin's name before they first log in
+            None if admins.iter().any(|admin| admin == username) => {
+                err("The account of an admin has to be created by another admin")
+            },
+            None if password.is_empty() => err("Choose a password to register this username"),
+            None => {
+                self.set_password(username, password)
+                    .map_err(RegisterError::AuthError)?;
+                info!(?username, "Registered account");
+                Ok(())
+            },
+        }
+    }
+
+    /// Returns whether the account was created
+    fn set_password(&mut self, username: &str, password: &str) -> Result<bool, String> {
+        if password.is_empty() {
+            return Err("The password must not be empty".to_owned());
+        }
+        self.limit_hashes()?;
+        let password_hash = hash_password(password)?;
+        self.updater
+            .update(username.to_owned(), password_hash.clone());
+        Ok(self
+            .password_hashes
+            .insert(username.to_owned(), password_hash)
+            .is_none())
+    }
+}
+
 pub struct LoginProvider {
     accounts: HashMap<Uuid, String>,
     auth_server: Option<AuthClient>,
+    local_accounts: Option<LocalAccounts>,
 }
 
 impl LoginProvider {
-    pub fn new(auth_addr: Option<String>) -> Self {
-        let auth_server = match auth_addr {
-            Some(addr) => Some(AuthClient::new(addr)),
-            None => None,
+    /// Local accounts are only used when there is no auth server
+    pub fn new(auth_addr: Option<String>, local_accounts: Option<LocalAccounts>) -> Self {
+        let (auth_server, local_accounts) = match auth_addr {
+            Some(addr) => (Some(AuthClient::new(addr)), None),
+            None => (None, local_accounts),
         };
 
         Self {
             accounts: HashMap::new(),
             auth_server,
+            local_accounts,
         }
     }
 
+    /// Whether players log in with the password of a local account
+    pub fn uses_local_accounts(&self) -> bool { self.local_accounts.is_some() }
+
     fn login(&mut self, uuid: Uuid, username: String) -> Result<(), RegisterError> {
         // make sure that the user is not logged in already
         if self.accounts.contains_key(&uuid) {
@@ -51,8 +253,11 @@ impl LoginProvider {
 
     pub fn try_login(
         &mut self,
+        pid: Pid,
         username_or_token: &str,
+        password: Option<&str>,
         whitelist: &[String],
+        admins: &[String],
     ) -> Result<(String, Uuid), RegisterError> {
         self
             // resolve user information
@@ -65,6 +270,10 @@ impl LoginProvider {
                     return Err(RegisterError::NotOnWhitelist);
                 }
 
+                if let Some(local_accounts) = &mut self.local_accounts {
+                    local_accounts.authenticate(pid, &username, password.unwrap_or(""), admins)?;
+                }
+
                 // add the user to self.accounts
                 self.login(uuid, username.clone())?;
 
@@ -96,4 +305,147 @@ impl LoginProvider {
             },
         }
     }
+
+    /// Change the password of a local account, which requires the current one
+    pub fn change_password(
+        &mut self,
+        pid: Pid,
+        username: &str,
+        old_password: &str,
+        new_password: &str,
+    ) -> Result<(), String> {
+        let local_accounts = self.local_accounts()?;
+        local_accounts.limit_connection(pid)?;
+        local_accounts.limit_hashes()?;
+        let verified = local_accounts
+            .password_hashes
+            .get(username)
+            .map(|password_hash| verify_password(password_hash, old_password));
+        match verified {
+            Some(true) => local_accounts
+                .set_password(username, new_password)
+                .map(|_| ()),
+            Some(false) => Err("Wrong password".to_owned()),
+            None => Err(format!("There is no account for \"{}\"", username)),
+        }
+    }
+
+    /// Create a local account or reset its password. Returns whether the
+    /// account was created.
+    pub fn set_password(&mut self, username: &str, password: &str) -> Result<bool, String> {
+        self.local_accounts()?.set_password(username, password)
+    }
+
+    fn local_accounts(&mut self) -> Result<&mut LocalAccounts, String> {
+        self.local_accounts
+            .as_mut()
+            .ok_or_else(|| "This server doesn't use local accounts".to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_salted_passwords() {
+        let first = hash_password("hunter2").unwrap();
+        let second = hash_password("hunter2").unwrap();
+        assert_ne!(first, second);
+        assert!(verify_password(&first, "hunter2"));
+        assert!(verify_password(&second, "hunter2"));
+        assert!(!verify_password(&first, "hunter3"));
+        assert!(!verify_password(&first, ""));
+    }
+
+    #[test]
+    fn limits_hashes_per_second() {
+        let now = Instant::now();
+        let mut limit = HashLimit::new(now);
+        for _ in 0..MAX_HASHES_PER_SECOND {
+            assert!(limit.take(now));
+        }
+        assert!(!limit.take(now + Duration::from_millis(500)));
+        assert!(limit.take(now + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn limits_attempts_per_connection() {
+        let now = Instant::now();
+        let mut limit = ConnectionLimit::default();
+        let (a, b) = (Pid::fake(0), Pid::fake(1));
+        assert!(limit.allow(a, now));
+        assert!(!limit.allow(a, now + CONNECTION_INTERVAL / 2));
+        assert!(limit.allow(b, now + CONNECTION_INTERVAL / 2));
+        assert!(limit.allow(a, now + CONNECTION_INTERVAL));
+    }
+
+    fn accounts(registration: bool) -> LocalAccounts {
+        let password_hashes = vec![(String::from("alice"), hash_password("hunter2").unwrap())];
+        LocalAccounts::new(password_hashes, registration, AccountUpdater::disabled())
+    }
+
+    fn auth_error(result: Result<(), RegisterError>) -> String {
+        match result {
+            Err(RegisterError::AuthError(msg)) => msg,
+            other => panic!("Expected an auth error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn authenticates_local_accounts() {
+        let admins = [String::from("admin")];
+        let mut accounts = accounts(true);
+        assert!(
+            accounts
+                .authenticate(Pid::fake(0), "alice", "hunter2", &admins)
+                .is_ok()
+        );
+        assert_eq!(
+            auth_error(accounts.authenticate(Pid::fake(1), "alice", "hunter3", &admins)),
+            "Wrong password"
+        );
+        // The same connection has to wait before trying again
+        assert_eq!(
+            auth_error(accounts.authenticate(Pid::fake(1), "alice", "hunter2", &admins)),
+            "Too many attempts, try again in a moment"
+        );
+
+        // Unused names are registered, unless they belong to an admin or
+        // come without a password
+        assert!(
+            auth_error(accounts.authenticate(Pid::fake(2), "admin", "secret", &admins))
+                .contains("admin")
+        );
+        assert!(
+            auth_error(accounts.authenticate(Pid::fake(3), "bob", "", &admins))
+                .contains("Choose a password")
+        );
+        assert!(!accounts.password_hashes.contains_key("admin"));
+        assert!(!accounts.password_hashes.contains_key("bob"));
+        assert!(
+            accounts
+                .authenticate(Pid::fake(4), "bob", "pass word", &admins)
+                .is_ok()
+        );
+        assert!(verify_password(
+            &accounts.password_hashes["bob"],
+            "pass word"
+        ));
+    }
+
+    #[test]
+    fn denies_unknown_names_without_registration() {
+        let mut accounts = accounts(false);
+        assert!(
+            auth_error(accounts.authenticate(Pid::fake(0), "bob", "hunter2", &[]))
+                .contains("no account")
+        );
+        assert!(!accounts.password_hashes.contains_key("bob"));
+        assert!(
+            accounts
+                .authenticate(Pid::fake(1), "alice", "hunter2", &[])
+                .is_ok()
+        );
+    }
 }
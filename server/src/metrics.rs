@@ -1,6 +1,7 @@
 use prometheus::{
     core::{AtomicU64 as PrometheusAtomicU64, GenericGauge},
-    Encoder, Gauge, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+    exponential_buckets, Encoder, Gauge, Histogram, HistogramOpts, HistogramVec, IntCounterVec,
+    IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
 };
 use std::{
     convert::TryInto,
@@ -23,6 +24,8 @@ pub struct TickMetrics {
     pub player_online: UintGauge,
     pub entity_count: IntGauge,
     pub tick_time: IntGaugeVec,
+    pub system_time: HistogramVec,
+    pub sync_bytes: IntCounterVec,
     pub build_info: IntGauge,
     pub start_time: IntGauge,
     pub time_of_day: Gauge,
@@ -30,6 +33,25 @@ pub struct TickMetrics {
     tick: Arc<AtomicU64>,
 }
 
+/// Messages exchanged with clients, by `ServerMsg`/`ClientMsg` variant
+#[derive(Clone)]
+pub struct MessageMetrics {
+    pub messages_sent: IntCounterVec,
+    pub messages_received: IntCounterVec,
+    pub bytes_sent: IntCounterVec,
+    pub bytes_received: IntCounterVec,
+}
+
+#[derive(Clone)]
+pub struct ChunkGenMetrics {
+    pub chunk_gen_time: Histogram,
+}
+
+#[derive(Clone)]
+pub struct PersistenceMetrics {
+    pub write_time: HistogramVec,
+}
+
 pub struct ServerMetrics {
     running: Arc<AtomicBool>,
     handle: Option<thread::JoinHandle<()>>,
@@ -38,7 +60,6 @@ pub struct ServerMetrics {
 }
 
 impl TickMetrics {
-
     pub fn new(tick: Arc<AtomicU64>) -> Result<Self, Box<dyn Error>> {
         let player_online = IntGauge::with_opts(Opts::new(
             "player_online",
@@ -74,6 +95,19 @@ impl TickMetrics {
             Opts::new("tick_time", "time in ns requiered for a tick of the server"),
             &["period"],
         )?;
+        // From 10µs up to about 300ms
+        let system_time = HistogramVec::new(
+            HistogramOpts::new("system_time", "time in s required to run an ECS system")
+                .buckets(exponential_buckets(0.000_01, 2.0, 16)?),
+            &["system"],
+        )?;
+        let sync_bytes = IntCounterVec::new(
+            Opts::new(
+                "sync_bytes",
+                "bytes of component updates sent to clients, by component",
+            ),
+            &["component"],
+        )?;
 
         let since_the_epoch = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -86,6 +120,8 @@ impl TickMetrics {
             player_online,
             entity_count,
             tick_time,
+            system_time,
+            sync_bytes,
             build_info,
             start_time,
             time_of_day,
@@ -103,12 +139,114 @@ impl TickMetrics {
         registry.register(Box::new(self.chonks_count.clone()))?;
         registry.register(Box::new(self.chunks_count.clone()))?;
         registry.register(Box::new(self.tick_time.clone()))?;
+        registry.register(Box::new(self.system_time.clone()))?;
+        registry.register(Box::new(self.sync_bytes.clone()))?;
         Ok(())
     }
 
     pub fn is_100th_tick(&self) -> bool { self.tick.load(Ordering::Relaxed).rem_euclid(100) == 0 }
 }
 
+impl MessageMetrics {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let messages_sent = IntCounterVec::new(
+            Opts::new("messages_sent", "messages sent to clients, by message"),
+            &["message"],
+        )?;
+        let messages_received = IntCounterVec::new(
+            Opts::new(
+                "messages_received",
+                "messages received from clients, by message",
+            ),
+            &["message"],
+        )?;
+        let bytes_sent = IntCounterVec::new(
+            Opts::new(
+                "message_bytes_sent",
+                "compressed bytes of messages sent to clients, by message",
+            ),
+            &["message"],
+        )?;
+        let bytes_received = IntCounterVec::new(
+            Opts::new(
+                "message_bytes_received",
+                "compressed bytes of messages received from clients, by message",
+            ),
+            &["message"],
+        )?;
+
+        Ok(Self {
+            messages_sent,
+            messages_received,
+            bytes_sent,
+            bytes_received,
+        })
+    }
+
+    pub fn register(&self, registry: &Registry) -> Result<(), Box<dyn Error>> {
+        registry.register(Box::new(self.messages_sent.clone()))?;
+        registry.register(Box::new(self.messages_received.clone()))?;
+        registry.register(Box::new(self.bytes_sent.clone()))?;
+        registry.register(Box::new(self.bytes_received.clone()))?;
+        Ok(())
+    }
+
+    pub fn sent(&self, message: &str, bytes: usize) {
+        self.messages_sent.with_label_values(&[message]).inc();
+        self.bytes_sent
+            .with_label_values(&[message])
+            .inc_by(bytes as i64);
+    }
+
+    pub fn received(&self, message: &str, bytes: usize) {
+        self.messages_received.with_label_values(&[message]).inc();
+        self.bytes_received
+            .with_label_values(&[message])
+            .inc_by(bytes as i64);
+    }
+}
+
+impl ChunkGenMetrics {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        // From 1ms up to about 16s
+        let chunk_gen_time = Histogram::with_opts(
+            HistogramOpts::new(
+                "chunk_gen_time",
+                "time in s from requesting a chunk until it is generated",
+            )
+            .buckets(exponential_buckets(0.001, 2.0, 15)?),
+        )?;
+
+        Ok(Self { chunk_gen_time })
+    }
+
+    pub fn register(&self, registry: &Registry) -> Result<(), Box<dyn Error>> {
+        registry.register(Box::new(self.chunk_gen_time.clone()))?;
+        Ok(())
+    }
+}
+
+impl PersistenceMetrics {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        // From 0.5ms up to about 8s
+        let write_time = HistogramVec::new(
+            HistogramOpts::new(
+                "persistence_write_time",
+                "time in s required to write a batch of updates to the database",
+            )
+            .buckets(exponential_buckets(0.000_5, 2.0, 15)?),
+            &["kind"],
+        )?;
+
+        Ok(Self { write_time })
+    }
+
+    pub fn register(&self, registry: &Registry) -> Result<(), Box<dyn Error>> {
+        registry.register(Box::new(self.write_time.clone()))?;
+        Ok(())
+    }
+}
+
 impl ServerMetrics {
     #[allow(clippy::new_without_default)] // TODO: Pending review in #587
     pub fn new() -> Self {
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS "container" (
+    pos_x INTEGER NOT NULL,
+    pos_y INTEGER NOT NULL,
+    pos_z INTEGER NOT NULL,
+    items TEXT NOT NULL,
+    PRIMARY KEY (pos_x, pos_y, pos_z)
+);
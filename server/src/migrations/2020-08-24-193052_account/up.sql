@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS "account" (
+    username TEXT NOT NULL PRIMARY KEY,
+    password_hash TEXT NOT NULL
+);
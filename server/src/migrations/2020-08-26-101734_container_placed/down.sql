@@ -0,0 +1,16 @@
+-- SQLite does not support removing columns from tables so we must rename the current table,
+-- recreate the previous version of the table, then copy over the data from the renamed table
+ALTER TABLE "container" RENAME TO _container_old;
+
+CREATE TABLE "container" (
+    pos_x INTEGER NOT NULL,
+    pos_y INTEGER NOT NULL,
+    pos_z INTEGER NOT NULL,
+    items TEXT NOT NULL,
+    PRIMARY KEY (pos_x, pos_y, pos_z)
+);
+
+INSERT INTO "container" (pos_x, pos_y, pos_z, items)
+SELECT pos_x, pos_y, pos_z, items FROM _container_old;
+
+DROP TABLE _container_old;
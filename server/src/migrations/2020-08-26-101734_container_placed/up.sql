@@ -0,0 +1,3 @@
+-- Marks the containers whose block was placed by a player, which has to be put
+-- back when its chunk is generated again
+ALTER TABLE "container" ADD COLUMN placed BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,110 @@
+//! Damage dealt to blocks that are being mined
+
+use common::terrain::BlockKind;
+use hashbrown::HashMap;
+use vek::*;
+
+/// Time after which a block that hasn't been struck again recovers, in seconds
+const RECOVER_TIME: f64 = 30.0;
+
+struct BlockDamage {
+    dealt: f32,
+    last_hit: f64,
+}
+
+/// The blocks that have been struck with a pickaxe but not broken yet.
+///
+/// Blocks take several hits to break depending on their hardness, so that
+/// mining takes time. Damage is forgotten once a block is left alone for a
+/// while.
+#[derive(Default)]
+pub struct MinedBlocks {
+    damaged: HashMap<Vec3<i32>, BlockDamage>,
+}
+
+impl MinedBlocks {
+    /// Strike the block of `kind` at `pos` with `power`, returning whether it
+    /// broke. Blocks that can't be mined never break.
+    pub fn hit(&mut self, pos: Vec3<i32>, kind: BlockKind, power: f32, time: f64) -> bool {
+        self.maintain(time);
+
+        let hardness = match kind.mining_hardness() {
+            Some(hardness) => hardness,
+            None => return false,
+        };
+        let damage = self.damaged.entry(pos).or_insert(BlockDamage {
+            dealt: 0.0,
+            last_hit: time,
+        });
+        damage.dealt += power;
+        damage.last_hit = time;
+
+        if damage.dealt >= hardness {
+            self.damaged.remove(&pos);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Forget the damage dealt to blocks that are no longer being mined
+    pub fn maintain(&mut self, time: f64) {
+        self.damaged
+            .retain(|_, damage| time - damage.last_hit < RECOVER_TIME);
+    }
+
+    /// How far the block at `pos` is from breaking, between 0 and 1
+    pub fn progress(&self, pos: Vec3<i32>, kind: BlockKind) -> f32 {
+        match (self.damaged.get(&pos), kind.mining_hardness()) {
+            (Some(damage), Some(hardness)) => (damage.dealt / hardness).min(1.0),
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hardness() {
+        let mut mined = MinedBlocks::default();
+        let pos = Vec3::new(1, 2, 3);
+
+        let hits = (1..)
+            .find(|i| mined.hit(pos, BlockKind::IronOre, 1.0, *i as f64))
+            .unwrap();
+        assert_eq!(hits as f32, BlockKind::IronOre.mining_hardness().unwrap());
+        assert_eq!(mined.progress(pos, BlockKind::IronOre), 0.0);
+
+        assert!(!mined.hit(pos, BlockKind::Normal, 1.0, 0.0));
+        assert!((mined.progress(pos, BlockKind::Normal) - 0.5).abs() < 0.001);
+        assert!(mined.hit(pos, BlockKind::Normal, 1.0, 0.0));
+
+        // Stronger pickaxes mine faster
+        assert!(mined.hit(pos, BlockKind::Rock, 4.0, 0.0));
+    }
+
+    #[test]
+    fn test_unmineable() {
+        let mut mined = MinedBlocks::default();
+        let pos = Vec3::zero();
+
+        assert!(!(0..100).any(|i| mined.hit(pos, BlockKind::Dense, 10.0, i as f64)));
+        assert!(!(0..100).any(|i| mined.hit(pos, BlockKind::Chest, 10.0, i as f64)));
+        assert_eq!(mined.progress(pos, BlockKind::Dense), 0.0);
+    }
+
+    #[test]
+    fn test_recover() {
+        let mut mined = MinedBlocks::default();
+        let pos = Vec3::new(-4, 8, 12);
+
+        assert!(!mined.hit(pos, BlockKind::Rock, 3.0, 0.0));
+        assert!(mined.progress(pos, BlockKind::Rock) > 0.0);
+
+        mined.maintain(RECOVER_TIME + 1.0);
+        assert_eq!(mined.progress(pos, BlockKind::Rock), 0.0);
+        assert!(!mined.hit(pos, BlockKind::Rock, 3.0, RECOVER_TIME + 1.0));
+    }
+}
@@ -0,0 +1,88 @@
+//! Database operations related to the built-in account store
+//!
+//! Accounts are loaded once at startup, new accounts and password changes are
+//! written back by the [`AccountUpdater`] in a background thread.
+
+extern crate diesel;
+
+use super::{error::Error, establish_connection, models::Account, schema};
+use crate::metrics::PersistenceMetrics;
+use crossbeam::channel;
+use diesel::prelude::*;
+use tracing::error;
+
+/// Load the password hashes of all accounts, by username
+pub fn load_accounts(db_dir: &str) -> Result<Vec<(String, String)>, Error> {
+    let accounts = schema::account::table.load::<Account>(&establish_connection(db_dir)?)?;
+
+    Ok(accounts
+        .into_iter()
+        .map(|account| (account.username, account.password_hash))
+        .collect())
+}
+
+/// A unidirectional messaging resource for saving accounts in a background
+/// thread.
+pub struct AccountUpdater {
+    update_tx: Option<channel::Sender<Account>>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl AccountUpdater {
+    pub fn new(db_dir: String, metrics: PersistenceMetrics) -> Self {
+        let (update_tx, update_rx) = channel::unbounded::<Account>();
+        let handle = std::thread::spawn(move || {
+            let write_time = metrics.write_time.with_label_values(&["account"]);
+            while let Ok(account) = update_rx.recv() {
+                let timer = write_time.start_timer();
+                update(account, &db_dir);
+                timer.observe_duration();
+            }
+        });
+
+        Self {
+            update_tx: Some(update_tx),
+            handle: Some(handle),
+        }
+    }
+
+    /// An updater that saves nothing, for tests
+    #[cfg(test)]
+    pub fn disabled() -> Self {
+        Self {
+            update_tx: None,
+            handle: None,
+        }
+    }
+
+    /// Insert an account or replace its password hash
+    pub fn update(&self, username: String, password_hash: String) {
+        if let Some(update_tx) = &self.update_tx {
+            if let Err(e) = update_tx.send(Account {
+                username,
+                password_hash,
+            }) {
+                error!(?e, "Could not send account update");
+            }
+        }
+    }
+}
+
+fn update(account: Account, db_dir: &str) {
+    if let Err(e) = establish_connection(db_dir).and_then(|connection| {
+        diesel::replace_into(schema::account::table)
+            .values(&account)
+            .execute(&connection)
+    }) {
+        error!(?e, username = ?account.username, "Error during account update");
+    }
+}
+
+impl Drop for AccountUpdater {
+    fn drop(&mut self) {
+        drop(self.update_tx.take());
+        if let Some(Err(e)) = self.handle.take().map(|handle| handle.join()) {
+            error!(?e, "Error from joining account update thread");
+        }
+    }
+}
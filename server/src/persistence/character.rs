@@ -16,7 +16,7 @@ use super::{
     },
     schema,
 };
-use crate::{comp, persistence::models::SkillSetData};
+use crate::{comp, metrics::PersistenceMetrics, persistence::models::SkillSetData};
 use common::{
     character::{Character as CharacterData, CharacterItem, MAX_CHARACTERS_PER_PLAYER},
     LoadoutBuilder,
@@ -467,11 +467,14 @@ pub struct CharacterUpdater {
 }
 
 impl CharacterUpdater {
-    pub fn new(db_dir: String) -> Self {
+    pub fn new(db_dir: String, metrics: PersistenceMetrics) -> Self {
         let (update_tx, update_rx) = channel::unbounded::<Vec<(i32, CharacterUpdateData)>>();
         let handle = std::thread::spawn(move || {
+            let write_time = metrics.write_time.with_label_values(&["character"]);
             while let Ok(updates) = update_rx.recv() {
+                let timer = write_time.start_timer();
                 batch_update(updates.into_iter(), &db_dir);
+                timer.observe_duration();
             }
         });
 
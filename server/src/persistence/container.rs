@@ -0,0 +1,97 @@
+//! Database operations related to container inventories
+//!
+//! Container contents are loaded once at startup, changes are written back by
+//! the [`ContainerUpdater`] in a background thread.
+
+extern crate diesel;
+
+use super::{error::Error, establish_connection, models::Container, schema};
+use crate::{container::ContainerData, metrics::PersistenceMetrics};
+use crossbeam::channel;
+use diesel::prelude::*;
+use tracing::error;
+use vek::*;
+
+/// Load the contents of all persisted containers
+pub fn load_containers(db_dir: &str) -> Result<Vec<(Vec3<i32>, ContainerData)>, Error> {
+    let containers = schema::container::table.load::<Container>(&establish_connection(db_dir)?)?;
+
+    Ok(containers.into_iter().map(Into::into).collect())
+}
+
+/// A unidirectional messaging resource for saving container contents in a
+/// background thread.
+pub struct ContainerUpdater {
+    update_tx: Option<channel::Sender<Vec<(Vec3<i32>, Option<ContainerData>)>>>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl ContainerUpdater {
+    pub fn new(db_dir: String, metrics: PersistenceMetrics) -> Self {
+        let (update_tx, update_rx) =
+            channel::unbounded::<Vec<(Vec3<i32>, Option<ContainerData>)>>();
+        let handle = std::thread::spawn(move || {
+            let write_time = metrics.write_time.with_label_values(&["container"]);
+            while let Ok(updates) = update_rx.recv() {
+                let timer = write_time.start_timer();
+                batch_update(updates, &db_dir);
+                timer.observe_duration();
+            }
+        });
+
+        Self {
+            update_tx: Some(update_tx),
+            handle: Some(handle),
+        }
+    }
+
+    /// Insert or replace the contents of a collection of containers, deleting
+    /// those that are `None`
+    pub fn batch_update(&self, updates: Vec<(Vec3<i32>, Option<ContainerData>)>) {
+        if updates.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.update_tx.as_ref().unwrap().send(updates) {
+            error!(?e, "Could not send container updates");
+        }
+    }
+}
+
+fn batch_update(updates: Vec<(Vec3<i32>, Option<ContainerData>)>, db_dir: &str) {
+    use schema::container::dsl::*;
+
+    let mut containers = Vec::new();
+    let mut removed = Vec::new();
+    for (pos, data) in updates {
+        match data {
+            Some(data) => containers.push(Container::from((pos, data))),
+            None => removed.push(pos),
+        }
+    }
+
+    if let Err(e) = establish_connection(db_dir).and_then(|connection| {
+        connection.transaction::<_, diesel::result::Error, _>(|| {
+            for pos in removed {
+                diesel::delete(
+                    container.filter(pos_x.eq(pos.x).and(pos_y.eq(pos.y)).and(pos_z.eq(pos.z))),
+                )
+                .execute(&connection)?;
+            }
+            diesel::replace_into(container)
+                .values(&containers)
+                .execute(&connection)
+        })
+    }) {
+        error!(?e, "Error during container batch update");
+    }
+}
+
+impl Drop for ContainerUpdater {
+    fn drop(&mut self) {
+        drop(self.update_tx.take());
+        if let Err(e) = self.handle.take().unwrap().join() {
+            error!(?e, "Error from joining container update thread");
+        }
+    }
+}
@@ -6,7 +6,9 @@
 //! - [`diesel-cli`](https://github.com/diesel-rs/diesel/tree/master/diesel_cli/)
 //!   for generating and testing migrations
 
+pub mod account;
 pub mod character;
+pub mod container;
 
 mod error;
 mod models;
@@ -1,11 +1,13 @@
 extern crate serde_json;
 
-use super::schema::{body, character, inventory, loadout, stats};
-use crate::comp;
+use super::schema::{account, body, character, container, inventory, loadout, stats};
+use crate::{comp, container::ContainerData};
 use common::character::Character as CharacterData;
 use diesel::sql_types::Text;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use tracing::warn;
+use vek::Vec3;
 
 /// The required elements to build comp::Stats from database data
 pub struct StatsJoinData<'a> {
@@ -14,6 +16,15 @@ pub struct StatsJoinData<'a> {
     pub stats: &'a Stats,
 }
 
+/// `Account` is a login of the built-in account store, used when the server
+/// doesn't use an auth server. The hash is a PHC string that includes its salt.
+#[derive(Queryable, Insertable, Debug)]
+#[table_name = "account"]
+pub struct Account {
+    pub username: String,
+    pub password_hash: String,
+}
+
 /// `Character` represents a playable character belonging to a player
 #[derive(Identifiable, Queryable, Debug)]
 #[table_name = "character"]
@@ -106,6 +117,9 @@ impl From<StatsJoinData<'_>> for comp::Stats {
 
         base_stats.exp.set_current(data.stats.exp as u32);
 
+        // Skills can raise the maximum health
+        base_stats.skill_set = data.stats.skills.0.clone();
+
         base_stats.update_max_hp(base_stats.body_type);
         base_stats
             .health
@@ -114,7 +128,6 @@ impl From<StatsJoinData<'_>> for comp::Stats {
         base_stats.endurance = data.stats.endurance as u32;
         base_stats.fitness = data.stats.fitness as u32;
         base_stats.willpower = data.stats.willpower as u32;
-        base_stats.skill_set = data.stats.skills.0.clone();
         base_stats
     }
 }
@@ -144,6 +157,51 @@ impl From<&comp::Stats> for StatsUpdate {
     }
 }
 
+/// A skill set as stored in the database. Skill sets saved before the skill
+/// trees were defined in assets can hold placeholder skills that no longer
+/// exist, those are dropped and their skill point refunded instead of
+/// discarding the whole skill set.
+#[derive(Deserialize)]
+struct StoredSkillSet {
+    skill_groups: Vec<comp::SkillGroup>,
+    skills: Vec<serde_json::Value>,
+}
+
+/// The skill group a placeholder skill belonged to
+fn placeholder_skill_group(skill: &str) -> Option<comp::SkillGroupType> {
+    if skill.starts_with("TestT1Skill") {
+        Some(comp::SkillGroupType::General)
+    } else if skill.starts_with("TestSwordSkill") {
+        Some(comp::SkillGroupType::Swords)
+    } else if skill.starts_with("TestAxeSkill") {
+        Some(comp::SkillGroupType::Axes)
+    } else {
+        None
+    }
+}
+
+impl From<StoredSkillSet> for comp::SkillSet {
+    fn from(stored: StoredSkillSet) -> comp::SkillSet {
+        let mut skill_set = comp::SkillSet {
+            skill_groups: stored.skill_groups,
+            skills: HashSet::new(),
+        };
+        for skill in stored.skills {
+            match serde_json::from_value::<comp::Skill>(skill.clone()) {
+                Ok(skill) => {
+                    skill_set.skills.insert(skill);
+                },
+                Err(_) => match skill.as_str().and_then(placeholder_skill_group) {
+                    // Placeholder skills all cost one skill point
+                    Some(skill_group_type) => skill_set.add_skill_points(skill_group_type, 1),
+                    None => warn!(?skill, "Dropping unknown skill"),
+                },
+            }
+        }
+        skill_set
+    }
+}
+
 /// A wrapper type for the SkillSet of a character used to serialise to and from
 /// JSON If the column contains malformed JSON, a default skillset is returned
 #[derive(AsExpression, Debug, Deserialize, Serialize, PartialEq, FromSqlRow)]
@@ -160,8 +218,8 @@ where
     ) -> diesel::deserialize::Result<Self> {
         let t = String::from_sql(bytes)?;
 
-        match serde_json::from_str(&t) {
-            Ok(data) => Ok(Self(data)),
+        match serde_json::from_str::<StoredSkillSet>(&t) {
+            Ok(data) => Ok(Self(data.into())),
             Err(e) => {
                 warn!(?e, "Failed to deserialize skill set data");
                 Ok(Self(comp::SkillSet::default()))
@@ -212,7 +270,21 @@ where
         bytes: Option<&<DB as diesel::backend::Backend>::RawValue>,
     ) -> diesel::deserialize::Result<Self> {
         let t = String::from_sql(bytes)?;
-        serde_json::from_str(&t).map_err(Box::from)
+        Self::from_json(&t).map_err(Box::from)
+    }
+}
+
+impl InventoryData {
+    /// Items saved before durability existed get the durability set by
+    /// their item asset
+    fn from_json(json: &str) -> serde_json::Result<Self> {
+        let mut inventory: comp::Inventory = serde_json::from_str(json)?;
+        inventory
+            .slots
+            .iter_mut()
+            .filter_map(Option::as_mut)
+            .for_each(comp::Item::fill_default_durability);
+        Ok(Self(inventory))
     }
 }
 
@@ -259,6 +331,46 @@ impl From<&comp::Inventory> for InventoryUpdate {
     }
 }
 
+/// The contents of a container block (such as a chest), identified by the
+/// position of the block in the world.
+///
+/// The items are stored in the same JSON format as character inventories.
+#[derive(Queryable, Insertable, Debug)]
+#[table_name = "container"]
+pub struct Container {
+    pub pos_x: i32,
+    pub pos_y: i32,
+    pub pos_z: i32,
+    pub items: InventoryData,
+    pub placed: bool,
+}
+
+impl From<(Vec3<i32>, ContainerData)> for Container {
+    fn from(data: (Vec3<i32>, ContainerData)) -> Container {
+        let (pos, container) = data;
+
+        Container {
+            pos_x: pos.x,
+            pos_y: pos.y,
+            pos_z: pos.z,
+            items: InventoryData(container.inventory),
+            placed: container.placed,
+        }
+    }
+}
+
+impl From<Container> for (Vec3<i32>, ContainerData) {
+    fn from(container: Container) -> (Vec3<i32>, ContainerData) {
+        (
+            Vec3::new(container.pos_x, container.pos_y, container.pos_z),
+            ContainerData {
+                inventory: container.items.0,
+                placed: container.placed,
+            },
+        )
+    }
+}
+
 /// Loadout holds the armor and weapons owned by a character. This data is
 /// seperate from the inventory. At the moment, characters have a single Loadout
 /// which is loaded with their character data, however there are plans for each
@@ -291,10 +403,51 @@ where
         bytes: Option<&<DB as diesel::backend::Backend>::RawValue>,
     ) -> diesel::deserialize::Result<Self> {
         let t = String::from_sql(bytes)?;
-        serde_json::from_str(&t).map_err(Box::from)
+        Self::from_json(&t).map_err(Box::from)
     }
 }
 
+impl LoadoutData {
+    /// Items saved before durability existed get the durability set by
+    /// their item asset
+    fn from_json(json: &str) -> serde_json::Result<Self> {
+        let mut loadout: comp::Loadout = serde_json::from_str(json)?;
+        fill_default_durability(&mut loadout);
+        Ok(Self(loadout))
+    }
+}
+
+fn fill_default_durability(loadout: &mut comp::Loadout) {
+    let comp::Loadout {
+        active_item,
+        second_item,
+        lantern,
+        shoulder,
+        chest,
+        belt,
+        hand,
+        pants,
+        foot,
+        back,
+        ring,
+        neck,
+        head,
+        tabard,
+    } = loadout;
+    active_item
+        .iter_mut()
+        .chain(second_item.iter_mut())
+        .map(|item_config| &mut item_config.item)
+        .chain(
+            [
+                lantern, shoulder, chest, belt, hand, pants, foot, back, ring, neck, head, tabard,
+            ]
+            .iter_mut()
+            .filter_map(|item| item.as_mut()),
+        )
+        .for_each(comp::Item::fill_default_durability);
+}
+
 impl<DB> diesel::serialize::ToSql<Text, DB> for LoadoutData
 where
     DB: diesel::backend::Backend,
@@ -377,6 +530,72 @@ mod tests {
         })
     }
 
+    #[test]
+    fn loads_skill_sets_saved_with_placeholder_skills() {
+        let json = r#"{
+            "skill_groups": [
+                {"skill_group_type": "T1", "exp": 10, "available_sp": 1},
+                {"skill_group_type": "Swords", "exp": 0, "available_sp": 0}
+            ],
+            "skills": ["TestT1Skill1", "TestT1Skill2", "TestSwordSkill3", "HealthBoost1"]
+        }"#;
+        let skill_set = comp::SkillSet::from(serde_json::from_str::<StoredSkillSet>(json).unwrap());
+
+        assert_eq!(skill_set.skill_groups, vec![
+            comp::SkillGroup {
+                skill_group_type: comp::SkillGroupType::General,
+                exp: 10,
+                available_sp: 3,
+            },
+            comp::SkillGroup {
+                skill_group_type: comp::SkillGroupType::Swords,
+                exp: 0,
+                available_sp: 1,
+            },
+        ]);
+        assert_eq!(
+            skill_set.skills,
+            [comp::Skill::HealthBoost1].iter().copied().collect()
+        );
+    }
+
+    #[test]
+    fn loads_items_saved_without_durability() {
+        let mut inventory = comp::Inventory {
+            slots: vec![None; 2],
+            amount: 0,
+        };
+        let mut sword: comp::Item =
+            common::assets::load_expect_cloned("common.items.weapons.sword.starter_sword");
+        let max_durability = sword.durability().unwrap().max();
+        sword.wear(10);
+        inventory.push(sword.clone());
+        inventory.push(common::assets::load_expect_cloned(
+            "common.items.armor.ring.ring_0",
+        ));
+
+        // Items saved before durability was added don't have the field, and
+        // get the durability their asset sets, if any
+        let mut json = serde_json::to_value(&inventory).unwrap();
+        for slot in 0..2 {
+            json["slots"][slot]
+                .as_object_mut()
+                .unwrap()
+                .remove("durability");
+        }
+        let legacy = InventoryData::from_json(&json.to_string()).unwrap().0;
+        let legacy_sword = legacy.get(0).unwrap();
+        assert_eq!(legacy_sword.durability().unwrap().current(), max_durability);
+        assert!(!legacy_sword.is_worn());
+        assert_eq!(legacy.get(1).unwrap().durability(), None);
+
+        // Wear is kept when saving and loading again
+        let saved = serde_json::to_string(&inventory).unwrap();
+        let loaded: comp::Inventory = serde_json::from_str(&saved).unwrap();
+        assert_eq!(loaded.get(0), Some(&sword));
+        assert_eq!(loaded.get(0).unwrap().durability().unwrap().current(), 390);
+    }
+
     #[test]
     fn loads_stats_with_correct_level() {
         let data = StatsJoinData {
@@ -409,4 +628,46 @@ mod tests {
         assert_eq!(stats.level.level(), 3);
         assert_eq!(stats.exp.current(), 70);
     }
+
+    #[test]
+    fn loads_stats_with_skill_health_bonus() {
+        let body = comp::Body::from(&Body {
+            character_id: 0,
+            species: 0,
+            body_type: comp::humanoid::BodyType::Female as i16,
+            hair_style: 0,
+            beard: 0,
+            eyes: 0,
+            accessory: 0,
+            hair_color: 0,
+            skin: 0,
+            eye_color: 0,
+        });
+        let mut skill_set = comp::SkillSet::new();
+        skill_set.unlock_skill_group(comp::SkillGroupType::General);
+        skill_set.add_skill_points(comp::SkillGroupType::General, 1);
+        skill_set.unlock_skill(comp::Skill::HealthBoost1).unwrap();
+        let bonus = skill_set.max_health_bonus();
+        assert!(bonus > 0);
+
+        let stats = comp::Stats::from(StatsJoinData {
+            alias: "test",
+            body: &body,
+            stats: &Stats {
+                character_id: 0,
+                level: 1,
+                exp: 0,
+                endurance: 0,
+                fitness: 0,
+                willpower: 0,
+                skills: SkillSetData(skill_set),
+            },
+        });
+
+        let mut unskilled = comp::Stats::new("test".to_owned(), body);
+        unskilled.level.set_level(1);
+        unskilled.update_max_hp(body);
+        assert_eq!(stats.health.maximum(), unskilled.health.maximum() + bonus);
+        assert_eq!(stats.health.current(), stats.health.maximum());
+    }
 }
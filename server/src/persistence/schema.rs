@@ -1,3 +1,10 @@
+table! {
+    account (username) {
+        username -> Text,
+        password_hash -> Text,
+    }
+}
+
 table! {
     body (character_id) {
         character_id -> Integer,
@@ -22,6 +29,16 @@ table! {
     }
 }
 
+table! {
+    container (pos_x, pos_y, pos_z) {
+        pos_x -> Integer,
+        pos_y -> Integer,
+        pos_z -> Integer,
+        items -> Text,
+        placed -> Bool,
+    }
+}
+
 table! {
     inventory (character_id) {
         character_id -> Integer,
@@ -54,4 +71,6 @@ joinable!(inventory -> character (character_id));
 joinable!(loadout -> character (character_id));
 joinable!(stats -> character (character_id));
 
-allow_tables_to_appear_in_same_query!(body, character, inventory, loadout, stats);
+allow_tables_to_appear_in_same_query!(
+    account, body, character, container, inventory, loadout, stats
+);
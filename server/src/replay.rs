@@ -0,0 +1,107 @@
+//! Recording what is sent to clients, to reproduce what players report
+//!
+//! Recording starts as soon as a client connects, so that the replay includes
+//! the initial sync it takes to rebuild the client's state. Until the player
+//! logs in it's unknown whether they are to be recorded, so the frames are kept
+//! in memory until then. Recordings are stopped once they reach the maximum
+//! size set in the server settings.
+
+use common::{
+    msg::ServerMsg,
+    replay::{ReplayError, ReplayFilter, ReplayWriter},
+};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// How often recordings are flushed to their file, so that little is lost if
+/// the server crashes
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Where the session of `username` starting now is recorded to in `dir`
+pub fn replay_path(dir: &Path, username: &str) -> PathBuf {
+    let since_the_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let username = username
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect::<String>();
+    dir.join(format!("{}-{}.vrep", since_the_epoch.as_secs(), username))
+}
+
+enum Output {
+    /// Waiting for the player to log in
+    Pending(ReplayWriter<Vec<u8>>),
+    File(ReplayWriter<BufWriter<File>>),
+}
+
+pub struct Recorder {
+    filter: ReplayFilter,
+    started: Instant,
+    output: Output,
+    /// Bytes of frames recorded so far
+    size: u64,
+    max_size: u64,
+    last_flush: Instant,
+}
+
+impl Recorder {
+    /// Start a recording that is stopped once its frames take up `max_size`
+    /// bytes
+    pub fn new(filter: ReplayFilter, max_size: u64) -> Self {
+        Self {
+            filter,
+            started: Instant::now(),
+            output: Output::Pending(
+                ReplayWriter::new(Vec::new()).expect("Writing to a Vec can't fail"),
+            ),
+            size: 0,
+            max_size,
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// Record `msg` if it passes the filter. Fails once the recording reached
+    /// its maximum size, after which it should be dropped.
+    pub fn record(&mut self, msg: &ServerMsg) -> Result<(), ReplayError> {
+        if !self.filter.records(msg) {
+            return Ok(());
+        }
+        if self.size >= self.max_size {
+            return Err(ReplayError::TooLarge);
+        }
+        let time = self.started.elapsed().as_secs_f64();
+        let size = match &mut self.output {
+            Output::Pending(writer) => writer.record(time, msg)?,
+            Output::File(writer) => {
+                let size = writer.record(time, msg)?;
+                if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+                    self.last_flush = Instant::now();
+                    writer.flush()?;
+                }
+                size
+            },
+        };
+        self.size += size as u64;
+        Ok(())
+    }
+
+    /// Write what has been recorded so far to the file at `path`, and the
+    /// rest of the recording after it
+    pub fn save_to(&mut self, path: &Path) -> Result<(), ReplayError> {
+        if let Output::Pending(writer) = &mut self.output {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let mut file = BufWriter::new(File::create(path)?);
+            let recorded = std::mem::replace(writer, ReplayWriter::resume(Vec::new()));
+            file.write_all(&recorded.into_inner())?;
+            self.output = Output::File(ReplayWriter::resume(file));
+        }
+        Ok(())
+    }
+}
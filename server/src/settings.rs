@@ -1,8 +1,9 @@
+use common::{comp::Player, replay::ReplayFilter};
 use portpicker::pick_unused_port;
 use serde::{Deserialize, Serialize};
 use std::{fs, io::prelude::*, net::SocketAddr, path::PathBuf};
 use tracing::{error, warn};
-use world::sim::FileOpts;
+use world::sim::{FileOpts, GenOpts};
 
 const DEFAULT_WORLD_SEED: u32 = 59686;
 
@@ -11,7 +12,19 @@ const DEFAULT_WORLD_SEED: u32 = 59686;
 pub struct ServerSettings {
     pub gameserver_address: SocketAddr,
     pub metrics_address: SocketAddr,
+    /// Where the server status is served to server browsers and monitoring,
+    /// not served at all when set to None, as it is by default
+    pub status_address: Option<SocketAddr>,
     pub auth_server_address: Option<String>,
+    /// Whether players log in with a password of an account stored by the
+    /// server itself. Only used when there is no auth server, without one
+    /// anyone can log in with any username otherwise. Passwords are sent to
+    /// the server in plain text, so only enable this where the connection to
+    /// players is trusted or encrypted, e.g. on a LAN or over a VPN.
+    pub local_accounts: bool,
+    /// Whether logging in with an unused username registers an account for
+    /// it. Otherwise accounts are only created by admins.
+    pub account_registration: bool,
     pub max_players: usize,
     pub world_seed: u32,
     //pub pvp_enabled: bool,
@@ -20,13 +33,28 @@ pub struct ServerSettings {
     pub start_time: f64,
     pub admins: Vec<String>,
     pub whitelist: Vec<String>,
-    /// When set to None, loads the default map file (if available); otherwise,
-    /// uses the value of the file options to decide how to proceed.
+    /// When set to None, loads the default map file (if available) unless
+    /// `world_gen` was changed from its defaults; otherwise, uses the value of
+    /// the file options to decide how to proceed.
     pub map_file: Option<FileOpts>,
+    /// Parameters used when generating a new world (world size, sea level,
+    /// ...). Ignored when the world is loaded from a map file, which records
+    /// the parameters it was generated with.
+    pub world_gen: GenOpts,
     pub persistence_db_dir: String,
     pub max_view_distance: Option<u32>,
     pub banned_words_files: Vec<PathBuf>,
     pub max_player_group_size: u32,
+    /// Directory the sessions of players are recorded to as replays, nothing
+    /// is recorded when set to None
+    pub replay_dir: Option<PathBuf>,
+    /// Players whose sessions are recorded, everyone's when empty
+    pub replay_players: Vec<String>,
+    pub replay_filter: ReplayFilter,
+    /// Size in megabytes at which the recording of a session is stopped
+    pub replay_max_size_mb: u64,
+    /// Players besides admins who may spectate, e.g. accounts streaming events
+    pub spectators: Vec<String>,
 }
 
 impl Default for ServerSettings {
@@ -34,13 +62,17 @@ impl Default for ServerSettings {
         Self {
             gameserver_address: SocketAddr::from(([0; 4], 14004)),
             metrics_address: SocketAddr::from(([0; 4], 14005)),
+            status_address: None,
             auth_server_address: Some("https://auth.veloren.net".into()),
+            local_accounts: false,
+            account_registration: true,
             world_seed: DEFAULT_WORLD_SEED,
             server_name: "Veloren Alpha".to_owned(),
             server_description: "This is the best Veloren server.".to_owned(),
             max_players: 100,
             start_time: 9.0 * 3600.0,
             map_file: None,
+            world_gen: GenOpts::default(),
             admins: [
                 "Pfau",
                 "zesterer",
@@ -67,6 +99,11 @@ impl Default for ServerSettings {
             max_view_distance: Some(30),
             banned_words_files: Vec::new(),
             max_player_group_size: 6,
+            replay_dir: None,
+            replay_players: Vec::new(),
+            replay_filter: ReplayFilter::default(),
+            replay_max_size_mb: 256,
+            spectators: Vec::new(),
         }
     }
 }
@@ -118,9 +155,11 @@ impl ServerSettings {
                 [127, 0, 0, 1],
                 pick_unused_port().expect("Failed to find unused port!"),
             )),
+            status_address: None,
             auth_server_address: None,
+            local_accounts: false,
             // If loading the default map file, make sure the seed is also default.
-            world_seed: if load.map_file.is_some() {
+            world_seed: if load.map_file.is_some() || load.world_gen != GenOpts::default() {
                 load.world_seed
             } else {
                 DEFAULT_WORLD_SEED
@@ -139,6 +178,12 @@ impl ServerSettings {
 
     fn get_settings_path() -> PathBuf { PathBuf::from(r"server_settings.ron") }
 
+    /// Whether a player may spectate. Besides admins, only the players named
+    /// in `spectators` may.
+    pub fn may_spectate(&self, player: Option<&Player>, is_admin: bool) -> bool {
+        is_admin || player.map_or(false, |player| self.spectators.contains(&player.alias))
+    }
+
     pub fn edit<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
         let r = f(self);
         self.save_to_file()
@@ -146,3 +191,25 @@ impl ServerSettings {
         r
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use authc::Uuid;
+
+    #[test]
+    fn test_may_spectate() {
+        let settings = ServerSettings {
+            spectators: vec![String::from("streamer")],
+            ..ServerSettings::default()
+        };
+        let streamer = Player::new(String::from("streamer"), None, None, Uuid::nil());
+        let player = Player::new(String::from("player"), None, None, Uuid::nil());
+
+        assert!(settings.may_spectate(Some(&streamer), false));
+        assert!(settings.may_spectate(Some(&player), true));
+        assert!(settings.may_spectate(None, true));
+        assert!(!settings.may_spectate(Some(&player), false));
+        assert!(!settings.may_spectate(None, false));
+    }
+}
@@ -0,0 +1,177 @@
+//! Orderly shutdowns and restarts
+//!
+//! A shutdown is announced to players with a countdown, during which nobody
+//! new can log in. Once it runs out the frontend is told to stop the server,
+//! which tells the clients and saves what is persisted when it is dropped.
+
+use std::time::{Duration, Instant};
+
+/// Remaining seconds at which the countdown is announced
+const ANNOUNCE_AT: [u64; 12] = [600, 300, 120, 60, 30, 20, 10, 5, 4, 3, 2, 1];
+/// Longest countdown, longer ones are cut down to it
+const MAX_DELAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShutdownKind {
+    Shutdown,
+    Restart,
+}
+
+impl ShutdownKind {
+    /// Status code for the server process to exit with, so that whatever runs
+    /// it can tell whether to start it again
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ShutdownKind::Shutdown => 0,
+            ShutdownKind::Restart => 3,
+        }
+    }
+
+    fn verb(self) -> &'static str {
+        match self {
+            ShutdownKind::Shutdown => "shut down",
+            ShutdownKind::Restart => "restart",
+        }
+    }
+}
+
+struct Countdown {
+    kind: ShutdownKind,
+    deadline: Instant,
+    /// Index into `ANNOUNCE_AT` of the next announcement
+    next_announcement: usize,
+}
+
+/// The shutdown that is counting down, if any
+#[derive(Default)]
+pub struct Shutdown(Option<Countdown>);
+
+impl Shutdown {
+    /// Start counting down to a shutdown, replacing the one that is pending.
+    /// Returns the announcement for it.
+    pub fn start(&mut self, kind: ShutdownKind, delay: Duration) -> String {
+        let delay = delay.min(MAX_DELAY);
+        let secs = delay.as_secs();
+        self.0 = Some(Countdown {
+            kind,
+            deadline: Instant::now() + delay,
+            next_announcement: ANNOUNCE_AT.iter().take_while(|at| **at >= secs).count(),
+        });
+        announcement(kind, secs)
+    }
+
+    /// Stop the pending shutdown, returning whether there was one
+    pub fn abort(&mut self) -> bool { self.0.take().is_some() }
+
+    pub fn pending(&self) -> Option<ShutdownKind> {
+        self.0.as_ref().map(|countdown| countdown.kind)
+    }
+
+    /// The announcement to broadcast this tick, if one is due
+    pub fn next_announcement(&mut self) -> Option<String> {
+        let countdown = self.0.as_mut()?;
+        let remaining = countdown
+            .deadline
+            .saturating_duration_since(Instant::now())
+            .as_secs_f64();
+        // Only the last of several announcements that are due at once is made
+        let due = ANNOUNCE_AT[countdown.next_announcement..]
+            .iter()
+            .take_while(|at| **at as f64 >= remaining)
+            .count();
+        if due == 0 {
+            return None;
+        }
+        countdown.next_announcement += due;
+        Some(announcement(
+            countdown.kind,
+            ANNOUNCE_AT[countdown.next_announcement - 1],
+        ))
+    }
+
+    /// The pending shutdown, once its countdown has run out
+    pub fn due(&self) -> Option<ShutdownKind> {
+        self.0
+            .as_ref()
+            .filter(|countdown| Instant::now() >= countdown.deadline)
+            .map(|countdown| countdown.kind)
+    }
+}
+
+fn announcement(kind: ShutdownKind, secs: u64) -> String {
+    if secs == 0 {
+        return format!("The server will {} now.", kind.verb());
+    }
+    let (amount, unit) = if secs >= 120 {
+        (secs / 60, "minute")
+    } else {
+        (secs, "second")
+    };
+    format!(
+        "The server will {} in {} {}{}.",
+        kind.verb(),
+        amount,
+        unit,
+        if amount == 1 { "" } else { "s" }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn announces_remaining_time() {
+        assert_eq!(
+            announcement(ShutdownKind::Restart, 300),
+            "The server will restart in 5 minutes."
+        );
+        assert_eq!(
+            announcement(ShutdownKind::Shutdown, 90),
+            "The server will shut down in 90 seconds."
+        );
+        assert_eq!(
+            announcement(ShutdownKind::Shutdown, 1),
+            "The server will shut down in 1 second."
+        );
+        assert_eq!(
+            announcement(ShutdownKind::Shutdown, 0),
+            "The server will shut down now."
+        );
+    }
+
+    #[test]
+    fn counts_down() {
+        let mut shutdown = Shutdown::default();
+        assert_eq!(shutdown.pending(), None);
+
+        shutdown.start(ShutdownKind::Restart, Duration::from_secs(60));
+        assert_eq!(shutdown.pending(), Some(ShutdownKind::Restart));
+        assert_eq!(shutdown.due(), None);
+        // The start was announced already
+        assert_eq!(shutdown.next_announcement(), None);
+
+        // The announcements that were skipped collapse into the last one
+        shutdown.0.as_mut().unwrap().deadline = Instant::now();
+        assert_eq!(shutdown.due(), Some(ShutdownKind::Restart));
+        assert_eq!(
+            shutdown.next_announcement(),
+            Some("The server will restart in 1 second.".to_owned())
+        );
+        assert_eq!(shutdown.next_announcement(), None);
+
+        assert!(shutdown.abort());
+        assert_eq!(shutdown.pending(), None);
+        assert!(!shutdown.abort());
+    }
+
+    #[test]
+    fn limits_delay() {
+        let mut shutdown = Shutdown::default();
+        assert_eq!(
+            shutdown.start(ShutdownKind::Shutdown, Duration::from_secs(u64::MAX)),
+            "The server will shut down in 1440 minutes."
+        );
+        assert_eq!(shutdown.due(), None);
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
-    client::Client, persistence::character::PersistedComponents, settings::ServerSettings,
-    sys::sentinel::DeletedEntities, SpawnPoint,
+    client::{Client, SpectatorCamera},
+    persistence::character::PersistedComponents,
+    settings::ServerSettings,
+    sys::sentinel::DeletedEntities,
+    SpawnPoint,
 };
 use common::{
     comp,
@@ -200,6 +203,9 @@ impl StateExt for State {
             self.write_component(entity, comp::Admin);
         }
 
+        // A spectator that picked a character sees the world through it again
+        self.ecs().write_storage::<SpectatorCamera>().remove(entity);
+
         // Tell the client its request was successful.
         if let Some(client) = self.ecs().write_storage::<Client>().get_mut(entity) {
             client.allow_state(ClientState::Character);
@@ -0,0 +1,191 @@
+//! Answering status queries from server browsers and monitoring
+//!
+//! The status is served as JSON over HTTP on a port of its own, so that it can
+//! be fetched with a single request instead of logging in with a game client.
+
+use common::msg::ServerStatus;
+use hashbrown::HashMap;
+use std::{
+    error::Error,
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::{debug, error};
+
+/// Time an address has to wait between status queries
+const QUERY_INTERVAL: Duration = Duration::from_secs(1);
+/// Number of addresses remembered at most. Those that may query again are
+/// forgotten first, then those that queried the longest time ago.
+const MAX_TRACKED_ADDRS: usize = 1024;
+
+/// Turns away addresses that query the status too often
+#[derive(Default)]
+pub struct RateLimiter {
+    last_query: HashMap<IpAddr, Instant>,
+}
+
+impl RateLimiter {
+    /// Whether a query from `addr` at `now` is answered
+    pub fn allow(&mut self, addr: IpAddr, now: Instant) -> bool {
+        match self.last_query.get(&addr) {
+            Some(last) if now.duration_since(*last) < QUERY_INTERVAL => false,
+            Some(_) => {
+                self.last_query.insert(addr, now);
+                true
+            },
+            None => {
+                if self.last_query.len() >= MAX_TRACKED_ADDRS {
+                    self.last_query
+                        .retain(|_, last| now.duration_since(*last) < QUERY_INTERVAL);
+                }
+                if self.last_query.len() >= MAX_TRACKED_ADDRS {
+                    if let Some(oldest) = self
+                        .last_query
+                        .iter()
+                        .min_by_key(|(_, last)| **last)
+                        .map(|(addr, _)| *addr)
+                    {
+                        self.last_query.remove(&oldest);
+                    }
+                }
+                self.last_query.insert(addr, now);
+                true
+            },
+        }
+    }
+}
+
+pub struct StatusServer {
+    running: Arc<AtomicBool>,
+    /// The status as it is sent, serialized once whenever it's updated
+    status: Arc<Mutex<String>>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl StatusServer {
+    /// Start answering status queries on `addr`
+    pub fn run(
+        addr: SocketAddr,
+        status: &ServerStatus,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let server = tiny_http::Server::http(addr)?;
+        let running = Arc::new(AtomicBool::new(true));
+        let status = Arc::new(Mutex::new(serde_json::to_string(status)?));
+
+        let handle = {
+            let (running, status) = (Arc::clone(&running), Arc::clone(&status));
+            thread::spawn(move || {
+                const TIMEOUT: Duration = Duration::from_secs(1);
+                let content_type =
+                    tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+                        .expect("Content type header is valid");
+                let mut rate_limiter = RateLimiter::default();
+                debug!("starting tiny_http server to serve the server status");
+                while running.load(Ordering::Relaxed) {
+                    let request = match server.recv_timeout(TIMEOUT) {
+                        Ok(Some(rq)) => rq,
+                        Ok(None) => continue,
+                        Err(e) => {
+                            error!(?e, "status http server error");
+                            break;
+                        },
+                    };
+                    let result = if rate_limiter.allow(request.remote_addr().ip(), Instant::now()) {
+                        let status = status.lock().unwrap().clone();
+                        request.respond(
+                            tiny_http::Response::from_string(status)
+                                .with_header(content_type.clone()),
+                        )
+                    } else {
+                        request.respond(
+                            tiny_http::Response::from_string("Too many requests")
+                                .with_status_code(429),
+                        )
+                    };
+                    if let Err(e) = result {
+                        debug!(?e, "Failed to answer a status query");
+                    }
+                }
+                debug!("stopping tiny_http server to serve the server status");
+            })
+        };
+
+        Ok(Self {
+            running,
+            status,
+            handle: Some(handle),
+        })
+    }
+
+    /// Set the status sent to queries from now on
+    pub fn update(&self, status: &ServerStatus) {
+        match serde_json::to_string(status) {
+            Ok(status) => *self.status.lock().unwrap() = status,
+            Err(e) => error!(?e, "Failed to serialize the server status"),
+        }
+    }
+}
+
+impl Drop for StatusServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                error!("Error shutting down the status server");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_rate_limit() {
+        let mut limiter = RateLimiter::default();
+        let a = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let b = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let now = Instant::now();
+
+        assert!(limiter.allow(a, now));
+        assert!(!limiter.allow(a, now + QUERY_INTERVAL / 2));
+        assert!(limiter.allow(b, now + QUERY_INTERVAL / 2));
+        assert!(limiter.allow(a, now + QUERY_INTERVAL));
+    }
+
+    #[test]
+    fn test_forget_addrs() {
+        let mut limiter = RateLimiter::default();
+        let now = Instant::now();
+        for i in 0..MAX_TRACKED_ADDRS as u32 {
+            assert!(limiter.allow(IpAddr::V4(i.into()), now));
+        }
+
+        // Addresses that may query again are forgotten to make room
+        let later = now + QUERY_INTERVAL;
+        assert!(limiter.allow(IpAddr::V4(Ipv4Addr::LOCALHOST), later));
+        assert_eq!(limiter.last_query.len(), 1);
+    }
+
+    #[test]
+    fn test_track_bounded_addrs() {
+        let mut limiter = RateLimiter::default();
+        let now = Instant::now();
+        for i in 0..MAX_TRACKED_ADDRS as u32 {
+            assert!(limiter.allow(IpAddr::V4(i.into()), now));
+        }
+
+        // Without any address to forget, the one that queried first makes room
+        let soon = now + QUERY_INTERVAL / 2;
+        assert!(limiter.allow(IpAddr::V4(Ipv4Addr::LOCALHOST), soon));
+        assert_eq!(limiter.last_query.len(), MAX_TRACKED_ADDRS);
+        assert!(!limiter.allow(IpAddr::V4(Ipv4Addr::LOCALHOST), soon));
+    }
+}
@@ -0,0 +1,52 @@
+use common::{
+    comp::InventoryManip,
+    event::{EventBus, ServerEvent},
+};
+use specs::{Component, Entities, HashMapStorage, Join, Read, ReadStorage, System};
+use std::time::{Duration, Instant};
+
+/// A recipe with a craft time that is being crafted by an entity
+pub struct PendingCraft {
+    pub recipe: String,
+    pub finish_at: Instant,
+}
+
+impl PendingCraft {
+    pub fn new(recipe: String, craft_time: Duration) -> Self {
+        Self {
+            recipe,
+            finish_at: Instant::now() + craft_time,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool { self.finish_at <= Instant::now() }
+}
+
+impl Component for PendingCraft {
+    type Storage = HashMapStorage<Self>;
+}
+
+/// This system finishes crafts once their craft time has elapsed
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, EventBus<ServerEvent>>,
+        ReadStorage<'a, PendingCraft>,
+    );
+
+    fn run(&mut self, (entities, server_bus, pending_crafts): Self::SystemData) {
+        let mut server_emitter = server_bus.emitter();
+
+        // The craft is performed (and the pending craft removed) by the inventory
+        // event handler, which checks the requirements again
+        for (entity, pending) in (&entities, &pending_crafts).join() {
+            if pending.is_finished() {
+                server_emitter.emit(ServerEvent::InventoryManip(
+                    entity,
+                    InventoryManip::CraftRecipe(pending.recipe.clone()),
+                ));
+            }
+        }
+    }
+}
@@ -3,24 +3,58 @@ use super::{
     SysTimer,
 };
 use crate::{
-    client::{Client, RegionSubscription},
+    client::{self, Client, RegionSubscription, SpectatorCamera},
     Tick,
 };
 use common::{
     comp::{ForceUpdate, Inventory, InventoryUpdate, Last, Ori, Player, Pos, Vel},
-    msg::ServerMsg,
+    msg::{
+        delta::{quantize_ori, quantize_pos, quantize_vel},
+        DeltaSyncPackage, EcsCompPacket, ServerMsg,
+    },
     outcome::Outcome,
     region::{Event as RegionEvent, RegionMap},
     state::TimeOfDay,
-    sync::{CompSyncPackage, Uid},
+    sync::{CompSyncPackage, CompUpdateKind, Uid},
     terrain::TerrainChunkSize,
     vol::RectVolSize,
 };
-use specs::{
-    Entities, Entity as EcsEntity, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage,
-};
+use hashbrown::HashMap;
+use serde::Serialize;
+use specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage};
 use vek::*;
 
+/// Bytes of component updates sent to clients by component, since the metrics
+/// last took them
+#[derive(Default)]
+pub struct SyncBandwidth {
+    bytes: HashMap<&'static str, u64>,
+}
+
+impl SyncBandwidth {
+    fn add(&mut self, comp: &'static str, bytes: u64) {
+        *self.bytes.entry(comp).or_default() += bytes;
+    }
+
+    /// Add the serialized size of `update`, sent to `clients` clients
+    fn add_sent<T: Serialize>(&mut self, comp: &'static str, update: &T, clients: usize) {
+        let size = bincode::serialized_size(update).unwrap_or(0);
+        self.add(comp, size * clients as u64);
+    }
+
+    fn add_comp_sync(&mut self, package: &CompSyncPackage<EcsCompPacket>, clients: usize) {
+        for (_, update) in &package.comp_updates {
+            let comp = match update {
+                CompUpdateKind::Inserted(comp) | CompUpdateKind::Modified(comp) => comp.comp_name(),
+                CompUpdateKind::Removed(phantom) => phantom.comp_name(),
+            };
+            self.add_sent(comp, update, clients);
+        }
+    }
+
+    pub fn take(&mut self) -> impl Iterator<Item = (&'static str, u64)> + '_ { self.bytes.drain() }
+}
+
 /// This system will send physics updates to the client
 pub struct Sys;
 impl<'a> System<'a> for Sys {
@@ -37,6 +71,7 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Ori>,
         ReadStorage<'a, Inventory>,
         ReadStorage<'a, RegionSubscription>,
+        ReadStorage<'a, SpectatorCamera>,
         ReadStorage<'a, Player>,
         WriteStorage<'a, Last<Pos>>,
         WriteStorage<'a, Last<Vel>>,
@@ -46,6 +81,7 @@ impl<'a> System<'a> for Sys {
         WriteStorage<'a, InventoryUpdate>,
         Write<'a, DeletedEntities>,
         Write<'a, Vec<Outcome>>,
+        Write<'a, SyncBandwidth>,
         TrackedComps<'a>,
         ReadTrackers<'a>,
     );
@@ -64,6 +100,7 @@ impl<'a> System<'a> for Sys {
             orientations,
             inventories,
             subscriptions,
+            cameras,
             players,
             mut last_pos,
             mut last_vel,
@@ -73,6 +110,7 @@ impl<'a> System<'a> for Sys {
             mut inventory_updates,
             mut deleted_entities,
             mut outcomes,
+            mut bandwidth,
             tracked_comps,
             trackers,
         ): Self::SystemData,
@@ -102,11 +140,23 @@ impl<'a> System<'a> for Sys {
         for (key, region) in region_map.iter() {
             // Assemble subscriber list for this region by iterating through clients and
             // checking if they are subscribed to this region
-            let mut subscribers = (&mut clients, &entities, &subscriptions, &positions)
+            let mut subscribers = (
+                &mut clients,
+                &entities,
+                &subscriptions,
+                positions.maybe(),
+                cameras.maybe(),
+            )
                 .join()
-                .filter_map(|(client, entity, subscription, pos)| {
+                .filter_map(|(client, entity, subscription, pos, camera)| {
                     if client.is_ingame() && subscription.regions.contains(&key) {
-                        Some((client, &subscription.regions, entity, *pos))
+                        Some((
+                            client,
+                            &subscription.regions,
+                            entity,
+                            Pos(client::viewpoint(pos, camera)?),
+                            DeltaSyncPackage::default(),
+                        ))
                     } else {
                         None
                     }
@@ -126,14 +176,14 @@ impl<'a> System<'a> for Sys {
                                 (uid, pos, velocities.get(entity), orientations.get(entity))
                             })
                         }) {
-                            let create_msg =
-                                ServerMsg::CreateEntity(tracked_comps.create_entity_package(
-                                    entity,
-                                    Some(*pos),
-                                    vel.copied(),
-                                    ori.copied(),
-                                ));
-                            for (client, regions, client_entity, _) in &mut subscribers {
+                            let entity_package = tracked_comps.create_entity_package(
+                                entity,
+                                Some(*pos),
+                                vel.copied(),
+                                ori.copied(),
+                            );
+                            let mut recipients = 0;
+                            for (client, regions, client_entity, _, _) in &mut subscribers {
                                 if maybe_key
                                     .as_ref()
                                     .map(|key| !regions.contains(key))
@@ -141,15 +191,19 @@ impl<'a> System<'a> for Sys {
                                     // Client doesn't need to know about itself
                                     && *client_entity != entity
                                 {
-                                    client.notify(create_msg.clone());
+                                    client.notify(ServerMsg::CreateEntity(entity_package.clone()));
+                                    recipients += 1;
                                 }
                             }
+                            for comp in &entity_package.comps {
+                                bandwidth.add_sent(comp.comp_name(), comp, recipients);
+                            }
                         }
                     },
                     RegionEvent::Left(id, maybe_key) => {
                         // Lookup UID for entity
                         if let Some(&uid) = uids.get(entities.entity(*id)) {
-                            for (client, regions, _, _) in &mut subscribers {
+                            for (client, regions, _, _, _) in &mut subscribers {
                                 if maybe_key
                                     .as_ref()
                                     .map(|key| !regions.contains(key))
@@ -172,48 +226,20 @@ impl<'a> System<'a> for Sys {
                     .take_deleted_in_region(key)
                     .unwrap_or_default(),
             );
+            bandwidth.add_comp_sync(&comp_sync_package, subscribers.len());
             let entity_sync_msg = ServerMsg::EntitySync(entity_sync_package);
             let comp_sync_msg = ServerMsg::CompSync(comp_sync_package);
-            subscribers.iter_mut().for_each(move |(client, _, _, _)| {
+            // Large components are sent as deltas against what each client last received
+            let delta_comps = trackers.delta_comp_modifications(&tracked_comps, region.entities());
+            for (client, _, _, _, delta_sync_package) in &mut subscribers {
                 client.notify(entity_sync_msg.clone());
                 client.notify(comp_sync_msg.clone());
-            });
-
-            let mut send_msg = |msg: ServerMsg,
-                                entity: EcsEntity,
-                                pos: Pos,
-                                force_update: Option<&ForceUpdate>,
-                                throttle: bool| {
-                for (client, _, client_entity, client_pos) in &mut subscribers {
-                    if if client_entity == &entity {
-                        // Don't send client physics updates about itself unless force update is set
-                        force_update.is_some()
-                    } else if !throttle {
-                        // Send the message if not throttling
-                        true
-                    } else {
-                        // Throttle update rate based on distance to client
-                        let distance_sq = client_pos.0.distance_squared(pos.0);
-                        let id_staggered_tick = tick + entity.id() as u64;
-                        // More entities farther away so checks start there
-                        if distance_sq > 300.0f32.powi(2) {
-                            id_staggered_tick % 32 == 0
-                        } else if distance_sq > 250.0f32.powi(2) {
-                            id_staggered_tick % 16 == 0
-                        } else if distance_sq > 200.0f32.powi(2) {
-                            id_staggered_tick % 8 == 0
-                        } else if distance_sq > 150.0f32.powi(2) {
-                            id_staggered_tick % 4 == 0
-                        } else if distance_sq > 100.0f32.powi(2) {
-                            id_staggered_tick % 2 == 0
-                        } else {
-                            true // Closer than 100 blocks
-                        }
-                    } {
-                        client.notify(msg.clone());
+                for (uid, kind, bytes) in &delta_comps {
+                    if let Some(delta) = client.sync_baselines.encode_comp(*uid, *kind, bytes) {
+                        delta_sync_package.comps.push((*uid, *kind, delta));
                     }
                 }
-            };
+            }
 
             // Sync physics components
             for (_, entity, &uid, &pos, maybe_vel, maybe_ori, force_update) in (
@@ -227,20 +253,17 @@ impl<'a> System<'a> for Sys {
             )
                 .join()
             {
+                // Removals are rare, so they're sent as they are
                 let mut comp_sync_package = CompSyncPackage::new();
                 let mut throttle = true;
-                // TODO: An entity that stoppped moving on a tick that it wasn't sent to the
-                // player will never have it's position updated
                 match last_pos.get(entity).map(|&l| l.0 != pos) {
                     Some(false) => {},
                     Some(true) => {
                         let _ = last_pos.insert(entity, Last(pos));
-                        comp_sync_package.comp_modified(uid, pos);
                     },
                     None => {
                         let _ = last_pos.insert(entity, Last(pos));
                         throttle = false;
-                        comp_sync_package.comp_inserted(uid, pos);
                     },
                 }
 
@@ -249,12 +272,10 @@ impl<'a> System<'a> for Sys {
                         Some(false) => {},
                         Some(true) => {
                             let _ = last_vel.insert(entity, Last(vel));
-                            comp_sync_package.comp_modified(uid, vel);
                         },
                         None => {
                             let _ = last_vel.insert(entity, Last(vel));
                             throttle = false;
-                            comp_sync_package.comp_inserted(uid, vel);
                         },
                     }
                 } else if last_vel.remove(entity).is_some() {
@@ -270,12 +291,10 @@ impl<'a> System<'a> for Sys {
                         Some(false) => {},
                         Some(true) => {
                             let _ = last_ori.insert(entity, Last(ori));
-                            comp_sync_package.comp_modified(uid, ori);
                         },
                         None => {
                             let _ = last_ori.insert(entity, Last(ori));
                             throttle = false;
-                            comp_sync_package.comp_inserted(uid, ori);
                         },
                     }
                 } else if last_ori.remove(entity).is_some() {
@@ -284,13 +303,68 @@ impl<'a> System<'a> for Sys {
                     comp_sync_package.comp_removed::<Ori>(uid);
                 }
 
-                send_msg(
-                    ServerMsg::CompSync(comp_sync_package),
-                    entity,
-                    pos,
-                    force_update,
-                    throttle,
-                );
+                let quantized_pos = quantize_pos(pos);
+                let quantized_vel = maybe_vel.copied().map(quantize_vel);
+                let quantized_ori = maybe_ori.copied().map(quantize_ori);
+                for (client, _, client_entity, client_pos, delta_sync_package) in &mut subscribers {
+                    if if client_entity == &entity {
+                        // Don't send client physics updates about itself unless force update is set
+                        force_update.is_some()
+                    } else if !throttle {
+                        // Send the message if not throttling
+                        true
+                    } else {
+                        // Throttle update rate based on distance to client
+                        let distance_sq = client_pos.0.distance_squared(pos.0);
+                        let id_staggered_tick = tick + entity.id() as u64;
+                        // More entities farther away so checks start there
+                        if distance_sq > 300.0f32.powi(2) {
+                            id_staggered_tick % 32 == 0
+                        } else if distance_sq > 250.0f32.powi(2) {
+                            id_staggered_tick % 16 == 0
+                        } else if distance_sq > 200.0f32.powi(2) {
+                            id_staggered_tick % 8 == 0
+                        } else if distance_sq > 150.0f32.powi(2) {
+                            id_staggered_tick % 4 == 0
+                        } else if distance_sq > 100.0f32.powi(2) {
+                            id_staggered_tick % 2 == 0
+                        } else {
+                            true // Closer than 100 blocks
+                        }
+                    } {
+                        // Only what changed since the client last heard of the entity is sent,
+                        // unless the client has to be corrected about itself
+                        let update = client.sync_baselines.encode_physics(
+                            uid.0,
+                            quantized_pos,
+                            quantized_vel,
+                            quantized_ori,
+                            client_entity == &entity,
+                        );
+                        if !update.is_empty() {
+                            delta_sync_package.physics.push((uid.0, update));
+                        }
+                        if !comp_sync_package.is_empty() {
+                            client.notify(ServerMsg::CompSync(comp_sync_package.clone()));
+                            bandwidth.add_comp_sync(&comp_sync_package, 1);
+                        }
+                    }
+                }
+            }
+
+            for (client, _, _, _, delta_sync_package) in subscribers {
+                if delta_sync_package.is_empty() {
+                    continue;
+                }
+                for (_, update) in &delta_sync_package.physics {
+                    bandwidth.add_sent("Pos", &(update.pos, update.pos_offset), 1);
+                    bandwidth.add_sent("Vel", &update.vel, 1);
+                    bandwidth.add_sent("Ori", &update.ori, 1);
+                }
+                for (_, kind, delta) in &delta_sync_package.comps {
+                    bandwidth.add_sent(kind.name(), delta, 1);
+                }
+                client.notify(ServerMsg::DeltaSync(delta_sync_package));
             }
         }
 
@@ -325,10 +399,13 @@ impl<'a> System<'a> for Sys {
         }
 
         // Sync outcomes
-        for (client, player, pos) in (&mut clients, &players, positions.maybe()).join() {
+        for (client, player, pos, camera) in
+            (&mut clients, &players, positions.maybe(), cameras.maybe()).join()
+        {
+            let pos = client::viewpoint(pos, camera);
             let is_near = |o_pos: Vec3<f32>| {
                 pos.zip_with(player.view_distance, |pos, vd| {
-                    pos.0.xy().distance_squared(o_pos.xy())
+                    pos.xy().distance_squared(o_pos.xy())
                         < (vd as f32 * TerrainChunkSize::RECT_SIZE.x as f32).powf(2.0)
                 })
             };
@@ -0,0 +1,32 @@
+use super::{SysScheduler, SysTimer};
+use crate::farming::Farms;
+use common::state::{BlockChange, Time};
+use specs::{Read, System, Write};
+
+/// This system grows the crops on farmland, changes to the farmland itself are
+/// handled in [`crate::farming::handle_terrain_changes`]
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    type SystemData = (
+        Read<'a, Time>,
+        Write<'a, BlockChange>,
+        Write<'a, Farms>,
+        Write<'a, SysScheduler<Self>>,
+        Write<'a, SysTimer<Self>>,
+    );
+
+    fn run(
+        &mut self,
+        (time, mut block_change, mut farms, mut scheduler, mut timer): Self::SystemData,
+    ) {
+        timer.start();
+
+        if scheduler.should_run() {
+            for (pos, block) in farms.grow(time.0) {
+                block_change.set(pos, block);
+            }
+        }
+
+        timer.end();
+    }
+}
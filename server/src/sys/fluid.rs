@@ -0,0 +1,32 @@
+use super::{SysScheduler, SysTimer};
+use crate::fluid::Fluids;
+use common::{state::BlockChange, terrain::TerrainGrid};
+use specs::{ReadExpect, System, Write};
+
+/// This system lets water flow around blocks that changed, see
+/// [`crate::fluid::handle_terrain_changes`]
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    type SystemData = (
+        ReadExpect<'a, TerrainGrid>,
+        Write<'a, BlockChange>,
+        Write<'a, Fluids>,
+        Write<'a, SysScheduler<Self>>,
+        Write<'a, SysTimer<Self>>,
+    );
+
+    fn run(
+        &mut self,
+        (terrain, mut block_change, mut fluids, mut scheduler, mut timer): Self::SystemData,
+    ) {
+        timer.start();
+
+        if scheduler.should_run() {
+            for (pos, block) in fluids.step(&*terrain) {
+                block_change.try_set(pos, block);
+            }
+        }
+
+        timer.end();
+    }
+}
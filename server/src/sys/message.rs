@@ -1,7 +1,12 @@
 use super::SysTimer;
 use crate::{
-    alias_validator::AliasValidator, client::Client, login_provider::LoginProvider,
-    persistence::character::CharacterLoader, ServerSettings, CLIENT_TIMEOUT,
+    alias_validator::AliasValidator,
+    client::{self, Client, SpectatorCamera},
+    login_provider::LoginProvider,
+    persistence::character::CharacterLoader,
+    replay,
+    shutdown::Shutdown,
+    ServerSettings, TickTime, CLIENT_TIMEOUT,
 };
 use common::{
     comp::{
@@ -36,6 +41,7 @@ impl Sys {
         server_emitter: &mut common::event::Emitter<'_, ServerEvent>,
         new_chat_msgs: &mut Vec<(Option<specs::Entity>, UnresolvedChatMsg)>,
         player_list: &HashMap<Uid, PlayerInfo>,
+        server_stats: &ServerStats,
         new_players: &mut Vec<specs::Entity>,
         entity: specs::Entity,
         client: &mut Client,
@@ -56,8 +62,10 @@ impl Sys {
         orientations: &mut WriteStorage<'_, Ori>,
         players: &mut WriteStorage<'_, Player>,
         controllers: &mut WriteStorage<'_, Controller>,
+        cameras: &mut WriteStorage<'_, SpectatorCamera>,
         settings: &Read<'_, ServerSettings>,
         alias_validator: &ReadExpect<'_, AliasValidator>,
+        shutdown: &Read<'_, Shutdown>,
     ) -> Result<(), crate::error::Error> {
         loop {
             let msg = client.recv().await?;
@@ -79,23 +87,62 @@ impl Sys {
                     ClientState::Connected => client.error_state(RequestStateError::Impossible),
                     ClientState::Spectator => client.error_state(RequestStateError::Already),
                     ClientState::Registered | ClientState::Character => {
-                        client.allow_state(ClientState::Spectator)
+                        if settings.may_spectate(players.get(entity), admins.get(entity).is_some())
+                        {
+                            server_emitter.emit(ServerEvent::Spectate {
+                                entity,
+                                follow: None,
+                            });
+                        } else {
+                            client.error_state(RequestStateError::Denied);
+                        }
                     },
                     ClientState::Pending => {},
                 },
+                ClientMsg::SpectatorPos(pos) => match client.client_state {
+                    ClientState::Spectator => {
+                        if let Some(camera) = cameras.get_mut(entity) {
+                            if pos.map(f32::is_finite).reduce_and() {
+                                camera.pos = pos;
+                                camera.follow = None;
+                            }
+                        }
+                    },
+                    _ => client.error_state(RequestStateError::Impossible),
+                },
+                ClientMsg::SpectatorFollow(follow) => match client.client_state {
+                    ClientState::Spectator => {
+                        // Whether there is anyone to follow is checked when moving the camera
+                        if let Some(camera) = cameras.get_mut(entity) {
+                            camera.follow = follow;
+                        }
+                    },
+                    _ => client.error_state(RequestStateError::Impossible),
+                },
                 // Request registered state (login)
                 ClientMsg::Register {
                     view_distance,
                     token_or_username,
+                    password,
                 } => {
-                    let (username, uuid) =
-                        match login_provider.try_login(&token_or_username, &settings.whitelist) {
-                            Err(err) => {
-                                client.error_state(RequestStateError::RegisterDenied(err));
-                                break Ok(());
-                            },
-                            Ok((username, uuid)) => (username, uuid),
-                        };
+                    // Nobody can log in while the server is shutting down
+                    if shutdown.pending().is_some() {
+                        client.notify(ServerMsg::Shutdown);
+                        break Ok(());
+                    }
+                    let (username, uuid) = match login_provider.try_login(
+                        client.pid,
+                        &token_or_username,
+                        password.as_deref(),
+                        &settings.whitelist,
+                        admin_list,
+                    ) {
+                        Err(err) => {
+                            client.error_state(RequestStateError::RegisterDenied(err));
+                            break Ok(());
+                        },
+                        Ok((username, uuid)) => (username, uuid),
+                    };
 
                     let vd =
                         view_distance.map(|vd| vd.min(settings.max_view_distance.unwrap_or(vd)));
@@ -119,6 +166,24 @@ impl Sys {
                                 let _ = admins.insert(entity, Admin);
                             }
 
+                            // Now that it's known who the client is, the recording of their
+                            // session is either saved or stopped
+                            if let Some(dir) = &settings.replay_dir {
+                                if settings.replay_players.is_empty()
+                                    || settings.replay_players.contains(&username)
+                                {
+                                    let path = replay::replay_path(dir, &username);
+                                    if let Some(Err(e)) =
+                                        client.recorder.as_mut().map(|r| r.save_to(&path))
+                                    {
+                                        warn!(?e, ?path, "Failed to save replay");
+                                        client.recorder = None;
+                                    }
+                                } else {
+                                    client.recorder = None;
+                                }
+                            }
+
                             // Tell the client its request was successful.
                             client.allow_state(ClientState::Registered);
 
@@ -311,9 +376,9 @@ impl Sys {
                     ClientState::Spectator | ClientState::Character => {
                         let in_vd = if let (Some(view_distance), Some(pos)) = (
                             players.get(entity).and_then(|p| p.view_distance),
-                            positions.get(entity),
+                            client::viewpoint(positions.get(entity), cameras.get(entity)),
                         ) {
-                            pos.0.xy().map(|e| e as f64).distance(
+                            pos.xy().map(|e| e as f64).distance(
                                 key.map(|e| e as f64 + 0.5)
                                     * TerrainChunkSize::RECT_SIZE.map(|e| e as f64),
                             ) < (view_distance as f64 - 1.0 + 2.5 * 2.0_f64.sqrt())
@@ -373,14 +438,20 @@ impl Sys {
                     }
                 },
                 ClientMsg::UnlockSkill(skill) => {
-                    stats
-                        .get_mut(entity)
-                        .map(|s| s.skill_set.unlock_skill(skill));
+                    if let Some(stats) = stats.get_mut(entity) {
+                        match stats.skill_set.unlock_skill(skill) {
+                            Ok(()) => stats.update_max_hp(stats.body_type),
+                            Err(error) => warn!(?error, ?skill, "Failed to unlock skill"),
+                        }
+                    }
                 },
                 ClientMsg::RefundSkill(skill) => {
-                    stats
-                        .get_mut(entity)
-                        .map(|s| s.skill_set.refund_skill(skill));
+                    if let Some(stats) = stats.get_mut(entity) {
+                        match stats.skill_set.refund_skill(skill) {
+                            Ok(()) => stats.update_max_hp(stats.body_type),
+                            Err(error) => warn!(?error, ?skill, "Failed to refund skill"),
+                        }
+                    }
                 },
                 ClientMsg::UnlockSkillGroup(skill_group_type) => {
                     stats
@@ -388,9 +459,7 @@ impl Sys {
                         .map(|s| s.skill_set.unlock_skill_group(skill_group_type));
                 },
                 ClientMsg::ServerStats => {
-                    client.notify(ServerMsg::ServerStats(ServerStats {
-                        player_count: player_list.len() as u64,
-                    }));
+                    client.notify(ServerMsg::ServerStats(server_stats.clone()));
                 },
             }
         }
@@ -405,6 +474,7 @@ impl<'a> System<'a> for Sys {
         Entities<'a>,
         Read<'a, EventBus<ServerEvent>>,
         Read<'a, Time>,
+        Read<'a, TickTime>,
         ReadExpect<'a, CharacterLoader>,
         ReadExpect<'a, TerrainGrid>,
         Write<'a, SysTimer<Self>>,
@@ -423,8 +493,10 @@ impl<'a> System<'a> for Sys {
         WriteStorage<'a, Player>,
         WriteStorage<'a, Client>,
         WriteStorage<'a, Controller>,
+        WriteStorage<'a, SpectatorCamera>,
         Read<'a, ServerSettings>,
         ReadExpect<'a, AliasValidator>,
+        Read<'a, Shutdown>,
     );
 
     #[allow(clippy::match_ref_pats)] // TODO: Pending review in #587
@@ -436,6 +508,7 @@ impl<'a> System<'a> for Sys {
             entities,
             server_event_bus,
             time,
+            tick_time,
             character_loader,
             terrain,
             mut timer,
@@ -454,8 +527,10 @@ impl<'a> System<'a> for Sys {
             mut players,
             mut clients,
             mut controllers,
+            mut cameras,
             settings,
             alias_validator,
+            shutdown,
         ): Self::SystemData,
     ) {
         timer.start();
@@ -479,6 +554,10 @@ impl<'a> System<'a> for Sys {
                 })
             })
             .collect::<HashMap<_, _>>();
+        let server_stats = ServerStats {
+            player_count: player_list.len() as u64,
+            tick_time: tick_time.0,
+        };
         // List of new players to update player lists of all clients.
         let mut new_players = Vec::new();
 
@@ -493,6 +572,7 @@ impl<'a> System<'a> for Sys {
                     &mut server_emitter,
                     &mut new_chat_msgs,
                     &player_list,
+                    &server_stats,
                     &mut new_players,
                     entity,
                     client,
@@ -514,8 +594,10 @@ impl<'a> System<'a> for Sys {
                     &mut orientations,
                     &mut players,
                     &mut controllers,
+                    &mut cameras,
                     &settings,
                     &alias_validator,
+                    &shutdown,
                     ).fuse() => err,
                 )
             });
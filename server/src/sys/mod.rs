@@ -1,4 +1,7 @@
+pub mod crafting;
 pub mod entity_sync;
+pub mod farming;
+pub mod fluid;
 pub mod invite_timeout;
 pub mod message;
 pub mod object;
@@ -8,7 +11,9 @@ pub mod subscription;
 pub mod terrain;
 pub mod terrain_sync;
 pub mod waypoint;
+pub mod weather;
 
+pub use common::sys::SysTimer;
 use specs::DispatcherBuilder;
 use std::{
     marker::PhantomData,
@@ -25,6 +30,12 @@ pub type WaypointTimer = SysTimer<waypoint::Sys>;
 pub type InviteTimeoutTimer = SysTimer<invite_timeout::Sys>;
 pub type PersistenceTimer = SysTimer<persistence::Sys>;
 pub type PersistenceScheduler = SysScheduler<persistence::Sys>;
+pub type FluidTimer = SysTimer<fluid::Sys>;
+pub type FluidScheduler = SysScheduler<fluid::Sys>;
+pub type FarmingTimer = SysTimer<farming::Sys>;
+pub type FarmingScheduler = SysScheduler<farming::Sys>;
+pub type WeatherTimer = SysTimer<weather::Sys>;
+pub type WeatherScheduler = SysScheduler<weather::Sys>;
 
 // System names
 // Note: commented names may be useful in the future
@@ -37,6 +48,10 @@ const WAYPOINT_SYS: &str = "server_waypoint_sys";
 const INVITE_TIMEOUT_SYS: &str = "server_invite_timeout_sys";
 const PERSISTENCE_SYS: &str = "server_persistence_sys";
 const OBJECT_SYS: &str = "server_object_sys";
+const CRAFTING_SYS: &str = "server_crafting_sys";
+const WEATHER_SYS: &str = "server_weather_sys";
+const FLUID_SYS: &str = "server_fluid_sys";
+const FARMING_SYS: &str = "server_farming_sys";
 
 pub fn add_server_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch_builder.add(terrain::Sys, TERRAIN_SYS, &[]);
@@ -44,6 +59,10 @@ pub fn add_server_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch_builder.add(invite_timeout::Sys, INVITE_TIMEOUT_SYS, &[]);
     dispatch_builder.add(persistence::Sys, PERSISTENCE_SYS, &[]);
     dispatch_builder.add(object::Sys, OBJECT_SYS, &[]);
+    dispatch_builder.add(crafting::Sys, CRAFTING_SYS, &[]);
+    dispatch_builder.add(weather::Sys, WEATHER_SYS, &[]);
+    dispatch_builder.add(fluid::Sys, FLUID_SYS, &[]);
+    dispatch_builder.add(farming::Sys, FARMING_SYS, &[TERRAIN_SYS]);
 }
 
 pub fn run_sync_systems(ecs: &mut specs::World) {
@@ -75,6 +94,8 @@ impl<S> SysScheduler<S> {
         }
     }
 
+    pub fn interval(&self) -> Duration { self.interval }
+
     pub fn should_run(&mut self) -> bool {
         if self.last_run.elapsed() > self.interval {
             self.last_run = Instant::now();
@@ -95,38 +116,3 @@ impl<S> Default for SysScheduler<S> {
         }
     }
 }
-
-/// Used to keep track of how much time each system takes
-pub struct SysTimer<S> {
-    pub nanos: u64,
-    start: Option<Instant>,
-    _phantom: PhantomData<S>,
-}
-
-impl<S> SysTimer<S> {
-    pub fn start(&mut self) {
-        if self.start.is_some() {
-            panic!("Timer already started");
-        }
-        self.start = Some(Instant::now());
-    }
-
-    pub fn end(&mut self) {
-        self.nanos = self
-            .start
-            .take()
-            .expect("Timer ended without starting it")
-            .elapsed()
-            .as_nanos() as u64;
-    }
-}
-
-impl<S> Default for SysTimer<S> {
-    fn default() -> Self {
-        Self {
-            nanos: 0,
-            start: None,
-            _phantom: PhantomData,
-        }
-    }
-}
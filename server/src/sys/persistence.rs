@@ -1,9 +1,10 @@
 use crate::{
-    persistence::character,
+    container::Containers,
+    persistence::{character, container},
     sys::{SysScheduler, SysTimer},
 };
 use common::comp::{Inventory, Loadout, Player, Stats};
-use specs::{Join, ReadExpect, ReadStorage, System, Write};
+use specs::{Join, ReadExpect, ReadStorage, System, Write, WriteExpect};
 
 pub struct Sys;
 
@@ -15,6 +16,8 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Inventory>,
         ReadStorage<'a, Loadout>,
         ReadExpect<'a, character::CharacterUpdater>,
+        ReadExpect<'a, container::ContainerUpdater>,
+        WriteExpect<'a, Containers>,
         Write<'a, SysScheduler<Self>>,
         Write<'a, SysTimer<Self>>,
     );
@@ -27,6 +30,8 @@ impl<'a> System<'a> for Sys {
             player_inventories,
             player_loadouts,
             updater,
+            container_updater,
+            mut containers,
             mut scheduler,
             mut timer,
         ): Self::SystemData,
@@ -47,6 +52,7 @@ impl<'a> System<'a> for Sys {
                             .map(|id| (id, stats, inventory, loadout))
                     }),
             );
+            container_updater.batch_update(containers.take_dirty());
             timer.end();
         }
     }
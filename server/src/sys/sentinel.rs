@@ -4,7 +4,7 @@ use common::{
         Body, CanBuild, CharacterState, Collider, Energy, Gravity, Group, Item, LightEmitter,
         Loadout, Mass, MountState, Mounting, Ori, Player, Pos, Scale, Stats, Sticky, Vel,
     },
-    msg::EcsCompPacket,
+    msg::{delta::DeltaComp, EcsCompPacket},
     sync::{CompSyncPackage, EntityPackage, EntitySyncPackage, Uid, UpdateTracker, WorldSyncExt},
 };
 use hashbrown::HashMap;
@@ -12,6 +12,7 @@ use specs::{
     shred::ResourceId, Entity as EcsEntity, Join, ReadExpect, ReadStorage, System, SystemData,
     World, Write, WriteExpect,
 };
+use tracing::error;
 use vek::*;
 
 /// Always watching
@@ -171,7 +172,7 @@ impl<'a> ReadTrackers<'a> {
         let comp_sync_package = CompSyncPackage::new()
             .with_component(&comps.uid, &*self.body, &comps.body, filter)
             .with_component(&comps.uid, &*self.player, &comps.player, filter)
-            .with_component(&comps.uid, &*self.stats, &comps.stats, filter)
+            .with_component_insertions_and_removals(&comps.uid, &*self.stats, &comps.stats, filter)
             .with_component(&comps.uid, &*self.energy, &comps.energy, filter)
             .with_component(&comps.uid, &*self.can_build, &comps.can_build, filter)
             .with_component(
@@ -189,8 +190,13 @@ impl<'a> ReadTrackers<'a> {
             .with_component(&comps.uid, &*self.collider, &comps.collider, filter)
             .with_component(&comps.uid, &*self.sticky, &comps.sticky, filter)
             .with_component(&comps.uid, &*self.gravity, &comps.gravity, filter)
-            .with_component(&comps.uid, &*self.loadout, &comps.loadout, filter)
-            .with_component(
+            .with_component_insertions_and_removals(
+                &comps.uid,
+                &*self.loadout,
+                &comps.loadout,
+                filter,
+            )
+            .with_component_insertions_and_removals(
                 &comps.uid,
                 &*self.character_state,
                 &comps.character_state,
@@ -199,6 +205,43 @@ impl<'a> ReadTrackers<'a> {
 
         (entity_sync_package, comp_sync_package)
     }
+
+    /// The modified components that are sent as deltas against what each
+    /// client last received, serialized
+    pub fn delta_comp_modifications(
+        &self,
+        comps: &TrackedComps,
+        filter: impl Join + Copy,
+    ) -> Vec<(u64, DeltaComp, Vec<u8>)> {
+        let mut modified = Vec::new();
+        let mut push = |uid: &Uid, kind, bytes: bincode::Result<Vec<u8>>| match bytes {
+            Ok(bytes) => modified.push((uid.0, kind, bytes)),
+            Err(e) => error!(?e, ?kind, "Failed to serialize a component for its delta"),
+        };
+        for (uid, stats, _, _) in (&comps.uid, &comps.stats, self.stats.modified(), filter).join() {
+            push(uid, DeltaComp::Stats, bincode::serialize(stats));
+        }
+        for (uid, loadout, _, _) in
+            (&comps.uid, &comps.loadout, self.loadout.modified(), filter).join()
+        {
+            push(uid, DeltaComp::Loadout, bincode::serialize(loadout));
+        }
+        for (uid, character_state, _, _) in (
+            &comps.uid,
+            &comps.character_state,
+            self.character_state.modified(),
+            filter,
+        )
+            .join()
+        {
+            push(
+                uid,
+                DeltaComp::CharacterState,
+                bincode::serialize(character_state),
+            );
+        }
+        modified
+    }
 }
 
 #[derive(SystemData)]
@@ -2,18 +2,18 @@ use super::{
     sentinel::{DeletedEntities, TrackedComps},
     SysTimer,
 };
-use crate::client::{self, Client, RegionSubscription};
+use crate::client::{self, Client, RegionSubscription, SpectatorCamera};
 use common::{
     comp::{Ori, Player, Pos, Vel},
     msg::ServerMsg,
     region::{region_in_vd, regions_in_vd, Event as RegionEvent, RegionMap},
-    sync::Uid,
+    sync::{Uid, UidAllocator},
     terrain::TerrainChunkSize,
     vol::RectVolSize,
 };
 use specs::{
-    Entities, Join, ReadExpect, ReadStorage, System, SystemData, World, WorldExt, Write,
-    WriteStorage,
+    saveload::MarkerAllocator, Entities, Join, Read, ReadExpect, ReadStorage, System, SystemData,
+    World, WorldExt, Write, WriteStorage,
 };
 use tracing::{debug, error};
 use vek::*;
@@ -33,6 +33,8 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Player>,
         WriteStorage<'a, Client>,
         WriteStorage<'a, RegionSubscription>,
+        WriteStorage<'a, SpectatorCamera>,
+        Read<'a, UidAllocator>,
         Write<'a, DeletedEntities>,
         TrackedComps<'a>,
     );
@@ -51,12 +53,33 @@ impl<'a> System<'a> for Sys {
             players,
             mut clients,
             mut subscriptions,
+            mut cameras,
+            uid_allocator,
             mut deleted_entities,
             tracked_comps,
         ): Self::SystemData,
     ) {
         timer.start();
 
+        // Move the cameras of spectators along with whom they follow
+        for (client, camera) in (&mut clients, &mut cameras).join() {
+            if let Some(follow) = camera.follow {
+                match uid_allocator
+                    .retrieve_entity_internal(follow.0)
+                    .and_then(|entity| positions.get(entity))
+                {
+                    Some(pos) => camera.pos = pos.0,
+                    None => {
+                        camera.follow = None;
+                        client.notify(ServerMsg::SpectatorCamera {
+                            pos: camera.pos,
+                            follow: None,
+                        });
+                    },
+                }
+            }
+        }
+
         // To update subscriptions
         // 1. Iterate through clients
         // 2. Calculate current chunk position
@@ -72,13 +95,15 @@ impl<'a> System<'a> for Sys {
         for (client, subscription, pos, vd, client_entity) in (
             &mut clients,
             &mut subscriptions,
-            &positions,
+            positions.maybe(),
+            cameras.maybe(),
             &players,
             &entities,
         )
             .join()
-            .filter_map(|(client, s, pos, player, e)| {
+            .filter_map(|(client, s, pos, camera, player, e)| {
                 if client.is_ingame() {
+                    let pos = Pos(client::viewpoint(pos, camera)?);
                     player.view_distance.map(|v| (client, s, pos, v, e))
                 } else {
                     None
@@ -216,7 +241,10 @@ impl<'a> System<'a> for Sys {
 /// Initialize region subscription
 pub fn initialize_region_subscription(world: &World, entity: specs::Entity) {
     if let (Some(client_pos), Some(client_vd), Some(client)) = (
-        world.read_storage::<Pos>().get(entity),
+        client::viewpoint(
+            world.read_storage::<Pos>().get(entity),
+            world.read_storage::<SpectatorCamera>().get(entity),
+        ),
         world
             .read_storage::<Player>()
             .get(entity)
@@ -224,11 +252,11 @@ pub fn initialize_region_subscription(world: &World, entity: specs::Entity) {
             .and_then(|v| v),
         world.write_storage::<Client>().get_mut(entity),
     ) {
-        let fuzzy_chunk = (Vec2::<f32>::from(client_pos.0))
+        let fuzzy_chunk = (Vec2::<f32>::from(client_pos))
             .map2(TerrainChunkSize::RECT_SIZE, |e, sz| e as i32 / sz as i32);
         let chunk_size = TerrainChunkSize::RECT_SIZE.reduce_max() as f32;
         let regions = common::region::regions_in_vd(
-            client_pos.0,
+            client_pos,
             (client_vd as f32 * chunk_size) as f32
                 + (client::CHUNK_FUZZ as f32 + chunk_size) * 2.0f32.sqrt(),
         );
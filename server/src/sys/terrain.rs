@@ -1,5 +1,9 @@
 use super::SysTimer;
-use crate::{chunk_generator::ChunkGenerator, client::Client, Tick};
+use crate::{
+    chunk_generator::ChunkGenerator,
+    client::{self, Client, SpectatorCamera},
+    Tick,
+};
 use common::{
     assets,
     comp::{self, item, Alignment, CharacterAbility, ItemConfig, Player, Pos},
@@ -33,6 +37,7 @@ impl<'a> System<'a> for Sys {
         WriteExpect<'a, TerrainGrid>,
         Write<'a, TerrainChanges>,
         ReadStorage<'a, Pos>,
+        ReadStorage<'a, SpectatorCamera>,
         ReadStorage<'a, Player>,
         WriteStorage<'a, Client>,
     );
@@ -47,6 +52,7 @@ impl<'a> System<'a> for Sys {
             mut terrain,
             mut terrain_changes,
             positions,
+            cameras,
             players,
             mut clients,
         ): Self::SystemData,
@@ -74,13 +80,17 @@ impl<'a> System<'a> for Sys {
                 },
             };
             // Send the chunk to all nearby players.
-            for (view_distance, pos, client) in (&players, &positions, &mut clients)
-                .join()
-                .filter_map(|(player, pos, client)| {
-                    player.view_distance.map(|vd| (vd, pos, client))
-                })
+            for (view_distance, pos, client) in
+                (&players, positions.maybe(), cameras.maybe(), &mut clients)
+                    .join()
+                    .filter_map(|(player, pos, camera, client)| {
+                        player
+                            .view_distance
+                            .zip(client::viewpoint(pos, camera))
+                            .map(|(vd, pos)| (vd, pos, client))
+                    })
             {
-                let chunk_pos = terrain.pos_key(pos.0.map(|e| e as i32));
+                let chunk_pos = terrain.pos_key(pos.map(|e| e as i32));
                 // Subtract 2 from the offset before computing squared magnitude
                 // 1 since chunks need neighbors to be meshed
                 // 1 to act as a buffer if the player moves in that direction
@@ -323,7 +333,11 @@ impl<'a> System<'a> for Sys {
                     stats,
                     loadout,
                     agent: if entity.has_agency {
-                        Some(comp::Agent::new(entity.pos, can_speak, &body))
+                        let agent = comp::Agent::new(entity.pos, can_speak, &body);
+                        Some(match entity.route {
+                            Some(route) => agent.with_route(route),
+                            None => agent,
+                        })
                     } else {
                         None
                     },
@@ -348,10 +362,13 @@ impl<'a> System<'a> for Sys {
                 let mut should_drop = true;
 
                 // For each player with a position, calculate the distance.
-                for (player, pos) in (&players, &positions).join() {
+                for (player, pos, camera) in
+                    (&players, positions.maybe(), cameras.maybe()).join()
+                {
                     if player
                         .view_distance
-                        .map(|vd| chunk_in_vd(pos.0, chunk_key, &terrain, vd))
+                        .zip(client::viewpoint(pos, camera))
+                        .map(|(vd, pos)| chunk_in_vd(pos, chunk_key, &terrain, vd))
                         .unwrap_or(false)
                     {
                         should_drop = false;
@@ -1,5 +1,5 @@
 use super::SysTimer;
-use crate::client::Client;
+use crate::client::{self, Client, SpectatorCamera};
 use common::{
     comp::{Player, Pos},
     msg::ServerMsg,
@@ -18,22 +18,26 @@ impl<'a> System<'a> for Sys {
         Read<'a, TerrainChanges>,
         Write<'a, SysTimer<Self>>,
         ReadStorage<'a, Pos>,
+        ReadStorage<'a, SpectatorCamera>,
         ReadStorage<'a, Player>,
         WriteStorage<'a, Client>,
     );
 
     fn run(
         &mut self,
-        (terrain, terrain_changes, mut timer, positions, players, mut clients): Self::SystemData,
+        (terrain, terrain_changes, mut timer, positions, cameras, players, mut clients): Self::SystemData,
     ) {
         timer.start();
 
         // Sync changed chunks
         'chunk: for chunk_key in &terrain_changes.modified_chunks {
-            for (player, pos, client) in (&players, &positions, &mut clients).join() {
+            for (player, pos, camera, client) in
+                (&players, positions.maybe(), cameras.maybe(), &mut clients).join()
+            {
                 if player
                     .view_distance
-                    .map(|vd| super::terrain::chunk_in_vd(pos.0, *chunk_key, &terrain, vd))
+                    .zip(client::viewpoint(pos, camera))
+                    .map(|(vd, pos)| super::terrain::chunk_in_vd(pos, *chunk_key, &terrain, vd))
                     .unwrap_or(false)
                 {
                     client.notify(ServerMsg::TerrainChunkUpdate {
@@ -41,6 +41,7 @@ impl<'a> System<'a> for Sys {
                     {
                         if wp_old.map_or(true, |w| w.elapsed(*time) > NOTIFY_TIME) {
                             client.notify(ServerMsg::Notification(Notification::WaypointSaved));
+                            client.notify(ServerMsg::WaypointUpdate(player_pos.0));
                         }
                     }
                 }
@@ -0,0 +1,36 @@
+use super::{SysScheduler, SysTimer};
+use crate::{client::Client, weather::WeatherSim};
+use common::{msg::ServerMsg, weather::WeatherGrid};
+use specs::{Join, System, Write, WriteExpect, WriteStorage};
+
+/// This system advances the weather simulation and sends the new weather to
+/// all clients
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)] // TODO: Pending review in #587
+    type SystemData = (
+        WriteExpect<'a, WeatherSim>,
+        Write<'a, WeatherGrid>,
+        WriteStorage<'a, Client>,
+        Write<'a, SysScheduler<Self>>,
+        Write<'a, SysTimer<Self>>,
+    );
+
+    fn run(
+        &mut self,
+        (mut sim, mut grid, mut clients, mut scheduler, mut timer): Self::SystemData,
+    ) {
+        if scheduler.should_run() {
+            timer.start();
+
+            sim.tick(scheduler.interval().as_secs_f32(), &mut grid);
+
+            let msg = ServerMsg::WeatherUpdate(grid.clone());
+            for client in (&mut clients).join().filter(|c| c.is_ingame()) {
+                client.notify(msg.clone());
+            }
+
+            timer.end();
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Simulation of the weather over the world map
+//!
+//! Every weather cell carries an amount of moisture. Moisture evaporates from
+//! the oceans and humid land, is carried along by the wind and falls as rain
+//! (or snow in cold climates) once the air can't hold any more of it.
+
+use common::weather::{WeatherGrid, CELL_SIZE};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use vek::*;
+#[cfg(feature = "worldgen")]
+use world::sim::WorldSim;
+
+/// Moisture gained per second over the ocean
+const EVAPORATION: f32 = 0.004;
+/// Moisture lost per second over dry land
+const DRYING: f32 = 0.002;
+/// Moisture above which clouds start to release precipitation
+const SATURATION: f32 = 0.6;
+/// Fraction of the moisture above saturation released per second
+const PRECIPITATION_RATE: f32 = 0.02;
+/// Temperature below which precipitation falls as snow
+const FREEZING_TEMP: f32 = -0.4;
+/// Average wind speed, in blocks per second
+const MEAN_WIND_SPEED: f32 = 8.0;
+const MAX_WIND_SPEED: f32 = 30.0;
+
+/// The climate of a weather cell, derived from the world generation data
+#[derive(Copy, Clone, Debug)]
+pub struct Climate {
+    /// Average humidity, between 0 and 1
+    pub humidity: f32,
+    /// Average temperature, roughly between -1 and 1
+    pub temp: f32,
+    /// Fraction of the cell covered by water, between 0 and 1
+    pub ocean: f32,
+}
+
+impl Default for Climate {
+    fn default() -> Self {
+        Self {
+            humidity: 0.5,
+            temp: 0.0,
+            ocean: 0.0,
+        }
+    }
+}
+
+pub struct WeatherSim {
+    size: Vec2<u32>,
+    climate: Vec<Climate>,
+    moisture: Vec<f32>,
+    /// Direction of the prevailing wind, in radians
+    wind_dir: f32,
+    wind_speed: f32,
+    rng: SmallRng,
+}
+
+impl WeatherSim {
+    /// Create a simulation for a grid of `size` cells with the given climate,
+    /// in row-major order
+    pub fn new(size: Vec2<u32>, climate: Vec<Climate>, seed: u64) -> Self {
+        assert_eq!(climate.len(), size.product() as usize);
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let moisture = climate
+            .iter()
+            .map(|c| c.humidity.max(c.ocean) * rng.gen_range(0.0, SATURATION))
+            .collect();
+
+        Self {
+            size,
+            climate,
+            moisture,
+            wind_dir: rng.gen_range(0.0, std::f32::consts::PI * 2.0),
+            wind_speed: MEAN_WIND_SPEED,
+            rng,
+        }
+    }
+
+    /// Average the climate of every weather cell from the world simulation
+    #[cfg(feature = "worldgen")]
+    pub fn from_world(sim: &WorldSim, seed: u64) -> Self {
+        let map_size = sim.get_size();
+        let size = WeatherGrid::size_for_map(map_size);
+        let sea_level = sim.sea_level();
+
+        let climate = (0..size.y as i32)
+            .flat_map(|y| (0..size.x as i32).map(move |x| Vec2::new(x, y)))
+            .map(|cell| {
+                let mut sum = Climate {
+                    humidity: 0.0,
+                    temp: 0.0,
+                    ocean: 0.0,
+                };
+                let mut count = 0.0;
+                for y in 0..CELL_SIZE as i32 {
+                    for x in 0..CELL_SIZE as i32 {
+                        if let Some(chunk) = sim.get(cell * CELL_SIZE as i32 + Vec2::new(x, y)) {
+                            sum.humidity += chunk.humidity;
+                            sum.temp += chunk.temp;
+                            if chunk.alt < sea_level || chunk.water_alt > chunk.alt {
+                                sum.ocean += 1.0;
+                            }
+                            count += 1.0;
+                        }
+                    }
+                }
+                if count > 0.0 {
+                    Climate {
+                        humidity: sum.humidity / count,
+                        temp: sum.temp / count,
+                        ocean: sum.ocean / count,
+                    }
+                } else {
+                    Climate::default()
+                }
+            })
+            .collect();
+
+        Self::new(size, climate, seed)
+    }
+
+    pub fn size(&self) -> Vec2<u32> { self.size }
+
+    fn moisture_at(&self, pos: Vec2<f32>) -> f32 {
+        let max = self.size.map(|e| e as i32 - 1);
+        let get = |cell: Vec2<i32>| {
+            let cell = cell.map2(max, |e, max| e.max(0).min(max));
+            self.moisture[(cell.y * self.size.x as i32 + cell.x) as usize]
+        };
+        let base = pos.map(|e| e.floor() as i32);
+        let frac = pos.map(|e| e - e.floor());
+        Lerp::lerp(
+            Lerp::lerp(get(base), get(base + Vec2::unit_x()), frac.x),
+            Lerp::lerp(
+                get(base + Vec2::unit_y()),
+                get(base + Vec2::new(1, 1)),
+                frac.x,
+            ),
+            frac.y,
+        )
+    }
+
+    /// Advance the simulation by `dt` seconds and write the resulting weather
+    /// into `grid`
+    pub fn tick(&mut self, dt: f32, grid: &mut WeatherGrid) {
+        if grid.size() != self.size {
+            *grid = WeatherGrid::new(self.size);
+        }
+
+        // The prevailing wind slowly changes direction and strength
+        self.wind_dir += self.rng.gen_range(-0.05, 0.05) * dt;
+        self.wind_speed = (self.wind_speed
+            + self.rng.gen_range(-0.5, 0.5) * dt
+            + (MEAN_WIND_SPEED - self.wind_speed) * 0.01 * dt)
+            .max(0.0)
+            .min(MAX_WIND_SPEED);
+        let wind = Vec2::new(self.wind_dir.cos(), self.wind_dir.sin()) * self.wind_speed;
+
+        // Carry moisture along with the wind
+        let cell_size = common::weather::cell_size_blocks();
+        let offset = wind * dt / cell_size;
+        let advected = (0..self.moisture.len())
+            .map(|i| {
+                let cell = Vec2::new(i as u32 % self.size.x, i as u32 / self.size.x);
+                self.moisture_at(cell.map(|e| e as f32) - offset)
+            })
+            .collect::<Vec<_>>();
+        self.moisture = advected;
+
+        for ((moisture, climate), (_, weather)) in self
+            .moisture
+            .iter_mut()
+            .zip(self.climate.iter())
+            .zip(grid.iter_mut())
+        {
+            *moisture += (EVAPORATION * climate.ocean.max(climate.humidity * 0.5)
+                - DRYING * (1.0 - climate.humidity))
+                * dt
+                + self.rng.gen_range(-0.002, 0.002) * dt * climate.humidity;
+            *moisture = moisture.max(0.0);
+
+            let excess = (*moisture - SATURATION).max(0.0);
+            *moisture -= excess * (PRECIPITATION_RATE * dt).min(1.0);
+            let precipitation = (excess / (1.0 - SATURATION)).min(1.0);
+
+            weather.cloud = (*moisture / SATURATION).min(1.0);
+            if climate.temp < FREEZING_TEMP {
+                weather.rain = 0.0;
+                weather.snow = precipitation;
+            } else {
+                weather.rain = precipitation;
+                weather.snow = 0.0;
+            }
+            // Storms bring gusts, open water lets the wind blow unhindered
+            weather.wind = wind * (0.6 + climate.ocean * 0.4 + precipitation * 0.8);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Vec2<u32> = Vec2 { x: 8, y: 8 };
+
+    fn run(climate: Climate, seed: u64, ticks: usize) -> WeatherGrid {
+        let mut sim = WeatherSim::new(SIZE, vec![climate; SIZE.product() as usize], seed);
+        let mut grid = WeatherGrid::default();
+        for _ in 0..ticks {
+            sim.tick(10.0, &mut grid);
+        }
+        grid
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let climate = Climate::default();
+        assert_eq!(run(climate, 42, 50), run(climate, 42, 50));
+    }
+
+    #[test]
+    fn test_bounds() {
+        for climate in &[Climate::default(), Climate {
+            humidity: 1.0,
+            temp: 1.0,
+            ocean: 1.0,
+        }] {
+            let grid = run(*climate, 7, 200);
+            assert_eq!(grid.size(), SIZE);
+            for (_, weather) in grid.iter() {
+                for value in &[weather.cloud, weather.rain, weather.snow] {
+                    assert!((0.0..=1.0).contains(value));
+                }
+                assert!(weather.wind.magnitude() <= MAX_WIND_SPEED * 2.2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_precipitation() {
+        let wet = |temp| Climate {
+            humidity: 0.9,
+            temp,
+            ocean: 1.0,
+        };
+
+        let rain = run(wet(0.5), 3, 200);
+        assert!(rain.iter().any(|(_, w)| w.rain > 0.0));
+        assert!(rain.iter().all(|(_, w)| w.snow == 0.0));
+
+        let snow = run(wet(-0.9), 3, 200);
+        assert!(snow.iter().any(|(_, w)| w.snow > 0.0));
+        assert!(snow.iter().all(|(_, w)| w.rain == 0.0));
+
+        let desert = run(
+            Climate {
+                humidity: 0.0,
+                temp: 0.8,
+                ocean: 0.0,
+            },
+            3,
+            200,
+        );
+        assert!(desert.iter().all(|(_, w)| w.precipitation() == 0.0));
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let mut grid = WeatherGrid::new(Vec2::new(2, 1));
+        grid.get_mut(Vec2::new(1, 0)).unwrap().rain = 1.0;
+        let cell = common::weather::cell_size_blocks();
+
+        assert_eq!(grid.get_interpolated(cell * 0.5).rain, 0.0);
+        assert_eq!(grid.get_interpolated(cell * Vec2::new(1.5, 0.5)).rain, 1.0);
+        assert!((grid.get_interpolated(cell * Vec2::new(1.0, 0.5)).rain - 0.5).abs() < 0.001);
+        // Outside of the map
+        assert_eq!(grid.get_interpolated(cell * -4.0).rain, 0.0);
+        assert_eq!(grid.get_interpolated(cell * 10.0).rain, 1.0);
+    }
+}
@@ -1,20 +1,37 @@
+mod world_preview;
+
 use std::{
     error::Error,
     ffi::OsString,
     path::{Component, PathBuf},
+    process,
 };
 use structopt::StructOpt;
 
 use common::{assets, comp};
-use comp::item::{
-    armor::{ArmorKind, Protection},
-    tool::ToolKind,
+use comp::{
+    item::{
+        armor::{ArmorKind, Protection},
+        tool::ToolKind,
+    },
+    projectile, CharacterAbility,
 };
+use world_preview::{world_preview, WorldPreviewOpts};
 
 #[derive(StructOpt)]
-struct Cli {
-    /// Available arguments: "armor_stats", "weapon_stats"
-    function: String,
+enum Cli {
+    /// Write the stats of all armor to armorstats.csv
+    #[structopt(name = "armor_stats")]
+    ArmorStats,
+    /// Write the stats of all weapons to weaponstats.csv
+    #[structopt(name = "weapon_stats")]
+    WeaponStats,
+    /// Write the abilities of all weapons to abilitystats.csv
+    #[structopt(name = "ability_stats")]
+    AbilityStats,
+    /// Generate a world, write preview images of it and print a summary
+    #[structopt(name = "world_preview")]
+    WorldPreview(WorldPreviewOpts),
 }
 
 fn armor_stats() -> Result<(), Box<dyn Error>> {
@@ -125,6 +142,7 @@ fn weapon_stats() -> Result<(), Box<dyn Error>> {
                                 ToolKind::Shield(_) => "Shield".to_string(),
                                 ToolKind::Debug(_) => "Debug".to_string(),
                                 ToolKind::Farming(_) => "Farming".to_string(),
+                                ToolKind::Pickaxe(_) => "Pickaxe".to_string(),
                                 ToolKind::Empty => "Empty".to_string(),
                             };
 
@@ -149,17 +167,202 @@ fn weapon_stats() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn main() {
-    let args = Cli::from_args();
-    if args.function.eq_ignore_ascii_case("armor_stats") {
-        if let Err(e) = armor_stats() {
-            println!("Error: {}", e)
-        }
-    } else if args.function.eq_ignore_ascii_case("weapon_stats") {
-        if let Err(e) = weapon_stats() {
-            println!("Error: {}", e)
+/// Name, energy cost, damage, wind-up and recovery (in milliseconds) of an
+/// ability, in that order
+fn ability_record(ability: &CharacterAbility) -> [String; 5] {
+    use CharacterAbility::*;
+
+    let millis = |d: &std::time::Duration| d.as_millis().to_string();
+    let projectile_damage = |projectile: &comp::Projectile| {
+        projectile
+            .hit_entity
+            .iter()
+            .find_map(|effect| match effect {
+                projectile::Effect::Damage(damage) => Some(damage.to_string()),
+                projectile::Effect::Explode { power } => Some(format!("Explode({})", power)),
+                _ => None,
+            })
+            .unwrap_or_default()
+    };
+
+    match ability {
+        BasicMelee {
+            energy_cost,
+            buildup_duration,
+            recover_duration,
+            base_healthchange,
+            ..
+        } => [
+            "BasicMelee".to_string(),
+            energy_cost.to_string(),
+            base_healthchange.to_string(),
+            millis(buildup_duration),
+            millis(recover_duration),
+        ],
+        BasicRanged {
+            energy_cost,
+            prepare_duration,
+            recover_duration,
+            projectile,
+            ..
+        } => [
+            "BasicRanged".to_string(),
+            energy_cost.to_string(),
+            projectile_damage(projectile),
+            millis(prepare_duration),
+            millis(recover_duration),
+        ],
+        Boost { duration, .. } => [
+            "Boost".to_string(),
+            String::new(),
+            String::new(),
+            millis(duration),
+            String::new(),
+        ],
+        DashMelee {
+            energy_cost,
+            buildup_duration,
+            recover_duration,
+            base_damage,
+        } => [
+            "DashMelee".to_string(),
+            energy_cost.to_string(),
+            base_damage.to_string(),
+            millis(buildup_duration),
+            millis(recover_duration),
+        ],
+        BasicBlock => [
+            "BasicBlock".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+        ],
+        Roll => [
+            "Roll".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+        ],
+        TripleStrike { base_damage, .. } => [
+            "TripleStrike".to_string(),
+            String::new(),
+            base_damage.to_string(),
+            String::new(),
+            String::new(),
+        ],
+        LeapMelee {
+            energy_cost,
+            buildup_duration,
+            recover_duration,
+            base_damage,
+            ..
+        } => [
+            "LeapMelee".to_string(),
+            energy_cost.to_string(),
+            base_damage.to_string(),
+            millis(buildup_duration),
+            millis(recover_duration),
+        ],
+        SpinMelee {
+            energy_cost,
+            buildup_duration,
+            recover_duration,
+            base_damage,
+        } => [
+            "SpinMelee".to_string(),
+            energy_cost.to_string(),
+            base_damage.to_string(),
+            millis(buildup_duration),
+            millis(recover_duration),
+        ],
+        ChargedRanged {
+            energy_cost,
+            initial_damage,
+            max_damage,
+            prepare_duration,
+            recover_duration,
+            ..
+        } => [
+            "ChargedRanged".to_string(),
+            energy_cost.to_string(),
+            format!("{}-{}", initial_damage, max_damage),
+            millis(prepare_duration),
+            millis(recover_duration),
+        ],
+    }
+}
+
+fn ability_stats() -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_path("abilitystats.csv")?;
+    wtr.write_record(&[
+        "Path",
+        "Name",
+        "Slot",
+        "Ability",
+        "Energy Cost",
+        "Damage",
+        "Buildup (ms)",
+        "Recover (ms)",
+    ])?;
+
+    for folder in
+        assets::read_dir("common.items.weapons").expect("Failed to iterate over weapon folders!")
+    {
+        match folder {
+            Ok(folder) => {
+                for file in std::fs::read_dir(folder.path())?.filter_map(|f| f.ok()) {
+                    let asset_identifier = &file
+                        .path()
+                        .components()
+                        .skip_while(|s| s != &Component::Normal(&OsString::from("common")))
+                        .collect::<PathBuf>()
+                        .with_extension("")
+                        .display()
+                        .to_string()
+                        .replace("/", ".");
+                    let asset = assets::load_expect_cloned::<comp::Item>(asset_identifier);
+
+                    match &asset.kind {
+                        comp::item::ItemKind::Tool(tool) => {
+                            for (slot, ability) in tool.get_abilities().iter().enumerate() {
+                                let [ability, energy_cost, damage, buildup, recover] =
+                                    ability_record(ability);
+                                wtr.write_record(&[
+                                    asset_identifier,
+                                    asset.name(),
+                                    &(slot + 1).to_string(),
+                                    &ability,
+                                    &energy_cost,
+                                    &damage,
+                                    &buildup,
+                                    &recover,
+                                ])?;
+                            }
+                        },
+                        // Skip non-weapons
+                        _ => println!("Skipping non-weapon item: {:?}", asset),
+                    }
+                }
+            },
+            Err(e) => println!("Skipping folder due to {}", e),
         }
-    } else {
-        println!("Invalid argument, available arguments:\n\"armor_stats\"\n\"weapon_stats\"")
+    }
+
+    wtr.flush()?;
+    Ok(())
+}
+
+fn main() {
+    let result = match Cli::from_args() {
+        Cli::ArmorStats => armor_stats(),
+        Cli::WeaponStats => weapon_stats(),
+        Cli::AbilityStats => ability_stats(),
+        Cli::WorldPreview(opts) => world_preview(opts),
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(1);
     }
 }
@@ -0,0 +1,192 @@
+//! Generate a world without a game client and export images of it, so seeds
+//! and worldgen changes can be compared on machines without a GPU.
+
+use common::{
+    msg::PoiKind,
+    terrain::{BiomeKind, TerrainChunkSize},
+    vol::RectVolSize,
+};
+use image::{Rgb, RgbImage};
+use std::{error::Error, fs, path::PathBuf, time::Instant};
+use structopt::StructOpt;
+use vek::*;
+use world::{
+    sim::{FileOpts, GenOpts, RiverKind, SimChunk, WorldOpts},
+    World,
+};
+
+#[derive(StructOpt)]
+pub struct WorldPreviewOpts {
+    /// World seed (defaults to the default server seed)
+    #[structopt(long, default_value = "59686")]
+    seed: u32,
+    /// Base two logarithm of the world size in chunks, per dimension
+    #[structopt(long)]
+    size_lg: Option<u32>,
+    /// Altitude of the sea surface, in blocks
+    #[structopt(long)]
+    sea_level: Option<f32>,
+    /// Scale of continents relative to the default
+    #[structopt(long)]
+    continent_scale: Option<f64>,
+    /// Number of erosion simulation steps
+    #[structopt(long)]
+    erosion_steps: Option<usize>,
+    /// Multiplier for the number of sites
+    #[structopt(long)]
+    site_density: Option<f32>,
+    /// Load the world from this map file instead of generating it
+    #[structopt(long, parse(from_os_str))]
+    map_file: Option<PathBuf>,
+    /// Directory the images are written to
+    #[structopt(long, default_value = "world_preview", parse(from_os_str))]
+    out: PathBuf,
+}
+
+impl WorldPreviewOpts {
+    fn gen_opts(&self) -> GenOpts {
+        let default = GenOpts::default();
+        GenOpts {
+            map_size_lg: self.size_lg.map_or(default.map_size_lg, Vec2::broadcast),
+            sea_level: self.sea_level.unwrap_or(default.sea_level),
+            continent_scale: self.continent_scale.unwrap_or(default.continent_scale),
+            erosion_steps: self.erosion_steps.unwrap_or(default.erosion_steps),
+            site_density: self.site_density.unwrap_or(default.site_density),
+        }
+    }
+}
+
+const TOWN_COLOR: Rgb<u8> = Rgb([255, 200, 40]);
+const DUNGEON_COLOR: Rgb<u8> = Rgb([220, 20, 20]);
+const CASTLE_COLOR: Rgb<u8> = Rgb([200, 200, 230]);
+
+fn biome_color(biome: BiomeKind) -> Rgb<u8> {
+    match biome {
+        BiomeKind::Void => Rgb([0, 0, 0]),
+        BiomeKind::Grassland => Rgb([110, 180, 60]),
+        BiomeKind::Ocean => Rgb([30, 60, 150]),
+        BiomeKind::Mountain => Rgb([130, 120, 110]),
+        BiomeKind::Snowlands => Rgb([235, 240, 250]),
+        BiomeKind::Desert => Rgb([220, 200, 120]),
+        BiomeKind::Swamp => Rgb([70, 90, 50]),
+        BiomeKind::Forest => Rgb([30, 110, 40]),
+    }
+}
+
+/// Greyscale land height, or blue water depth
+fn topography_color(chunk: &SimChunk, sea_level: f32, max_height: f32) -> Rgb<u8> {
+    if chunk.water_alt > chunk.alt {
+        let depth = ((chunk.water_alt - chunk.alt) / 64.0).min(1.0);
+        Rgb([
+            0,
+            (80.0 * (1.0 - depth)) as u8,
+            (255.0 - 155.0 * depth) as u8,
+        ])
+    } else {
+        let height = ((chunk.alt - sea_level) / max_height).max(0.0).min(1.0);
+        let v = (40.0 + 215.0 * height) as u8;
+        Rgb([v, v, v])
+    }
+}
+
+fn ways_color(chunk: &SimChunk, sea_level: f32, max_height: f32) -> Rgb<u8> {
+    match chunk.river.river_kind {
+        Some(RiverKind::Ocean) => Rgb([20, 40, 110]),
+        Some(RiverKind::Lake { .. }) => Rgb([40, 90, 200]),
+        Some(RiverKind::River { .. }) => Rgb([90, 170, 255]),
+        None if chunk.path.0.is_way() => Rgb([160, 100, 40]),
+        None if chunk.cave.0.is_way() => Rgb([150, 60, 160]),
+        None => {
+            // Faded topography, so rivers and ways stand out
+            let Rgb([v, _, _]) = topography_color(chunk, sea_level, max_height);
+            let v = v / 2;
+            Rgb([v, v, v])
+        },
+    }
+}
+
+pub fn world_preview(opts: WorldPreviewOpts) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(&opts.out)?;
+
+    let start = Instant::now();
+    let (world, index) = World::generate(opts.seed, WorldOpts {
+        seed_elements: true,
+        world_file: match &opts.map_file {
+            Some(path) => FileOpts::Load(path.clone()),
+            None => FileOpts::Generate,
+        },
+        gen_opts: opts.gen_opts(),
+    });
+    let gen_time = start.elapsed();
+
+    let start = Instant::now();
+    let map = world.get_map_data(index.as_index_ref());
+    let map_time = start.elapsed();
+
+    let sim = world.sim();
+    let size = sim.get_size();
+    let sea_level = sim.sea_level();
+    let max_height = sim.max_height;
+
+    // Images are drawn with north at the top, one pixel per chunk
+    let chunk_at = |x: u32, y: u32| sim.get(Vec2::new(x, size.y - 1 - y).map(|e| e as i32));
+    let draw = |name: &str, color: &dyn Fn(&SimChunk) -> Rgb<u8>| {
+        RgbImage::from_fn(size.x, size.y, |x, y| {
+            chunk_at(x, y).map_or(Rgb([0, 0, 0]), color)
+        })
+        .save(opts.out.join(name))
+    };
+
+    draw("topography.png", &|chunk| {
+        topography_color(chunk, sea_level, max_height)
+    })?;
+    draw("biomes.png", &|chunk| {
+        biome_color(chunk.get_biome(sea_level))
+    })?;
+    draw("ways.png", &|chunk| {
+        ways_color(chunk, sea_level, max_height)
+    })?;
+
+    // The exact colors sent to clients for their map
+    let map_image = RgbImage::from_fn(size.x, size.y, |x, y| {
+        let [r, g, b, _] = map.rgba[((size.y - 1 - y) * size.x + x) as usize].to_le_bytes();
+        Rgb([r, g, b])
+    });
+    map_image.save(opts.out.join("map.png"))?;
+
+    let mut sites_image = map_image;
+    for poi in map.pois.iter() {
+        let color = match poi.kind {
+            PoiKind::Town => TOWN_COLOR,
+            PoiKind::Dungeon => DUNGEON_COLOR,
+            PoiKind::Castle => CASTLE_COLOR,
+        };
+        let chunk_pos = poi
+            .wpos
+            .map2(TerrainChunkSize::RECT_SIZE, |e, sz: u32| e / sz as i32);
+        for offs in (-1..=1).flat_map(|x| (-1..=1).map(move |y| Vec2::new(x, y))) {
+            let pos = chunk_pos + offs;
+            if pos.x >= 0 && pos.y >= 0 && pos.x < size.x as i32 && pos.y < size.y as i32 {
+                sites_image.put_pixel(pos.x as u32, size.y - 1 - pos.y as u32, color);
+            }
+        }
+    }
+    sites_image.save(opts.out.join("sites.png"))?;
+
+    let count = |kind| map.pois.iter().filter(|poi| poi.kind == kind).count();
+    let gen_opts = sim.gen_opts();
+    println!("seed: {}", opts.seed);
+    println!("size: {}x{} chunks", size.x, size.y);
+    println!("sea level: {}", gen_opts.sea_level);
+    println!("continent scale: {}", gen_opts.continent_scale);
+    println!("erosion steps: {}", gen_opts.erosion_steps);
+    println!("site density: {}", gen_opts.site_density);
+    println!("generation time: {:.2}s", gen_time.as_secs_f64());
+    println!("map time: {:.2}s", map_time.as_secs_f64());
+    println!("towns: {}", count(PoiKind::Town));
+    println!("dungeons: {}", count(PoiKind::Dungeon));
+    println!("castles: {}", count(PoiKind::Castle));
+    println!("images written to: {}", opts.out.display());
+
+    Ok(())
+}
@@ -392,7 +392,7 @@ impl Animation for SwimWieldAnimation {
                     Quaternion::rotation_x(u_slow * 0.2) * Quaternion::rotation_z(u_slowalt * 0.1);
                 next.control.scale = Vec3::one();
             },
-            Some(ToolKind::Farming(_)) => {
+            Some(ToolKind::Farming(_)) | Some(ToolKind::Pickaxe(_)) => {
                 if velocity < 0.5 {
                     next.head.orientation = Quaternion::rotation_z(head_look.x)
                         * Quaternion::rotation_x(-0.2 + head_look.y.abs());
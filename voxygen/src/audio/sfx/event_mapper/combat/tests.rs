@@ -114,6 +114,7 @@ fn matches_ability_stage() {
         &CharacterState::TripleStrike(states::triple_strike::Data {
             base_damage: 10,
             stage: states::triple_strike::Stage::First,
+            stages: 3,
             stage_time_active: Duration::default(),
             stage_exhausted: false,
             initialized: true,
@@ -155,6 +156,7 @@ fn ignores_different_ability_stage() {
         &CharacterState::TripleStrike(states::triple_strike::Data {
             base_damage: 10,
             stage: states::triple_strike::Stage::Second,
+            stages: 3,
             stage_time_active: Duration::default(),
             stage_exhausted: false,
             initialized: true,
@@ -0,0 +1,208 @@
+use super::{
+    img_ids::{Imgs, ImgsRot},
+    item_imgs::ItemImgs,
+    slots::{ContainerSlot, SlotManager},
+    TEXT_COLOR, UI_MAIN,
+};
+use crate::{
+    i18n::VoxygenLocalization,
+    ui::{
+        fonts::ConrodVoxygenFonts,
+        slot::{ContentSize, SlotMaker},
+        ImageFrame, Tooltip, TooltipManager, Tooltipable,
+    },
+};
+use common::comp::Inventory;
+use conrod_core::{
+    color,
+    widget::{self, Button, Rectangle, Text},
+    widget_ids, Color, Colorable, Positionable, Sizeable, Widget, WidgetCommon,
+};
+use vek::Vec2;
+
+widget_ids! {
+    pub struct Ids {
+        bg,
+        close,
+        title,
+        title_bg,
+        slots_align,
+        slots[],
+    }
+}
+
+/// Number of slots per row in the container window
+const SLOTS_PER_ROW: usize = 9;
+
+/// Window showing the contents of the container the player has open
+#[derive(WidgetCommon)]
+pub struct Container<'a> {
+    inventory: &'a Inventory,
+    imgs: &'a Imgs,
+    item_imgs: &'a ItemImgs,
+    fonts: &'a ConrodVoxygenFonts,
+    rot_imgs: &'a ImgsRot,
+    tooltip_manager: &'a mut TooltipManager,
+    slot_manager: &'a mut SlotManager,
+    localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
+    #[conrod(common_builder)]
+    common: widget::CommonBuilder,
+}
+
+impl<'a> Container<'a> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        inventory: &'a Inventory,
+        imgs: &'a Imgs,
+        item_imgs: &'a ItemImgs,
+        fonts: &'a ConrodVoxygenFonts,
+        rot_imgs: &'a ImgsRot,
+        tooltip_manager: &'a mut TooltipManager,
+        slot_manager: &'a mut SlotManager,
+        localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
+    ) -> Self {
+        Self {
+            inventory,
+            imgs,
+            item_imgs,
+            fonts,
+            rot_imgs,
+            tooltip_manager,
+            slot_manager,
+            localized_strings,
+            common: widget::CommonBuilder::default(),
+        }
+    }
+}
+
+pub struct State {
+    ids: Ids,
+}
+
+pub enum Event {
+    Close,
+}
+
+impl<'a> Widget for Container<'a> {
+    type Event = Option<Event>;
+    type State = State;
+    type Style = ();
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+        }
+    }
+
+    #[allow(clippy::unused_unit)] // TODO: Pending review in #587
+    fn style(&self) -> Self::Style { () }
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs { state, ui, .. } = args;
+
+        let mut event = None;
+
+        let rows = (self.inventory.len() + SLOTS_PER_ROW - 1) / SLOTS_PER_ROW;
+        let width = SLOTS_PER_ROW as f64 * 40.0 + 20.0;
+        let height = rows as f64 * 40.0 + 60.0;
+
+        // Tooltips
+        let item_tooltip = Tooltip::new({
+            // Edge images [t, b, r, l]
+            // Corner images [tr, tl, br, bl]
+            let edge = &self.rot_imgs.tt_side;
+            let corner = &self.rot_imgs.tt_corner;
+            ImageFrame::new(
+                [edge.cw180, edge.none, edge.cw270, edge.cw90],
+                [corner.none, corner.cw270, corner.cw90, corner.cw180],
+                Color::Rgba(0.08, 0.07, 0.04, 1.0),
+                5.0,
+            )
+        })
+        .title_font_size(self.fonts.cyri.scale(15))
+        .parent(ui.window)
+        .desc_font_size(self.fonts.cyri.scale(12))
+        .title_text_color(TEXT_COLOR)
+        .font_id(self.fonts.cyri.conrod_id)
+        .desc_text_color(TEXT_COLOR);
+
+        // BG, placed to the left of the bag
+        Rectangle::fill_with([width, height], Color::Rgba(0.0, 0.0, 0.0, 0.8))
+            .bottom_right_with_margins_on(ui.window, 60.0, 434.0)
+            .set(state.ids.bg, ui);
+
+        // Title
+        Text::new(&self.localized_strings.get("hud.container"))
+            .mid_top_with_margin_on(state.ids.bg, 9.0)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(20))
+            .color(Color::Rgba(0.0, 0.0, 0.0, 1.0))
+            .set(state.ids.title_bg, ui);
+        Text::new(&self.localized_strings.get("hud.container"))
+            .top_left_with_margins_on(state.ids.title_bg, -2.0, -2.0)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(20))
+            .color(TEXT_COLOR)
+            .set(state.ids.title, ui);
+
+        // Close button
+        if Button::image(self.imgs.close_btn)
+            .w_h(24.0, 25.0)
+            .hover_image(self.imgs.close_btn_hover)
+            .press_image(self.imgs.close_btn_press)
+            .top_right_with_margins_on(state.ids.bg, 0.0, 0.0)
+            .set(state.ids.close, ui)
+            .was_clicked()
+        {
+            event = Some(Event::Close);
+        }
+
+        // Slots
+        Rectangle::fill_with([width - 20.0, height - 60.0], color::TRANSPARENT)
+            .mid_bottom_with_margin_on(state.ids.bg, 10.0)
+            .set(state.ids.slots_align, ui);
+        if state.ids.slots.len() < self.inventory.len() {
+            state.update(|s| {
+                s.ids
+                    .slots
+                    .resize(self.inventory.len(), &mut ui.widget_id_generator());
+            });
+        }
+        let mut slot_maker = SlotMaker {
+            empty_slot: self.imgs.inv_slot,
+            filled_slot: self.imgs.inv_slot,
+            selected_slot: self.imgs.inv_slot_sel,
+            background_color: Some(UI_MAIN),
+            content_size: ContentSize {
+                width_height_ratio: 1.0,
+                max_fraction: 0.75,
+            },
+            selected_content_scale: 1.067,
+            amount_font: self.fonts.cyri.conrod_id,
+            amount_margins: Vec2::new(-4.0, 0.0),
+            amount_font_size: self.fonts.cyri.scale(12),
+            amount_text_color: TEXT_COLOR,
+            content_source: self.inventory,
+            image_source: self.item_imgs,
+            slot_manager: Some(self.slot_manager),
+        };
+        for (i, item) in self.inventory.slots().iter().enumerate() {
+            let x = i % SLOTS_PER_ROW;
+            let y = i / SLOTS_PER_ROW;
+
+            let slot_widget = slot_maker
+                .fabricate(ContainerSlot(i), [40.0; 2])
+                .top_left_with_margins_on(state.ids.slots_align, y as f64 * 40.0, x as f64 * 40.0);
+            if let Some(item) = item {
+                let (title, desc) = super::util::item_text(item);
+                slot_widget
+                    .with_tooltip(self.tooltip_manager, title, &*desc, &item_tooltip)
+                    .set(state.ids.slots[i], ui);
+            } else {
+                slot_widget.set(state.ids.slots[i], ui);
+            }
+        }
+
+        event
+    }
+}
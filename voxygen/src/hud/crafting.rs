@@ -8,12 +8,13 @@ use crate::{
     ui::{fonts::ConrodVoxygenFonts, ImageFrame, Tooltip, TooltipManager, Tooltipable},
 };
 use client::{self, Client};
-use common::comp::Inventory;
+use common::{comp::Inventory, recipe::Recipe};
 use conrod_core::{
     color,
     widget::{self, Button, Image, Rectangle, Scrollbar, Text},
     widget_ids, Color, Colorable, Labelable, Positionable, Sizeable, Widget, WidgetCommon,
 };
+use std::time::Duration;
 
 widget_ids! {
     pub struct Ids {
@@ -37,6 +38,8 @@ widget_ids! {
         ingredient_img[],
         req_text[],
         ingredients_txt,
+        station_txt,
+        craft_time_txt,
         output_img_frame,
         output_img,
     }
@@ -268,6 +271,21 @@ impl<'a> Widget for Crafting<'a> {
             },
         }
 
+        // Repair recipes are named after the kind of item they repair, since any
+        // worn item of that kind can be repaired
+        let localized_strings = self.localized_strings;
+        let recipe_title = |recipe: &Recipe| {
+            if recipe.repair {
+                localized_strings.get("hud.crafting.repair").replace(
+                    "{item}",
+                    super::util::item_kind(&recipe.output.0)
+                        .unwrap_or_else(|| recipe.output.0.name()),
+                )
+            } else {
+                recipe.output.0.name().to_string()
+            }
+        };
+
         // Recipe list
         for (i, (name, recipe, can_perform)) in recipe_iter.enumerate() {
             let button = Button::image(
@@ -292,7 +310,7 @@ impl<'a> Widget for Crafting<'a> {
                 button.mid_bottom_with_margin_on(state.ids.recipe_names[i - 1], -25.0)
             };
             if button
-                .label(recipe.output.0.name())
+                .label(&recipe_title(recipe))
                 .w_h(130.0, 20.0)
                 .hover_image(self.imgs.selection_hover)
                 .press_image(self.imgs.selection_press)
@@ -341,50 +359,56 @@ impl<'a> Widget for Crafting<'a> {
             .and_then(|r| self.client.recipe_book().get(r.as_str()))
         {
             // Title
-            Text::new(&recipe.output.0.name().to_string())
+            Text::new(&recipe_title(recipe))
                 .mid_top_with_margin_on(state.ids.align_ing, -22.0)
                 .font_id(self.fonts.cyri.conrod_id)
                 .font_size(self.fonts.cyri.scale(14))
                 .color(TEXT_COLOR)
                 .parent(state.ids.window)
                 .set(state.ids.title_ing, ui);
+            // Tools are shown like ingredients with an amount of 0
+            let ingredient_count = recipe.inputs().len() + recipe.tools().len();
             // Ingredient images with tooltip
-            if state.ids.ingredient_frame.len() < recipe.inputs().len() {
+            if state.ids.ingredient_frame.len() < ingredient_count {
                 state.update(|state| {
                     state
                         .ids
                         .ingredient_frame
-                        .resize(recipe.inputs().len(), &mut ui.widget_id_generator())
+                        .resize(ingredient_count, &mut ui.widget_id_generator())
                 });
             };
-            if state.ids.ingredients.len() < recipe.inputs().len() {
+            if state.ids.ingredients.len() < ingredient_count {
                 state.update(|state| {
                     state
                         .ids
                         .ingredients
-                        .resize(recipe.inputs().len(), &mut ui.widget_id_generator())
+                        .resize(ingredient_count, &mut ui.widget_id_generator())
                 });
             };
-            if state.ids.ingredient_img.len() < recipe.inputs().len() {
+            if state.ids.ingredient_img.len() < ingredient_count {
                 state.update(|state| {
                     state
                         .ids
                         .ingredient_img
-                        .resize(recipe.inputs().len(), &mut ui.widget_id_generator())
+                        .resize(ingredient_count, &mut ui.widget_id_generator())
                 });
             };
-            if state.ids.req_text.len() < recipe.inputs().len() {
+            if state.ids.req_text.len() < ingredient_count {
                 state.update(|state| {
                     state
                         .ids
                         .req_text
-                        .resize(recipe.inputs().len(), &mut ui.widget_id_generator())
+                        .resize(ingredient_count, &mut ui.widget_id_generator())
                 });
             };
             // Widget generation for every ingredient
-            for (i, (item, amount)) in recipe.inputs.iter().enumerate() {
+            let ingredients = recipe
+                .inputs()
+                .chain(recipe.tools().map(|tool| (tool, 0)))
+                .collect::<Vec<_>>();
+            for (i, &(item, amount)) in ingredients.iter().enumerate() {
                 // Grey color for images and text if their amount is too low to craft the item
-                let col = if self.inventory.item_count(item) as f32 / *amount as f32 >= 1.0 {
+                let col = if self.inventory.item_count(item) as f32 / amount as f32 >= 1.0 {
                     TEXT_COLOR
                 } else {
                     TEXT_DULL_RED_COLOR
@@ -399,13 +423,13 @@ impl<'a> Widget for Crafting<'a> {
                 // Catalysts/Tools"
                 let frame_offset = if i == 0 {
                     10.0
-                } else if *amount == 0 {
+                } else if amount == 0 {
                     5.0
                 } else {
                     0.0
                 };
                 let frame = Image::new(self.imgs.inv_slot).w_h(25.0, 25.0);
-                let frame = if *amount == 0 {
+                let frame = if amount == 0 {
                     frame.down_from(state.ids.req_text[i], 10.0 + frame_offset)
                 } else {
                     frame.down_from(frame_pos, 10.0 + frame_offset)
@@ -426,7 +450,7 @@ impl<'a> Widget for Crafting<'a> {
                 let in_inv: &str = &self.inventory.item_count(item).to_string();
                 // Show Ingredients
                 // Align "Required" Text below last ingredient
-                if *amount == 0 {
+                if amount == 0 {
                     // Catalysts/Tools
                     Text::new(&self.localized_strings.get("hud.crafting.tool_cata"))
                         .down_from(state.ids.ingredient_frame[i - 1], 20.0)
@@ -461,6 +485,41 @@ impl<'a> Widget for Crafting<'a> {
                         .set(state.ids.ingredients[i], ui);
                 }
             }
+            // Crafting station, red if there is none nearby
+            let mut last_txt = ingredient_count
+                .checked_sub(1)
+                .map_or(state.ids.ingredients_txt, |i| state.ids.ingredient_frame[i]);
+            if let Some(station) = recipe.station {
+                Text::new(
+                    &self
+                        .localized_strings
+                        .get("hud.crafting.station")
+                        .replace("{station}", station.name()),
+                )
+                .down_from(last_txt, 20.0)
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(14))
+                .color(if recipe.has_station(self.client.nearby_stations()) {
+                    TEXT_COLOR
+                } else {
+                    TEXT_DULL_RED_COLOR
+                })
+                .set(state.ids.station_txt, ui);
+                last_txt = state.ids.station_txt;
+            }
+            if recipe.craft_time > Duration::default() {
+                Text::new(
+                    &self
+                        .localized_strings
+                        .get("hud.crafting.craft_time")
+                        .replace("{time}", &format!("{:.1}", recipe.craft_time.as_secs_f32())),
+                )
+                .down_from(last_txt, 10.0)
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(14))
+                .color(TEXT_COLOR)
+                .set(state.ids.craft_time_txt, ui);
+            }
         }
 
         let ids = &state.ids;
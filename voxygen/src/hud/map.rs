@@ -4,15 +4,15 @@ use super::{
 };
 use crate::{
     i18n::VoxygenLocalization,
-    ui::{fonts::ConrodVoxygenFonts, img_ids, ImageSlider},
+    ui::{fonts::ConrodVoxygenFonts, img_ids, ImageSlider, ToggleButton},
     GlobalState,
 };
 use client::{self, Client};
-use common::{comp, terrain::TerrainChunkSize, vol::RectVolSize};
+use common::{comp, msg::PoiKind, terrain::TerrainChunkSize, vol::RectVolSize};
 use conrod_core::{
     color, position,
     widget::{self, Button, Image, Rectangle, Text},
-    widget_ids, Colorable, Positionable, Sizeable, Widget, WidgetCommon,
+    widget_ids, Color, Colorable, Positionable, Sizeable, Widget, WidgetCommon,
 };
 use specs::WorldExt;
 use vek::*;
@@ -31,9 +31,30 @@ widget_ids! {
         map_title,
         qlog_title,
         zoom_slider,
+        filter_title,
+        show_towns_box,
+        show_towns_text,
+        show_dungeons_box,
+        show_dungeons_text,
+        show_castles_box,
+        show_castles_text,
+        show_waypoint_box,
+        show_waypoint_text,
+        poi_markers[],
+        poi_names[],
+        waypoint_marker,
+        waypoint_name,
     }
 }
 
+/// Size (in pixels) of the map image
+const MAP_SIZE: f64 = 760.0;
+
+const TOWN_COLOR: Color = Color::Rgba(0.9, 0.75, 0.3, 1.0);
+const DUNGEON_COLOR: Color = Color::Rgba(0.75, 0.1, 0.1, 1.0);
+const CASTLE_COLOR: Color = Color::Rgba(0.55, 0.55, 0.65, 1.0);
+const WAYPOINT_COLOR: Color = Color::Rgba(0.3, 0.8, 1.0, 1.0);
+
 #[derive(WidgetCommon)]
 pub struct Map<'a> {
     _show: &'a Show,
@@ -82,6 +103,10 @@ pub struct State {
 
 pub enum Event {
     MapZoom(f64),
+    ShowTowns(bool),
+    ShowDungeons(bool),
+    ShowCastles(bool),
+    ShowWaypoint(bool),
     Close,
 }
 
@@ -149,6 +174,69 @@ impl<'a> Widget for Map<'a> {
             .color(TEXT_COLOR)
             .set(state.ids.qlog_title, ui);
 
+        // Marker filters
+        Text::new(self.localized_strings.get("hud.map.filters"))
+            .top_left_with_margins_on(state.ids.qlog_align, 50.0, 15.0)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(18))
+            .color(TEXT_COLOR)
+            .set(state.ids.filter_title, ui);
+
+        let gameplay = &self.global_state.settings.gameplay;
+        let filters = [
+            (
+                gameplay.map_show_towns,
+                Event::ShowTowns as fn(bool) -> Event,
+                "hud.map.towns",
+                state.ids.show_towns_box,
+                state.ids.show_towns_text,
+            ),
+            (
+                gameplay.map_show_dungeons,
+                Event::ShowDungeons,
+                "hud.map.dungeons",
+                state.ids.show_dungeons_box,
+                state.ids.show_dungeons_text,
+            ),
+            (
+                gameplay.map_show_castles,
+                Event::ShowCastles,
+                "hud.map.castles",
+                state.ids.show_castles_box,
+                state.ids.show_castles_text,
+            ),
+            (
+                gameplay.map_show_waypoint,
+                Event::ShowWaypoint,
+                "hud.map.waypoint",
+                state.ids.show_waypoint_box,
+                state.ids.show_waypoint_text,
+            ),
+        ];
+        let mut prev_id = state.ids.filter_title;
+        for (shown, event, label, box_id, text_id) in filters.iter() {
+            let show = ToggleButton::new(*shown, self.imgs.checkbox, self.imgs.checkbox_checked)
+                .w_h(18.0, 18.0)
+                .down_from(prev_id, 10.0)
+                .align_left_of(state.ids.filter_title)
+                .hover_images(self.imgs.checkbox_mo, self.imgs.checkbox_checked_mo)
+                .press_images(self.imgs.checkbox_press, self.imgs.checkbox_checked)
+                .set(*box_id, ui);
+
+            if show != *shown {
+                events.push(event(show));
+            }
+
+            Text::new(self.localized_strings.get(label))
+                .right_from(*box_id, 10.0)
+                .font_size(self.fonts.cyri.scale(14))
+                .font_id(self.fonts.cyri.conrod_id)
+                .graphics_for(*box_id)
+                .color(TEXT_COLOR)
+                .set(*text_id, ui);
+            prev_id = *box_id;
+        }
+
         // X-Button
         if Button::image(self.imgs.close_button)
             .w_h(24.0, 25.0)
@@ -207,7 +295,7 @@ impl<'a> Widget for Map<'a> {
         );
         Image::new(world_map.none)
             .mid_top_with_margin_on(state.ids.map_align, 10.0)
-            .w_h(760.0, 760.0)
+            .w_h(MAP_SIZE, MAP_SIZE)
             .parent(state.ids.bg)
             .source_rectangle(rect_src)
             .set(state.ids.grid, ui);
@@ -246,6 +334,99 @@ impl<'a> Widget for Map<'a> {
             .parent(ui.window)
             .set(state.ids.indicator, ui);
 
+        // Offset of a world position from the center of the map in pixels, if it is
+        // within the visible part of the map
+        let map_offset = |wpos: Vec2<f32>| {
+            let offset =
+                (wpos - Vec2::from(player_pos)).map2(TerrainChunkSize::RECT_SIZE, |e, sz: u32| {
+                    e as f64 / sz as f64
+                }) * MAP_SIZE
+                    / w_src;
+            offset
+                .map(|e| e.abs() < MAP_SIZE / 2.0)
+                .reduce_and()
+                .then_some(offset)
+        };
+
+        // Points of interest
+        let pois = self
+            .client
+            .pois
+            .iter()
+            .filter(|poi| match poi.kind {
+                PoiKind::Town => gameplay.map_show_towns,
+                PoiKind::Dungeon => gameplay.map_show_dungeons,
+                PoiKind::Castle => gameplay.map_show_castles,
+            })
+            .filter_map(|poi| Some((poi, map_offset(poi.wpos.map(|e| e as f32))?)))
+            .collect::<Vec<_>>();
+        if state.ids.poi_markers.len() < pois.len() {
+            state.update(|s| {
+                s.ids
+                    .poi_markers
+                    .resize(pois.len(), &mut ui.widget_id_generator());
+                s.ids
+                    .poi_names
+                    .resize(pois.len(), &mut ui.widget_id_generator());
+            });
+        }
+        for (i, (poi, offset)) in pois.iter().enumerate() {
+            let color = match poi.kind {
+                PoiKind::Town => TOWN_COLOR,
+                PoiKind::Dungeon => DUNGEON_COLOR,
+                PoiKind::Castle => CASTLE_COLOR,
+            };
+            let name = match poi.difficulty {
+                Some(difficulty) => format!(
+                    "{} ({})",
+                    poi.name,
+                    self.localized_strings
+                        .get("hud.map.difficulty")
+                        .replace("{difficulty}", &difficulty.to_string())
+                ),
+                None => poi.name.clone(),
+            };
+            Rectangle::fill_with([8.0, 8.0], color)
+                .x_y_position_relative_to(
+                    state.ids.grid,
+                    position::Relative::Scalar(offset.x),
+                    position::Relative::Scalar(offset.y),
+                )
+                .parent(state.ids.grid)
+                .set(state.ids.poi_markers[i], ui);
+            Text::new(&name)
+                .down_from(state.ids.poi_markers[i], 2.0)
+                .font_size(self.fonts.cyri.scale(12))
+                .font_id(self.fonts.cyri.conrod_id)
+                .color(color)
+                .parent(state.ids.grid)
+                .set(state.ids.poi_names[i], ui);
+        }
+
+        // Waypoint
+        if let Some(offset) = self
+            .client
+            .waypoint()
+            .filter(|_| gameplay.map_show_waypoint)
+            .and_then(|wpos| map_offset(wpos.xy()))
+        {
+            Rectangle::fill_with([10.0, 10.0], WAYPOINT_COLOR)
+                .x_y_position_relative_to(
+                    state.ids.grid,
+                    position::Relative::Scalar(offset.x),
+                    position::Relative::Scalar(offset.y),
+                )
+                .parent(state.ids.grid)
+                .set(state.ids.waypoint_marker, ui);
+            Text::new(self.localized_strings.get("hud.map.waypoint"))
+                .down_from(state.ids.waypoint_marker, 2.0)
+                .font_size(self.fonts.cyri.scale(12))
+                .font_id(self.fonts.cyri.conrod_id)
+                .color(WAYPOINT_COLOR)
+                .parent(state.ids.grid)
+                .set(state.ids.waypoint_name, ui);
+        }
+
         events
     }
 }
@@ -1,6 +1,7 @@
 mod bag;
 mod buttons;
 mod chat;
+mod container;
 mod crafting;
 mod esc_menu;
 mod group;
@@ -221,6 +222,7 @@ widget_ids! {
         small_window,
         social_window,
         crafting_window,
+        container_window,
         settings_window,
         group_window,
 
@@ -284,6 +286,10 @@ pub enum Event {
     ChangeFOV(u16),
     ChangeGamma(f32),
     MapZoom(f64),
+    MapShowTowns(bool),
+    MapShowDungeons(bool),
+    MapShowCastles(bool),
+    MapShowWaypoint(bool),
     AdjustWindowSize([u16; 2]),
     ToggleParticlesEnabled(bool),
     ToggleFullscreen,
@@ -321,6 +327,7 @@ pub enum Event {
     ChangeAutoWalkBehavior(PressBehavior),
     ChangeStopAutoWalkOnInput(bool),
     CraftRecipe(String),
+    CloseContainer,
     InviteMember(common::sync::Uid),
     AcceptInvite,
     DeclineInvite,
@@ -571,6 +578,8 @@ pub struct Hud {
     hotbar: hotbar::State,
     events: Vec<Event>,
     crosshair_opacity: f32,
+    // Position of the container that was open during the last update
+    open_container: Option<Vec3<i32>>,
 }
 
 impl Hud {
@@ -667,6 +676,7 @@ impl Hud {
             hotbar: hotbar_state,
             events: Vec::new(),
             crosshair_opacity: 0.0,
+            open_container: None,
         }
     }
 
@@ -1680,6 +1690,13 @@ impl Hud {
             None => {},
         }
 
+        // Open the bag together with a newly opened container
+        let container_pos = client.container().map(|(pos, _)| pos);
+        if container_pos.is_some() && container_pos != self.open_container {
+            self.show.bag(true);
+        }
+        self.open_container = container_pos;
+
         // Bag contents
         if self.show.bag {
             if let Some(player_stats) = stats.get(client.entity()) {
@@ -1707,6 +1724,27 @@ impl Hud {
                 }
             }
         }
+        // Container contents
+        if let Some((_, inventory)) = client.container() {
+            if !self.show.bag {
+                // Closing the bag also closes the container
+                events.push(Event::CloseContainer);
+            } else if let Some(container::Event::Close) = container::Container::new(
+                inventory,
+                &self.imgs,
+                &self.item_imgs,
+                &self.fonts,
+                &self.rot_imgs,
+                tooltip_manager,
+                &mut self.slot_manager,
+                &self.voxygen_i18n,
+            )
+            .set(self.ids.container_window, ui_widgets)
+            {
+                events.push(Event::CloseContainer);
+            }
+        }
+
         // Skillbar
         // Get player stats
         let ecs = client.state().ecs();
@@ -2069,6 +2107,18 @@ impl Hud {
                     map::Event::MapZoom(map_zoom) => {
                         events.push(Event::MapZoom(map_zoom));
                     },
+                    map::Event::ShowTowns(show) => {
+                        events.push(Event::MapShowTowns(show));
+                    },
+                    map::Event::ShowDungeons(show) => {
+                        events.push(Event::MapShowDungeons(show));
+                    },
+                    map::Event::ShowCastles(show) => {
+                        events.push(Event::MapShowCastles(show));
+                    },
+                    map::Event::ShowWaypoint(show) => {
+                        events.push(Event::MapShowWaypoint(show));
+                    },
                 }
             }
         }
@@ -2151,6 +2201,7 @@ impl Hud {
                 Inventory(i) => Some(Slot::Inventory(i.0)),
                 Equip(e) => Some(Slot::Equip(e)),
                 Hotbar(_) => None,
+                Container(c) => Some(Slot::Container(c.0)),
             };
             match event {
                 slot::Event::Dragged(a, b) => {
@@ -20,6 +20,7 @@ pub enum SlotKind {
     Inventory(InventorySlot),
     Equip(EquipSlot),
     Hotbar(HotbarSlot),
+    Container(ContainerSlot),
     /* Spellbook(SpellbookSlot), TODO */
 }
 
@@ -53,6 +54,24 @@ impl SlotKey<Inventory, ItemImgs> for InventorySlot {
     }
 }
 
+/// A slot of the container the player has open
+#[derive(Clone, Copy, PartialEq)]
+pub struct ContainerSlot(pub usize);
+
+impl SlotKey<Inventory, ItemImgs> for ContainerSlot {
+    type ImageKey = ItemKey;
+
+    fn image_key(&self, source: &Inventory) -> Option<(Self::ImageKey, Option<Color>)> {
+        InventorySlot(self.0).image_key(source)
+    }
+
+    fn amount(&self, source: &Inventory) -> Option<u32> { InventorySlot(self.0).amount(source) }
+
+    fn image_id(key: &Self::ImageKey, source: &ItemImgs) -> image::Id {
+        InventorySlot::image_id(key, source)
+    }
+}
+
 impl SlotKey<Loadout, ItemImgs> for EquipSlot {
     type ImageKey = ItemKey;
 
@@ -170,4 +189,8 @@ impl From<HotbarSlot> for SlotKind {
     fn from(hotbar: HotbarSlot) -> Self { Self::Hotbar(hotbar) }
 }
 
+impl From<ContainerSlot> for SlotKind {
+    fn from(container: ContainerSlot) -> Self { Self::Container(container) }
+}
+
 impl SumSlot for SlotKind {}
@@ -1,7 +1,7 @@
 use common::comp::item::{
     armor::{Armor, ArmorKind, Protection},
     tool::{Tool, ToolKind},
-    Item, ItemKind,
+    Durability, Item, ItemKind,
 };
 use std::borrow::Cow;
 
@@ -20,8 +20,10 @@ pub fn loadout_slot_text<'a>(
 
 pub fn item_text<'a>(item: &'a Item) -> (&'_ str, Cow<'a, str>) {
     let desc = match &item.kind {
-        ItemKind::Armor(armor) => Cow::Owned(armor_desc(&armor, item.description())),
-        ItemKind::Tool(tool) => Cow::Owned(tool_desc(&tool, item.description())),
+        ItemKind::Armor(armor) => {
+            Cow::Owned(armor_desc(&armor, item.durability(), item.description()))
+        },
+        ItemKind::Tool(tool) => Cow::Owned(tool_desc(&tool, item.durability(), item.description())),
         /*ItemKind::Consumable(kind, effect, ..) => {
             Cow::Owned(consumable_desc(consumable, item.description()))
         },*/
@@ -34,10 +36,17 @@ pub fn item_text<'a>(item: &'a Item) -> (&'_ str, Cow<'a, str>) {
 
     (item.name(), desc)
 }
-// Armor Description
-fn armor_desc(armor: &Armor, desc: &str) -> String {
+/// Name of the kind of armor or tool an item is, e.g. "Chest" or "Sword"
+pub fn item_kind(item: &Item) -> Option<&'static str> {
+    match &item.kind {
+        ItemKind::Armor(armor) => Some(armor_kind(armor)),
+        ItemKind::Tool(tool) => Some(tool_kind(tool)),
+        _ => None,
+    }
+}
+fn armor_kind(armor: &Armor) -> &'static str {
     // TODO: localization
-    let kind = match armor.kind {
+    match armor.kind {
         ArmorKind::Shoulder(_) => "Shoulders",
         ArmorKind::Chest(_) => "Chest",
         ArmorKind::Belt(_) => "Belt",
@@ -49,7 +58,40 @@ fn armor_desc(armor: &Armor, desc: &str) -> String {
         ArmorKind::Neck(_) => "Neck",
         ArmorKind::Head(_) => "Head",
         ArmorKind::Tabard(_) => "Tabard",
-    };
+    }
+}
+fn tool_kind(tool: &Tool) -> &'static str {
+    // TODO: localization
+    match tool.kind {
+        ToolKind::Sword(_) => "Sword",
+        ToolKind::Axe(_) => "Axe",
+        ToolKind::Hammer(_) => "Hammer",
+        ToolKind::Bow(_) => "Bow",
+        ToolKind::Dagger(_) => "Dagger",
+        ToolKind::Staff(_) => "Staff",
+        ToolKind::Shield(_) => "Shield",
+        ToolKind::Debug(_) => "Debug",
+        ToolKind::Farming(_) => "Farming Tool",
+        ToolKind::Pickaxe(_) => "Pickaxe",
+        ToolKind::Empty => "Empty",
+    }
+}
+// Durability line of armor and tool descriptions
+fn durability_desc(durability: Option<Durability>) -> String {
+    // TODO: localization
+    match durability {
+        Some(durability) if durability.is_broken() => "\n\nDurability: Broken".to_string(),
+        Some(durability) => format!(
+            "\n\nDurability: {}/{}",
+            durability.current(),
+            durability.max()
+        ),
+        None => String::new(),
+    }
+}
+// Armor Description
+fn armor_desc(armor: &Armor, durability: Option<Durability>, desc: &str) -> String {
+    let kind = armor_kind(armor);
     let armor = match armor.get_protection() {
         Protection::Normal(a) => a.to_string(),
         Protection::Invincible => "Inf".to_string(),
@@ -62,30 +104,23 @@ fn armor_desc(armor: &Armor, desc: &str) -> String {
         desc
     };
 
+    let durability = durability_desc(durability);
+
     if !desc.is_empty() {
         format!(
-            "{}\n\nArmor: {}\n\n{}\n\n<Right-Click to use>",
-            kind, armor, desc
+            "{}\n\nArmor: {}{}\n\n{}\n\n<Right-Click to use>",
+            kind, armor, durability, desc
         )
     } else {
-        format!("{}\n\nArmor: {}\n\n<Right-Click to use>", kind, armor)
+        format!(
+            "{}\n\nArmor: {}{}\n\n<Right-Click to use>",
+            kind, armor, durability
+        )
     }
 }
 // Weapon/Tool Description
-fn tool_desc(tool: &Tool, desc: &str) -> String {
-    // TODO: localization
-    let kind = match tool.kind {
-        ToolKind::Sword(_) => "Sword",
-        ToolKind::Axe(_) => "Axe",
-        ToolKind::Hammer(_) => "Hammer",
-        ToolKind::Bow(_) => "Bow",
-        ToolKind::Dagger(_) => "Dagger",
-        ToolKind::Staff(_) => "Staff",
-        ToolKind::Shield(_) => "Shield",
-        ToolKind::Debug(_) => "Debug",
-        ToolKind::Farming(_) => "Farming Tool",
-        ToolKind::Empty => "Empty",
-    };
+fn tool_desc(tool: &Tool, durability: Option<Durability>, desc: &str) -> String {
+    let kind = tool_kind(tool);
     let power = tool.base_power();
 
     // TODO: remove when legacy descriptions are fixed by persistence overhaul
@@ -95,18 +130,22 @@ fn tool_desc(tool: &Tool, desc: &str) -> String {
         desc
     };
 
+    let durability = durability_desc(durability);
+
     if !desc.is_empty() {
         format!(
-            "{}\n\nPower: {:0.1}\n\n{}\n\n<Right-Click to use>",
+            "{}\n\nPower: {:0.1}{}\n\n{}\n\n<Right-Click to use>",
             kind,
             power * 10.0,
+            durability,
             desc
         )
     } else {
         format!(
-            "{}\n\nPower: {:0.1}\n\n<Right-Click to use>",
+            "{}\n\nPower: {:0.1}{}\n\n<Right-Click to use>",
             kind,
-            power * 10.0
+            power * 10.0,
+            durability
         )
     }
 }
@@ -109,6 +109,13 @@ impl PlayState for MainMenuState {
                             client::Error::AuthServerNotTrusted => localized_strings
                                 .get("main.login.untrusted_auth_server")
                                 .into(),
+                            client::Error::IncompatibleProtocol { client, server } => {
+                                localized_strings
+                                    .get("main.login.incompatible_protocol")
+                                    .replace("{client}", &client.to_string())
+                                    .replace("{oldest}", &server.oldest_supported.to_string())
+                                    .replace("{current}", &server.current.to_string())
+                            },
                             client::Error::ServerWentMad => localized_strings
                                 .get("main.login.outdated_client_or_server")
                                 .into(),
@@ -88,6 +88,7 @@ impl Vertex {
     }
 }
 
+#[derive(Copy, Clone, Debug)]
 pub enum ParticleMode {
     CampfireSmoke = 0,
     CampfireFire = 1,
@@ -98,6 +99,8 @@ pub enum ParticleMode {
     FireworkPurple = 6,
     FireworkRed = 7,
     FireworkYellow = 8,
+    Rain = 9,
+    Snow = 10,
 }
 
 impl ParticleMode {
@@ -11,6 +11,7 @@ use common::{
     comp::{item::Reagent, object, Body, CharacterState, Pos},
     figure::Segment,
     outcome::Outcome,
+    weather::WeatherGrid,
 };
 use dot_vox::DotVoxData;
 use hashbrown::HashMap;
@@ -19,6 +20,11 @@ use specs::{Join, WorldExt};
 use std::time::Duration;
 use vek::*;
 
+/// Rain and snow particles spawned every 10ms in the heaviest weather
+const WEATHER_PARTICLES: f32 = 3.0;
+/// Distance around the player that rain and snow are spawned in, in blocks
+const WEATHER_RADIUS: f32 = 24.0;
+
 pub struct ParticleMgr {
     /// keep track of lifespans
     particles: Vec<Particle>,
@@ -95,6 +101,7 @@ impl ParticleMgr {
             // add new Particle
             self.maintain_body_particles(scene_data);
             self.maintain_boost_particles(scene_data);
+            self.maintain_weather_particles(scene_data);
         } else {
             // remove all particle lifespans
             self.particles.clear();
@@ -245,6 +252,38 @@ impl ParticleMgr {
         }
     }
 
+    fn maintain_weather_particles(&mut self, scene_data: &SceneData) {
+        let state = scene_data.state;
+        let ecs = state.ecs();
+        let time = state.get_time();
+        let mut rng = rand::thread_rng();
+
+        let player_pos = match ecs.read_storage::<Pos>().get(scene_data.player_entity) {
+            Some(pos) => pos.0,
+            None => return,
+        };
+        let weather = ecs
+            .read_resource::<WeatherGrid>()
+            .get_interpolated(player_pos.xy());
+        let heartbeats = self.scheduler.heartbeats(Duration::from_millis(10));
+
+        for (mode, intensity, lifespan) in &[
+            (ParticleMode::Rain, weather.rain, Duration::from_secs(2)),
+            (ParticleMode::Snow, weather.snow, Duration::from_secs(10)),
+        ] {
+            let count = (f32::from(heartbeats) * intensity * WEATHER_PARTICLES).round() as usize;
+            self.particles.extend((0..count).map(|_| {
+                let offs = Vec2::<f32>::zero().map(|_| rng.gen_range(-1.0, 1.0) * WEATHER_RADIUS);
+                Particle::new(
+                    *lifespan,
+                    time,
+                    *mode,
+                    player_pos + Vec3::new(offs.x, offs.y, 20.0),
+                )
+            }));
+        }
+    }
+
     fn upload_particles(&mut self, renderer: &mut Renderer) {
         let all_cpu_instances = self
             .particles
@@ -251,6 +251,10 @@ fn sprite_config_for(kind: BlockKind) -> Option<SpriteConfig> {
             variations: 6,
             wind_sway: 0.1,
         }),
+        BlockKind::Sprout => Some(SpriteConfig {
+            variations: 3,
+            wind_sway: 0.1,
+        }),
         BlockKind::Coconut => Some(SpriteConfig {
             variations: 1,
             wind_sway: 0.0,
@@ -379,6 +383,22 @@ fn sprite_config_for(kind: BlockKind) -> Option<SpriteConfig> {
             variations: 10,
             wind_sway: 0.2,
         }),
+        BlockKind::Anvil => Some(SpriteConfig {
+            variations: 1,
+            wind_sway: 0.0,
+        }),
+        BlockKind::Cauldron => Some(SpriteConfig {
+            variations: 1,
+            wind_sway: 0.0,
+        }),
+        BlockKind::CraftingBench => Some(SpriteConfig {
+            variations: 1,
+            wind_sway: 0.0,
+        }),
+        BlockKind::BossChest => Some(SpriteConfig {
+            variations: 2,
+            wind_sway: 0.0,
+        }),
         _ => None,
     }
 }
@@ -1208,6 +1228,18 @@ impl<V: RectRasterableVol> Terrain<V> {
                 Vec3::new(-7.0, -5.0, -0.0),
                 Vec3::one(),
             ),
+            make_models(
+                (BlockKind::BossChest, 0),
+                "voxygen.voxel.sprite.chests.chest_skull",
+                Vec3::new(-7.0, -5.0, -0.0),
+                Vec3::one(),
+            ),
+            make_models(
+                (BlockKind::BossChest, 1),
+                "voxygen.voxel.sprite.chests.chest_demon",
+                Vec3::new(-7.0, -5.0, -0.0),
+                Vec3::one(),
+            ),
             //Welwitch
             make_models(
                 (BlockKind::Welwitch, 0),
@@ -1854,6 +1886,25 @@ impl<V: RectRasterableVol> Terrain<V> {
                 Vec3::new(-5.5, -5.5, -0.25),
                 Vec3::one(),
             ),
+            // Sprouts
+            make_models(
+                (BlockKind::Sprout, 0),
+                "voxygen.voxel.sprite.grass.grass_short_1",
+                Vec3::new(-6.0, -6.0, 0.0),
+                Vec3::unit_z() * 0.5,
+            ),
+            make_models(
+                (BlockKind::Sprout, 1),
+                "voxygen.voxel.sprite.grass.grass_short_2",
+                Vec3::new(-6.0, -6.0, 0.0),
+                Vec3::unit_z() * 0.5,
+            ),
+            make_models(
+                (BlockKind::Sprout, 2),
+                "voxygen.voxel.sprite.grass.grass_short_3",
+                Vec3::new(-6.0, -6.0, 0.0),
+                Vec3::unit_z() * 0.5,
+            ),
             // Coconut
             make_models(
                 (BlockKind::Coconut, 0),
@@ -2375,6 +2426,26 @@ impl<V: RectRasterableVol> Terrain<V> {
                 Vec3::new(-2.5, -2.5, 0.0),
                 Vec3::one(),
             ),
+            // Crafting stations
+            make_models(
+                (BlockKind::Anvil, 0),
+                "voxygen.voxel.object.anvil",
+                Vec3::new(-3.0, -7.0, 0.0),
+                Vec3::one(),
+            ),
+            make_models(
+                (BlockKind::Cauldron, 0),
+                "voxygen.voxel.object.cauldron",
+                Vec3::new(-10.0, -10.0, 0.0),
+                Vec3::one(),
+            ),
+            // TODO: Dedicated workbench model
+            make_models(
+                (BlockKind::CraftingBench, 0),
+                "voxygen.voxel.sprite.furniture.table_side-0",
+                Vec3::new(-5.5, -5.5, 0.0),
+                Vec3::one(),
+            ),
         ]
         .into_iter()
         .collect();
@@ -104,6 +104,32 @@ impl SessionState {
         self.key_state.auto_walk = false;
     }
 
+    /// How far a freely flying camera moves this frame when flown in `dir`.
+    /// Acceleration is applied so that the movement feels more natural and
+    /// controlled.
+    fn freefly_offset(&mut self, dir: Vec3<f32>, dt: f32) -> Vec3<f32> {
+        const FREEFLY_ACCEL: f32 = 120.0;
+        const FREEFLY_DAMPING: f32 = 80.0;
+        const FREEFLY_MAX_SPEED: f32 = 50.0;
+
+        if self.freefly_vel.magnitude_squared() > 0.01 {
+            let new_vel = self.freefly_vel - self.freefly_vel.normalized() * (FREEFLY_DAMPING * dt);
+            if self.freefly_vel.dot(new_vel) > 0.0 {
+                self.freefly_vel = new_vel;
+            } else {
+                self.freefly_vel = Vec3::zero();
+            }
+        }
+        if dir.magnitude_squared() > 0.01 {
+            self.freefly_vel += dir * (FREEFLY_ACCEL * dt);
+            if self.freefly_vel.magnitude() > FREEFLY_MAX_SPEED {
+                self.freefly_vel = self.freefly_vel.normalized() * FREEFLY_MAX_SPEED;
+            }
+        }
+
+        self.freefly_vel * dt
+    }
+
     /// Tick the session (and the client attached to it).
     fn tick(
         &mut self,
@@ -203,7 +229,8 @@ impl PlayState for SessionState {
         // TODO: can this be a method on the session or are there borrowcheck issues?
 
         let client_state = self.client.borrow().get_client_state();
-        if let ClientState::Pending | ClientState::Character = client_state {
+        if let ClientState::Pending | ClientState::Character | ClientState::Spectator = client_state
+        {
             // Update MyEntity
             // Note: Alternatively, the client could emit an event when the entity changes
             // which may or may not be more elegant
@@ -260,14 +287,14 @@ impl PlayState for SessionState {
                 .get(self.client.borrow().entity())
                 .is_some();
 
-            // Only highlight collectables
+            // Only highlight collectables, containers and sealed doors
             self.scene.set_select_pos(select_pos.filter(|sp| {
                 self.client
                     .borrow()
                     .state()
                     .terrain()
                     .get(*sp)
-                    .map(|b| b.is_collectible() || can_build)
+                    .map(|b| b.is_collectible() || b.is_container() || b.is_sealed() || can_build)
                     .unwrap_or(false)
             }));
 
@@ -609,6 +636,29 @@ impl PlayState for SessionState {
             let (axis_right, axis_up) = (input_vec[0], input_vec[1]);
 
             match self.scene.camera().get_mode() {
+                camera::CameraMode::FirstPerson | camera::CameraMode::ThirdPerson
+                    if client_state == ClientState::Spectator =>
+                {
+                    // Spectators have no character, the movement keys fly their camera
+                    let forward = self.scene.camera().forward();
+                    let right = self.scene.camera().right();
+                    let dir = right * axis_right + forward * axis_up;
+                    let dt = global_state.clock.get_last_delta().as_secs_f32();
+                    let offset = self.freefly_offset(dir, dt);
+
+                    if offset != Vec3::zero() {
+                        let mut client = self.client.borrow_mut();
+                        let pos = client
+                            .state()
+                            .read_storage::<comp::Pos>()
+                            .get(client.entity())
+                            .map(|pos| pos.0);
+                        if let Some(pos) = pos {
+                            client.move_spectator_camera(pos + offset);
+                        }
+                    }
+                    self.inputs.move_dir = Vec2::zero();
+                }
                 camera::CameraMode::FirstPerson | camera::CameraMode::ThirdPerson => {
                     // Move the player character based on their walking direction.
                     // This could be different from the camera direction if free look is enabled.
@@ -618,37 +668,15 @@ impl PlayState for SessionState {
                 },
 
                 camera::CameraMode::Freefly => {
-                    // Move the camera freely in 3d space. Apply acceleration so that
-                    // the movement feels more natural and controlled.
-                    const FREEFLY_ACCEL: f32 = 120.0;
-                    const FREEFLY_DAMPING: f32 = 80.0;
-                    const FREEFLY_MAX_SPEED: f32 = 50.0;
-
+                    // Move the camera freely in 3d space.
                     let forward = self.scene.camera().forward();
                     let right = self.scene.camera().right();
                     let dir = right * axis_right + forward * axis_up;
-
                     let dt = global_state.clock.get_last_delta().as_secs_f32();
-                    if self.freefly_vel.magnitude_squared() > 0.01 {
-                        let new_vel = self.freefly_vel
-                            - self.freefly_vel.normalized() * (FREEFLY_DAMPING * dt);
-                        if self.freefly_vel.dot(new_vel) > 0.0 {
-                            self.freefly_vel = new_vel;
-                        } else {
-                            self.freefly_vel = Vec3::zero();
-                        }
-                    }
-                    if dir.magnitude_squared() > 0.01 {
-                        self.freefly_vel += dir * (FREEFLY_ACCEL * dt);
-                        if self.freefly_vel.magnitude() > FREEFLY_MAX_SPEED {
-                            self.freefly_vel = self.freefly_vel.normalized() * FREEFLY_MAX_SPEED;
-                        }
-                    }
+                    let offset = self.freefly_offset(dir, dt);
 
                     let pos = self.scene.camera().get_focus_pos();
-                    self.scene
-                        .camera_mut()
-                        .set_focus_pos(pos + self.freefly_vel * dt);
+                    self.scene.camera_mut().set_focus_pos(pos + offset);
 
                     // Do not apply any movement to the player character
                     self.inputs.move_dir = Vec2::zero();
@@ -940,6 +968,22 @@ impl PlayState for SessionState {
                         global_state.settings.gameplay.map_zoom = map_zoom;
                         global_state.settings.save_to_file_warn();
                     },
+                    HudEvent::MapShowTowns(show) => {
+                        global_state.settings.gameplay.map_show_towns = show;
+                        global_state.settings.save_to_file_warn();
+                    },
+                    HudEvent::MapShowDungeons(show) => {
+                        global_state.settings.gameplay.map_show_dungeons = show;
+                        global_state.settings.save_to_file_warn();
+                    },
+                    HudEvent::MapShowCastles(show) => {
+                        global_state.settings.gameplay.map_show_castles = show;
+                        global_state.settings.save_to_file_warn();
+                    },
+                    HudEvent::MapShowWaypoint(show) => {
+                        global_state.settings.gameplay.map_show_waypoint = show;
+                        global_state.settings.save_to_file_warn();
+                    },
                     HudEvent::ChangeGamma(new_gamma) => {
                         global_state.settings.graphics.gamma = new_gamma;
                         global_state.settings.save_to_file_warn();
@@ -1029,6 +1073,9 @@ impl PlayState for SessionState {
                     HudEvent::CraftRecipe(r) => {
                         self.client.borrow_mut().craft_recipe(&r);
                     },
+                    HudEvent::CloseContainer => {
+                        self.client.borrow_mut().close_container();
+                    },
                     HudEvent::InviteMember(uid) => {
                         self.client.borrow_mut().send_group_invite(uid);
                     },
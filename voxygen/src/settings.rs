@@ -513,6 +513,10 @@ pub struct GameplaySettings {
     pub auto_walk_behavior: PressBehavior,
     pub stop_auto_walk_on_input: bool,
     pub map_zoom: f64,
+    pub map_show_towns: bool,
+    pub map_show_dungeons: bool,
+    pub map_show_castles: bool,
+    pub map_show_waypoint: bool,
     pub loading_tips: bool,
 }
 
@@ -543,6 +547,10 @@ impl Default for GameplaySettings {
             auto_walk_behavior: PressBehavior::Toggle,
             stop_auto_walk_on_input: true,
             map_zoom: 4.0,
+            map_show_towns: true,
+            map_show_dungeons: true,
+            map_show_castles: true,
+            map_show_waypoint: true,
             loading_tips: true,
         }
     }
@@ -112,7 +112,7 @@ fn run_server(mut server: Server, rec: Receiver<Msg>, paused: Arc<AtomicBool>) {
     // Set up an fps clock
     let mut clock = Clock::start();
 
-    loop {
+    'running: loop {
         // Check any event such as stopping and pausing
         match rec.try_recv() {
             Ok(msg) => match msg {
@@ -143,6 +143,8 @@ fn run_server(mut server: Server, rec: Receiver<Msg>, paused: Arc<AtomicBool>) {
                 Event::ClientConnected { .. } => info!("Client connected!"),
                 Event::ClientDisconnected { .. } => info!("Client disconnected!"),
                 Event::Chat { entity: _, msg } => info!("[Client] {}", msg),
+                // The client is told about the shutdown and goes back to the main menu
+                Event::Shutdown { .. } => break 'running,
             }
         }
 
@@ -1,11 +1,11 @@
 #![allow(dead_code)]
 
 mod econ;
+mod travel;
 
 use self::{Occupation::*, Stock::*};
 use crate::{
-    config::CONFIG,
-    sim::WorldSim,
+    sim::{generate_name, WorldSim},
     site::{Castle, Dungeon, Settlement, Site as WorldSite},
     util::{attempt, seed_expan, MapVec, CARDINALS, NEIGHBORS},
     Index,
@@ -81,7 +81,9 @@ impl Civs {
     pub fn generate(seed: u32, sim: &mut WorldSim, index: &mut Index) -> Self {
         let mut this = Self::default();
         let rng = ChaChaRng::from_seed(seed_expan::rng_state(seed));
-        let initial_civ_count = initial_civ_count(sim.map_size_lg());
+        let initial_civ_count = (initial_civ_count(sim.map_size_lg()) as f32
+            * sim.gen_opts().site_density)
+            .round() as u32;
         let mut ctx = GenCtx { sim, rng };
 
         // TODO: Care about world size when generating caves.
@@ -134,6 +136,7 @@ impl Civs {
         }
 
         // Flatten ground around sites
+        let sea_level = ctx.sim.sea_level();
         for site in this.sites.values() {
             let radius = 48i32;
 
@@ -176,7 +179,7 @@ impl Civs {
                             // to worry about the case where water_alt is already set to a correct
                             // value higher than alt, since this chunk should have been filtered
                             // out in that case).
-                            chunk.water_alt = CONFIG.sea_level.max(chunk.water_alt + diff);
+                            chunk.water_alt = sea_level.max(chunk.water_alt + diff);
                             chunk.alt += diff;
                             chunk.basement += diff;
                             chunk.rockiness = 0.0;
@@ -198,7 +201,7 @@ impl Civs {
                 });
 
             let mut rng = ctx.reseed().rng;
-            let site = index.sites.insert(match &sim_site.kind {
+            let mut site = match &sim_site.kind {
                 SiteKind::Settlement => {
                     WorldSite::settlement(Settlement::generate(wpos, Some(ctx.sim), &mut rng))
                 },
@@ -208,7 +211,9 @@ impl Civs {
                 SiteKind::Castle => {
                     WorldSite::castle(Castle::generate(wpos, Some(ctx.sim), &mut rng))
                 },
-            });
+            };
+            site.name = generate_name(&mut rng);
+            let site = index.sites.insert(site);
             let site_ref = &index.sites[site];
 
             let radius_chunks =
@@ -296,6 +301,10 @@ impl Civs {
 
     pub fn sites(&self) -> impl Iterator<Item = &Site> + '_ { self.sites.values() }
 
+    pub fn site(&self, id: Id<Site>) -> &Site { self.sites.get(id) }
+
+    pub fn track(&self, id: Id<Track>) -> &Track { self.tracks.get(id) }
+
     #[allow(dead_code)]
     #[allow(clippy::print_literal)] // TODO: Pending review in #587
     fn display_info(&self) {
@@ -521,11 +530,20 @@ impl Civs {
                         }
 
                         // Take note of the track
-                        let track = self.tracks.insert(Track { cost, path });
+                        let track = self.tracks.insert(Track {
+                            cost,
+                            path,
+                            ends: (site, nearby),
+                        });
                         self.track_map
                             .entry(site)
                             .or_default()
                             .insert(nearby, track);
+                        for loc in self.tracks.get(track).path.nodes() {
+                            if let Some(chunk) = ctx.sim.get_mut(*loc) {
+                                chunk.track.get_or_insert(track);
+                            }
+                        }
                     }
                 }
             }
@@ -791,6 +809,16 @@ pub struct Track {
     /// costs.
     cost: f32,
     path: Path<Vec2<i32>>,
+    /// The sites at either end of the track
+    ends: (Id<Site>, Id<Site>),
+}
+
+impl Track {
+    pub fn cost(&self) -> f32 { self.cost }
+
+    pub fn path(&self) -> &Path<Vec2<i32>> { &self.path }
+
+    pub fn ends(&self) -> (Id<Site>, Id<Site>) { self.ends }
 }
 
 #[derive(Debug)]
@@ -866,6 +894,20 @@ pub enum SiteKind {
 }
 
 impl Site {
+    /// The traded good this site has the largest surplus of, which its traders
+    /// carry to other sites
+    pub fn top_export(&self) -> Option<Stock> {
+        TRADE_STOCKS
+            .iter()
+            .copied()
+            .filter(|stock| self.surplus[*stock] > 0.0)
+            .max_by(|a, b| {
+                self.surplus[*a]
+                    .partial_cmp(&self.surplus[*b])
+                    .unwrap_or(core::cmp::Ordering::Equal)
+            })
+    }
+
     #[allow(clippy::let_and_return)] // TODO: Pending review in #587
     pub fn simulate(&mut self, years: f32, nat_res: &NaturalResources) {
         // Insert natural resources into the economy
@@ -0,0 +1,298 @@
+//! Travellers on the tracks between sites
+//!
+//! Traders, caravans and patrols are spawned along the tracks connecting sites
+//! and follow them to the site at the other end. Traders carry the goods their
+//! home site has the largest surplus of. Bandits lie in wait on the remote
+//! stretches of road far away from any site.
+
+use super::{Civs, SiteKind, Stock, Track};
+use crate::sim::WorldSim;
+use common::{
+    assets,
+    comp::{self, humanoid, quadruped_medium, Item},
+    generation::{ChunkSupplement, EntityInfo},
+    hierarchical::ChunkPath,
+    store::Id,
+    terrain::TerrainChunkSize,
+    vol::RectVolSize,
+};
+use rand::prelude::*;
+use vek::*;
+
+/// Chance of a group of travellers being on a chunk of track when it is
+/// generated
+const TRAVELER_CHANCE: f32 = 1.0 / 40.0;
+/// Chance of bandits lying in wait on a remote chunk of track
+const AMBUSH_CHANCE: f32 = 1.0 / 60.0;
+/// Distance to the nearest site beyond which a road is remote, in chunks
+const REMOTE_DIST: f32 = 24.0;
+
+const GUARD_WEAPONS: &[&str] = &[
+    "common.items.weapons.sword.short_sword_0",
+    "common.items.weapons.axe.iron_axe-0",
+    "common.items.weapons.hammer.hammer_1",
+    "common.items.weapons.bow.wood_longbow-0",
+];
+
+const BANDIT_WEAPONS: &[&str] = &[
+    "common.items.weapons.sword.wood_sword",
+    "common.items.weapons.axe.worn_iron_axe-0",
+    "common.items.weapons.hammer.flimsy_hammer",
+    "common.items.weapons.bow.wood_shortbow-0",
+];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Encounter {
+    /// A lone trader carrying goods
+    Trader,
+    /// A trader with guards and pack animals laden with goods
+    Caravan,
+    /// Guards keeping the road safe
+    Patrol,
+    /// Bandits waiting for travellers to rob
+    Ambush,
+}
+
+/// The item standing in for a traded good
+fn goods_item(stock: Stock) -> &'static str {
+    match stock {
+        Stock::Wheat | Stock::Flour => "common.items.food.apple",
+        Stock::Meat | Stock::Fish | Stock::Game => "common.items.food.cheese",
+        Stock::Food => "common.items.food.apple_mushroom_curry",
+        Stock::Logs | Stock::Wood => "common.items.crafting_ing.twigs",
+        Stock::Rock | Stock::Stone => "common.items.crafting_ing.stones",
+    }
+}
+
+fn with_title(mut entity: EntityInfo, title: &str) -> EntityInfo {
+    entity.name = entity.name.map(|name| format!("{}, {}", name, title));
+    entity
+}
+
+/// The part of a track from `from` to the end closest to `dest`, or `None` if
+/// the track doesn't pass through `from`
+fn route_towards(nodes: &[Vec2<i32>], from: Vec2<i32>, dest: Vec2<i32>) -> Option<Vec<Vec2<i32>>> {
+    let idx = nodes.iter().position(|node| *node == from)?;
+    let forward = nodes.last()?.distance_squared(dest) <= nodes.first()?.distance_squared(dest);
+    Some(if forward {
+        nodes[idx..].to_vec()
+    } else {
+        nodes[..=idx].iter().rev().copied().collect()
+    })
+}
+
+/// The members of an encounter, gathered around `pos`. Travellers set off
+/// along `route`, bandits stay where they are.
+fn encounter(
+    rng: &mut impl Rng,
+    kind: Encounter,
+    pos: Vec3<f32>,
+    route: Option<&ChunkPath>,
+    goods: Option<Stock>,
+) -> Vec<EntityInfo> {
+    let mut members = 0;
+    let mut member_pos = || {
+        members += 1;
+        pos + Vec3::new((members % 3) as f32 - 1.0, (members / 3) as f32, 0.0) * 2.0
+    };
+    let cargo = || goods.map(|stock| assets::load_expect_cloned::<Item>(goods_item(stock)));
+    let person = |pos, alignment| {
+        EntityInfo::at(pos)
+            .with_alignment(alignment)
+            .with_body(comp::Body::Humanoid(humanoid::Body::random()))
+            .with_automatic_name()
+    };
+
+    let mut entities = Vec::new();
+    match kind {
+        Encounter::Trader => {
+            let mut trader = with_title(person(member_pos(), comp::Alignment::Npc), "Trader");
+            trader.loot_drop = cargo();
+            entities.push(trader);
+        },
+        Encounter::Caravan => {
+            let mut trader = with_title(person(member_pos(), comp::Alignment::Npc), "Merchant");
+            trader.loot_drop = cargo();
+            entities.push(trader);
+            for _ in 0..rng.gen_range(1, 3) {
+                let mut animal = EntityInfo::at(member_pos())
+                    .with_alignment(comp::Alignment::Tame)
+                    .with_body(comp::Body::QuadrupedMedium(
+                        quadruped_medium::Body::random_with(
+                            rng,
+                            &quadruped_medium::Species::Mouflon,
+                        ),
+                    ))
+                    .with_automatic_name();
+                animal.loot_drop = cargo();
+                entities.push(animal);
+            }
+            for _ in 0..rng.gen_range(1, 3) {
+                let weapon = *GUARD_WEAPONS.choose(rng).unwrap();
+                entities.push(with_title(
+                    person(member_pos(), comp::Alignment::Npc)
+                        .with_main_tool(assets::load_expect_cloned(weapon)),
+                    "Caravan Guard",
+                ));
+            }
+        },
+        Encounter::Patrol => {
+            for _ in 0..rng.gen_range(2, 4) {
+                let weapon = *GUARD_WEAPONS.choose(rng).unwrap();
+                entities.push(with_title(
+                    person(member_pos(), comp::Alignment::Npc)
+                        .with_main_tool(assets::load_expect_cloned(weapon)),
+                    "Road Warden",
+                ));
+            }
+        },
+        Encounter::Ambush => {
+            for _ in 0..rng.gen_range(2, 5) {
+                let weapon = *BANDIT_WEAPONS.choose(rng).unwrap();
+                entities.push(with_title(
+                    person(member_pos(), comp::Alignment::Enemy)
+                        .with_main_tool(assets::load_expect_cloned(weapon)),
+                    "Highwayman",
+                ));
+            }
+        },
+    }
+
+    if let Some(route) = route {
+        for entity in &mut entities {
+            entity.route = Some(route.clone());
+        }
+    }
+    entities
+}
+
+impl Civs {
+    /// Spawn the travellers, or the bandits waiting for them, on a chunk of
+    /// `track` at `pos`
+    pub fn apply_travelers_supplement(
+        &self,
+        rng: &mut impl Rng,
+        sim: &WorldSim,
+        chunk_pos: Vec2<i32>,
+        track: Id<Track>,
+        pos: Vec3<f32>,
+        supplement: &mut ChunkSupplement,
+    ) {
+        let track = self.tracks.get(track);
+        let (a, b) = track.ends;
+        let site_dist = |site| {
+            chunk_pos
+                .map(|e| e as f32)
+                .distance(self.sites.get(site).center.map(|e| e as f32))
+        };
+
+        if site_dist(a).min(site_dist(b)) > REMOTE_DIST && rng.gen::<f32>() < AMBUSH_CHANCE {
+            supplement
+                .entities
+                .extend(encounter(rng, Encounter::Ambush, pos, None, None));
+            return;
+        }
+
+        if rng.gen::<f32>() >= TRAVELER_CHANCE {
+            return;
+        }
+
+        let (from, to) = if rng.gen() { (a, b) } else { (b, a) };
+        let (from, to) = (self.sites.get(from), self.sites.get(to));
+        let route = match route_towards(track.path.nodes(), chunk_pos, to.center) {
+            Some(route) => route,
+            None => return,
+        };
+        let dest_alt = sim.get(to.center).map_or(pos.z, |chunk| chunk.alt);
+        let dest = Vec3::from(to.center.map2(TerrainChunkSize::RECT_SIZE, |e, sz: u32| {
+            (e * sz as i32 + sz as i32 / 2) as f32
+        })) + Vec3::unit_z() * dest_alt;
+        let route = ChunkPath::along(route, dest);
+
+        let kind = match (&from.kind, rng.gen_range(0, 4)) {
+            (SiteKind::Castle, _) | (_, 0) => Encounter::Patrol,
+            (_, 1) => Encounter::Caravan,
+            _ => Encounter::Trader,
+        };
+        supplement
+            .entities
+            .extend(encounter(rng, kind, pos, Some(&route), from.top_export()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaChaRng;
+
+    #[test]
+    fn test_route_towards() {
+        let nodes = (0..10).map(|x| Vec2::new(x, 0)).collect::<Vec<_>>();
+
+        let route = route_towards(&nodes, Vec2::new(4, 0), Vec2::new(9, 0)).unwrap();
+        assert_eq!(route.first(), Some(&Vec2::new(4, 0)));
+        assert_eq!(route.last(), Some(&Vec2::new(9, 0)));
+        assert_eq!(route.len(), 6);
+
+        let route = route_towards(&nodes, Vec2::new(4, 0), Vec2::new(0, 0)).unwrap();
+        assert_eq!(route, vec![
+            Vec2::new(4, 0),
+            Vec2::new(3, 0),
+            Vec2::new(2, 0),
+            Vec2::new(1, 0),
+            Vec2::new(0, 0),
+        ]);
+
+        assert!(route_towards(&nodes, Vec2::new(4, 1), Vec2::new(9, 0)).is_none());
+    }
+
+    #[test]
+    fn test_encounters() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let pos = Vec3::new(100.0, 100.0, 50.0);
+        let route = ChunkPath::along(vec![Vec2::new(3, 3), Vec2::new(4, 3)], Vec3::zero());
+
+        let trader = encounter(
+            &mut rng,
+            Encounter::Trader,
+            pos,
+            Some(&route),
+            Some(Stock::Wood),
+        );
+        assert_eq!(trader.len(), 1);
+        assert!(trader[0].loot_drop.is_some());
+        assert!(trader[0].route.is_some());
+
+        let caravan = encounter(
+            &mut rng,
+            Encounter::Caravan,
+            pos,
+            Some(&route),
+            Some(Stock::Food),
+        );
+        assert!(caravan.len() >= 3);
+        assert!(caravan.iter().all(|e| e.route.is_some()));
+        assert!(
+            caravan
+                .iter()
+                .any(|e| e.alignment == comp::Alignment::Tame && e.loot_drop.is_some())
+        );
+        assert!(
+            caravan
+                .iter()
+                .all(|e| !e.alignment.hostile_towards(comp::Alignment::Npc))
+        );
+
+        let patrol = encounter(&mut rng, Encounter::Patrol, pos, Some(&route), None);
+        assert!(patrol.len() >= 2);
+        assert!(patrol.iter().all(|e| e.alignment == comp::Alignment::Npc));
+
+        let ambush = encounter(&mut rng, Encounter::Ambush, pos, None, None);
+        assert!(ambush.len() >= 2);
+        assert!(
+            ambush
+                .iter()
+                .all(|e| e.alignment == comp::Alignment::Enemy && e.route.is_none())
+        );
+    }
+}
@@ -479,7 +479,7 @@ impl<'a> Sampler<'a> for ColumnGen<'a> {
 
         let downhill = sim_chunk.downhill;
         let downhill_pos = downhill.and_then(|downhill_pos| sim.get(downhill_pos));
-        debug_assert!(sim_chunk.water_alt >= CONFIG.sea_level);
+        debug_assert!(sim_chunk.water_alt >= sim.sea_level());
 
         let downhill_water_alt = downhill_pos
             .map(|downhill_chunk| {
@@ -488,7 +488,7 @@ impl<'a> Sampler<'a> for ColumnGen<'a> {
                     .min(sim_chunk.water_alt)
                     .max(sim_chunk.alt.min(sim_chunk.water_alt))
             })
-            .unwrap_or(CONFIG.sea_level);
+            .unwrap_or(sim.sea_level());
 
         let is_cliffs = sim_chunk.is_cliffs;
         let near_cliffs = sim_chunk.near_cliffs;
@@ -996,7 +996,7 @@ impl<'a> Sampler<'a> for ColumnGen<'a> {
 
         let near_ocean = max_river.and_then(|(_, _, river_data, _)| {
             if (river_data.is_lake() || river_data.river_kind == Some(RiverKind::Ocean))
-                && ((alt <= water_level.max(CONFIG.sea_level + 5.0) && !is_cliffs) || !near_cliffs)
+                && ((alt <= water_level.max(sim.sea_level() + 5.0) && !is_cliffs) || !near_cliffs)
             {
                 Some(water_level)
             } else {
@@ -1005,7 +1005,7 @@ impl<'a> Sampler<'a> for ColumnGen<'a> {
         });
 
         let ocean_level = if let Some(_sea_level) = near_ocean {
-            alt - CONFIG.sea_level
+            alt - sim.sea_level()
         } else {
             5.0
         };
@@ -106,6 +106,7 @@ impl IndexOwned {
 pub struct Noise {
     pub cave_nz: SuperSimplex,
     pub scatter_nz: SuperSimplex,
+    pub ore_nz: SuperSimplex,
 }
 
 impl Noise {
@@ -114,6 +115,7 @@ impl Noise {
         Self {
             cave_nz: SuperSimplex::new().set_seed(seed + 0),
             scatter_nz: SuperSimplex::new().set_seed(seed + 1),
+            ore_nz: SuperSimplex::new().set_seed(seed + 2),
         }
     }
 }
@@ -24,6 +24,9 @@ use vek::*;
 pub struct Colors {
     pub bridge: (u8, u8, u8),
     pub stalagtite: (u8, u8, u8),
+    pub copper_ore: (u8, u8, u8),
+    pub iron_ore: (u8, u8, u8),
+    pub coal: (u8, u8, u8),
 }
 
 fn close(x: f32, tgt: f32, falloff: f32) -> f32 {
@@ -330,6 +333,108 @@ pub fn apply_caves_to<'a>(
     }
 }
 
+/// Depth below the surface of mountains within which ore veins are found
+const MOUNTAIN_ORE_DEPTH: i32 = 24;
+/// Distance into the walls of caves within which ore veins are found
+const CAVE_ORE_DEPTH: f32 = 6.0;
+
+/// The ore found in a vein `depth` blocks below the surface. `choice` (between
+/// 0 and 1) picks between the ores found at that depth.
+fn ore_at(depth: f32, choice: f32) -> BlockKind {
+    if depth < 32.0 {
+        if choice < 0.6 {
+            BlockKind::Coal
+        } else {
+            BlockKind::CopperOre
+        }
+    } else if depth < 80.0 {
+        if choice < 0.4 {
+            BlockKind::CopperOre
+        } else if choice < 0.7 {
+            BlockKind::Coal
+        } else {
+            BlockKind::IronOre
+        }
+    } else if choice < 0.6 {
+        BlockKind::IronOre
+    } else {
+        BlockKind::CopperOre
+    }
+}
+
+/// Ore veins running through the rock of mountains and the walls of caves
+pub fn apply_ores_to<'a>(
+    wpos2d: Vec2<i32>,
+    mut get_column: impl FnMut(Vec2<i32>) -> Option<&'a ColumnSample<'a>>,
+    vol: &mut (impl BaseVol<Vox = Block> + RectSizedVol + ReadVol + WriteVol),
+    index: IndexRef,
+) {
+    let colors = &index.colors.layer;
+    for y in 0..vol.size_xy().y as i32 {
+        for x in 0..vol.size_xy().x as i32 {
+            let offs = Vec2::new(x, y);
+
+            let wpos2d = wpos2d + offs;
+
+            // Sample terrain
+            let col_sample = if let Some(col_sample) = get_column(offs) {
+                col_sample
+            } else {
+                continue;
+            };
+            let surface_z = col_sample.alt as i32;
+
+            // Veins are thicker in more rugged terrain
+            let richness = close(col_sample.chaos, 1.0, 0.7);
+            let mountain_range = if richness > 0.0 {
+                surface_z - MOUNTAIN_ORE_DEPTH..surface_z
+            } else {
+                0..0
+            };
+            let cave_range = col_sample
+                .cave
+                .filter(|(dist, _, cave, _)| *dist < cave.width + CAVE_ORE_DEPTH)
+                .map(|(_, _, cave, _)| {
+                    (cave.alt - cave.width - CAVE_ORE_DEPTH) as i32
+                        ..(cave.alt + cave.width + CAVE_ORE_DEPTH) as i32
+                })
+                .unwrap_or(0..0);
+
+            for z in mountain_range.chain(cave_range) {
+                let pos = Vec3::new(offs.x, offs.y, z);
+                // Leave soil and structures alone
+                let is_stone = vol
+                    .get(pos)
+                    .map(|b| matches!(b.kind(), BlockKind::Normal | BlockKind::Rock))
+                    .unwrap_or(false);
+                if !is_stone || z > surface_z - 3 {
+                    continue;
+                }
+
+                let wpos = Vec3::new(wpos2d.x, wpos2d.y, z);
+                let vein = index
+                    .noise
+                    .ore_nz
+                    .get(wpos.map(|e| e as f64 * 0.06).into_array())
+                    .abs();
+                if vein > 0.02 + richness as f64 * 0.03 {
+                    continue;
+                }
+
+                // Each stretch of rock holds one kind of ore
+                let choice = RandomField::new(index.seed + 3).get(wpos.map(|e| e.div_euclid(16)));
+                let kind = ore_at((surface_z - z) as f32, (choice % 1024) as f32 / 1024.0);
+                let color = match kind {
+                    BlockKind::CopperOre => colors.copper_ore,
+                    BlockKind::IronOre => colors.iron_ore,
+                    _ => colors.coal,
+                };
+                let _ = vol.set(pos, Block::new(kind, color.into()));
+            }
+        }
+    }
+}
+
 pub fn apply_caves_supplement<'a>(
     rng: &mut impl Rng,
     wpos2d: Vec2<i32>,
@@ -31,13 +31,14 @@ pub use index::{IndexOwned, IndexRef};
 use crate::{
     column::ColumnGen,
     index::Index,
+    site::SiteKind,
     util::{Grid, Sampler},
 };
 use common::{
     assets::{self, Asset},
     comp::{self, bird_medium, critter, quadruped_low, quadruped_medium, quadruped_small},
     generation::{ChunkSupplement, EntityInfo},
-    msg::server::WorldMapMsg,
+    msg::server::{Poi, PoiKind, WorldMapMsg},
     terrain::{Block, BlockKind, TerrainChunk, TerrainChunkMeta, TerrainChunkSize},
     vol::{ReadVol, RectVolSize, Vox, WriteVol},
 };
@@ -94,7 +95,28 @@ impl World {
         // TODO
     }
 
-    pub fn get_map_data(&self, index: IndexRef) -> WorldMapMsg { self.sim.get_map(index) }
+    pub fn get_map_data(&self, index: IndexRef) -> WorldMapMsg {
+        WorldMapMsg {
+            pois: index
+                .sites
+                .values()
+                .map(|site| Poi {
+                    kind: match &site.kind {
+                        SiteKind::Settlement(_) => PoiKind::Town,
+                        SiteKind::Dungeon(_) => PoiKind::Dungeon,
+                        SiteKind::Castle(_) => PoiKind::Castle,
+                    },
+                    name: site.name.clone(),
+                    wpos: site.get_origin(),
+                    difficulty: match &site.kind {
+                        SiteKind::Dungeon(d) => Some(d.difficulty()),
+                        _ => None,
+                    },
+                })
+                .collect(),
+            ..self.sim.get_map(index)
+        }
+    }
 
     pub fn sample_columns(
         &self,
@@ -147,7 +169,7 @@ impl World {
             None => {
                 return Ok((
                     TerrainChunk::new(
-                        CONFIG.sea_level as i32,
+                        self.sim.sea_level() as i32,
                         water,
                         air,
                         TerrainChunkMeta::void(),
@@ -157,7 +179,10 @@ impl World {
             },
         };
 
-        let meta = TerrainChunkMeta::new(sim_chunk.get_name(&self.sim), sim_chunk.get_biome());
+        let meta = TerrainChunkMeta::new(
+            sim_chunk.get_name(&self.sim),
+            sim_chunk.get_biome(self.sim.sea_level()),
+        );
 
         let mut chunk = TerrainChunk::new(base_z, stone, air, meta);
         for y in 0..TerrainChunkSize::RECT_SIZE.y as i32 {
@@ -208,6 +233,7 @@ impl World {
         layer::apply_scatter_to(chunk_wpos2d, sample_get, &mut chunk, index, sim_chunk);
         layer::apply_paths_to(chunk_wpos2d, sample_get, &mut chunk, index);
         layer::apply_caves_to(chunk_wpos2d, sample_get, &mut chunk, index);
+        layer::apply_ores_to(chunk_wpos2d, sample_get, &mut chunk, index);
 
         // Apply site generation
         sim_chunk.sites.iter().for_each(|site| {
@@ -268,6 +294,18 @@ impl World {
             &mut supplement,
         );
 
+        // Travellers and bandits on the roads between sites
+        if let Some(track) = sim_chunk.track {
+            self.civs.apply_travelers_supplement(
+                &mut rng,
+                &self.sim,
+                chunk_pos,
+                track,
+                gen_entity_pos(),
+                &mut supplement,
+            );
+        }
+
         // Apply site supplementary information
         sim_chunk.sites.iter().for_each(|site| {
             index.sites[*site].apply_supplement(&mut rng, chunk_wpos2d, sample_get, &mut supplement)
@@ -1,7 +1,7 @@
 use crate::{
     column::ColumnSample,
     sim::{RiverKind, WorldSim},
-    IndexRef, CONFIG,
+    IndexRef,
 };
 use common::{
     terrain::{
@@ -50,7 +50,7 @@ pub fn sample_wpos(config: &MapConfig, sampler: &WorldSim, wpos: Vec2<i32>) -> f
                 -f32::INFINITY
             })
         })
-        .unwrap_or(CONFIG.sea_level)
+        .unwrap_or(sampler.sea_level())
         - focus.z as f32)
         / gain as f32
 }
@@ -89,7 +89,7 @@ pub fn sample_pos(
         ..
     } = *config;
 
-    let true_sea_level = (CONFIG.sea_level as f64 - focus.z) / gain as f64;
+    let true_sea_level = (sampler.sea_level() as f64 - focus.z) / gain as f64;
 
     let (
         chunk_idx,
@@ -129,9 +129,9 @@ pub fn sample_pos(
         })
         .unwrap_or((
             None,
-            CONFIG.sea_level,
-            CONFIG.sea_level,
-            CONFIG.sea_level,
+            sampler.sea_level(),
+            sampler.sea_level(),
+            sampler.sea_level(),
             0.0,
             0.0,
             None,
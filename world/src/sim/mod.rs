@@ -7,6 +7,7 @@ mod way;
 
 // Reexports
 use self::erosion::Compute;
+pub(crate) use self::location::generate_name;
 pub use self::{
     diffusion::diffusion,
     erosion::{
@@ -25,7 +26,7 @@ pub use self::{
 use crate::{
     all::ForestKind,
     block::BlockGen,
-    civ::Place,
+    civ::{Place, Track},
     column::ColumnGen,
     site::Site,
     util::{seed_expan, FastNoise, RandomField, Sampler, StructureGen2d, LOCALITY, NEIGHBORS},
@@ -33,6 +34,7 @@ use crate::{
 };
 use common::{
     assets,
+    hierarchical::{NavChunk, NavGrid},
     msg::server::WorldMapMsg,
     store::Id,
     terrain::{
@@ -150,10 +152,46 @@ impl Default for FileOpts {
     fn default() -> Self { Self::Generate }
 }
 
+/// Parameters that control world generation.
+///
+/// These are recorded in saved world files; when a world file is loaded, the
+/// parameters it was generated with take precedence over the ones passed in.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GenOpts {
+    /// Base two logarithm of the world size, in chunks, per dimension.
+    pub map_size_lg: Vec2<u32>,
+    /// Altitude of the sea surface, in blocks.
+    pub sea_level: f32,
+    /// Scale of continents relative to the default.  Larger worlds generally
+    /// want larger continents.
+    pub continent_scale: f64,
+    /// Number of erosion simulation steps.  Fewer steps generate much faster,
+    /// at the cost of less realistic terrain and rivers.
+    pub erosion_steps: usize,
+    /// Multiplier for the number of civilisations and other sites (dungeons,
+    /// castles) placed in the world.
+    pub site_density: f32,
+}
+
+impl Default for GenOpts {
+    fn default() -> Self {
+        Self {
+            map_size_lg: DEFAULT_WORLD_CHUNKS_LG.vec(),
+            sea_level: CONFIG.sea_level,
+            continent_scale: 1.0,
+            erosion_steps: 100,
+            site_density: 1.0,
+        }
+    }
+}
+
 pub struct WorldOpts {
     /// Set to false to disable seeding elements during worldgen.
     pub seed_elements: bool,
     pub world_file: FileOpts,
+    /// Generation parameters, ignored if the world is loaded from a file.
+    pub gen_opts: GenOpts,
 }
 
 impl Default for WorldOpts {
@@ -161,6 +199,7 @@ impl Default for WorldOpts {
         Self {
             seed_elements: true,
             world_file: Default::default(),
+            gen_opts: GenOpts::default(),
         }
     }
 }
@@ -202,6 +241,19 @@ pub struct WorldMap_0_7_0 {
     pub basement: Box<[Alt]>,
 }
 
+/// Version of the world map intended for use in Veloren 0.8.0.
+#[derive(Serialize, Deserialize)]
+#[repr(C)]
+pub struct WorldMap_0_8_0 {
+    /// Saved generation parameters, including the map size and continent
+    /// scale.
+    pub gen_opts: GenOpts,
+    /// Saved altitude height map.
+    pub alt: Box<[Alt]>,
+    /// Saved basement height map.
+    pub basement: Box<[Alt]>,
+}
+
 /// Errors when converting a map to the most recent type (currently,
 /// shared by the various map types, but at some point we might switch to
 /// version-specific errors if it feels worthwhile).
@@ -241,11 +293,12 @@ pub enum WorldFileError {
 pub enum WorldFile {
     Veloren0_5_0(WorldMap_0_5_0) = 0,
     Veloren0_7_0(WorldMap_0_7_0) = 1,
+    Veloren0_8_0(WorldMap_0_8_0) = 2,
 }
 
 /// Data for the most recent map type.  Update this when you add a new map
 /// verson.
-pub type ModernMap = WorldMap_0_7_0;
+pub type ModernMap = WorldMap_0_8_0;
 
 /// The default world map.
 ///
@@ -308,9 +361,29 @@ impl WorldMap_0_5_0 {
 impl WorldMap_0_7_0 {
     #[inline]
     pub fn into_modern(self) -> Result<ModernMap, WorldFileError> {
+        // Maps from version 0.7.0 were always generated with the default values for
+        // the parameters that weren't saved.
+        let map = WorldMap_0_8_0 {
+            gen_opts: GenOpts {
+                map_size_lg: self.map_size_lg,
+                continent_scale: self.continent_scale_hack,
+                ..GenOpts::default()
+            },
+            alt: self.alt,
+            basement: self.basement,
+        };
+
+        map.into_modern()
+    }
+}
+
+impl WorldMap_0_8_0 {
+    #[inline]
+    pub fn into_modern(self) -> Result<ModernMap, WorldFileError> {
+        let map_size_lg = self.gen_opts.map_size_lg;
         if self.alt.len() != self.basement.len()
-            || self.alt.len() != (1 << (self.map_size_lg.x + self.map_size_lg.y))
-            || self.continent_scale_hack <= 0.0
+            || self.alt.len() != (1 << (map_size_lg.x + map_size_lg.y))
+            || self.gen_opts.continent_scale <= 0.0
         {
             return Err(WorldFileError::WorldSizeInvalid);
         }
@@ -325,7 +398,7 @@ impl WorldFile {
     /// variant we construct here to make sure we're using the latest map
     /// version.
 
-    pub fn new(map: ModernMap) -> Self { WorldFile::Veloren0_7_0(map) }
+    pub fn new(map: ModernMap) -> Self { WorldFile::Veloren0_8_0(map) }
 
     #[inline]
     /// Turns a WorldFile into the latest version.  Whenever a new map version
@@ -334,6 +407,7 @@ impl WorldFile {
         match self {
             WorldFile::Veloren0_5_0(map) => map.into_modern(),
             WorldFile::Veloren0_7_0(map) => map.into_modern(),
+            WorldFile::Veloren0_8_0(map) => map.into_modern(),
         }
     }
 }
@@ -342,6 +416,8 @@ pub struct WorldSim {
     pub seed: u32,
     /// Base 2 logarithm of the map size.
     map_size_lg: MapSizeLg,
+    /// The parameters the world was generated with.
+    gen_opts: GenOpts,
     /// Maximum height above sea level of any chunk in the map (not including
     /// post-erosion warping, cliffs, and other things like that).
     pub max_height: f32,
@@ -444,29 +520,41 @@ impl WorldSim {
             }
         })();
 
-        // NOTE: Change 1.0 to 4.0 for a 4x
-        // improvement in world detail.  We also use this to automatically adjust
-        // grid_scale (multiplying by 4.0) and multiply mins_per_sec by
-        // 1.0 / (4.0 * 4.0) in ./erosion.rs, in order to get a similar rate of river
-        // formation.
-        //
-        // FIXME: This is a hack!  At some point we will hae a more principled way of
-        // dealing with this.
-        let continent_scale_hack = 1.0/*4.0*/;
         let (parsed_world_file, map_size_lg) = parsed_world_file
-            .and_then(|map| match MapSizeLg::new(map.map_size_lg) {
+            .and_then(|map| match MapSizeLg::new(map.gen_opts.map_size_lg) {
                 Ok(map_size_lg) => Some((Some(map), map_size_lg)),
                 Err(e) => {
                     warn!("World size of map does not satisfy invariants: {:?}", e);
                     None
                 },
             })
-            .unwrap_or((None, DEFAULT_WORLD_CHUNKS_LG));
-        let continent_scale_hack = if let Some(map) = &parsed_world_file {
-            map.continent_scale_hack
+            .unwrap_or_else(|| {
+                let map_size_lg = MapSizeLg::new(opts.gen_opts.map_size_lg).unwrap_or_else(|e| {
+                    warn!(
+                        ?e,
+                        "Configured world size does not satisfy invariants, using the default size"
+                    );
+                    DEFAULT_WORLD_CHUNKS_LG
+                });
+                (None, map_size_lg)
+            });
+        let gen_opts = if let Some(map) = &parsed_world_file {
+            map.gen_opts.clone()
         } else {
-            continent_scale_hack
+            GenOpts {
+                map_size_lg: map_size_lg.vec(),
+                ..opts.gen_opts.clone()
+            }
         };
+        let sea_level = gen_opts.sea_level;
+        // NOTE: Set the continent scale to 4.0 for a 4x improvement in world detail.
+        // We also use this to automatically adjust grid_scale (multiplying by 4.0)
+        // and multiply mins_per_sec by 1.0 / (4.0 * 4.0) in ./erosion.rs, in order
+        // to get a similar rate of river formation.
+        //
+        // FIXME: This is a hack!  At some point we will hae a more principled way of
+        // dealing with this.
+        let continent_scale_hack = gen_opts.continent_scale;
 
         let mut rng = ChaChaRng::from_seed(seed_expan::rng_state(seed));
         let continent_scale = continent_scale_hack
@@ -592,7 +680,7 @@ impl WorldSim {
         // grid (when a chunk isn't available).
         let n_approx = 1.0;
         let max_erosion_per_delta_t = 64.0 * delta_t_scale(n_approx);
-        let n_steps = 100;
+        let n_steps = gen_opts.erosion_steps;
         let n_small_steps = 0;
         let n_post_load_steps = 0;
 
@@ -768,11 +856,11 @@ impl WorldSim {
                 ((alt_base[posi].1 + alt_main.mul((chaos[posi].1 as f64).powf(1.2)))
                     .mul(map_edge_factor(map_size_lg, posi) as f64)
                     .add(
-                        (CONFIG.sea_level as f64)
+                        (sea_level as f64)
                             .div(CONFIG.mountain_scale as f64)
                             .mul(map_edge_factor(map_size_lg, posi) as f64),
                     )
-                    .sub((CONFIG.sea_level as f64).div(CONFIG.mountain_scale as f64)))
+                    .sub((sea_level as f64).div(CONFIG.mountain_scale as f64)))
                     as f32,
             )
         });
@@ -1087,8 +1175,7 @@ impl WorldSim {
         // Save map, if necessary.
         // NOTE: We wll always save a map with latest version.
         let map = WorldFile::new(ModernMap {
-            continent_scale_hack,
-            map_size_lg: map_size_lg.vec(),
+            gen_opts: gen_opts.clone(),
             alt,
             basement,
         });
@@ -1129,8 +1216,7 @@ impl WorldSim {
         // Skip validation--we just performed a no-op conversion for this map, so it had
         // better be valid!
         let ModernMap {
-            continent_scale_hack: _,
-            map_size_lg: _,
+            gen_opts: _,
             alt,
             basement,
         } = map.into_modern().unwrap();
@@ -1381,12 +1467,13 @@ impl WorldSim {
 
         let chunks = (0..map_size_lg.chunks_len())
             .into_par_iter()
-            .map(|i| SimChunk::generate(map_size_lg, i, &gen_ctx, &gen_cdf))
+            .map(|i| SimChunk::generate(map_size_lg, sea_level, i, &gen_ctx, &gen_cdf))
             .collect::<Vec<_>>();
 
         let mut this = Self {
             seed,
             map_size_lg,
+            gen_opts,
             max_height: maxh as f32,
             chunks,
             locations: Vec::new(),
@@ -1406,12 +1493,37 @@ impl WorldSim {
 
     pub fn get_size(&self) -> Vec2<u32> { self.map_size_lg().chunks().map(u32::from) }
 
+    /// The parameters the world was generated with
+    pub fn gen_opts(&self) -> &GenOpts { &self.gen_opts }
+
+    /// Altitude of the sea surface
+    pub fn sea_level(&self) -> f32 { self.gen_opts.sea_level }
+
+    /// Navigation data of every chunk, used by NPCs to plan routes across the
+    /// world
+    pub fn nav_grid(&self) -> NavGrid {
+        let size = self.get_size();
+        let chunks = (0..size.y as i32)
+            .flat_map(|y| (0..size.x as i32).map(move |x| Vec2::new(x, y)))
+            .map(|chunk_pos| {
+                self.get(chunk_pos)
+                    .map(|chunk| NavChunk {
+                        alt: chunk.alt,
+                        water: chunk.river.is_ocean() || chunk.river.is_lake(),
+                        way: chunk.path.0.is_way(),
+                    })
+                    .unwrap_or_default()
+            })
+            .collect();
+        NavGrid::new(size, chunks)
+    }
+
     /// Draw a map of the world based on chunk information.  Returns a buffer of
     /// u32s.
     pub fn get_map(&self, index: IndexRef) -> WorldMapMsg {
         let mut map_config = MapConfig::orthographic(
             self.map_size_lg(),
-            core::ops::RangeInclusive::new(CONFIG.sea_level, CONFIG.sea_level + self.max_height),
+            core::ops::RangeInclusive::new(self.sea_level(), self.sea_level() + self.max_height),
         );
         // Build a horizon map.
         let scale_angle = |angle: Alt| {
@@ -1470,15 +1582,15 @@ impl WorldSim {
                 min: Vec2::zero(),
                 max: self.map_size_lg().chunks().map(|e| e as i32),
             },
-            CONFIG.sea_level,
-            CONFIG.sea_level + self.max_height,
+            self.sea_level(),
+            self.sea_level() + self.max_height,
             |posi| {
                 /* let chunk = &self.chunks[posi];
                 chunk.alt.max(chunk.water_alt) as Alt */
                 let sample = samples_data[posi].as_ref();
                 sample
                     .map(|s| s.alt.max(s.water_level))
-                    .unwrap_or(CONFIG.sea_level)
+                    .unwrap_or(self.sea_level())
             },
             |a| scale_angle(a.into()),
             |h| scale_height(h.into()),
@@ -1511,11 +1623,12 @@ impl WorldSim {
         );
         WorldMapMsg {
             dimensions_lg: self.map_size_lg().vec(),
-            sea_level: CONFIG.sea_level,
+            sea_level: self.sea_level(),
             max_height: self.max_height,
             rgba: v,
             alt: alts,
             horizons,
+            pois: Vec::new(),
         }
     }
 
@@ -2024,6 +2137,8 @@ pub struct SimChunk {
 
     pub path: (Way, Path),
     pub cave: (Way, Cave),
+    /// The track between sites that runs through this chunk, if any
+    pub track: Option<Id<Track>>,
 
     pub contains_waypoint: bool,
 }
@@ -2039,7 +2154,13 @@ pub struct RegionInfo {
 impl SimChunk {
     #[allow(clippy::if_same_then_else)] // TODO: Pending review in #587
     #[allow(clippy::unnested_or_patterns)] // TODO: Pending review in #587
-    fn generate(map_size_lg: MapSizeLg, posi: usize, gen_ctx: &GenCtx, gen_cdf: &GenCdf) -> Self {
+    fn generate(
+        map_size_lg: MapSizeLg,
+        sea_level: f32,
+        posi: usize,
+        gen_ctx: &GenCtx,
+        gen_cdf: &GenCdf,
+    ) -> Self {
         let pos = uniform_idx_as_vec2(map_size_lg, posi);
         let wposf = (pos * TerrainChunkSize::RECT_SIZE.map(|e| e as i32)).map(|e| e as f64);
 
@@ -2087,9 +2208,9 @@ impl SimChunk {
         .sub(0.5)
         .mul(2.0);
 
-        let mut alt = CONFIG.sea_level.add(alt_pre);
-        let basement = CONFIG.sea_level.add(basement_pre);
-        let water_alt = CONFIG.sea_level.add(water_alt_pre);
+        let mut alt = sea_level.add(alt_pre);
+        let basement = sea_level.add(basement_pre);
+        let water_alt = sea_level.add(water_alt_pre);
         let downhill = if downhill_pre == -2 {
             None
         } else if downhill_pre < 0 {
@@ -2261,6 +2382,7 @@ impl SimChunk {
             place: None,
             path: Default::default(),
             cave: Default::default(),
+            track: None,
             contains_waypoint: false,
         }
     }
@@ -2284,8 +2406,8 @@ impl SimChunk {
         */
     }
 
-    pub fn get_biome(&self) -> BiomeKind {
-        if self.alt < CONFIG.sea_level {
+    pub fn get_biome(&self, sea_level: f32) -> BiomeKind {
+        if self.alt < sea_level {
             BiomeKind::Ocean
         } else if self.chaos > 0.6 {
             BiomeKind::Mountain
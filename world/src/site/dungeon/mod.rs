@@ -10,8 +10,9 @@ use crate::{
 use common::{
     assets,
     astar::Astar,
-    comp,
+    comp::{self, biped_large, humanoid, quadruped_medium},
     generation::{ChunkSupplement, EntityInfo},
+    lottery::Lottery,
     npc,
     store::{Id, Store},
     terrain::{Block, BlockKind, Structure, TerrainChunkSize},
@@ -32,6 +33,8 @@ pub struct Dungeon {
     #[allow(dead_code)]
     noise: RandomField,
     floors: Vec<Floor>,
+    difficulty: u32,
+    theme: Theme,
 }
 
 pub struct GenCtx<'a, R: Rng> {
@@ -42,16 +45,201 @@ pub struct GenCtx<'a, R: Rng> {
 #[derive(Deserialize, Serialize)]
 pub struct Colors {
     pub stone: (u8, u8, u8),
+    /// Sealed doors between floors
+    pub seal: (u8, u8, u8),
 }
 
 const ALT_OFFSET: i32 = -2;
 
 const LEVELS: usize = 5;
 
+/// Dungeons are rated from 0 to `MAX_DIFFICULTY`, harder dungeons have more
+/// and stronger enemies in their rooms
+const MAX_DIFFICULTY: u32 = 5;
+
+/// Number of enemy tiers in the rosters of each theme
+const TIERS: usize = 3;
+
+const CULTIST_WEAPONS: [&[&str]; TIERS] = [
+    &[
+        "common.items.weapons.sword.starter_sword",
+        "common.items.weapons.axe.starter_axe",
+        "common.items.weapons.staff.starter_staff",
+        "common.items.weapons.bow.starter_bow",
+    ],
+    &[
+        "common.items.weapons.sword.short_sword_0",
+        "common.items.weapons.hammer.hammer_1",
+        "common.items.weapons.axe.iron_axe-0",
+        "common.items.weapons.bow.wood_longbow-0",
+        "common.items.weapons.staff.staff_1",
+    ],
+    &[
+        "common.items.weapons.sword.cultist_purp_2h-0",
+        "common.items.weapons.hammer.cultist_purp_2h-0",
+        "common.items.weapons.staff.cultist_staff",
+        "common.items.weapons.bow.iron_longbow-0",
+    ],
+];
+
+const BANDIT_WEAPONS: [&[&str]; TIERS] = [
+    &[
+        "common.items.weapons.sword.wood_sword",
+        "common.items.weapons.axe.starter_axe",
+        "common.items.weapons.hammer.flimsy_hammer",
+        "common.items.weapons.bow.wood_shortbow-0",
+    ],
+    &[
+        "common.items.weapons.sword.long_2h_simple-0",
+        "common.items.weapons.axe.worn_iron_axe-0",
+        "common.items.weapons.hammer.iron_hammer-0",
+        "common.items.weapons.bow.wood_shortbow-1",
+    ],
+    &[
+        "common.items.weapons.sword.greatsword_2h_simple-0",
+        "common.items.weapons.axe.steel_axe-0",
+        "common.items.weapons.hammer.steel_hammer-0",
+        "common.items.weapons.bow.horn_longbow-0",
+    ],
+];
+
+const BEAST_SPECIES: [&[quadruped_medium::Species]; TIERS] = [
+    &[
+        quadruped_medium::Species::Wolf,
+        quadruped_medium::Species::Tuskram,
+    ],
+    &[
+        quadruped_medium::Species::Saber,
+        quadruped_medium::Species::Tiger,
+        quadruped_medium::Species::Bonerattler,
+    ],
+    &[
+        quadruped_medium::Species::Frostfang,
+        quadruped_medium::Species::Tarasque,
+        quadruped_medium::Species::Grolgar,
+    ],
+];
+
+/// The inhabitants of a dungeon. Each theme has its own roster of enemies,
+/// which get stronger on deeper floors and in harder dungeons, and its own
+/// boss.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Theme {
+    Cultist,
+    Bandit,
+    Beast,
+}
+
+impl Theme {
+    /// The theme of the dungeon at `wpos`
+    fn at(seed: u32, wpos: Vec2<i32>) -> Self {
+        match RandomField::new(seed.wrapping_add(4)).get(wpos.with_z(0)) % 3 {
+            0 => Theme::Cultist,
+            1 => Theme::Bandit,
+            _ => Theme::Beast,
+        }
+    }
+
+    /// The roster tier of the enemies on the floor at `level`
+    fn tier(level: i32, difficulty: u32) -> usize {
+        let max = LEVELS - 1 + MAX_DIFFICULTY as usize;
+        ((level.max(0) as usize + difficulty as usize) * TIERS / (max + 1)).min(TIERS - 1)
+    }
+
+    /// A regular enemy from the roster of the given tier. Only humanoids can
+    /// be giants.
+    fn enemy(&self, rng: &mut impl Rng, pos: Vec3<f32>, tier: usize, giant: bool) -> EntityInfo {
+        match self {
+            Theme::Cultist | Theme::Bandit => {
+                let weapons = if *self == Theme::Cultist {
+                    CULTIST_WEAPONS[tier]
+                } else {
+                    BANDIT_WEAPONS[tier]
+                };
+                EntityInfo::at(pos)
+                    .do_if(giant, |e| e.into_giant())
+                    .with_body(comp::Body::Humanoid(humanoid::Body::random()))
+                    .with_automatic_name()
+                    .with_main_tool(assets::load_expect_cloned(weapons.choose(rng).unwrap()))
+            },
+            Theme::Beast => {
+                let species = BEAST_SPECIES[tier].choose(rng).unwrap();
+                EntityInfo::at(pos)
+                    .with_body(comp::Body::QuadrupedMedium(
+                        quadruped_medium::Body::random_with(rng, species),
+                    ))
+                    .with_automatic_name()
+            },
+        }
+    }
+
+    /// The enemy that carries the key to the sealed door of its floor
+    fn key_holder(&self, rng: &mut impl Rng, pos: Vec3<f32>, tier: usize) -> EntityInfo {
+        let title = match self {
+            Theme::Cultist => "Cult Warden",
+            Theme::Bandit => "Bandit Lieutenant",
+            Theme::Beast => "Pack Leader",
+        };
+        let mut entity = self.enemy(rng, pos, tier, false).with_scale(1.5);
+        entity.name = entity.name.map(|name| format!("{}, {}", name, title));
+        entity.with_loot_drop(assets::load_expect_cloned(
+            "common.items.utility.dungeon_key",
+        ))
+    }
+
+    /// The boss of the final floor
+    fn boss(&self, rng: &mut impl Rng, pos: Vec3<f32>) -> EntityInfo {
+        let entity = match self {
+            Theme::Cultist => EntityInfo::at(pos)
+                .with_scale(4.0)
+                .with_body(comp::Body::Humanoid(humanoid::Body::random()))
+                .with_name(format!(
+                    "{}, Cult Leader",
+                    npc::get_npc_name(npc::NpcKind::Humanoid)
+                ))
+                .with_main_tool(assets::load_expect_cloned(
+                    "common.items.weapons.sword.cultist_purp_2h-0",
+                )),
+            Theme::Bandit => EntityInfo::at(pos)
+                .with_scale(3.0)
+                .with_body(comp::Body::Humanoid(humanoid::Body::random()))
+                .with_name(format!(
+                    "{}, Bandit Chief",
+                    npc::get_npc_name(npc::NpcKind::Humanoid)
+                ))
+                .with_main_tool(assets::load_expect_cloned(
+                    "common.items.weapons.sword.greatsword_2h_orn-0",
+                )),
+            Theme::Beast => {
+                let species = [biped_large::Species::Troll, biped_large::Species::Wendigo]
+                    .choose(rng)
+                    .unwrap();
+                let mut entity = EntityInfo::at(pos)
+                    .with_scale(1.5)
+                    .with_body(comp::Body::BipedLarge(biped_large::Body::random_with(
+                        rng, species,
+                    )))
+                    .with_automatic_name();
+                entity.name = entity.name.map(|name| format!("{}, Den Keeper", name));
+                entity
+            },
+        };
+
+        let loot = assets::load_expect::<Lottery<String>>("common.loot_table_boss");
+        entity.with_loot_drop(assets::load_expect_cloned(loot.choose_seeded(rng.gen())))
+    }
+}
+
 impl Dungeon {
     #[allow(clippy::let_and_return)] // TODO: Pending review in #587
     pub fn generate(wpos: Vec2<i32>, sim: Option<&WorldSim>, rng: &mut impl Rng) -> Self {
         let mut ctx = GenCtx { sim, rng };
+        // Taken from the position rather than the world rng, so that the layouts of
+        // dungeons stay the same for a given seed
+        let world_seed = sim.map_or(0, |sim| sim.seed);
+        let difficulty =
+            RandomField::new(world_seed.wrapping_add(3)).get(wpos.with_z(0)) % (MAX_DIFFICULTY + 1);
+        let theme = Theme::at(world_seed, wpos);
         let this = Self {
             origin: wpos - TILE_SIZE / 2,
             alt: ctx
@@ -63,11 +251,14 @@ impl Dungeon {
             noise: RandomField::new(ctx.rng.gen()),
             floors: (0..LEVELS)
                 .scan(Vec2::zero(), |stair_tile, level| {
-                    let (floor, st) = Floor::generate(&mut ctx, *stair_tile, level as i32);
+                    let (floor, st) =
+                        Floor::generate(&mut ctx, *stair_tile, level as i32, difficulty, theme);
                     *stair_tile = st;
                     Some(floor)
                 })
                 .collect(),
+            difficulty,
+            theme,
         };
 
         this
@@ -75,6 +266,10 @@ impl Dungeon {
 
     pub fn get_origin(&self) -> Vec2<i32> { self.origin }
 
+    pub fn difficulty(&self) -> u32 { self.difficulty }
+
+    pub fn theme(&self) -> Theme { self.theme }
+
     pub fn radius(&self) -> f32 { 1200.0 }
 
     #[allow(clippy::needless_update)] // TODO: Pending review in #587
@@ -208,6 +403,29 @@ pub struct Room {
     pillars: Option<i32>, // Pillars with the given separation
 }
 
+impl Room {
+    fn is_pillar(&self, tile_pos: Vec2<i32>) -> bool {
+        self.pillars
+            .map(|pillar_space| {
+                tile_pos
+                    .map(|e| e.rem_euclid(pillar_space) == 0)
+                    .reduce_and()
+            })
+            .unwrap_or(false)
+    }
+
+    /// The tile at the center of the room, or next to it if there's a pillar
+    /// there
+    fn center_tile(&self) -> Vec2<i32> {
+        let center = self.area.center();
+        if self.is_pillar(center) {
+            center + 1
+        } else {
+            center
+        }
+    }
+}
+
 pub struct Floor {
     tile_offset: Vec2<i32>,
     tiles: Grid<Tile>,
@@ -217,6 +435,14 @@ pub struct Floor {
     #[allow(dead_code)]
     stair_tile: Vec2<i32>,
     final_level: bool,
+    level: i32,
+    difficulty: u32,
+    theme: Theme,
+    /// The room of the enemy carrying the key to the sealed downstairs. Floors
+    /// without one aren't sealed.
+    key_room: Option<Id<Room>>,
+    /// The tile of the boss room where the boss chest stands
+    boss_chest: Option<Vec2<i32>>,
 }
 
 const FLOOR_SIZE: Vec2<i32> = Vec2::new(18, 18);
@@ -226,6 +452,8 @@ impl Floor {
         ctx: &mut GenCtx<impl Rng>,
         stair_tile: Vec2<i32>,
         level: i32,
+        difficulty: u32,
+        theme: Theme,
     ) -> (Self, Vec2<i32>) {
         let final_level = level == LEVELS as i32 - 1;
 
@@ -250,6 +478,11 @@ impl Floor {
             hollow_depth: 30,
             stair_tile: new_stair_tile - tile_offset,
             final_level,
+            level,
+            difficulty,
+            theme,
+            key_room: None,
+            boss_chest: None,
         };
 
         const STAIR_ROOM_HEIGHT: i32 = 13;
@@ -267,7 +500,7 @@ impl Floor {
             .set(stair_tile - tile_offset, Tile::UpStair(upstair_room));
         if final_level {
             // Boss room
-            this.create_room(Room {
+            let boss_room = this.create_room(Room {
                 seed: ctx.rng.gen(),
                 loot_density: 0.0,
                 enemy_density: Some(0.001), // Minions!
@@ -276,6 +509,14 @@ impl Floor {
                 height: 30,
                 pillars: Some(2),
             });
+            // The chest stands behind the boss
+            let room = &this.rooms[boss_room];
+            let chest_tile = room.area.center() + Vec2::new(0, 3);
+            this.boss_chest = Some(if room.is_pillar(chest_tile) {
+                chest_tile + Vec2::unit_x()
+            } else {
+                chest_tile
+            });
         } else {
             // Create downstairs room
             let downstair_room = this.create_room(Room {
@@ -293,7 +534,17 @@ impl Floor {
             );
         }
 
-        this.create_rooms(ctx, level, 7);
+        this.create_rooms(ctx, level, difficulty, 7);
+        // Seal the way down, the key is carried by an enemy in one of the rooms
+        if !final_level {
+            let rooms = this
+                .rooms
+                .iter()
+                .filter(|(_, room)| room.enemy_density.is_some() && !room.boss)
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>();
+            this.key_room = rooms.choose(ctx.rng).copied();
+        }
         // Create routes between all rooms
         let room_areas = this.rooms.values().map(|r| r.area).collect::<Vec<_>>();
         for a in room_areas.iter() {
@@ -317,7 +568,7 @@ impl Floor {
         id
     }
 
-    fn create_rooms(&mut self, ctx: &mut GenCtx<impl Rng>, level: i32, n: usize) {
+    fn create_rooms(&mut self, ctx: &mut GenCtx<impl Rng>, level: i32, difficulty: u32, n: usize) {
         let dim_limits = (3, 6);
 
         for _ in 0..n {
@@ -347,7 +598,9 @@ impl Floor {
             self.create_room(Room {
                 seed: ctx.rng.gen(),
                 loot_density: 0.000025 + level as f32 * 0.00015,
-                enemy_density: Some(0.001 + level as f32 * 0.00006),
+                enemy_density: Some(
+                    (0.001 + level as f32 * 0.00006) * (1.0 + difficulty as f32 * 0.2),
+                ),
                 boss: false,
                 area,
                 height: ctx.rng.gen_range(10, 15),
@@ -405,7 +658,6 @@ impl Floor {
         }
     }
 
-    #[allow(clippy::match_single_binding)] // TODO: Pending review in #587
     pub fn apply_supplement(
         &self,
         rng: &mut impl Rng,
@@ -435,8 +687,8 @@ impl Floor {
             for y in area.min.y..area.max.y {
                 let tile_pos = Vec2::new(x, y).map(|e| e.div_euclid(TILE_SIZE)) - self.tile_offset;
                 let wpos2d = origin.xy() + Vec2::new(x, y);
-                if let Some(Tile::Room(room)) = self.tiles.get(tile_pos) {
-                    let room = &self.rooms[*room];
+                if let Some(Tile::Room(room_id)) = self.tiles.get(tile_pos) {
+                    let room = &self.rooms[*room_id];
 
                     let tile_wcenter = origin
                         + Vec3::from(
@@ -444,128 +696,71 @@ impl Floor {
                                 .map(|e| e.div_euclid(TILE_SIZE) * TILE_SIZE + TILE_SIZE / 2),
                         );
 
-                    let tile_is_pillar = room
-                        .pillars
-                        .map(|pillar_space| {
-                            tile_pos
-                                .map(|e| e.rem_euclid(pillar_space) == 0)
-                                .reduce_and()
-                        })
-                        .unwrap_or(false);
-
                     if room
                         .enemy_density
                         .map(|density| rng.gen_range(0, density.recip() as usize) == 0)
                         .unwrap_or(false)
-                        && !tile_is_pillar
+                        && !room.is_pillar(tile_pos)
                     {
                         // Bad
-                        let entity = EntityInfo::at(
-                            tile_wcenter.map(|e| e as f32)
+                        let pos = tile_wcenter.map(|e| e as f32)
                             // Randomly displace them a little
                             + Vec3::<u32>::iota()
                                 .map(|e| (RandomField::new(room.seed.wrapping_add(10 + e)).get(Vec3::from(tile_pos)) % 32) as i32 - 16)
-                                .map(|e| e as f32 / 16.0),
-                        )
-                        .do_if(RandomField::new(room.seed.wrapping_add(1)).chance(Vec3::from(tile_pos), 0.2) && !room.boss, |e| e.into_giant())
-                        .with_alignment(comp::Alignment::Enemy)
-                        .with_body(comp::Body::Humanoid(comp::humanoid::Body::random()))
-                        .with_automatic_name()
-                        .with_main_tool(assets::load_expect_cloned(match rng.gen_range(0, 6) {
-                            0 => "common.items.weapons.axe.starter_axe",
-                            1 => "common.items.weapons.sword.starter_sword",
-                            2 => "common.items.weapons.sword.short_sword_0",
-                            3 => "common.items.weapons.hammer.hammer_1",
-                            4 => "common.items.weapons.staff.starter_staff",
-                            _ => "common.items.weapons.bow.starter_bow",
-                        }));
+                                .map(|e| e as f32 / 16.0);
+                        let giant = RandomField::new(room.seed.wrapping_add(1))
+                            .chance(Vec3::from(tile_pos), 0.2)
+                            && !room.boss;
+                        let level = self.enemy_level(rng);
+                        let entity = self
+                            .theme
+                            .enemy(rng, pos, self.tier(), giant)
+                            .with_level(level)
+                            .with_alignment(comp::Alignment::Enemy);
 
                         supplement.add_entity(entity);
                     }
 
-                    if room.boss {
-                        let boss_spawn_tile = room.area.center();
-                        // Don't spawn the boss in a pillar
-                        let boss_tile_is_pillar = room
-                            .pillars
-                            .map(|pillar_space| {
-                                boss_spawn_tile
-                                    .map(|e| e.rem_euclid(pillar_space) == 0)
-                                    .reduce_and()
-                            })
-                            .unwrap_or(false);
-                        let boss_spawn_tile =
-                            boss_spawn_tile + if boss_tile_is_pillar { 1 } else { 0 };
-
-                        if tile_pos == boss_spawn_tile && tile_wcenter.xy() == wpos2d {
-                            let entity = EntityInfo::at(tile_wcenter.map(|e| e as f32))
-                                .with_scale(4.0)
-                                .with_level(rng.gen_range(75, 100))
-                                .with_alignment(comp::Alignment::Enemy)
-                                .with_body(comp::Body::Humanoid(comp::humanoid::Body::random()))
-                                .with_name(format!(
-                                    "{}, Cult Leader",
-                                    npc::get_npc_name(npc::NpcKind::Humanoid)
-                                ))
-                                .with_main_tool(assets::load_expect_cloned(
-                                    match rng.gen_range(0, 1) {
-                                        //Add more possible cult leader weapons here
-                                        _ => "common.items.weapons.sword.cultist_purp_2h-0",
-                                    },
-                                ))
-                                .with_loot_drop(match rng.gen_range(0, 20) {
-                                    0 => comp::Item::expect_from_asset(
-                                        "common.items.boss_drops.lantern",
-                                    ),
-                                    1 => comp::Item::expect_from_asset(
-                                        "common.items.boss_drops.potions",
-                                    ),
-                                    2 => comp::Item::expect_from_asset(
-                                        "common.items.armor.belt.cultist_belt",
-                                    ),
-                                    3 => comp::Item::expect_from_asset(
-                                        "common.items.armor.chest.cultist_chest_purple",
-                                    ),
-                                    4 => comp::Item::expect_from_asset(
-                                        "common.items.armor.foot.cultist_boots",
-                                    ),
-                                    5 => comp::Item::expect_from_asset(
-                                        "common.items.armor.hand.cultist_hands_purple",
-                                    ),
-                                    6 => comp::Item::expect_from_asset(
-                                        "common.items.armor.pants.cultist_legs_purple",
-                                    ),
-                                    7 => comp::Item::expect_from_asset(
-                                        "common.items.armor.shoulder.cultist_shoulder_purple",
-                                    ),
-                                    8 => comp::Item::expect_from_asset(
-                                        "common.items.weapons.staff.cultist_staff",
-                                    ),
-                                    9 => comp::Item::expect_from_asset(
-                                        "common.items.weapons.sword.greatsword_2h_fine-1",
-                                    ),
-                                    10 => comp::Item::expect_from_asset(
-                                        "common.items.weapons.sword.greatsword_2h_fine-2",
-                                    ),
-                                    11 => comp::Item::expect_from_asset(
-                                        "common.items.weapons.sword.cultist_purp_2h-0",
-                                    ),
-                                    12 => comp::Item::expect_from_asset(
-                                        "common.items.armor.back.dungeon_purple-0",
-                                    ),
-                                    _ => comp::Item::expect_from_asset(
-                                        "common.items.boss_drops.exp_flask",
-                                    ),
-                                });
-
-                            supplement.add_entity(entity);
-                        }
+                    let is_center = tile_pos == room.center_tile() && tile_wcenter.xy() == wpos2d;
+
+                    if self.key_room == Some(*room_id) && is_center {
+                        let level = self.enemy_level(rng) + 5;
+                        let entity = self
+                            .theme
+                            .key_holder(rng, tile_wcenter.map(|e| e as f32), self.tier())
+                            .with_level(level)
+                            .with_alignment(comp::Alignment::Enemy);
+
+                        supplement.add_entity(entity);
+                    }
+
+                    if room.boss && is_center {
+                        let level = 60 + self.difficulty * 8 + rng.gen_range(0, 10);
+                        let entity = self
+                            .theme
+                            .boss(rng, tile_wcenter.map(|e| e as f32))
+                            .with_level(level)
+                            .with_alignment(comp::Alignment::Enemy);
+
+                        supplement.add_entity(entity);
                     }
                 }
             }
         }
     }
 
+    /// The roster tier of the enemies on this floor
+    fn tier(&self) -> usize { Theme::tier(self.level, self.difficulty) }
+
+    /// Enemies are stronger on deeper floors and in harder dungeons
+    fn enemy_level(&self, rng: &mut impl Rng) -> u32 {
+        4 + self.level.max(0) as u32 * 4 + self.difficulty * 3 + rng.gen_range(0, 5)
+    }
+
+    /// Whether the way down from this floor is sealed by a door that needs a
+    /// key
+    pub fn is_sealed(&self) -> bool { self.key_room.is_some() }
+
     pub fn total_depth(&self) -> i32 { self.solid_depth + self.hollow_depth }
 
     pub fn nearest_wall(&self, rpos: Vec2<i32>) -> Option<Vec2<i32>> {
@@ -638,7 +833,9 @@ impl Floor {
         let tunnel_dist =
             1.0 - (dist_to_wall - wall_thickness).max(0.0) / (TILE_SIZE as f32 - wall_thickness);
 
-        let floor_sprite = if RandomField::new(7331).chance(Vec3::from(pos), 0.00005) {
+        let floor_sprite = if self.boss_chest == Some(tile_pos) && rtile_pos == Vec2::zero() {
+            BlockMask::new(Block::new(BlockKind::BossChest, Rgb::white()), 1)
+        } else if RandomField::new(7331).chance(Vec3::from(pos), 0.00005) {
             BlockMask::new(
                 Block::new(
                     match (RandomField::new(1337).get(Vec3::from(pos)) / 2) % 20 {
@@ -702,6 +899,16 @@ impl Floor {
                     empty
                 }
             },
+            Some(Tile::DownStair(_)) if z == 0 && self.is_sealed() => {
+                // Cover the staircase with a sealed door
+                if rtile_pos.map(|e| e as f32).magnitude_squared()
+                    < (TILE_SIZE as f32 / 2.0).powf(2.0)
+                {
+                    BlockMask::new(Block::new(BlockKind::SealedDoor, colors.seal.into()), 5)
+                } else {
+                    empty
+                }
+            },
             Some(Tile::DownStair(_)) => {
                 make_staircase(Vec3::new(rtile_pos.x, rtile_pos.y, z), 0.0, 0.5, 9.0)
                     .resolve_with(empty)
@@ -722,3 +929,121 @@ impl Floor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaChaRng;
+
+    fn generate(seed: u64) -> Dungeon {
+        Dungeon::generate(Vec2::zero(), None, &mut ChaChaRng::seed_from_u64(seed))
+    }
+
+    /// The room areas, stairs, key rooms and boss chests of each floor
+    #[allow(clippy::type_complexity)]
+    fn layout(
+        dungeon: &Dungeon,
+    ) -> Vec<(
+        Vec<Rect<i32, i32>>,
+        Vec2<i32>,
+        Option<Rect<i32, i32>>,
+        Option<Vec2<i32>>,
+    )> {
+        dungeon
+            .floors
+            .iter()
+            .map(|floor| {
+                (
+                    floor.rooms.values().map(|room| room.area).collect(),
+                    floor.stair_tile,
+                    floor.key_room.map(|room| floor.rooms[room].area),
+                    floor.boss_chest,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fixed_seed_layout() {
+        let a = generate(1337);
+        let b = generate(1337);
+        assert_eq!(a.difficulty(), b.difficulty());
+        assert_eq!(a.theme(), b.theme());
+        assert_eq!(layout(&a), layout(&b));
+    }
+
+    #[test]
+    fn test_difficulty_keeps_layout() {
+        let seed = 7;
+        let a = Dungeon::generate(Vec2::zero(), None, &mut ChaChaRng::seed_from_u64(seed));
+        let b = (1..64)
+            .map(|x| {
+                Dungeon::generate(
+                    Vec2::new(x * 1000, 0),
+                    None,
+                    &mut ChaChaRng::seed_from_u64(seed),
+                )
+            })
+            .find(|b| b.difficulty() != a.difficulty())
+            .expect("Dungeons at different positions should differ in difficulty");
+        assert_eq!(layout(&a), layout(&b));
+    }
+
+    #[test]
+    fn test_floors() {
+        for seed in 0..16 {
+            let dungeon = generate(seed);
+            assert_eq!(dungeon.floors.len(), LEVELS);
+            assert!(dungeon.difficulty() <= MAX_DIFFICULTY);
+
+            let (last, others) = dungeon.floors.split_last().unwrap();
+            for floor in others {
+                assert!(!floor.final_level);
+                assert!(floor.boss_chest.is_none());
+                // Every floor but the last is sealed, with the key in a regular room
+                let key_room = &floor.rooms[floor.key_room.expect("Floor should be sealed")];
+                assert!(key_room.enemy_density.is_some() && !key_room.boss);
+            }
+
+            assert!(last.final_level);
+            assert!(!last.is_sealed());
+            let boss_room = last
+                .rooms
+                .values()
+                .find(|room| room.boss)
+                .expect("Final floor should have a boss room");
+            let chest = last
+                .boss_chest
+                .expect("Final floor should have a boss chest");
+            assert!(boss_room.area.contains_point(chest));
+            assert!(!boss_room.is_pillar(chest));
+            assert!(!boss_room.is_pillar(boss_room.center_tile()));
+        }
+    }
+
+    #[test]
+    fn test_tiers() {
+        assert_eq!(Theme::tier(0, 0), 0);
+        assert_eq!(Theme::tier(LEVELS as i32 - 1, MAX_DIFFICULTY), TIERS - 1);
+        for difficulty in 0..=MAX_DIFFICULTY {
+            for level in 1..LEVELS as i32 {
+                assert!(Theme::tier(level, difficulty) >= Theme::tier(level - 1, difficulty));
+            }
+        }
+    }
+
+    #[test]
+    fn test_rosters() {
+        for weapons in CULTIST_WEAPONS.iter().chain(BANDIT_WEAPONS.iter()) {
+            assert!(!weapons.is_empty());
+            for weapon in weapons.iter() {
+                assert!(
+                    assets::load::<comp::Item>(weapon).is_ok(),
+                    "Invalid roster weapon '{}'",
+                    weapon
+                );
+            }
+        }
+        assert!(BEAST_SPECIES.iter().all(|species| !species.is_empty()));
+    }
+}
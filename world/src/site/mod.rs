@@ -38,6 +38,8 @@ impl Default for SpawnRules {
 pub struct Site {
     pub kind: SiteKind,
     pub economy: Economy,
+    /// Name shown for the site on the world map
+    pub name: String,
 }
 
 pub enum SiteKind {
@@ -51,6 +53,7 @@ impl Site {
         Self {
             kind: SiteKind::Settlement(s),
             economy: Economy::default(),
+            name: String::new(),
         }
     }
 
@@ -58,6 +61,7 @@ impl Site {
         Self {
             kind: SiteKind::Dungeon(d),
             economy: Economy::default(),
+            name: String::new(),
         }
     }
 
@@ -65,6 +69,7 @@ impl Site {
         Self {
             kind: SiteKind::Castle(c),
             economy: Economy::default(),
+            name: String::new(),
         }
     }
 
@@ -539,7 +539,7 @@ impl Archetype for House {
                             center_offset.x,
                             center_offset.y,
                             z + 100,
-                        )) % 11
+                        )) % 14
                         {
                             0 => BlockKind::Planter,
                             1 => BlockKind::ChairSingle,
@@ -550,6 +550,9 @@ impl Archetype for House {
                             7 => BlockKind::DrawerSmall,
                             8 => BlockKind::TableSide,
                             9 => BlockKind::WardrobeSingle,
+                            10 => BlockKind::CraftingBench,
+                            11 => BlockKind::Anvil,
+                            12 => BlockKind::Cauldron,
                             _ => BlockKind::Pot,
                         };
 